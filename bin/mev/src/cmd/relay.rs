@@ -1,7 +1,7 @@
 use crate::cmd::config::Config;
 use clap::{Args, Subcommand};
 use eyre::OptionExt;
-use mev_relay_rs::Service;
+use mev_relay_rs::{MockRelay, Service};
 use tracing::info;
 
 #[derive(Debug, Args)]
@@ -16,12 +16,13 @@ pub struct Command {
 
 #[derive(Debug, Subcommand)]
 pub enum Commands {
+    /// Run an in-memory relay serving deterministic bids, configured by `[relay.mock]`
     Mock { config_file: String },
 }
 
 impl Command {
     pub async fn execute(self) -> eyre::Result<()> {
-        let (config_file, _mock) = if let Some(subcommand) = self.command.as_ref() {
+        let (config_file, mock) = if let Some(subcommand) = self.command.as_ref() {
             match subcommand {
                 Commands::Mock { config_file } => (config_file, true),
             }
@@ -35,7 +36,12 @@ impl Command {
         info!("configured for `{network}`");
 
         if let Some(config) = config.relay {
-            let service = Service::from(network, config).spawn().await?;
+            if mock {
+                let server = MockRelay::spawn(network, config)?;
+                return Ok(server.await?)
+            }
+            let service =
+                Service::from(network, config).with_config_file(config_file).spawn().await?;
             Ok(service.await?)
         } else {
             Err(eyre::eyre!("missing relay config from file provided"))
@@ -7,7 +7,10 @@ use tracing::warn;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[cfg(feature = "build")]
-use ::{clap::CommandFactory, eyre::OptionExt, std::path::PathBuf};
+use ::{
+    clap::CommandFactory, ethereum_consensus::networks::Network, eyre::OptionExt,
+    std::path::PathBuf,
+};
 
 const MINIMAL_PRESET_NOTICE: &str =
     "`minimal-preset` feature is enabled. The `minimal` consensus preset is being used.";
@@ -94,12 +97,20 @@ fn main() -> eyre::Result<()> {
             if cfg!(feature = "minimal-preset") {
                 warn!("{MINIMAL_PRESET_NOTICE}");
             }
+            let config_file = PathBuf::from(&cli_args.config_file);
             let config: cmd::config::Config = cli_args.try_into()?;
-            if let Some(network) = config.network {
-                warn!(%network, "`network` option provided in configuration but ignored in favor of `reth` configuration");
-            }
+            // NOTE: `reth` selects the chain, but the consensus configuration of a custom chain
+            // may be kept apart from its genesis
+            let custom_chain_config_directory = match config.network {
+                Some(Network::Custom(path)) => Some(PathBuf::from(path)),
+                Some(network) => {
+                    warn!(%network, "`network` option provided in configuration but ignored in favor of `reth` configuration");
+                    custom_chain_config_directory
+                }
+                None => custom_chain_config_directory,
+            };
             let config = config.builder.ok_or_eyre("missing `builder` configuration")?;
-            mev_build_rs::launch(node_builder, custom_chain_config_directory,  config).await
+            mev_build_rs::launch(node_builder, custom_chain_config_directory, config, Some(config_file)).await
         }),
         #[cfg(feature = "relay")]
         Commands::Relay(cmd) => run_task_until_signal(cmd.execute()),
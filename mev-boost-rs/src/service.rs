@@ -58,7 +58,7 @@ impl Service {
         let relay_task = tokio::spawn(async move {
             let relay_mux = relay_mux_clone;
             let genesis_time =
-                get_genesis_time(&context, config.beacon_node_url.as_ref(), None).await;
+                get_genesis_time(&context, config.beacon_node_url.as_slice(), None).await;
             let clock = context.clock_at(genesis_time);
             let mut slots = clock.clone().into_stream();
 
@@ -0,0 +1,550 @@
+//! Authenticated endpoint for trusted block-building pipelines, e.g. one co-located with the
+//! builder, to submit blocks they built in full, which the builder signs and submits to the relays
+//! along with its own
+
+use crate::auctioneer::AuctionContext;
+use alloy_eips::eip4844::kzg_to_versioned_hash;
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use beacon_api_client::ApiError;
+use ethereum_consensus::primitives::Slot;
+use mev_rs::{blinded_block_relayer::API_KEY_HEADER, compute_preferred_gas_limit};
+use reth::{
+    payload::EthBuiltPayload,
+    primitives::{
+        revm_primitives::{Address, B256, U256},
+        BlobTransactionSidecar, Receipts, SealedBlock, TransactionSigned,
+    },
+    providers::{ExecutionOutcome, HeaderProvider, StateProviderFactory},
+    revm::database::StateProviderDatabase,
+    rpc::{
+        compat::engine::payload::try_into_sealed_block,
+        types::engine::{BlobsBundleV1, ExecutionPayload, ExecutionPayloadV3, PayloadError},
+    },
+};
+use reth_evm::execute::{BlockExecutionInput, BlockExecutorProvider, Executor};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use tracing::{error, info};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PipelineConfig {
+    /// Name of the pipeline, used to label its blocks in logs and metrics
+    pub name: String,
+    /// Key the pipeline sends in the `x-api-key` header of each request
+    pub api_key: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Address to serve the endpoint on; if missing, it is only served to the local host
+    pub host: Option<Ipv4Addr>,
+    pub port: u16,
+    pub pipelines: Vec<PipelineConfig>,
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("missing or unknown API key")]
+    Unauthorized,
+    #[error("no open auction for a block with parent {parent_hash} at timestamp {timestamp}")]
+    NoAuction { parent_hash: B256, timestamp: u64 },
+    #[error(transparent)]
+    InvalidPayload(#[from] PayloadError),
+    #[error("withdrawals do not match those of the auction")]
+    Withdrawals,
+    #[error("last transaction of the block does not pay the proposer")]
+    MissingPayment,
+    #[error("payment to the proposer of {payment} is less than the value {value}")]
+    Underpaid { payment: U256, value: U256 },
+    #[error("blobs bundle does not match the blob transactions of the block")]
+    Blobs,
+    #[error("fee recipient {fee_recipient} of the block is not the builder's {expected}")]
+    FeeRecipient { fee_recipient: Address, expected: Address },
+    #[error("gas limit {gas_limit} of the block is not {expected}, as preferred by the proposer")]
+    GasLimit { gas_limit: u64, expected: u64 },
+    #[error("parent block {0} is unknown")]
+    UnknownParent(B256),
+    #[error("could not execute block: {0}")]
+    Execution(String),
+    #[error("block does not commit to the {0} of its execution")]
+    ExecutionMismatch(&'static str),
+    #[error("payment to the proposer reverted")]
+    PaymentReverted,
+    #[error("builder is not accepting blocks")]
+    Unavailable,
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        let code = match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::BAD_REQUEST,
+        };
+        (code, Json(ApiError::ErrorMessage { code, message })).into_response()
+    }
+}
+
+/// A block built in full by a pipeline, with the `value` it bids to the proposer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitBlockRequest {
+    pub execution_payload: ExecutionPayloadV3,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blobs_bundle: Option<BlobsBundleV1>,
+    pub value: U256,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitBlockResponse {
+    pub block_hash: B256,
+    pub slot: Slot,
+}
+
+/// A block submitted by `pipeline`, awaiting the auctioneer's `response`
+#[derive(Debug)]
+pub struct ExternalBlock {
+    pub pipeline: String,
+    pub request: SubmitBlockRequest,
+    pub response: oneshot::Sender<Result<SubmitBlockResponse, Error>>,
+}
+
+// NOTE: like the builder's own blocks, the last transaction of the block pays the proposer
+fn proposer_payment(transactions: &[TransactionSigned]) -> Option<(Address, U256)> {
+    let transaction = transactions.last()?;
+    Some((transaction.to()?, transaction.value()))
+}
+
+// Split the `blobs_bundle` into the sidecars of the blob transactions, in the order of the block,
+// checking the commitments match the versioned hashes of each transaction
+fn to_sidecars(
+    transactions: &[TransactionSigned],
+    blobs_bundle: Option<BlobsBundleV1>,
+) -> Result<Vec<BlobTransactionSidecar>, Error> {
+    let (commitments, proofs, blobs) = blobs_bundle
+        .map(|bundle| (bundle.commitments, bundle.proofs, bundle.blobs))
+        .unwrap_or_default();
+    if commitments.len() != proofs.len() || commitments.len() != blobs.len() {
+        return Err(Error::Blobs)
+    }
+    let (mut commitments, mut proofs, mut blobs) =
+        (commitments.into_iter(), proofs.into_iter(), blobs.into_iter());
+    let mut sidecars = vec![];
+    for versioned_hashes in
+        transactions.iter().filter_map(|transaction| transaction.blob_versioned_hashes())
+    {
+        let count = versioned_hashes.len();
+        let sidecar = BlobTransactionSidecar {
+            commitments: commitments.by_ref().take(count).collect(),
+            proofs: proofs.by_ref().take(count).collect(),
+            blobs: blobs.by_ref().take(count).collect(),
+        };
+        let matches =
+            sidecar.commitments.len() == count &&
+                sidecar.commitments.iter().zip(&versioned_hashes).all(|(commitment, hash)| {
+                    &kzg_to_versioned_hash(commitment.as_slice()) == hash
+                });
+        if !matches {
+            return Err(Error::Blobs)
+        }
+        sidecars.push(sidecar);
+    }
+    if commitments.next().is_some() {
+        return Err(Error::Blobs)
+    }
+    Ok(sidecars)
+}
+
+/// Match the block in `request` with one of the open `auctions`, returning the payload to submit
+/// for it along with the auction.
+///
+/// The block must build on the parent with the attributes of the auction and pay its proposer at
+/// least `value`; it is then executed with a [`BlockValidator`] before the builder bids with it.
+pub fn to_payload<'a>(
+    request: SubmitBlockRequest,
+    auctions: impl IntoIterator<Item = &'a AuctionContext>,
+) -> Result<(EthBuiltPayload, &'a AuctionContext), Error> {
+    let SubmitBlockRequest { execution_payload, blobs_bundle, value } = request;
+    let inner = &execution_payload.payload_inner.payload_inner;
+    let (parent_hash, timestamp, prev_randao) =
+        (inner.parent_hash, inner.timestamp, inner.prev_randao);
+    let auctions = auctions
+        .into_iter()
+        .filter(|auction| {
+            let attributes = &auction.attributes.inner;
+            attributes.parent == parent_hash &&
+                attributes.timestamp == timestamp &&
+                attributes.prev_randao == prev_randao
+        })
+        .collect::<Vec<_>>();
+    let parent_beacon_block_root = auctions
+        .first()
+        .ok_or(Error::NoAuction { parent_hash, timestamp })?
+        .attributes
+        .inner
+        .parent_beacon_block_root;
+
+    // NOTE: checks the block hash commits to the contents of the payload
+    let block =
+        try_into_sealed_block(ExecutionPayload::V3(execution_payload), parent_beacon_block_root)?;
+    let (recipient, payment) =
+        proposer_payment(&block.body.transactions).ok_or(Error::MissingPayment)?;
+    let auction = auctions
+        .into_iter()
+        .find(|auction| auction.proposer.fee_recipient == recipient)
+        .ok_or(Error::MissingPayment)?;
+    if block.body.withdrawals.as_ref() != Some(&auction.attributes.inner.withdrawals) {
+        return Err(Error::Withdrawals)
+    }
+    if payment < value {
+        return Err(Error::Underpaid { payment, value })
+    }
+    let sidecars = to_sidecars(&block.body.transactions, blobs_bundle)?;
+
+    let mut payload = EthBuiltPayload::new(auction.attributes.payload_id(), block, value, None);
+    payload.extend_sidecars(sidecars);
+    Ok((payload, auction))
+}
+
+/// Checks an external block is valid before the builder signs a bid for it
+pub trait BlockValidator: Send + Sync {
+    /// Execute `block` on top of its parent, checking it commits to the outcome of its execution
+    /// and has the gas limit a proposer with `proposer_gas_limit` prefers
+    fn validate(&self, block: &SealedBlock, proposer_gas_limit: u64) -> Result<(), Error>;
+}
+
+/// Validates external blocks by executing them against the state of the node, like any block of
+/// the chain
+pub struct ExecutionValidator<Client, BlockExecutor> {
+    client: Client,
+    block_executor: BlockExecutor,
+    // NOTE: the builder only signs bids for blocks whose fees it collects, like its own
+    fee_recipient: Address,
+}
+
+impl<Client, BlockExecutor> ExecutionValidator<Client, BlockExecutor> {
+    pub fn new(client: Client, block_executor: BlockExecutor, fee_recipient: Address) -> Self {
+        Self { client, block_executor, fee_recipient }
+    }
+}
+
+impl<Client, BlockExecutor> BlockValidator for ExecutionValidator<Client, BlockExecutor>
+where
+    Client: StateProviderFactory + HeaderProvider + Send + Sync,
+    BlockExecutor: BlockExecutorProvider,
+{
+    fn validate(&self, block: &SealedBlock, proposer_gas_limit: u64) -> Result<(), Error> {
+        if block.beneficiary != self.fee_recipient {
+            return Err(Error::FeeRecipient {
+                fee_recipient: block.beneficiary,
+                expected: self.fee_recipient,
+            })
+        }
+        let parent = self
+            .client
+            .header(&block.parent_hash)
+            .map_err(|err| Error::Execution(err.to_string()))?
+            .ok_or(Error::UnknownParent(block.parent_hash))?;
+        let expected = compute_preferred_gas_limit(proposer_gas_limit, parent.gas_limit);
+        if block.gas_limit != expected {
+            return Err(Error::GasLimit { gas_limit: block.gas_limit, expected })
+        }
+
+        let state_provider = self
+            .client
+            .state_by_block_hash(block.parent_hash)
+            .map_err(|err| Error::Execution(err.to_string()))?;
+        let block_with_senders = block
+            .clone()
+            .unseal()
+            .with_recovered_senders()
+            .ok_or(Error::Execution("could not recover senders".to_string()))?;
+        let output = self
+            .block_executor
+            .executor(StateProviderDatabase::new(&state_provider))
+            .execute(BlockExecutionInput::new(&block_with_senders, U256::ZERO))
+            .map_err(|err| Error::Execution(err.to_string()))?;
+        if output.gas_used != block.gas_used {
+            return Err(Error::ExecutionMismatch("gas used"))
+        }
+        if !output.receipts.last().is_some_and(|receipt| receipt.success) {
+            return Err(Error::PaymentReverted)
+        }
+
+        let receipts = Receipts::from(vec![output.receipts.into_iter().map(Some).collect()]);
+        let execution_outcome = ExecutionOutcome::new(output.state, receipts, block.number, vec![]);
+        let receipts_root =
+            execution_outcome.receipts_root_slow(block.number).expect("number is in range");
+        if receipts_root != block.receipts_root {
+            return Err(Error::ExecutionMismatch("receipts root"))
+        }
+        let logs_bloom =
+            execution_outcome.block_logs_bloom(block.number).expect("number is in range");
+        if logs_bloom != block.logs_bloom {
+            return Err(Error::ExecutionMismatch("logs bloom"))
+        }
+        let state_root = state_provider
+            .state_root(execution_outcome.hash_state_slow())
+            .map_err(|err| Error::Execution(err.to_string()))?;
+        if state_root != block.state_root {
+            return Err(Error::ExecutionMismatch("state root"))
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Pipelines {
+    // name of each pipeline, by its API key
+    pipelines: Arc<HashMap<String, String>>,
+    blocks: mpsc::Sender<ExternalBlock>,
+}
+
+impl Pipelines {
+    fn authenticate(&self, headers: &HeaderMap) -> Result<String, Error> {
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|api_key| self.pipelines.get(api_key))
+            .cloned()
+            .ok_or(Error::Unauthorized)
+    }
+}
+
+async fn handle_submit_block(
+    State(pipelines): State<Pipelines>,
+    headers: HeaderMap,
+    Json(request): Json<SubmitBlockRequest>,
+) -> Result<Json<SubmitBlockResponse>, Error> {
+    let pipeline = pipelines.authenticate(&headers)?;
+    let (response, rx) = oneshot::channel();
+    let block = ExternalBlock { pipeline, request, response };
+    pipelines.blocks.send(block).await.map_err(|_| Error::Unavailable)?;
+    let response = rx.await.map_err(|_| Error::Unavailable)??;
+    Ok(Json(response))
+}
+
+/// Serve the endpoint for pipelines to submit blocks, sending each to the auctioneer over `blocks`
+pub fn spawn(config: Config, blocks: mpsc::Sender<ExternalBlock>) -> JoinHandle<()> {
+    let pipelines = config
+        .pipelines
+        .into_iter()
+        .map(|pipeline| (pipeline.api_key, pipeline.name))
+        .collect::<HashMap<_, _>>();
+    let pipeline_count = pipelines.len();
+    let state = Pipelines { pipelines: Arc::new(pipelines), blocks };
+    let router =
+        Router::new().route("/blocks/v1/submit", post(handle_submit_block)).with_state(state);
+    let addr = SocketAddr::from((config.host.unwrap_or(Ipv4Addr::LOCALHOST), config.port));
+    let server = axum::Server::bind(&addr).serve(router.into_make_service());
+    tokio::spawn(async move {
+        info!(%addr, pipelines = pipeline_count, "serving endpoint for external blocks...");
+        if let Err(err) = server.await {
+            error!(%err, "error while serving endpoint for external blocks")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        auctioneer::auction_schedule::Proposer,
+        payload::attributes::BuilderPayloadBuilderAttributes,
+    };
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::{TxEip1559, TxEip4844};
+    use reth::{
+        api::PayloadBuilderAttributes,
+        primitives::{
+            revm_primitives::{
+                alloy_primitives::{Bloom, FixedBytes, Parity},
+                TxKind,
+            },
+            Signature, Transaction,
+        },
+        rpc::{
+            compat::engine::payload::try_into_block,
+            types::engine::{ExecutionPayloadV1, ExecutionPayloadV2, PayloadAttributes},
+        },
+    };
+
+    fn sign(tx: Transaction) -> TransactionSigned {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        )
+    }
+
+    fn blob_transaction(commitments: &[FixedBytes<48>]) -> TransactionSigned {
+        sign(Transaction::Eip4844(TxEip4844 {
+            chain_id: 1,
+            blob_versioned_hashes: commitments
+                .iter()
+                .map(|commitment| kzg_to_versioned_hash(commitment.as_slice()))
+                .collect(),
+            ..Default::default()
+        }))
+    }
+
+    fn payment(to: Address, value: u64) -> TransactionSigned {
+        sign(Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            to: TxKind::Call(to),
+            value: U256::from(value),
+            ..Default::default()
+        }))
+    }
+
+    fn auction(slot: Slot, parent: B256, fee_recipient: Address) -> AuctionContext {
+        let attributes = PayloadAttributes {
+            timestamp: 12 * slot,
+            prev_randao: B256::repeat_byte(3),
+            suggested_fee_recipient: Address::ZERO,
+            withdrawals: Some(vec![]),
+            parent_beacon_block_root: Some(B256::repeat_byte(4)),
+        };
+        AuctionContext {
+            slot,
+            attributes: BuilderPayloadBuilderAttributes::new(parent, attributes),
+            proposer: Proposer { fee_recipient, gas_limit: 30_000_000, ..Default::default() },
+            relays: Default::default(),
+        }
+    }
+
+    // A payload for a block with `transactions` in `auction`, committing to its contents
+    fn execution_payload(
+        auction: &AuctionContext,
+        transactions: &[TransactionSigned],
+    ) -> ExecutionPayloadV3 {
+        let attributes = &auction.attributes;
+        let mut execution_payload = ExecutionPayloadV3 {
+            payload_inner: ExecutionPayloadV2 {
+                payload_inner: ExecutionPayloadV1 {
+                    parent_hash: attributes.parent(),
+                    fee_recipient: Address::repeat_byte(5),
+                    state_root: B256::repeat_byte(6),
+                    receipts_root: B256::repeat_byte(7),
+                    logs_bloom: Bloom::ZERO,
+                    prev_randao: attributes.prev_randao(),
+                    block_number: 1,
+                    gas_limit: 30_000_000,
+                    gas_used: 21_000,
+                    timestamp: attributes.timestamp(),
+                    extra_data: Default::default(),
+                    base_fee_per_gas: U256::from(7),
+                    block_hash: B256::ZERO,
+                    transactions: transactions
+                        .iter()
+                        .map(|transaction| transaction.envelope_encoded())
+                        .collect(),
+                },
+                withdrawals: vec![],
+            },
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+        };
+        let block = try_into_block(
+            ExecutionPayload::V3(execution_payload.clone()),
+            attributes.parent_beacon_block_root(),
+        )
+        .unwrap();
+        execution_payload.payload_inner.payload_inner.block_hash = block.seal_slow().hash();
+        execution_payload
+    }
+
+    #[test]
+    fn test_to_payload() {
+        let proposer = Address::repeat_byte(2);
+        let auctions = [
+            auction(10, B256::repeat_byte(1), Address::repeat_byte(8)),
+            auction(10, B256::repeat_byte(1), proposer),
+            auction(11, B256::repeat_byte(2), proposer),
+        ];
+        let request = |transactions: &[TransactionSigned], value: u64| SubmitBlockRequest {
+            execution_payload: execution_payload(&auctions[1], transactions),
+            blobs_bundle: None,
+            value: U256::from(value),
+        };
+
+        // NOTE: the block is matched with the auction for the proposer it pays
+        let (payload, auction) =
+            to_payload(request(&[payment(proposer, 100)], 90), &auctions).unwrap();
+        assert_eq!(auction.slot, 10);
+        assert_eq!(auction.proposer.fee_recipient, proposer);
+        assert_eq!(payload.id(), auctions[1].attributes.payload_id());
+        assert_eq!(payload.fees(), U256::from(90));
+
+        assert!(matches!(
+            to_payload(request(&[payment(proposer, 100)], 101), &auctions),
+            Err(Error::Underpaid { .. })
+        ));
+        assert!(matches!(
+            to_payload(request(&[payment(Address::repeat_byte(9), 100)], 90), &auctions),
+            Err(Error::MissingPayment)
+        ));
+        assert!(matches!(to_payload(request(&[], 0), &auctions), Err(Error::MissingPayment)));
+        assert!(matches!(
+            to_payload(request(&[payment(proposer, 100)], 90), &auctions[2..]),
+            Err(Error::NoAuction { .. })
+        ));
+
+        let mut tampered = request(&[payment(proposer, 100)], 90);
+        tampered.execution_payload.payload_inner.payload_inner.gas_used += 1;
+        assert!(matches!(to_payload(tampered, &auctions), Err(Error::InvalidPayload(..))));
+    }
+
+    #[test]
+    fn test_proposer_payment() {
+        let proposer = Address::repeat_byte(2);
+        let payment = sign(Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            to: TxKind::Call(proposer),
+            value: U256::from(100),
+            ..Default::default()
+        }));
+        assert_eq!(proposer_payment(&[payment]), Some((proposer, U256::from(100))));
+        assert_eq!(proposer_payment(&[]), None);
+    }
+
+    #[test]
+    fn test_sidecars() {
+        let commitments = [FixedBytes::repeat_byte(1), FixedBytes::repeat_byte(2)];
+        let transactions =
+            [blob_transaction(&commitments[..1]), blob_transaction(&commitments[1..])];
+        let bundle = BlobsBundleV1 {
+            commitments: commitments.to_vec(),
+            proofs: vec![FixedBytes::ZERO; 2],
+            blobs: vec![FixedBytes::ZERO; 2],
+        };
+        let sidecars = to_sidecars(&transactions, Some(bundle.clone())).unwrap();
+        assert_eq!(sidecars.len(), 2);
+        assert_eq!(sidecars[1].commitments, [commitments[1]]);
+
+        // NOTE: blobs must be in the order of the transactions carrying them
+        let reordered = [transactions[1].clone(), transactions[0].clone()];
+        assert!(matches!(to_sidecars(&reordered, Some(bundle.clone())), Err(Error::Blobs)));
+        assert!(matches!(to_sidecars(&transactions[..1], Some(bundle)), Err(Error::Blobs)));
+        assert!(matches!(to_sidecars(&transactions, None), Err(Error::Blobs)));
+        assert!(to_sidecars(&[], None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::auctioneer::submission::Error;
+use beacon_api_client::{ApiError, Error as ApiClientError};
+use ethereum_consensus::primitives::Slot;
+use mev_rs::Error as RelayError;
+use serde::Deserialize;
+
+/// How a relay responded to a submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionResponse {
+    Accepted,
+    TooLate,
+    RateLimited,
+    SimulationFailed,
+    // the relay does not accept submissions from this builder
+    UnknownBuilder,
+    Failed,
+}
+
+impl SubmissionResponse {
+    pub fn from_result(result: &Result<(), Error>) -> Self {
+        let (code, message) = match result {
+            Ok(()) => return Self::Accepted,
+            Err(Error::Relay(RelayError::Api(ApiClientError::Api(ApiError::ErrorMessage {
+                code,
+                message,
+            })))) => (code.as_u16(), message.to_lowercase()),
+            Err(_) => return Self::Failed,
+        };
+        // NOTE: relays do not agree on how they report these rejections, so match on the common
+        // wording of their error messages
+        if code == 429 ||
+            message.contains("too many") ||
+            message.contains("rate limit") ||
+            message.contains("submission limit") ||
+            message.contains("exceeded its limit")
+        {
+            Self::RateLimited
+        } else if code == 401 ||
+            code == 403 ||
+            message.contains("not currently registered") ||
+            message.contains("api key") ||
+            message.contains("unknown builder") ||
+            message.contains("not allowed")
+        {
+            Self::UnknownBuilder
+        } else if message.contains("too late") ||
+            message.contains("in time") ||
+            message.contains("outside of its window") ||
+            message.contains("past slot") ||
+            message.contains("deadline")
+        {
+            Self::TooLate
+        } else if message.contains("simulat") || message.contains("validation") {
+            Self::SimulationFailed
+        } else {
+            Self::Failed
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accepted => "accepted",
+            Self::TooLate => "too_late",
+            Self::RateLimited => "rate_limited",
+            Self::SimulationFailed => "simulation_failed",
+            Self::UnknownBuilder => "unknown_builder",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct Config {
+    /// Adapt when and how often submissions are made to each relay to its responses
+    pub enabled: bool,
+    /// Margin in milliseconds to stop submitting to a relay ahead of the time into the slot of a
+    /// submission it rejected as too late
+    pub late_margin_ms: u64,
+    /// Amount in milliseconds each relay's cutoff moves later by every epoch, so the cutoff
+    /// converges on the latest time the relay accepts submissions
+    pub cutoff_step_ms: u64,
+    /// Most a single submission rejected as too late moves the relay's cutoff earlier by, in
+    /// milliseconds, so one rejection made early in the slot, e.g. while the relay is overloaded,
+    /// does not hold submissions back for many epochs
+    pub max_cutoff_drop_ms: u64,
+    /// Least delay in milliseconds between submissions to a relay that rate limited the builder;
+    /// the delay doubles each time the relay rate limits the builder and halves each time it
+    /// accepts a submission
+    pub rate_limit_delay_ms: u64,
+    /// Greatest delay in milliseconds between submissions to a relay that rate limited the builder
+    pub max_rate_limit_delay_ms: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            late_margin_ms: 50,
+            cutoff_step_ms: 10,
+            max_cutoff_drop_ms: 100,
+            rate_limit_delay_ms: 25,
+            max_rate_limit_delay_ms: 1000,
+        }
+    }
+}
+
+/// Timing of submissions to a relay, learned from its responses
+#[derive(Debug, Default, Clone)]
+pub struct SubmissionTiming {
+    // time into the slot in milliseconds after which the relay is expected to reject submissions
+    cutoff_ms: Option<u64>,
+    // delay in milliseconds to keep between submissions in the same slot
+    delay_ms: u64,
+    // slot and time into the slot of the most recent submission
+    last_submission: Option<(Slot, u64)>,
+}
+
+impl SubmissionTiming {
+    pub fn cutoff_ms(&self) -> Option<u64> {
+        self.cutoff_ms
+    }
+
+    pub fn delay_ms(&self) -> u64 {
+        self.delay_ms
+    }
+
+    /// Indicates if a submission for `slot` at `offset_ms` into the slot is expected to be
+    /// accepted by the relay
+    pub fn allows(&self, slot: Slot, offset_ms: u64) -> bool {
+        if self.cutoff_ms.is_some_and(|cutoff| offset_ms > cutoff) {
+            return false
+        }
+        match self.last_submission {
+            Some((last_slot, last_offset)) if last_slot == slot => {
+                offset_ms >= last_offset.saturating_add(self.delay_ms)
+            }
+            _ => true,
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        config: &Config,
+        slot: Slot,
+        offset_ms: u64,
+        response: SubmissionResponse,
+    ) {
+        self.last_submission = Some((slot, offset_ms));
+        match response {
+            SubmissionResponse::Accepted => {
+                self.delay_ms /= 2;
+                if self.delay_ms < config.rate_limit_delay_ms {
+                    self.delay_ms = 0;
+                }
+            }
+            SubmissionResponse::TooLate => {
+                let cutoff = offset_ms.saturating_sub(config.late_margin_ms);
+                // NOTE: the cutoff only moves earlier by a bounded step, so it converges on the
+                // relay's cutoff over repeated rejections rather than following a single one
+                let cutoff = match self.cutoff_ms {
+                    Some(current) => {
+                        cutoff.max(current.saturating_sub(config.max_cutoff_drop_ms)).min(current)
+                    }
+                    None => cutoff,
+                };
+                self.cutoff_ms = Some(cutoff);
+            }
+            SubmissionResponse::RateLimited => {
+                self.delay_ms = self
+                    .delay_ms
+                    .saturating_mul(2)
+                    .clamp(config.rate_limit_delay_ms, config.max_rate_limit_delay_ms);
+            }
+            SubmissionResponse::SimulationFailed |
+            SubmissionResponse::UnknownBuilder |
+            SubmissionResponse::Failed => {}
+        }
+    }
+
+    /// Move the cutoff later to probe for the latest time the relay accepts submissions
+    pub fn relax(&mut self, config: &Config) {
+        if let Some(cutoff) = self.cutoff_ms.as_mut() {
+            *cutoff = cutoff.saturating_add(config.cutoff_step_ms);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    fn rejection(code: u16, message: &str) -> Result<(), Error> {
+        let code = StatusCode::from_u16(code).unwrap();
+        let err = ApiError::ErrorMessage { code, message: message.to_string() };
+        Err(Error::Relay(RelayError::Api(ApiClientError::Api(err))))
+    }
+
+    #[test]
+    fn test_response_from_result() {
+        let cases = [
+            (Ok(()), SubmissionResponse::Accepted),
+            (rejection(429, "slow down"), SubmissionResponse::RateLimited),
+            (rejection(400, "Too many submissions for slot"), SubmissionResponse::RateLimited),
+            (rejection(403, "forbidden"), SubmissionResponse::UnknownBuilder),
+            (rejection(400, "builder not allowed"), SubmissionResponse::UnknownBuilder),
+            (rejection(400, "submission too late"), SubmissionResponse::TooLate),
+            (rejection(400, "submission for past slot"), SubmissionResponse::TooLate),
+            (
+                rejection(400, "simulation failed: invalid block"),
+                SubmissionResponse::SimulationFailed,
+            ),
+            (rejection(500, "internal error"), SubmissionResponse::Failed),
+            (Err(Error::Encoding("invalid".to_string())), SubmissionResponse::Failed),
+        ];
+        for (result, response) in cases {
+            assert_eq!(SubmissionResponse::from_result(&result), response, "{result:?}");
+        }
+    }
+
+    #[test]
+    fn test_submission_timing() {
+        let config = Config { enabled: true, ..Default::default() };
+        let mut timing = SubmissionTiming::default();
+        assert!(timing.allows(1, 3000));
+
+        timing.record(&config, 1, 3000, SubmissionResponse::TooLate);
+        assert_eq!(timing.cutoff_ms(), Some(2950));
+        assert!(!timing.allows(2, 2960));
+        assert!(timing.allows(2, 2950));
+        timing.relax(&config);
+        assert!(timing.allows(2, 2960));
+
+        // NOTE: a rejection early in the slot only moves the cutoff earlier by a bounded step
+        timing.record(&config, 2, 500, SubmissionResponse::TooLate);
+        assert_eq!(timing.cutoff_ms(), Some(2860));
+        timing.record(&config, 2, 2900, SubmissionResponse::TooLate);
+        assert_eq!(timing.cutoff_ms(), Some(2850));
+        timing.record(&config, 2, 3000, SubmissionResponse::TooLate);
+        assert_eq!(timing.cutoff_ms(), Some(2850));
+
+        timing.record(&config, 2, 100, SubmissionResponse::RateLimited);
+        assert_eq!(timing.delay_ms(), 25);
+        timing.record(&config, 2, 130, SubmissionResponse::RateLimited);
+        assert_eq!(timing.delay_ms(), 50);
+        assert!(!timing.allows(2, 170));
+        assert!(timing.allows(2, 180));
+        assert!(timing.allows(3, 0));
+
+        timing.record(&config, 2, 180, SubmissionResponse::Accepted);
+        assert_eq!(timing.delay_ms(), 25);
+        timing.record(&config, 2, 210, SubmissionResponse::Accepted);
+        assert_eq!(timing.delay_ms(), 0);
+    }
+}
@@ -0,0 +1,423 @@
+//! Serve the builder API directly to local validators, so a home staker can take the builder's own
+//! bid alongside those of the relays, without a relay in between
+
+use crate::{
+    auctioneer::{auction_schedule::Proposer, AuctionContext, Signer},
+    compat::{to_blobs_bundle, to_execution_payload, to_execution_payload_header},
+    Error as BuilderError,
+};
+use async_trait::async_trait;
+use ethereum_consensus::{
+    builder::ValidatorRegistration,
+    primitives::{BlsPublicKey, Hash32, Root, Slot, U256},
+    state_transition::Context,
+};
+use mev_rs::{
+    signing::{compute_consensus_domain, verify_signed_builder_data, verify_signed_data},
+    types::{
+        auction_contents, builder_bid, AuctionContents, AuctionRequest, BlobsBundle, BuilderBid,
+        ExecutionPayload, ExecutionPayloadHeader, SignedBlindedBeaconBlock, SignedBuilderBid,
+        SignedValidatorRegistration,
+    },
+    BlindedBlockProvider, Error, RelayError,
+};
+use reth::{payload::EthBuiltPayload, primitives::revm_primitives::Address};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    net::Ipv4Addr,
+    sync::{Arc, Mutex},
+};
+use tracing::{debug, info, warn};
+
+const DEFAULT_PORT: u16 = 18550;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Address to serve the builder API on; if missing, it is only served to the local host
+    pub host: Option<Ipv4Addr>,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Public keys of the validators allowed to register with the builder; any other
+    /// registration is rejected
+    pub public_keys: Vec<BlsPublicKey>,
+    /// Least value in wei of a bid served to a local validator; below it, no bid is served and
+    /// the validator takes the best bid of the relays or builds the block itself
+    #[serde(default)]
+    pub min_bid_wei: U256,
+    /// Genesis validators root of the chain, to verify the signatures of local validators on
+    /// their blocks; if missing, it is fetched from the beacon node
+    pub genesis_validators_root: Option<Root>,
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+// A bid served to a local validator, along with the contents revealed once it is signed
+#[derive(Debug, Clone)]
+struct LocalBid {
+    parent_hash: Hash32,
+    fee_recipient: Address,
+    block_hash: Hash32,
+    signed_builder_bid: SignedBuilderBid,
+    execution_payload: ExecutionPayload,
+    blobs_bundle: BlobsBundle,
+}
+
+impl LocalBid {
+    fn value(&self) -> U256 {
+        self.signed_builder_bid.message.value()
+    }
+
+    fn to_auction_contents(&self) -> AuctionContents {
+        match &self.execution_payload {
+            execution_payload @ ExecutionPayload::Bellatrix(..) => {
+                AuctionContents::Bellatrix(execution_payload.clone())
+            }
+            execution_payload @ ExecutionPayload::Capella(..) => {
+                AuctionContents::Capella(execution_payload.clone())
+            }
+            execution_payload @ ExecutionPayload::Deneb(..) => {
+                AuctionContents::Deneb(auction_contents::deneb::AuctionContents {
+                    execution_payload: execution_payload.clone(),
+                    blobs_bundle: self.blobs_bundle.clone(),
+                })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    registrations: HashMap<BlsPublicKey, ValidatorRegistration>,
+    // most valuable bid for each slot
+    bids: HashMap<Slot, LocalBid>,
+    // bids served to a local validator, by block hash, along with the validator they were served
+    // to; kept until the end of their slot so that a better bid arriving later does not lose the
+    // payload for a header already signed
+    served: HashMap<Hash32, (Slot, BlsPublicKey, LocalBid)>,
+    // block hash of the payload revealed for each slot
+    revealed: HashMap<Slot, Hash32>,
+}
+
+/// The builder's bids for the slots of its local validators, served over the builder API
+#[derive(Clone)]
+pub struct LocalProposer {
+    config: Arc<Config>,
+    context: Arc<Context>,
+    state: Arc<Mutex<State>>,
+}
+
+impl LocalProposer {
+    pub fn new(config: Config, context: Arc<Context>) -> Self {
+        Self { config: Arc::new(config), context, state: Default::default() }
+    }
+
+    pub fn host(&self) -> Ipv4Addr {
+        self.config.host.unwrap_or(Ipv4Addr::LOCALHOST)
+    }
+
+    pub fn port(&self) -> u16 {
+        self.config.port
+    }
+
+    /// Return the registered local validator proposing with `fee_recipient`, if any
+    pub fn proposer_for(&self, fee_recipient: Address) -> Option<Proposer> {
+        let state = self.state.lock().unwrap();
+        state.registrations.values().find_map(|registration| {
+            let proposer_fee_recipient = Address::from_slice(registration.fee_recipient.as_ref());
+            (proposer_fee_recipient == fee_recipient).then(|| Proposer {
+                public_key: registration.public_key.clone(),
+                fee_recipient,
+                gas_limit: registration.gas_limit,
+            })
+        })
+    }
+
+    /// Indicates if `public_key` belongs to a registered local validator
+    pub fn is_registered(&self, public_key: &BlsPublicKey) -> bool {
+        self.state.lock().unwrap().registrations.contains_key(public_key)
+    }
+
+    /// Offer `payload` to the local validator proposing in the `auction`, keeping it if it is
+    /// the most valuable bid for the slot
+    pub async fn offer(
+        &self,
+        payload: &EthBuiltPayload,
+        auction: &AuctionContext,
+        signer: &Signer,
+    ) -> Result<(), BuilderError> {
+        let slot = auction.slot;
+        let value = payload.fees();
+        if let Some(bid) = self.state.lock().unwrap().bids.get(&slot) {
+            if bid.value() >= value {
+                return Ok(())
+            }
+        }
+
+        let fork = self.context.fork_for(slot);
+        let execution_payload = to_execution_payload(payload.block(), fork)?;
+        let header = to_execution_payload_header(&execution_payload)?;
+        let blobs_bundle = to_blobs_bundle(payload.sidecars())?;
+        let block_hash = execution_payload.block_hash().clone();
+        let parent_hash = execution_payload.parent_hash().clone();
+        let public_key = signer.public_key().clone();
+        let message = match header {
+            header @ ExecutionPayloadHeader::Bellatrix(..) => {
+                BuilderBid::Bellatrix(builder_bid::bellatrix::BuilderBid {
+                    header,
+                    value,
+                    public_key,
+                })
+            }
+            header @ ExecutionPayloadHeader::Capella(..) => {
+                BuilderBid::Capella(builder_bid::capella::BuilderBid { header, value, public_key })
+            }
+            header @ ExecutionPayloadHeader::Deneb(..) => {
+                BuilderBid::Deneb(builder_bid::deneb::BuilderBid {
+                    header,
+                    blob_kzg_commitments: blobs_bundle.commitments.clone(),
+                    value,
+                    public_key,
+                })
+            }
+        };
+        let signature = signer.sign_builder_message(&message, &self.context).await?;
+        let bid = LocalBid {
+            parent_hash,
+            fee_recipient: auction.proposer.fee_recipient,
+            block_hash,
+            signed_builder_bid: SignedBuilderBid { message, signature },
+            execution_payload,
+            blobs_bundle,
+        };
+
+        let mut state = self.state.lock().unwrap();
+        // NOTE: another bid may have landed while this one was signed
+        match state.bids.get(&slot) {
+            Some(existing) if existing.value() >= value => {}
+            _ => {
+                debug!(slot, %value, block_hash = %bid.block_hash, "offering bid to local proposer");
+                state.bids.insert(slot, bid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the bids and reveals for any slot before `retain_slot`
+    pub fn clear(&self, retain_slot: Slot) {
+        let mut state = self.state.lock().unwrap();
+        state.bids.retain(|&slot, _| slot >= retain_slot);
+        state.served.retain(|_, (slot, ..)| *slot >= retain_slot);
+        state.revealed.retain(|&slot, _| slot >= retain_slot);
+    }
+}
+
+#[async_trait]
+impl BlindedBlockProvider for LocalProposer {
+    async fn register_validators(
+        &self,
+        registrations: &[SignedValidatorRegistration],
+    ) -> Result<(), Error> {
+        let mut accepted = Vec::with_capacity(registrations.len());
+        for registration in registrations {
+            let public_key = &registration.message.public_key;
+            if !self.config.public_keys.contains(public_key) {
+                warn!(%public_key, "rejecting registration of validator not configured as local");
+                return Err(RelayError::ProposerNotAllowed(public_key.clone()).into())
+            }
+            verify_signed_builder_data(
+                &registration.message,
+                public_key,
+                &registration.signature,
+                &self.context,
+            )?;
+            accepted.push(registration.message.clone());
+        }
+        let mut state = self.state.lock().unwrap();
+        for registration in accepted {
+            state.registrations.insert(registration.public_key.clone(), registration);
+        }
+        Ok(())
+    }
+
+    async fn fetch_best_bid(
+        &self,
+        auction_request: &AuctionRequest,
+    ) -> Result<SignedBuilderBid, Error> {
+        let AuctionRequest { slot, parent_hash, public_key } = auction_request;
+        let mut state = self.state.lock().unwrap();
+        let registration = state
+            .registrations
+            .get(public_key)
+            .ok_or_else(|| RelayError::ValidatorNotRegistered(public_key.clone()))?;
+        let fee_recipient = Address::from_slice(registration.fee_recipient.as_ref());
+        // NOTE: only serve a bid built on the requested parent for the fee recipient of the
+        // requesting validator, and only if it is worth taking over the public market
+        let bid = state
+            .bids
+            .get(slot)
+            .filter(|bid| &bid.parent_hash == parent_hash && bid.fee_recipient == fee_recipient)
+            .cloned()
+            .ok_or_else(|| Error::NoBidPrepared(auction_request.clone()))?;
+        if bid.value() < self.config.min_bid_wei {
+            debug!(slot, value = %bid.value(), min_bid_wei = %self.config.min_bid_wei, "not serving bid below minimum to local proposer");
+            return Err(Error::NoBidPrepared(auction_request.clone()))
+        }
+        info!(slot, value = %bid.value(), block_hash = %bid.block_hash, %public_key, "serving bid to local proposer");
+        let signed_builder_bid = bid.signed_builder_bid.clone();
+        state.served.insert(bid.block_hash.clone(), (*slot, public_key.clone(), bid));
+        Ok(signed_builder_bid)
+    }
+
+    async fn open_bid(
+        &self,
+        signed_block: &SignedBlindedBeaconBlock,
+    ) -> Result<AuctionContents, Error> {
+        let block = signed_block.message();
+        let slot = block.slot();
+        let block_hash = block.body().execution_payload_header().block_hash().clone();
+        let genesis_validators_root = self
+            .config
+            .genesis_validators_root
+            .as_ref()
+            .ok_or(RelayError::InvalidSignedBlindedBeaconBlock)?;
+        let mut state = self.state.lock().unwrap();
+        let (public_key, bid) = state
+            .served
+            .get(&block_hash)
+            .filter(|(served_slot, ..)| *served_slot == slot)
+            .map(|(_, public_key, bid)| (public_key.clone(), bid.clone()))
+            .ok_or_else(|| RelayError::PayloadNotUploaded(block_hash.clone()))?;
+        // NOTE: only the validator the bid was served to can have the payload revealed, and only
+        // with a block it signed, so that nothing is revealed or recorded for anyone else
+        let domain = compute_consensus_domain(slot, genesis_validators_root, &self.context)?;
+        if let Err(err) = verify_signed_data(&block, signed_block.signature(), &public_key, domain)
+        {
+            warn!(slot, %block_hash, %public_key, %err, "refusing to reveal payload for block not signed by local proposer");
+            return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+        }
+        // NOTE: reveal at most one payload per slot, so the builder never helps its validators
+        // sign two blocks for the same slot
+        if let Some(revealed) = state.revealed.get(&slot) {
+            if revealed != &block_hash {
+                warn!(slot, %block_hash, %revealed, "refusing to reveal a second payload for slot");
+                return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+            }
+        }
+        let auction_contents = bid.to_auction_contents();
+        state.revealed.insert(slot, block_hash.clone());
+        info!(slot, %block_hash, "revealed payload to local proposer");
+        Ok(auction_contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::{crypto::SecretKey, signing::sign_with_domain};
+
+    #[cfg(not(feature = "minimal-preset"))]
+    use ethereum_consensus::capella::mainnet as capella;
+    #[cfg(feature = "minimal-preset")]
+    use ethereum_consensus::capella::minimal as capella;
+
+    fn local_proposer(public_key: BlsPublicKey, context: Arc<Context>) -> LocalProposer {
+        let config = Config {
+            host: None,
+            port: DEFAULT_PORT,
+            public_keys: vec![public_key.clone()],
+            min_bid_wei: Default::default(),
+            genesis_validators_root: Some(Root::try_from([23u8; 32].as_ref()).unwrap()),
+        };
+        let local_proposer = LocalProposer::new(config, context);
+        let registration = ValidatorRegistration {
+            fee_recipient: Default::default(),
+            gas_limit: 30_000_000,
+            timestamp: 0,
+            public_key: public_key.clone(),
+        };
+        local_proposer.state.lock().unwrap().registrations.insert(public_key, registration);
+        local_proposer
+    }
+
+    fn signed_block(
+        slot: Slot,
+        header: capella::ExecutionPayloadHeader,
+        signing_key: &SecretKey,
+        local_proposer: &LocalProposer,
+    ) -> SignedBlindedBeaconBlock {
+        let message = capella::BlindedBeaconBlock {
+            slot,
+            body: capella::BlindedBeaconBlockBody {
+                execution_payload_header: header,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let genesis_validators_root = local_proposer.config.genesis_validators_root.unwrap();
+        let domain =
+            compute_consensus_domain(slot, &genesis_validators_root, &local_proposer.context)
+                .unwrap();
+        let signature = sign_with_domain(&message, signing_key, domain).unwrap();
+        SignedBlindedBeaconBlock::Capella(capella::SignedBlindedBeaconBlock { message, signature })
+    }
+
+    #[tokio::test]
+    async fn test_payload_is_only_revealed_to_signing_proposer() {
+        let context = Arc::new(Context::for_mainnet());
+        let proposer = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let other = SecretKey::key_gen(&[2u8; 32]).unwrap();
+        let local_proposer = local_proposer(proposer.public_key(), context.clone());
+
+        let slot = context.capella_fork_epoch * context.slots_per_epoch + 1;
+        let header = capella::ExecutionPayloadHeader {
+            block_hash: Hash32::try_from([7u8; 32].as_ref()).unwrap(),
+            ..Default::default()
+        };
+        let execution_payload = capella::ExecutionPayload {
+            block_hash: header.block_hash.clone(),
+            ..Default::default()
+        };
+        let bid = LocalBid {
+            parent_hash: header.parent_hash.clone(),
+            fee_recipient: Address::ZERO,
+            block_hash: header.block_hash.clone(),
+            signed_builder_bid: SignedBuilderBid {
+                message: BuilderBid::Capella(builder_bid::capella::BuilderBid {
+                    header: ExecutionPayloadHeader::Capella(header.clone()),
+                    value: U256::from(1),
+                    public_key: other.public_key(),
+                }),
+                signature: Default::default(),
+            },
+            execution_payload: ExecutionPayload::Capella(execution_payload),
+            blobs_bundle: Default::default(),
+        };
+        local_proposer.state.lock().unwrap().bids.insert(slot, bid);
+
+        let auction_request = AuctionRequest {
+            slot,
+            parent_hash: header.parent_hash.clone(),
+            public_key: proposer.public_key(),
+        };
+        local_proposer.fetch_best_bid(&auction_request).await.unwrap();
+
+        // a block signed by anyone but the proposer the bid was served to reveals nothing
+        let forged = signed_block(slot, header.clone(), &other, &local_proposer);
+        assert!(local_proposer.open_bid(&forged).await.is_err());
+        assert!(local_proposer.state.lock().unwrap().revealed.is_empty());
+
+        let signed = signed_block(slot, header.clone(), &proposer, &local_proposer);
+        let auction_contents = local_proposer.open_bid(&signed).await.unwrap();
+        assert!(matches!(auction_contents, AuctionContents::Capella(..)));
+        assert_eq!(auction_contents.execution_payload().block_hash(), &header.block_hash);
+
+        // a second block for the same slot is refused, even if signed by the proposer
+        let mut other_header = header.clone();
+        other_header.block_hash = Hash32::try_from([8u8; 32].as_ref()).unwrap();
+        let equivocation = signed_block(slot, other_header, &proposer, &local_proposer);
+        assert!(local_proposer.open_bid(&equivocation).await.is_err());
+    }
+}
@@ -0,0 +1,275 @@
+use beacon_api_client::{ApiResult, Error as ApiClientError};
+use ethereum_consensus::primitives::{BlsPublicKey, Hash32, Slot, U256};
+use futures_util::future::join_all;
+use mev_rs::types::BidTrace;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::{sync::watch, time::timeout};
+use tracing::{debug, trace};
+use url::Url;
+
+const DELIVERED_PAYLOADS_PATH: &str = "/relay/v1/data/bidtraces/proposer_payload_delivered";
+const TOP_BIDS_PATH: &str = "/relay/v1/data/events/top_bid";
+// Delay before subscribing again to a relay's stream of top bids, once it ends or fails
+const TOP_BIDS_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(12);
+// Longest a relay's stream of top bids may go without sending anything, including the keep-alives
+// relays send every 15 seconds, before it is taken to be stalled and subscribed to again
+const TOP_BIDS_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+enum TopBidsError {
+    #[error(transparent)]
+    Api(#[from] ApiClientError),
+    #[error("no data for {0:?}")]
+    Idle(Duration),
+}
+
+/// The payload a relay delivered to the proposer of a slot, as reported by its data API
+// NOTE: only the fields every relay reports are read, as relays differ in any others
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeliveredPayload {
+    #[serde(with = "mev_rs::serde::as_str")]
+    pub slot: Slot,
+    pub block_hash: Hash32,
+    #[serde(rename = "builder_pubkey")]
+    pub builder_public_key: BlsPublicKey,
+    #[serde(with = "mev_rs::serde::as_str")]
+    pub value: U256,
+}
+
+/// A relay's data API, used to learn which payloads won the auctions the builder took part in
+#[derive(Debug, Clone)]
+pub struct DataApi {
+    pub relay: String,
+    endpoint: Url,
+}
+
+impl DataApi {
+    pub fn new(relay: String, mut endpoint: Url) -> Self {
+        let _ = endpoint.set_username("");
+        let _ = endpoint.set_password(None);
+        Self { relay, endpoint }
+    }
+
+    async fn fetch_delivered_payload(
+        &self,
+        client: &reqwest::Client,
+        slot: Slot,
+    ) -> Result<Option<DeliveredPayload>, ApiClientError> {
+        let mut url = self.endpoint.join(DELIVERED_PAYLOADS_PATH).map_err(ApiClientError::Url)?;
+        url.set_query(Some(&format!("slot={slot}")));
+        let response = client.get(url).send().await.map_err(ApiClientError::Http)?;
+        let result = response
+            .json::<ApiResult<Vec<DeliveredPayload>>>()
+            .await
+            .map_err(ApiClientError::Http)?;
+        match result {
+            ApiResult::Ok(payloads) => {
+                Ok(payloads.into_iter().find(|payload| payload.slot == slot))
+            }
+            ApiResult::Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Follow the relay's stream of top bids for as long as the builder runs, recording the bids
+    /// of builders other than `builder_public_key` in `top_bids`
+    pub async fn stream_top_bids(
+        self,
+        client: reqwest::Client,
+        builder_public_key: BlsPublicKey,
+        top_bids: TopBids,
+    ) {
+        loop {
+            match self.follow_top_bids(&client, &builder_public_key, &top_bids).await {
+                Ok(()) => debug!(relay = self.relay, "stream of top bids ended"),
+                Err(err) => debug!(%err, relay = self.relay, "could not stream top bids"),
+            }
+            tokio::time::sleep(TOP_BIDS_RESUBSCRIBE_DELAY).await;
+        }
+    }
+
+    async fn follow_top_bids(
+        &self,
+        client: &reqwest::Client,
+        builder_public_key: &BlsPublicKey,
+        top_bids: &TopBids,
+    ) -> Result<(), TopBidsError> {
+        let url = self.endpoint.join(TOP_BIDS_PATH).map_err(ApiClientError::Url)?;
+        let idle = |_| TopBidsError::Idle(TOP_BIDS_IDLE_TIMEOUT);
+        // NOTE: a stream can stall without the connection closing, e.g. behind a proxy, so it is
+        // dropped once nothing arrives for a while rather than read from forever
+        let mut response = timeout(TOP_BIDS_IDLE_TIMEOUT, client.get(url).send())
+            .await
+            .map_err(idle)?
+            .and_then(|response| response.error_for_status())
+            .map_err(ApiClientError::Http)?;
+        let mut buffer = String::new();
+        while let Some(chunk) = timeout(TOP_BIDS_IDLE_TIMEOUT, response.chunk())
+            .await
+            .map_err(idle)?
+            .map_err(ApiClientError::Http)?
+        {
+            buffer.push_str(&String::from_utf8_lossy(&chunk));
+            for data in take_event_data(&mut buffer) {
+                match serde_json::from_str::<BidTrace>(&data) {
+                    Ok(bid_trace) if &bid_trace.builder_public_key != builder_public_key => {
+                        trace!(relay = self.relay, slot = bid_trace.slot, value = %bid_trace.value, "received top bid");
+                        top_bids.insert(&bid_trace);
+                    }
+                    Ok(_) => {}
+                    Err(err) => debug!(%err, relay = self.relay, "could not parse top bid"),
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Remove each complete server-sent event from the start of `buffer`, returning their data
+// NOTE: events end with a blank line, and comments like keep-alives carry no data
+fn take_event_data(buffer: &mut String) -> Vec<String> {
+    let mut data = Vec::new();
+    while let Some(end) = buffer.find("\n\n") {
+        let event = buffer.drain(..end + 2).collect::<String>();
+        let lines = event.lines().filter_map(|line| line.strip_prefix("data:"));
+        data.extend(lines.map(|line| line.trim().to_string()));
+    }
+    data
+}
+
+/// The most valuable bid from any other builder in each auction, as streamed by the relays
+// NOTE: the value of a top bid may fall once its builder bids again, but the most valuable bid
+// seen is kept so the builder does not underbid a competitor that may still win
+#[derive(Debug, Clone)]
+pub struct TopBids {
+    bids: Arc<Mutex<HashMap<(Slot, Hash32), U256>>>,
+    // signals each rise of a top bid, so bids shaded down to it can be raised
+    updates: Arc<watch::Sender<()>>,
+}
+
+impl Default for TopBids {
+    fn default() -> Self {
+        let (updates, _) = watch::channel(());
+        Self { bids: Default::default(), updates: Arc::new(updates) }
+    }
+}
+
+impl TopBids {
+    pub fn insert(&self, bid_trace: &BidTrace) {
+        let mut bids = self.bids.lock().expect("can lock");
+        let value = bids.entry((bid_trace.slot, bid_trace.parent_hash.clone())).or_default();
+        if bid_trace.value > *value {
+            *value = bid_trace.value;
+            self.updates.send_replace(());
+        }
+    }
+
+    /// Value of the most valuable bid from any other builder building on `parent_hash` for `slot`
+    pub fn get(&self, slot: Slot, parent_hash: &Hash32) -> Option<U256> {
+        self.bids.lock().expect("can lock").get(&(slot, parent_hash.clone())).copied()
+    }
+
+    /// Receiver notified whenever any top bid rises
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.updates.subscribe()
+    }
+
+    /// Drop the top bids for any slot before `slot`
+    pub fn retain(&self, slot: Slot) {
+        self.bids.lock().expect("can lock").retain(|(bid_slot, _), _| *bid_slot >= slot);
+    }
+}
+
+/// Fetch the payload delivered for `slot` from any of `apis`, along with the relay that
+/// delivered it
+pub async fn fetch_winning_payload(
+    apis: &[DataApi],
+    client: &reqwest::Client,
+    slot: Slot,
+) -> Option<(String, DeliveredPayload)> {
+    let results = join_all(apis.iter().map(|api| api.fetch_delivered_payload(client, slot))).await;
+    apis.iter().zip(results).find_map(|(api, result)| match result {
+        Ok(payload) => payload.map(|payload| (api.relay.clone(), payload)),
+        Err(err) => {
+            debug!(%err, relay = api.relay, slot, "could not fetch delivered payload");
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use ethereum_consensus::crypto::SecretKey;
+    use std::net::TcpListener;
+
+    // Serve a stream of `bid_traces` as top bids, ending once they are all sent
+    fn spawn_relay(bid_traces: Vec<BidTrace>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let events = bid_traces
+            .iter()
+            .map(|bid_trace| {
+                format!(
+                    "event: top_bid\ndata: {}\n\n:\n\n",
+                    serde_json::to_string(bid_trace).unwrap()
+                )
+            })
+            .collect::<String>();
+        let router = Router::new().route(TOP_BIDS_PATH, get(move || async move { events }));
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+        format!("http://{addr}").parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_stream_top_bids() {
+        let builder = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let other = SecretKey::key_gen(&[2u8; 32]).unwrap().public_key();
+        let parent_hash = Hash32::try_from([3u8; 32].as_ref()).unwrap();
+        let bid_trace = |builder_public_key: &BlsPublicKey, value: u64| BidTrace {
+            slot: 7,
+            parent_hash: parent_hash.clone(),
+            builder_public_key: builder_public_key.clone(),
+            value: U256::from(value),
+            ..Default::default()
+        };
+        // NOTE: the builder's own bids are not competition
+        let endpoint = spawn_relay(vec![
+            bid_trace(&other, 10),
+            bid_trace(&builder, 20),
+            bid_trace(&other, 5),
+            bid_trace(&other, 15),
+        ]);
+
+        let top_bids = TopBids::default();
+        let mut updates = top_bids.subscribe();
+        let api = DataApi::new("relay".to_string(), endpoint);
+        tokio::spawn(api.stream_top_bids(reqwest::Client::new(), builder, top_bids.clone()));
+        while top_bids.get(7, &parent_hash) != Some(U256::from(15)) {
+            timeout(Duration::from_secs(5), updates.changed()).await.unwrap().unwrap();
+        }
+        assert_eq!(top_bids.get(8, &parent_hash), None);
+        top_bids.retain(8);
+        assert_eq!(top_bids.get(7, &parent_hash), None);
+    }
+
+    #[test]
+    fn test_take_event_data() {
+        let mut buffer =
+            "event: top_bid\ndata: {\"slot\":\"1\"}\n\n:\n\nevent: top_bid\ndata: {\"sl"
+                .to_string();
+        assert_eq!(take_event_data(&mut buffer), vec!["{\"slot\":\"1\"}".to_string()]);
+        assert_eq!(buffer, "event: top_bid\ndata: {\"sl");
+
+        buffer.push_str("ot\":\"2\"}\n\n");
+        assert_eq!(take_event_data(&mut buffer), vec!["{\"slot\":\"2\"}".to_string()]);
+        assert!(buffer.is_empty());
+    }
+}
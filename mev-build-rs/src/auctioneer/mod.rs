@@ -1,4 +1,19 @@
 mod auction_schedule;
+mod external_blocks;
+mod feedback;
+mod local_proposer;
+mod market;
+mod onboarding;
 mod service;
+mod submission;
 
+pub use external_blocks::{
+    BlockValidator, Config as ExternalBlocksConfig, Error as ExternalBlockError,
+    ExecutionValidator, PipelineConfig, SubmitBlockRequest, SubmitBlockResponse,
+};
+pub use feedback::Config as SubmissionFeedbackConfig;
+pub use local_proposer::Config as LocalProposerConfig;
+pub use market::TopBids;
+pub use mev_rs::signer::{Config as SignerConfig, RemoteConfig as RemoteSignerConfig, Signer};
 pub use service::{AuctionContext, Config, Service};
+pub use submission::{Encoding, RelayConfig, RelayOptions, RetryPolicy, SubmissionProfile};
@@ -0,0 +1,44 @@
+use ethereum_consensus::primitives::BlsPublicKey;
+use serde::Serialize;
+use std::fmt;
+
+/// Whether a relay is ready to accept submissions from the builder
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Readiness {
+    /// The relay has not been checked yet
+    #[default]
+    Unchecked,
+    /// The relay is reachable but has not yet responded to a submission from the builder
+    Pending,
+    /// The relay has accepted a submission from the builder
+    Ready,
+    /// The relay could not be reached
+    Unreachable(String),
+    /// The relay rejected the builder, e.g. as its public key is not accepted by the relay
+    Rejected(String),
+}
+
+impl Readiness {
+    pub fn is_usable(&self) -> bool {
+        matches!(self, Self::Pending | Self::Ready)
+    }
+}
+
+impl fmt::Display for Readiness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unchecked => write!(f, "unchecked"),
+            Self::Pending => write!(f, "pending"),
+            Self::Ready => write!(f, "ready"),
+            Self::Unreachable(err) => write!(f, "unreachable: {err}"),
+            Self::Rejected(reason) => write!(f, "rejected: {reason}"),
+        }
+    }
+}
+
+/// Body of a request to a relay's registration endpoint
+#[derive(Debug, Serialize)]
+pub struct RegistrationRequest<'a> {
+    #[serde(rename = "pubkey")]
+    pub public_key: &'a BlsPublicKey,
+}
@@ -1,32 +1,47 @@
 use crate::{
-    auctioneer::auction_schedule::{AuctionSchedule, Proposals, Proposer, RelayIndex, RelaySet},
+    auctioneer::{
+        auction_schedule::{AuctionSchedule, Proposals, Proposer, RelayIndex, RelaySet},
+        external_blocks::{
+            self, BlockValidator, Config as ExternalBlocksConfig, ExternalBlock,
+            SubmitBlockResponse,
+        },
+        feedback::Config as FeedbackConfig,
+        local_proposer::{Config as LocalProposerConfig, LocalProposer},
+        market::fetch_winning_payload,
+        onboarding::Readiness,
+        submission::{RelayConfig, RetryPolicy, SubmissionRelay},
+    },
     bidder::Service as Bidder,
     compat::{to_blobs_bundle, to_bytes20, to_bytes32, to_execution_payload},
+    metrics,
     payload::attributes::{BuilderPayloadBuilderAttributes, ProposalAttributes},
+    reload::Settings,
     service::ClockMessage,
     Error,
 };
 use ethereum_consensus::{
-    clock::convert_timestamp_to_slot,
-    crypto::SecretKey,
-    primitives::{BlsPublicKey, Epoch, Slot},
+    clock::{convert_timestamp_to_slot, duration_since_unix_epoch},
+    primitives::{Epoch, Slot},
     state_transition::Context,
     Fork,
 };
+use futures_util::future::join_all;
 use mev_rs::{
-    relay::parse_relay_endpoints,
-    signing::sign_builder_message,
-    types::{block_submission, BidTrace, SignedBidSubmission},
-    BlindedBlockRelayer, Relay,
+    blinded_block_provider::Server as BlindedBlockProviderServer,
+    signer::{Config as SignerConfig, Signer},
+    types::{block_submission, BidTrace, ProposerSchedule, SignedBidSubmission},
+    BlindedBlockRelayer,
 };
 use reth::{
     api::{EngineTypes, PayloadBuilderAttributes},
     payload::{EthBuiltPayload, Events, PayloadBuilder, PayloadBuilderHandle, PayloadId},
+    primitives::revm_primitives::{B256, U256},
 };
 use serde::Deserialize;
 use std::{
     collections::{HashMap, HashSet},
     sync::Arc,
+    time::{Duration, Instant},
 };
 use tokio::sync::{
     broadcast,
@@ -41,10 +56,15 @@ const PROPOSAL_SCHEDULE_INTERVAL: u64 = 2;
 
 const DEFAULT_BUILDER_BIDDER_CHANNEL_SIZE: usize = 16;
 
-fn prepare_submission(
+const DEFAULT_SETTINGS_CHANNEL_SIZE: usize = 1;
+
+const DEFAULT_EXTERNAL_BLOCKS_CHANNEL_SIZE: usize = 16;
+
+const DEFAULT_READINESS_CHANNEL_SIZE: usize = 16;
+
+async fn prepare_submission(
     payload: &EthBuiltPayload,
-    signing_key: &SecretKey,
-    public_key: &BlsPublicKey,
+    signer: &Signer,
     auction_context: &AuctionContext,
     context: &Context,
 ) -> Result<SignedBidSubmission, Error> {
@@ -52,7 +72,7 @@ fn prepare_submission(
         slot: auction_context.slot,
         parent_hash: to_bytes32(auction_context.attributes.inner.parent),
         block_hash: to_bytes32(payload.block().hash()),
-        builder_public_key: public_key.clone(),
+        builder_public_key: signer.public_key().clone(),
         proposer_public_key: auction_context.proposer.public_key.clone(),
         proposer_fee_recipient: to_bytes20(auction_context.proposer.fee_recipient),
         gas_limit: payload.block().gas_limit,
@@ -61,7 +81,7 @@ fn prepare_submission(
     };
     let fork = context.fork_for(auction_context.slot);
     let execution_payload = to_execution_payload(payload.block(), fork)?;
-    let signature = sign_builder_message(&message, signing_key, context)?;
+    let signature = signer.sign_builder_message(&message, context).await?;
     let submission = match fork {
         Fork::Bellatrix => {
             SignedBidSubmission::Bellatrix(block_submission::bellatrix::SignedBidSubmission {
@@ -96,15 +116,41 @@ pub struct AuctionContext {
     pub relays: RelaySet,
 }
 
+// The most valuable bid the builder made, or would have made, in the auction for a slot, to
+// compare with the payload delivered for the slot
+#[derive(Debug, Clone, Copy)]
+struct BestBid {
+    value: U256,
+    block_hash: B256,
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct Config {
-    /// Secret key used to sign builder messages to relay
-    pub secret_key: SecretKey,
-    #[serde(skip)]
-    /// Public key corresponding to secret key
-    pub public_key: BlsPublicKey,
+    /// Either a `secret_key` used to sign builder messages to relays or the `remote_signer`
+    /// holding it
+    #[serde(flatten)]
+    pub signer: SignerConfig,
     /// List of relays to submit bids
-    pub relays: Vec<String>,
+    pub relays: Vec<RelayConfig>,
+    /// Policy to retry failed submissions to any relay without its own policy
+    #[serde(default)]
+    pub submission_retry: RetryPolicy,
+    /// Adapt the timing of submissions to each relay to how it responds to them
+    #[serde(default)]
+    pub submission_feedback: FeedbackConfig,
+    /// Number of slots between fetches of the proposer schedules from the relays; if missing, the
+    /// schedules are fetched twice per epoch
+    pub proposer_schedule_refresh_slots: Option<u64>,
+    /// Serve the builder API to the local validators listed, so they can take the builder's own
+    /// bid when no relay offers a better one
+    pub local_proposer: Option<LocalProposerConfig>,
+    /// Build payloads and compute bids without submitting them to any relay, comparing the best
+    /// bid for each slot with the payload the relays delivered instead
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Serve an endpoint for trusted pipelines to submit blocks they built, which are signed and
+    /// submitted to the relays like the builder's own
+    pub external_blocks: Option<ExternalBlocksConfig>,
 }
 
 pub struct Service<
@@ -115,13 +161,32 @@ pub struct Service<
 > {
     clock: broadcast::Receiver<ClockMessage>,
     builder: PayloadBuilderHandle<Engine>,
-    relays: Vec<Relay>,
+    relays: Vec<SubmissionRelay>,
+    signer: Signer,
     config: Config,
     context: Arc<Context>,
     // TODO consolidate this somewhere...
     genesis_time: u64,
     bidder: Bidder,
     bids: Receiver<EthBuiltPayload>,
+    settings: Receiver<Settings>,
+    settings_tx: mpsc::Sender<Settings>,
+    external_blocks: Receiver<ExternalBlock>,
+    external_blocks_tx: mpsc::Sender<ExternalBlock>,
+    block_validator: Arc<dyn BlockValidator>,
+    // external blocks once executed, to submit to the relays
+    external_payloads: Receiver<EthBuiltPayload>,
+    external_payloads_tx: mpsc::Sender<EthBuiltPayload>,
+    // readiness of relays checked in the background, by their index
+    readiness_updates: Receiver<(RelayIndex, Readiness)>,
+    readiness_tx: mpsc::Sender<(RelayIndex, Readiness)>,
+    // proposer schedules fetched in the background, by the index of their relay
+    proposer_schedules: Receiver<(RelayIndex, Vec<ProposerSchedule>)>,
+    proposer_schedules_tx: mpsc::Sender<(RelayIndex, Vec<ProposerSchedule>)>,
+    // client for the data APIs of the relays
+    client: reqwest::Client,
+    best_bids: HashMap<Slot, BestBid>,
+    local_proposer: Option<LocalProposer>,
 
     auction_schedule: AuctionSchedule,
     open_auctions: HashMap<PayloadId, Arc<AuctionContext>>,
@@ -140,28 +205,68 @@ impl<
         builder: PayloadBuilderHandle<Engine>,
         bidder: Bidder,
         bids: Receiver<EthBuiltPayload>,
-        mut config: Config,
+        config: Config,
         context: Arc<Context>,
         genesis_time: u64,
-    ) -> Self {
-        let relays =
-            parse_relay_endpoints(&config.relays).into_iter().map(Relay::from).collect::<Vec<_>>();
-
-        config.public_key = config.secret_key.public_key();
-
-        Self {
+        block_validator: Arc<dyn BlockValidator>,
+    ) -> Result<Self, url::ParseError> {
+        let signer = Signer::new(config.signer.clone())?;
+        info!(public_key = %signer.public_key(), "configured builder signer");
+
+        let relays = SubmissionRelay::load_all(
+            &config.relays,
+            config.submission_retry,
+            config.submission_feedback,
+        );
+        if relays.is_empty() {
+            error!("no relays could be loaded from the configuration; please fix and restart");
+        }
+        let (settings_tx, settings) = mpsc::channel(DEFAULT_SETTINGS_CHANNEL_SIZE);
+        let (external_blocks_tx, external_blocks) =
+            mpsc::channel(DEFAULT_EXTERNAL_BLOCKS_CHANNEL_SIZE);
+        let (external_payloads_tx, external_payloads) =
+            mpsc::channel(DEFAULT_EXTERNAL_BLOCKS_CHANNEL_SIZE);
+        let (readiness_tx, readiness_updates) = mpsc::channel(DEFAULT_READINESS_CHANNEL_SIZE);
+        let (proposer_schedules_tx, proposer_schedules) =
+            mpsc::channel(DEFAULT_READINESS_CHANNEL_SIZE);
+        let local_proposer = config
+            .local_proposer
+            .clone()
+            .map(|local_config| LocalProposer::new(local_config, context.clone()));
+
+        Ok(Self {
             clock,
             builder,
             relays,
+            signer,
             config,
             context,
             genesis_time,
             bidder,
             bids,
+            settings,
+            settings_tx,
+            external_blocks,
+            external_blocks_tx,
+            block_validator,
+            external_payloads,
+            external_payloads_tx,
+            readiness_updates,
+            readiness_tx,
+            proposer_schedules,
+            proposer_schedules_tx,
+            client: reqwest::Client::new(),
+            best_bids: Default::default(),
+            local_proposer,
             auction_schedule: Default::default(),
             open_auctions: Default::default(),
             processed_payload_attributes: Default::default(),
-        }
+        })
+    }
+
+    /// Return a handle to send the auctioneer new [`Settings`] to apply
+    pub fn settings_handle(&self) -> mpsc::Sender<Settings> {
+        self.settings_tx.clone()
     }
 
     async fn fetch_proposer_schedules(&mut self) {
@@ -172,7 +277,10 @@ impl<
         // TODO: rework `auction_schedule` so there is no issue with confusing relays and their
         // indices
         for (relay_index, relay) in self.relays.iter().enumerate() {
-            match relay.get_proposal_schedule().await {
+            if relay.is_retired() {
+                continue
+            }
+            match relay.relay.get_proposal_schedule().await {
                 Ok(schedule) => {
                     let slots = self.auction_schedule.process(relay_index, &schedule);
                     info!(?slots, %relay, "processed proposer schedule");
@@ -184,20 +292,176 @@ impl<
         }
     }
 
+    // NOTE: fetch the proposer schedules in the background, e.g. when reloading the settings, so
+    // the auctioneer keeps processing other events while the relays respond
+    fn refetch_proposer_schedules(&self) {
+        for (relay_index, relay) in self.relays.iter().enumerate() {
+            if relay.is_retired() {
+                continue
+            }
+            let relay = relay.relay.clone();
+            let proposer_schedules_tx = self.proposer_schedules_tx.clone();
+            tokio::spawn(async move {
+                match relay.get_proposal_schedule().await {
+                    Ok(schedule) => {
+                        let _ = proposer_schedules_tx.send((relay_index, schedule)).await;
+                    }
+                    Err(err) => {
+                        warn!(err = %err, %relay, "error fetching proposer schedule from relay")
+                    }
+                }
+            });
+        }
+    }
+
+    fn on_proposer_schedule(&mut self, relay_index: RelayIndex, schedule: Vec<ProposerSchedule>) {
+        let Some(relay) = self.relays.get(relay_index) else { return };
+        if relay.is_retired() {
+            return
+        }
+        let slots = self.auction_schedule.process(relay_index, &schedule);
+        info!(?slots, %relay, "processed proposer schedule");
+    }
+
     async fn on_slot(&mut self, slot: Slot) {
         debug!(slot, "processed");
-        if (slot * PROPOSAL_SCHEDULE_INTERVAL) % self.context.slots_per_epoch == 0 {
+        let refresh_interval = self
+            .config
+            .proposer_schedule_refresh_slots
+            .unwrap_or(self.context.slots_per_epoch / PROPOSAL_SCHEDULE_INTERVAL)
+            .max(1);
+        if slot % refresh_interval == 0 {
             self.fetch_proposer_schedules().await;
         }
+        self.compare_best_bids(slot);
+        // NOTE: dropping the auctions for past slots closes their bidders once the build jobs for
+        // them end, rather than at the end of the epoch
+        self.open_auctions.retain(|_, auction| auction.slot >= slot);
+    }
+
+    fn record_bid(&mut self, slot: Slot, payload: &EthBuiltPayload) -> BestBid {
+        let bid = BestBid { value: payload.fees(), block_hash: payload.block().hash() };
+        let best_bid = self.best_bids.entry(slot).or_insert(bid);
+        if bid.value > best_bid.value {
+            *best_bid = bid;
+        }
+        bid
+    }
+
+    // Compare the best bids for slots before `current_slot` with the payloads the relays
+    // delivered for those slots
+    // NOTE: the payload for a slot is delivered at the start of the slot, so it is available from
+    // the data APIs by the start of the next slot
+    fn compare_best_bids(&mut self, current_slot: Slot) {
+        let slots =
+            self.best_bids.keys().copied().filter(|&slot| slot < current_slot).collect::<Vec<_>>();
+        if slots.is_empty() {
+            return
+        }
+        let apis = self
+            .relays
+            .iter()
+            .filter(|relay| !relay.is_retired())
+            .map(|relay| relay.data_api())
+            .collect::<Vec<_>>();
+        let dry_run = self.config.dry_run;
+        for slot in slots {
+            let Some(bid) = self.best_bids.remove(&slot) else { continue };
+            let apis = apis.clone();
+            let client = self.client.clone();
+            tokio::spawn(async move {
+                let Some((relay, delivered)) = fetch_winning_payload(&apis, &client, slot).await
+                else {
+                    metrics::on_auction_compared("undelivered");
+                    info!(
+                        slot,
+                        value = %bid.value,
+                        block_hash = %bid.block_hash,
+                        dry_run,
+                        "no relay delivered a payload for the slot"
+                    );
+                    return
+                };
+                // NOTE: on a dry run, the builder's bid would have won if it was worth more than
+                // the delivered payload
+                let won = if dry_run {
+                    bid.value > delivered.value
+                } else {
+                    delivered.block_hash == to_bytes32(bid.block_hash)
+                };
+                metrics::on_auction_compared(if won { "won" } else { "lost" });
+                metrics::on_bid_value_gap(bid.value, delivered.value);
+                info!(
+                    slot,
+                    value = %bid.value,
+                    block_hash = %bid.block_hash,
+                    delivered_value = %delivered.value,
+                    delivered_block_hash = %delivered.block_hash,
+                    delivered_builder = %delivered.builder_public_key,
+                    %relay,
+                    won,
+                    dry_run,
+                    "compared best bid with the delivered payload"
+                );
+            });
+        }
     }
 
     async fn on_epoch(&mut self, epoch: Epoch) {
         debug!(epoch, "processed");
+        for relay in &mut self.relays {
+            let stats = relay.take_stats();
+            if stats.successes + stats.failures > 0 {
+                info!(
+                    %relay,
+                    successes = stats.successes,
+                    failures = stats.failures,
+                    retries = stats.retries,
+                    too_late = stats.too_late,
+                    rate_limited = stats.rate_limited,
+                    simulation_failed = stats.simulation_failed,
+                    cutoff_ms = ?relay.timing().cutoff_ms(),
+                    delay_ms = relay.timing().delay_ms(),
+                    "relay submissions in last epoch"
+                );
+            }
+            relay.relax_timing();
+        }
+        self.check_unready_relays();
+
         // NOTE: clear stale state
         let retain_slot = epoch * self.context.slots_per_epoch;
         self.auction_schedule.clear(retain_slot);
         self.open_auctions.retain(|_, auction| auction.slot >= retain_slot);
         self.processed_payload_attributes.retain(|&slot, _| slot >= retain_slot);
+        self.best_bids.retain(|&slot, _| slot >= retain_slot);
+        self.bidder.top_bids().retain(retain_slot);
+        if let Some(local_proposer) = &self.local_proposer {
+            local_proposer.clear(retain_slot);
+        }
+    }
+
+    // NOTE: relays not ready for submissions are checked again in the background each epoch, so a
+    // relay that was down when the builder launched, or that later accepts the builder, is used
+    // once it is ready
+    fn check_unready_relays(&self) {
+        let public_key = self.signer.public_key();
+        for (relay_index, relay) in self.relays.iter().enumerate() {
+            if relay.is_retired() || relay.readiness().is_usable() {
+                continue
+            }
+            let check = relay.check_readiness(public_key);
+            let readiness_tx = self.readiness_tx.clone();
+            tokio::spawn(async move {
+                let _ = readiness_tx.send((relay_index, check.await)).await;
+            });
+        }
+    }
+
+    fn on_readiness(&mut self, relay_index: RelayIndex, readiness: Readiness) {
+        if let Some(relay) = self.relays.get_mut(relay_index) {
+            relay.set_readiness(readiness);
+        }
     }
 
     fn get_proposals(&self, slot: Slot) -> Option<Proposals> {
@@ -240,6 +504,7 @@ impl<
         }
 
         let payload_id = auction.attributes.payload_id();
+        metrics::on_auction_opened();
         self.bidder.start_bid(auction, revenue_updates);
         Some(payload_id)
     }
@@ -266,36 +531,86 @@ impl<
             return
         }
 
-        if let Some(proposals) = self.get_proposals(slot) {
-            for (proposer, relays) in proposals {
-                if let Some(payload_id) =
-                    self.open_auction(slot, proposer, relays, attributes.clone()).await
-                {
-                    self.observe_payload_id(slot, payload_id);
-                }
+        // NOTE: only build for slots whose proposer is registered with one of the relays or is a
+        // local validator, as the builder can not win any other auction
+        let Some(proposals) = self.get_proposals(slot).or_else(|| {
+            let local_proposer = self.local_proposer.as_ref()?;
+            let proposer = local_proposer.proposer_for(attributes.inner.suggested_fee_recipient)?;
+            Some(Proposals::from([(proposer, RelaySet::default())]))
+        }) else {
+            debug!(slot, "no proposer for the slot is registered with any relay; not building");
+            metrics::on_auction_skipped();
+            return
+        };
+        for (proposer, relays) in proposals {
+            if let Some(payload_id) =
+                self.open_auction(slot, proposer, relays, attributes.clone()).await
+            {
+                self.observe_payload_id(slot, payload_id);
+            }
+        }
+    }
+
+    // The open auction to submit `payload` in, unless it closed or the builder is only recording
+    // its bids
+    fn auction_for_submission(&mut self, payload: &EthBuiltPayload) -> Option<Arc<AuctionContext>> {
+        // NOTE: a payload finalized late may arrive once its auction has closed
+        let Some(auction) = self.open_auctions.get(&payload.id()).cloned() else {
+            debug!(payload_id = %payload.id(), block_hash = %payload.block().hash(), "dropping payload for closed auction");
+            return None
+        };
+        if self.config.dry_run {
+            let bid = self.record_bid(auction.slot, payload);
+            info!(
+                slot = auction.slot,
+                block_number = payload.block().number,
+                block_hash = %bid.block_hash,
+                value = %bid.value,
+                "dry run: not submitting payload"
+            );
+            return None
+        }
+        Some(auction)
+    }
+
+    async fn submit_payload(&mut self, payload: EthBuiltPayload) {
+        let Some(auction) = self.auction_for_submission(&payload) else { return };
+        if let Some(local_proposer) = self
+            .local_proposer
+            .as_ref()
+            .filter(|local| local.is_registered(&auction.proposer.public_key))
+        {
+            if let Err(err) = local_proposer.offer(&payload, &auction, &self.signer).await {
+                warn!(%err, slot = auction.slot, "could not offer bid to local proposer");
             }
         }
+        self.submit_to_relays(&auction, payload).await;
     }
 
-    async fn submit_payload(&self, payload: EthBuiltPayload) {
-        let auction = self.open_auctions.get(&payload.id()).expect("has auction");
+    async fn submit_to_relays(&mut self, auction: &AuctionContext, payload: EthBuiltPayload) {
         let mut successful_relays_for_submission = Vec::with_capacity(auction.relays.len());
-        match prepare_submission(
-            &payload,
-            &self.config.secret_key,
-            &self.config.public_key,
-            auction,
-            &self.context,
-        ) {
+        // NOTE: the submission phase spans signing the bid and waiting on every relay
+        let start = Instant::now();
+        match prepare_submission(&payload, &self.signer, auction, &self.context).await {
             Ok(signed_submission) => {
-                // TODO: parallel dispatch
+                let slot_start = Duration::from_secs(auction.attributes.timestamp());
+                let offset_ms =
+                    duration_since_unix_epoch().saturating_sub(slot_start).as_millis() as u64;
+                let mut relay_indices = Vec::with_capacity(auction.relays.len());
+                let mut submissions = Vec::with_capacity(auction.relays.len());
                 for &relay_index in &auction.relays {
-                    match self.relays.get(relay_index) {
+                    match self.relays.get_mut(relay_index) {
+                        Some(relay) if relay.is_retired() => {
+                            debug!(%relay, slot = auction.slot, "skipping submission to relay removed from the configuration");
+                        }
+                        Some(relay) if !relay.readiness().is_usable() => {
+                            debug!(%relay, slot = auction.slot, readiness = %relay.readiness(), "skipping submission to relay not ready for the builder");
+                        }
                         Some(relay) => {
-                            if let Err(err) = relay.submit_bid(&signed_submission).await {
-                                warn!(%err, ?relay, slot = auction.slot, "could not submit payload");
+                            if relay.try_acquire_submission(auction.slot, offset_ms) {
+                                relay_indices.push(relay_index);
                             } else {
-                                successful_relays_for_submission.push(relay_index);
+                                debug!(%relay, slot = auction.slot, offset_ms, "skipping submission to relay at its limit for the slot or past its expected cutoff");
                             }
                         }
                         None => {
@@ -305,12 +620,35 @@ impl<
                         }
                     }
                 }
+                for &relay_index in &relay_indices {
+                    submissions.push(self.relays[relay_index].submit_bid(&signed_submission));
+                }
+                let outcomes = join_all(submissions).await;
+                if !relay_indices.is_empty() {
+                    metrics::on_build_phase("submission", start.elapsed());
+                }
+                for (relay_index, outcome) in relay_indices.into_iter().zip(outcomes) {
+                    let relay = &mut self.relays[relay_index];
+                    let response = relay.record(&outcome, auction.slot, offset_ms);
+                    match outcome.result {
+                        Ok(()) => {
+                            metrics::on_submission_accepted(&relay.to_string());
+                            successful_relays_for_submission.push(relay_index)
+                        }
+                        Err(err) => {
+                            metrics::on_submission_rejected(&relay.to_string(), response.as_str());
+                            warn!(%err, %relay, retries = outcome.retries, slot = auction.slot, offset_ms, response = response.as_str(), "could not submit payload")
+                        }
+                    }
+                }
             }
             Err(err) => {
                 warn!(%err, slot = auction.slot, "could not prepare submission")
             }
         }
         if !successful_relays_for_submission.is_empty() {
+            self.record_bid(auction.slot, &payload);
+            metrics::on_bid(payload.fees());
             let relay_set = successful_relays_for_submission
                 .into_iter()
                 .map(|index| format!("{0}", self.relays[index]))
@@ -329,6 +667,78 @@ impl<
         }
     }
 
+    fn on_external_block(&mut self, block: ExternalBlock) {
+        let ExternalBlock { pipeline, request, response } = block;
+        let block_hash = request.execution_payload.payload_inner.payload_inner.block_hash;
+        let (payload, slot, proposer_gas_limit) = match external_blocks::to_payload(
+            request,
+            self.open_auctions.values().map(Arc::as_ref),
+        ) {
+            Ok((payload, auction)) => (payload, auction.slot, auction.proposer.gas_limit),
+            Err(err) => {
+                metrics::on_external_block(&pipeline, false);
+                warn!(%err, %block_hash, pipeline, "rejected external block");
+                let _ = response.send(Err(err));
+                return
+            }
+        };
+        // NOTE: the block is executed on a blocking task so as not to hold up the auctioneer, which
+        // only submits it once it is found valid
+        let block_validator = self.block_validator.clone();
+        let external_payloads = self.external_payloads_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            if let Err(err) = block_validator.validate(payload.block(), proposer_gas_limit) {
+                metrics::on_external_block(&pipeline, false);
+                warn!(%err, slot, %block_hash, pipeline, "rejected external block");
+                let _ = response.send(Err(err));
+                return
+            }
+            metrics::on_external_block(&pipeline, true);
+            info!(slot, %block_hash, value = %payload.fees(), pipeline, "received external block");
+            let _ = response.send(Ok(SubmitBlockResponse { block_hash, slot }));
+            if external_payloads.blocking_send(payload).is_err() {
+                warn!(slot, %block_hash, "could not submit external block");
+            }
+        });
+    }
+
+    // NOTE: external blocks are only submitted to the relays, as local proposers are only offered
+    // blocks the builder built itself
+    async fn submit_external_payload(&mut self, payload: EthBuiltPayload) {
+        let Some(auction) = self.auction_for_submission(&payload) else { return };
+        self.submit_to_relays(&auction, payload).await;
+    }
+
+    // NOTE: relays keep their index across reloads, as open auctions refer to relays by index;
+    // removed relays are retired in place rather than dropped
+    fn reload(&mut self, settings: Settings) {
+        let Settings { bidder, relays } = settings;
+        self.bidder.reconfigure(bidder);
+
+        for relay in &mut self.relays {
+            relay.retire();
+        }
+        for relay in SubmissionRelay::load_all(
+            &relays,
+            self.config.submission_retry,
+            self.config.submission_feedback,
+        ) {
+            match self.relays.iter_mut().find(|existing| existing.url() == relay.url()) {
+                Some(existing) => existing.reconfigure(relay),
+                None => self.relays.push(relay),
+            }
+        }
+        self.config.relays = relays;
+        self.check_unready_relays();
+
+        // NOTE: refetch the proposer schedules so that only the current relays are in them
+        self.auction_schedule.clear(Slot::MAX);
+        self.refetch_proposer_schedules();
+
+        let count = self.relays.iter().filter(|relay| !relay.is_retired()).count();
+        info!(count, "reloaded auctioneer settings");
+    }
+
     async fn process_clock(&mut self, message: ClockMessage) {
         use ClockMessage::*;
         match message {
@@ -350,6 +760,43 @@ impl<
             let count = self.relays.len();
             info!(count, relays = ?self.relays, "configured with relay(s)");
         }
+        if self.config.dry_run {
+            warn!("running as a dry run; payloads will not be submitted to any relay");
+        }
+
+        let public_key = self.signer.public_key().clone();
+        join_all(self.relays.iter_mut().map(|relay| relay.onboard(&public_key))).await;
+        let ready = self.relays.iter().filter(|relay| relay.readiness().is_usable()).count();
+        if ready < self.relays.len() {
+            warn!(ready, count = self.relays.len(), "not all relays are ready for submissions");
+        }
+
+        if let Some(local_proposer) = &self.local_proposer {
+            let server = BlindedBlockProviderServer::new(
+                local_proposer.host(),
+                local_proposer.port(),
+                local_proposer.clone(),
+            );
+            info!(public_key = %self.signer.public_key(), "serving builder API to local proposer");
+            server.spawn();
+        }
+
+        if let Some(external_blocks_config) = self.config.external_blocks.clone() {
+            external_blocks::spawn(external_blocks_config, self.external_blocks_tx.clone());
+        }
+
+        // NOTE: a relay not serving a stream of top bids is retried in the background, while bids
+        // are shaded against the top bids streamed by the other relays
+        if self.bidder.shades_bids() {
+            for relay in &self.relays {
+                let stream = relay.data_api().stream_top_bids(
+                    self.client.clone(),
+                    public_key.clone(),
+                    self.bidder.top_bids().clone(),
+                );
+                tokio::spawn(stream);
+            }
+        }
 
         // initialize proposer schedule
         self.fetch_proposer_schedules().await;
@@ -365,6 +812,11 @@ impl<
                     Err(err) => warn!(%err, "error getting payload event"),
                 },
                 Some(payload) = self.bids.recv() => self.submit_payload(payload).await,
+                Some(settings) = self.settings.recv() => self.reload(settings),
+                Some(block) = self.external_blocks.recv() => self.on_external_block(block),
+                Some(payload) = self.external_payloads.recv() => self.submit_external_payload(payload).await,
+                Some((relay_index, readiness)) = self.readiness_updates.recv() => self.on_readiness(relay_index, readiness),
+                Some((relay_index, schedule)) = self.proposer_schedules.recv() => self.on_proposer_schedule(relay_index, schedule),
             }
         }
     }
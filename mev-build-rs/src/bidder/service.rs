@@ -1,22 +1,57 @@
 use crate::{
-    auctioneer::AuctionContext,
+    auctioneer::{AuctionContext, TopBids},
     bidder::{strategies::BasicStrategy, Config},
+    compat::to_bytes32,
+};
+use ethereum_consensus::clock::duration_since_unix_epoch;
+use reth::{api::PayloadBuilderAttributes, primitives::revm_primitives::U256, tasks::TaskExecutor};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    sync::mpsc::{Receiver, Sender},
+    time::{sleep_until, Instant},
 };
-use reth::{primitives::revm_primitives::U256, tasks::TaskExecutor};
-use std::sync::Arc;
-use tokio::sync::{mpsc::Receiver, oneshot};
 use tracing::trace;
 
-pub type RevenueUpdate = (U256, oneshot::Sender<Option<U256>>);
+/// The revenue of a payload built for an auction, along with a channel to send the value to bid for
+/// it; a bid can be raised by sending a greater value for the same payload
+pub type RevenueUpdate = (U256, Sender<U256>);
+
+// The latest bid made in an auction
+struct Bid {
+    revenue: U256,
+    value: Option<U256>,
+    dispatch: Sender<U256>,
+}
+
+enum Update {
+    Revenue(RevenueUpdate),
+    TopBid,
+}
 
 pub struct Service {
     executor: TaskExecutor,
     config: Config,
+    // most valuable bids from other builders, to shade bids down to
+    top_bids: TopBids,
 }
 
 impl Service {
     pub fn new(executor: TaskExecutor, config: Config) -> Self {
-        Self { executor, config }
+        Self { executor, config, top_bids: Default::default() }
+    }
+
+    /// Indicates if bids are shaded down to the top bids from other builders
+    pub fn shades_bids(&self) -> bool {
+        self.config.top_bid_increment_wei.is_some()
+    }
+
+    pub fn top_bids(&self) -> &TopBids {
+        &self.top_bids
+    }
+
+    /// Apply `config` to auctions started from now on
+    pub fn reconfigure(&mut self, config: Config) {
+        self.config = config;
     }
 
     pub fn start_bid(
@@ -26,15 +61,99 @@ impl Service {
     ) {
         // TODO: make strategies configurable...
         let mut strategy = BasicStrategy::new(&self.config);
+        let rebid_interval = self.config.rebid_interval_ms.map(Duration::from_millis);
+        let cutoff = self.config.bid_cutoff_ms.map(|cutoff_ms| {
+            let slot_start = Duration::from_secs(auction.attributes.timestamp());
+            let cutoff = slot_start + Duration::from_millis(cutoff_ms);
+            Instant::now() + cutoff.saturating_sub(duration_since_unix_epoch())
+        });
+        let top_bids = self.top_bids.clone();
+        let mut top_bid_updates = top_bids.subscribe();
+        let shades_bids = self.shades_bids();
+        let slot = auction.slot;
+        let parent_hash = to_bytes32(auction.attributes.inner.parent);
+        // NOTE: the auction's attributes hold a sender for `revenue_updates`, so only a weak
+        // reference to the auction is kept for the channel to close once the auctioneer and the
+        // build job are done with the auction
+        let auction = Arc::downgrade(&auction);
         self.executor.spawn_blocking(async move {
+            let mut last_bid: Option<Instant> = None;
+            let mut bid: Option<Bid> = None;
             // NOTE: `revenue_updates` will be closed when the builder is done with new payloads for
             // this auction so we can just loop on `recv` and return naturally once the
-            // channel is closed
-            while let Some((current_revenue, dispatch)) = revenue_updates.recv().await {
-                let value = strategy.run(&auction, current_revenue).await;
-                if dispatch.send(value).is_err() {
-                    trace!("channel closed; could not send bid value to builder");
+            // channel is closed; returning early ends the builder's job for this auction
+            loop {
+                let until_cutoff = async {
+                    match cutoff {
+                        Some(cutoff) => sleep_until(cutoff).await,
+                        None => std::future::pending().await,
+                    }
+                };
+                let update = tokio::select! {
+                    update = revenue_updates.recv() => match update {
+                        Some(update) => Update::Revenue(update),
+                        None => break,
+                    },
+                    // NOTE: a bid shaded down to the top bid is raised once the top bid rises
+                    Ok(()) = top_bid_updates.changed(), if shades_bids && bid.is_some() => {
+                        Update::TopBid
+                    }
+                    _ = until_cutoff => break,
+                };
+                let mut revenue_update = match update {
+                    Update::Revenue(update) => Some(update),
+                    Update::TopBid => None,
+                };
+
+                if let (Some(last_bid), Some(rebid_interval)) = (last_bid, rebid_interval) {
+                    let next_bid = last_bid + rebid_interval;
+                    if cutoff.is_some_and(|cutoff| cutoff <= next_bid) {
+                        trace!(slot, "no time to bid again before the cutoff");
+                        break
+                    }
+                    sleep_until(next_bid).await;
+                    // NOTE: only bid for the most recent payload built while waiting
+                    while let Ok(newer) = revenue_updates.try_recv() {
+                        revenue_update = Some(newer);
+                    }
+                }
+
+                let Some(auction) = auction.upgrade() else {
+                    trace!(slot, "auction closed; not bidding");
                     break
+                };
+                let top_bid = top_bids.get(slot, &parent_hash);
+                match revenue_update {
+                    Some((revenue, dispatch)) => {
+                        let value = strategy.run(&auction, revenue, top_bid).await;
+                        if let Some(value) = value {
+                            if dispatch.send(value).await.is_err() {
+                                // NOTE: the builder has moved on to a better payload
+                                trace!("channel closed; could not send bid value to builder");
+                                bid = None;
+                                continue
+                            }
+                            last_bid = Some(Instant::now());
+                        }
+                        bid = Some(Bid { revenue, value, dispatch });
+                    }
+                    None => {
+                        let Some(current) = bid.as_mut() else { continue };
+                        // NOTE: the strategy bids at most the full value of the payload, so a
+                        // bid is only raised up to it
+                        let value = strategy.run(&auction, current.revenue, top_bid).await;
+                        let Some(value) = value.filter(|&value| Some(value) > current.value) else {
+                            continue
+                        };
+                        if current.dispatch.send(value).await.is_err() {
+                            trace!("channel closed; could not raise bid for payload");
+                            bid = None;
+                            continue
+                        }
+                        trace!(slot, %value, ?top_bid, "raised bid above top bid");
+                        current.value = Some(value);
+                        last_bid = Some(Instant::now());
+                    }
                 }
             }
         });
@@ -10,32 +10,102 @@ pub struct Config {
     // amount to add from the builder's wallet as a subsidy to the auction bid
     // if missing, defaults to 0
     pub subsidy_wei: Option<U256>,
+    // most the bid can exceed the block's value by, i.e. the most the builder can lose on a block
+    // if missing, defaults to `subsidy_wei`
+    pub max_loss_wei: Option<U256>,
+    // minimum amount of time in milliseconds between bids in the same auction; better payloads
+    // built in the meantime are bid together once the interval has passed
+    // if missing, bids are made as soon as each better payload is built
+    pub rebid_interval_ms: Option<u64>,
+    // amount of time in milliseconds into the auction's slot after which no more bids are made
+    // if missing, bids are made until the build job for the auction ends
+    pub bid_cutoff_ms: Option<u64>,
+    // amount to bid above the most valuable bid from any other builder, as streamed by the relays,
+    // when the value computed above is greater; bids are only ever lowered by this
+    // if missing, bids are made at the value computed above
+    pub top_bid_increment_wei: Option<U256>,
 }
 
 /// `BasicStrategy` submits a bid for each built payload, with configurable options for:
 /// - percent of the revenue to bid
 /// - a "subsidy" to add
+/// - a maximum loss, capping the bid relative to the revenue
+/// - an increment over the top bid from other builders, to shade the bid down to
 pub struct BasicStrategy {
     bid_percent: f64,
     subsidy_wei: U256,
+    max_loss_wei: U256,
+    top_bid_increment_wei: Option<U256>,
 }
 
 impl BasicStrategy {
     pub fn new(config: &Config) -> Self {
+        let subsidy_wei = config.subsidy_wei.unwrap_or_default();
         Self {
             bid_percent: config.bid_percent.unwrap_or(1.0).clamp(0.0, 1.0),
-            subsidy_wei: config.subsidy_wei.unwrap_or_default(),
+            subsidy_wei,
+            max_loss_wei: config.max_loss_wei.unwrap_or(subsidy_wei),
+            top_bid_increment_wei: config.top_bid_increment_wei,
         }
     }
 
     fn compute_value(&self, current_revenue: U256) -> U256 {
         let mut value = current_revenue * U256::from(self.bid_percent * 100.0) / U256::from(100);
         value += self.subsidy_wei;
-        value
+        value.min(current_revenue.saturating_add(self.max_loss_wei))
     }
 
-    pub async fn run(&mut self, _auction: &AuctionContext, current_revenue: U256) -> Option<U256> {
-        let value = self.compute_value(current_revenue);
+    // Lower `value` to just above `top_bid`, if known, as any more is given up to the proposer
+    fn shade(&self, value: U256, top_bid: Option<U256>) -> U256 {
+        match (self.top_bid_increment_wei, top_bid) {
+            (Some(increment), Some(top_bid)) => value.min(top_bid.saturating_add(increment)),
+            _ => value,
+        }
+    }
+
+    pub async fn run(
+        &mut self,
+        _auction: &AuctionContext,
+        current_revenue: U256,
+        top_bid: Option<U256>,
+    ) -> Option<U256> {
+        let value = self.shade(self.compute_value(current_revenue), top_bid);
         Some(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_value() {
+        let config = Config {
+            bid_percent: Some(0.9),
+            subsidy_wei: Some(U256::from(200)),
+            max_loss_wei: Some(U256::from(50)),
+            ..Default::default()
+        };
+        let strategy = BasicStrategy::new(&config);
+        // NOTE: bid is capped at the revenue plus the maximum loss
+        assert_eq!(strategy.compute_value(U256::from(1000)), U256::from(1050));
+        assert_eq!(strategy.compute_value(U256::from(10_000)), U256::from(9200));
+
+        let config = Config { subsidy_wei: Some(U256::from(200)), ..Default::default() };
+        let strategy = BasicStrategy::new(&config);
+        assert_eq!(strategy.compute_value(U256::from(1000)), U256::from(1200));
+    }
+
+    #[test]
+    fn test_shade() {
+        let config = Config { top_bid_increment_wei: Some(U256::from(10)), ..Default::default() };
+        let strategy = BasicStrategy::new(&config);
+        assert_eq!(strategy.shade(U256::from(1000), Some(U256::from(500))), U256::from(510));
+        // NOTE: bids are never raised to beat the top bid
+        assert_eq!(strategy.shade(U256::from(1000), Some(U256::from(995))), U256::from(1000));
+        assert_eq!(strategy.shade(U256::from(1000), None), U256::from(1000));
+
+        let strategy = BasicStrategy::new(&Default::default());
+        assert_eq!(strategy.shade(U256::from(1000), Some(U256::from(500))), U256::from(1000));
+    }
+}
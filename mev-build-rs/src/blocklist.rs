@@ -0,0 +1,110 @@
+use reth::primitives::revm_primitives::{Address, EvmState};
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+};
+use thiserror::Error;
+use tracing::info;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("could not read blocklist from {source}: {err}")]
+    Read { source: String, err: String },
+    #[error("could not parse blocklist from {source}: {err}")]
+    Parse { source: String, err: serde_json::Error },
+}
+
+/// Addresses the builder never includes transactions touching, e.g. for operators with
+/// compliance requirements
+///
+/// NOTE: clones share the same addresses, so replacing the addresses of one replaces them for all
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist(Arc<RwLock<HashSet<Address>>>);
+
+impl Blocklist {
+    /// Load a blocklist from `source`, either a URL or the path to a file, holding a JSON array
+    /// of addresses
+    pub async fn load(source: &str) -> Result<Self, Error> {
+        let addresses = Self::load_addresses(source).await?;
+        Ok(Self(Arc::new(RwLock::new(addresses))))
+    }
+
+    /// Replace the addresses of this blocklist with those loaded from `source`, or with none if
+    /// `source` is missing
+    pub async fn reload(&self, source: Option<&str>) -> Result<(), Error> {
+        let addresses = match source {
+            Some(source) => Self::load_addresses(source).await?,
+            None => Default::default(),
+        };
+        *self.0.write().expect("can lock") = addresses;
+        Ok(())
+    }
+
+    async fn load_addresses(source: &str) -> Result<HashSet<Address>, Error> {
+        let read_error = |err: String| Error::Read { source: source.to_string(), err };
+        let contents = if source.starts_with("http://") || source.starts_with("https://") {
+            reqwest::get(source)
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|err| read_error(err.to_string()))?
+                .text()
+                .await
+                .map_err(|err| read_error(err.to_string()))?
+        } else {
+            tokio::fs::read_to_string(source).await.map_err(|err| read_error(err.to_string()))?
+        };
+        let addresses = serde_json::from_str::<Vec<Address>>(&contents)
+            .map_err(|err| Error::Parse { source: source.to_string(), err })?;
+        info!(count = addresses.len(), %source, "loaded blocklist");
+        Ok(addresses.into_iter().collect())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.read().expect("can lock").is_empty()
+    }
+
+    pub fn contains(&self, address: &Address) -> bool {
+        self.0.read().expect("can lock").contains(address)
+    }
+
+    /// Return the first listed address in `addresses`, if any
+    pub fn find(&self, addresses: impl IntoIterator<Item = Address>) -> Option<Address> {
+        let listed = self.0.read().expect("can lock");
+        if listed.is_empty() {
+            return None
+        }
+        addresses.into_iter().find(|address| listed.contains(address))
+    }
+
+    /// Return a listed address touched by a transaction from `sender` with state changes `state`,
+    /// if any
+    ///
+    /// NOTE: `state` holds every account loaded during execution, so this covers the recipient
+    /// and any account reached through calls, not just the accounts whose state changed.
+    pub fn touched_by(&self, sender: Address, state: &EvmState) -> Option<Address> {
+        self.find(std::iter::once(sender).chain(state.keys().copied()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth::primitives::revm_primitives::Account;
+
+    #[test]
+    fn test_touched_by() {
+        let listed = Address::repeat_byte(1);
+        let sender = Address::repeat_byte(2);
+        let other = Address::repeat_byte(3);
+        let blocklist = Blocklist(Arc::new(RwLock::new(HashSet::from([listed]))));
+
+        let mut state = EvmState::default();
+        state.insert(other, Account::default());
+        assert_eq!(blocklist.touched_by(sender, &state), None);
+        assert_eq!(blocklist.touched_by(listed, &state), Some(listed));
+
+        state.insert(listed, Account::default());
+        assert_eq!(blocklist.touched_by(sender, &state), Some(listed));
+        assert_eq!(Blocklist::default().touched_by(listed, &state), None);
+    }
+}
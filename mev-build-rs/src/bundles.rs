@@ -0,0 +1,1041 @@
+use crate::metrics;
+use alloy_eips::eip2718::Decodable2718;
+use reth::{
+    primitives::{
+        revm_primitives::{keccak256, Address, Bytes, EvmState, B256, U256},
+        TransactionSigned, TransactionSignedEcRecovered,
+    },
+    providers::ExecutionOutcome,
+};
+use std::{
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use thiserror::Error;
+
+// Number of blocks a private transaction is considered for, if the sender does not provide a bound
+pub const DEFAULT_PRIVATE_TRANSACTION_BLOCK_RANGE: u64 = 25;
+// Number of blocks included private transactions are kept for, so they can be restored if their
+// block is reorged out of the chain, and that the transactions of bundles are remembered for
+const MAX_REORG_DEPTH: u64 = 64;
+// Number of bundles kept for any one block, beyond which the bundle bidding the least is dropped
+pub const DEFAULT_MAX_BUNDLES_PER_BLOCK: usize = 10_000;
+// Number of bundles kept across all blocks, beyond which the bundle bidding the least is dropped
+pub const DEFAULT_MAX_BUNDLES: usize = 50_000;
+// Number of pending private transactions kept, beyond which the transaction bidding the least is
+// dropped
+pub const DEFAULT_MAX_PRIVATE_TRANSACTIONS: usize = 10_000;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("bundle does not contain any transactions")]
+    Empty,
+    #[error("could not decode transaction at index {0} of bundle")]
+    InvalidTransaction(usize),
+    #[error("could not recover signer of transaction {0}")]
+    InvalidSignature(B256),
+    #[error("blob transaction {0} can not be included in a bundle")]
+    BlobTransaction(B256),
+    #[error("bundle targets block {target} but the chain is already at block {head}")]
+    StaleBlock { target: u64, head: u64 },
+    #[error("transaction {0} has already been received")]
+    DuplicateTransaction(B256),
+    #[error("transaction {0} is not pending or can not be backrun")]
+    NotBackrunnable(B256),
+    #[error("backrun targets block {target} but transaction {hash} is only considered up to block {max_block_number}")]
+    BeyondMaxBlock { hash: B256, target: u64, max_block_number: u64 },
+    #[error("bundle bids less than any bundle in the full pool")]
+    PoolFull,
+    #[error("transaction bids less than any private transaction in the full pool")]
+    PrivatePoolFull,
+    #[error("transaction {0} can only be cancelled by its sender")]
+    NotSender(B256),
+}
+
+/// Decode a signed transaction from its EIP-2718 `encoding`, recovering its signer
+fn decode_transaction(
+    index: usize,
+    encoding: &Bytes,
+) -> Result<TransactionSignedEcRecovered, Error> {
+    let transaction = TransactionSigned::decode_2718(&mut encoding.as_ref())
+        .map_err(|_| Error::InvalidTransaction(index))?;
+    if transaction.is_eip4844() {
+        return Err(Error::BlobTransaction(transaction.hash))
+    }
+    let hash = transaction.hash;
+    transaction.into_ecrecovered().ok_or(Error::InvalidSignature(hash))
+}
+
+// The priority fees `transaction` bids over its gas limit
+fn bid(transaction: &TransactionSignedEcRecovered) -> u128 {
+    let tip_per_gas = transaction.effective_tip_per_gas(None).unwrap_or_default();
+    tip_per_gas.saturating_mul(transaction.gas_limit() as u128)
+}
+
+/// Return the nonces of the accounts changed in `execution_outcome`, against which the transactions
+/// waiting for inclusion are checked
+pub fn changed_nonces(execution_outcome: &ExecutionOutcome) -> HashMap<Address, u64> {
+    execution_outcome
+        .bundle_accounts_iter()
+        .filter_map(|(address, account)| account.info.as_ref().map(|info| (address, info.nonce)))
+        .collect()
+}
+
+// Indicates if `transaction` can no longer be executed, as the `nonces` of the accounts changed on
+// chain have moved past its nonce
+fn is_stale(transaction: &TransactionSignedEcRecovered, nonces: &HashMap<Address, u64>) -> bool {
+    nonces.get(&transaction.signer()).is_some_and(|&nonce| transaction.nonce() < nonce)
+}
+
+/// The orderflow provider that sent a bundle or private transaction over the exclusive orderflow
+/// endpoint, used to account for its orderflow
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub provider: String,
+    // NOTE: bundles with priority are included ahead of any bundles without it
+    pub priority: bool,
+}
+
+/// An ordered list of transactions from a searcher to include atomically at the top of a block
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub hash: B256,
+    pub transactions: Vec<TransactionSignedEcRecovered>,
+    pub block_number: u64,
+    pub min_timestamp: Option<u64>,
+    pub max_timestamp: Option<u64>,
+    pub reverting_tx_hashes: HashSet<B256>,
+    pub origin: Option<Origin>,
+    // number of blocks the bundle has been carried over past the block it targeted
+    pub carried_over: u64,
+    /// Hash of the private transaction the bundle backruns, placed ahead of its transactions
+    /// only while the transaction is pending, see [`PrivateTransactionPool::resolve_backrun`]
+    pub backrun: Option<B256>,
+}
+
+impl Bundle {
+    pub fn try_new(
+        encoded_transactions: &[Bytes],
+        block_number: u64,
+        min_timestamp: Option<u64>,
+        max_timestamp: Option<u64>,
+        reverting_tx_hashes: HashSet<B256>,
+    ) -> Result<Self, Error> {
+        if encoded_transactions.is_empty() {
+            return Err(Error::Empty)
+        }
+        let transactions = encoded_transactions
+            .iter()
+            .enumerate()
+            .map(|(index, encoding)| decode_transaction(index, encoding))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::new(transactions, block_number, min_timestamp, max_timestamp, reverting_tx_hashes))
+    }
+
+    pub fn new(
+        transactions: Vec<TransactionSignedEcRecovered>,
+        block_number: u64,
+        min_timestamp: Option<u64>,
+        max_timestamp: Option<u64>,
+        reverting_tx_hashes: HashSet<B256>,
+    ) -> Self {
+        // NOTE: follows the convention of other builders, where the bundle hash commits to the
+        // hashes of its transactions, in order
+        let hash = keccak256(
+            transactions.iter().flat_map(|transaction| transaction.hash.0).collect::<Vec<_>>(),
+        );
+        Self {
+            hash,
+            transactions,
+            block_number,
+            min_timestamp,
+            max_timestamp,
+            reverting_tx_hashes,
+            origin: None,
+            carried_over: 0,
+            backrun: None,
+        }
+    }
+
+    /// Build a bundle placing the transactions with `encoded_transactions` right after the
+    /// private transaction with `tx_hash`, e.g. one a searcher was hinted about.
+    ///
+    /// The private transaction is only referenced by its hash, so the bundle is never built once
+    /// the transaction is cancelled, included or expired.
+    pub fn try_backrun(
+        tx_hash: B256,
+        encoded_transactions: &[Bytes],
+        block_number: u64,
+    ) -> Result<Self, Error> {
+        let mut bundle =
+            Self::try_new(encoded_transactions, block_number, None, None, Default::default())?;
+        bundle.hash = keccak256(
+            std::iter::once(&tx_hash)
+                .chain(bundle.transactions.iter().map(|transaction| &transaction.hash))
+                .flat_map(|hash| hash.0)
+                .collect::<Vec<_>>(),
+        );
+        bundle.backrun = Some(tx_hash);
+        Ok(bundle)
+    }
+
+    /// Place `prerequisites`, e.g. transactions from the mempool the bundle depends on, ahead of
+    /// the transactions of the bundle, which can not revert
+    pub fn with_prerequisites(mut self, prerequisites: Vec<TransactionSignedEcRecovered>) -> Self {
+        if prerequisites.is_empty() {
+            return self
+        }
+        // NOTE: the hash commits to the prerequisites, so that simulations of the bundle with
+        // other prerequisites are never confused for one another
+        self.hash = keccak256(
+            prerequisites
+                .iter()
+                .map(|transaction| &transaction.hash)
+                .chain(std::iter::once(&self.hash))
+                .flat_map(|hash| hash.0)
+                .collect::<Vec<_>>(),
+        );
+        self.transactions.splice(0..0, prerequisites);
+        self
+    }
+
+    pub fn with_origin(mut self, origin: Origin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Indicates if the bundle is included ahead of bundles without priority
+    pub fn has_priority(&self) -> bool {
+        self.origin.as_ref().is_some_and(|origin| origin.priority)
+    }
+
+    /// Indicates if the bundle can be included in the block with `block_number` and `timestamp`
+    pub fn is_eligible(&self, block_number: u64, timestamp: u64) -> bool {
+        // NOTE: searchers commonly send `0` to indicate there is no bound
+        let after_min =
+            self.min_timestamp.filter(|&bound| bound != 0).map_or(true, |bound| timestamp >= bound);
+        let before_max =
+            self.max_timestamp.filter(|&bound| bound != 0).map_or(true, |bound| timestamp <= bound);
+        self.block_number == block_number && after_min && before_max
+    }
+
+    /// Indicates if the transaction with `hash` is allowed to revert without invalidating the
+    /// bundle
+    pub fn can_revert(&self, hash: &B256) -> bool {
+        self.reverting_tx_hashes.contains(hash)
+    }
+
+    /// The priority fees the bundle's transactions bid over their gas limit, by which bundles are
+    /// dropped from a full pool, as the payment of a bundle is only known once it is simulated
+    pub fn bid(&self) -> u128 {
+        self.transactions.iter().fold(0u128, |total, tx| total.saturating_add(bid(tx)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum StateKey {
+    Account(Address),
+    Storage(Address, U256),
+}
+
+/// The accounts and storage slots read and written when executing a bundle
+#[derive(Debug, Default, Clone)]
+pub struct StateAccess {
+    pub(crate) reads: HashSet<StateKey>,
+    pub(crate) writes: HashSet<StateKey>,
+}
+
+impl StateAccess {
+    /// Record the access in the state changes `state` of a transaction, ignoring `coinbase` as
+    /// payments to it from different bundles do not conflict
+    pub fn record(&mut self, state: &EvmState, coinbase: Address) {
+        for (&address, account) in state.iter().filter(|(&address, _)| address != coinbase) {
+            self.reads.insert(StateKey::Account(address));
+            // NOTE: a contract is touched whenever it is called, so only track changes to accounts
+            // without code, i.e. their balance and nonce, at the level of the account; changes to
+            // contracts are tracked by their storage
+            if account.is_touched() && account.info.is_empty_code_hash() {
+                self.writes.insert(StateKey::Account(address));
+            }
+            for (&slot, value) in &account.storage {
+                let key = StateKey::Storage(address, slot);
+                self.reads.insert(key);
+                if value.is_changed() {
+                    self.writes.insert(key);
+                }
+            }
+        }
+    }
+
+    /// The accounts accessed, other than the coinbase
+    pub fn accounts(&self) -> impl Iterator<Item = Address> + '_ {
+        self.reads.iter().filter_map(|key| match key {
+            StateKey::Account(address) => Some(*address),
+            StateKey::Storage(..) => None,
+        })
+    }
+
+    /// Indicates if executing one of the bundles could change the outcome of the other
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        !self.writes.is_disjoint(&other.writes) ||
+            !self.writes.is_disjoint(&other.reads) ||
+            !other.writes.is_disjoint(&self.reads)
+    }
+}
+
+/// A bundle, along with the outcome of simulating it against the state at the top of the block
+#[derive(Debug)]
+pub struct BundleCandidate {
+    pub bundle: Bundle,
+    pub payment: U256,
+    pub gas_used: u64,
+    pub access: StateAccess,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    head: u64,
+    bundles: BTreeMap<u64, HashMap<B256, Bundle>>,
+    // number of blocks past its target block a bundle is considered for, until it is included
+    carry_over_blocks: u64,
+    max_bundles_per_block: usize,
+    max_bundles: usize,
+    // hashes of the transactions of any bundle received, along with the last block the bundle
+    // targeted, so they are known as builder-only even once the bundle is dropped or included
+    transactions: HashMap<B256, u64>,
+}
+
+impl State {
+    fn len(&self) -> usize {
+        self.bundles.values().map(HashMap::len).sum()
+    }
+
+    // Make room for a bundle bidding `bid` for `block_number`, dropping the bundle bidding the
+    // least for the block, or for any block, if the pool is full
+    fn make_room(&mut self, block_number: u64, bid: u128) -> Result<(), Error> {
+        let lowest = |bundles: &HashMap<B256, Bundle>| {
+            bundles.values().map(|bundle| (bundle.bid(), bundle.block_number, bundle.hash)).min()
+        };
+        let per_block = self.bundles.get(&block_number).map_or(0, HashMap::len);
+        let evicted = if per_block >= self.max_bundles_per_block {
+            self.bundles.get(&block_number).and_then(lowest)
+        } else if self.len() >= self.max_bundles {
+            self.bundles.values().filter_map(lowest).min()
+        } else {
+            return Ok(())
+        };
+        match evicted {
+            Some((lowest_bid, block_number, hash)) if lowest_bid < bid => {
+                if let Some(bundles) = self.bundles.get_mut(&block_number) {
+                    bundles.remove(&hash);
+                }
+                metrics::on_bundle_evicted();
+                Ok(())
+            }
+            _ => Err(Error::PoolFull),
+        }
+    }
+}
+
+/// Bundles received from searchers, indexed by their target block
+#[derive(Debug, Clone, Default)]
+pub struct BundlePool(Arc<Mutex<State>>);
+
+impl BundlePool {
+    /// Create a pool carrying each bundle not included in its target block over to the next
+    /// block, for up to `carry_over_blocks` blocks
+    pub fn new(carry_over_blocks: u64) -> Self {
+        Self::with_limits(carry_over_blocks, DEFAULT_MAX_BUNDLES_PER_BLOCK, DEFAULT_MAX_BUNDLES)
+    }
+
+    /// Create a pool keeping at most `max_bundles_per_block` bundles for any one block and
+    /// `max_bundles` bundles overall
+    pub fn with_limits(
+        carry_over_blocks: u64,
+        max_bundles_per_block: usize,
+        max_bundles: usize,
+    ) -> Self {
+        Self(Arc::new(Mutex::new(State {
+            carry_over_blocks,
+            max_bundles_per_block,
+            max_bundles,
+            ..Default::default()
+        })))
+    }
+
+    /// Add `bundle` to the pool, replacing any bundle with the same hash for the same block.
+    ///
+    /// Once the pool is full, the bundle bidding the least is dropped to make room for `bundle`, if
+    /// it bids more, see [`Bundle::bid`].
+    pub fn insert(&self, bundle: Bundle) -> Result<B256, Error> {
+        let mut state = self.0.lock().expect("can lock");
+        if bundle.block_number <= state.head {
+            return Err(Error::StaleBlock { target: bundle.block_number, head: state.head })
+        }
+        let hash = bundle.hash;
+        let replaces = state
+            .bundles
+            .get(&bundle.block_number)
+            .is_some_and(|bundles| bundles.contains_key(&hash));
+        if !replaces {
+            state.make_room(bundle.block_number, bundle.bid())?;
+        }
+        for transaction in &bundle.transactions {
+            state.transactions.insert(transaction.hash, bundle.block_number);
+        }
+        state.bundles.entry(bundle.block_number).or_default().insert(hash, bundle);
+        metrics::on_bundle_pool_size(state.len());
+        Ok(hash)
+    }
+
+    /// Return the bundles that can be included in the block with `block_number` and `timestamp`
+    pub fn bundles_for(&self, block_number: u64, timestamp: u64) -> Vec<Bundle> {
+        let state = self.0.lock().expect("can lock");
+        state
+            .bundles
+            .get(&block_number)
+            .map(|bundles| {
+                bundles
+                    .values()
+                    .filter(|bundle| bundle.is_eligible(block_number, timestamp))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Indicates if any bundle in the pool contains the transaction with `hash`, or did within
+    /// the last blocks it may return from after a reorg
+    pub fn contains_transaction(&self, hash: &B256) -> bool {
+        let state = self.0.lock().expect("can lock");
+        state.transactions.contains_key(hash)
+    }
+
+    /// Drop any bundles that can no longer be included once the chain is at `head`, returning the
+    /// origins of the bundles targeting `head` whose transactions are all `included` in its block.
+    ///
+    /// Bundles not included are carried over to the next block, within the pool's limit, as long
+    /// as the `nonces` of the accounts changed on chain leave all of their transactions valid.
+    pub fn on_new_head(
+        &self,
+        head: u64,
+        included: &HashSet<B256>,
+        nonces: &HashMap<Address, u64>,
+    ) -> Vec<Origin> {
+        let mut state = self.0.lock().expect("can lock");
+        let origins = state
+            .bundles
+            .get(&head)
+            .map(|bundles| {
+                bundles
+                    .values()
+                    .filter(|bundle| {
+                        bundle.transactions.iter().all(|tx| included.contains(&tx.hash))
+                    })
+                    .filter_map(|bundle| bundle.origin.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        state.head = state.head.max(head);
+        let retain_block = state.head + 1;
+        let retained = state.bundles.split_off(&retain_block);
+        let expired = std::mem::replace(&mut state.bundles, retained);
+        let carry_over_blocks = state.carry_over_blocks;
+        let mut carried_over = 0;
+        for mut bundle in expired.into_values().flat_map(HashMap::into_values) {
+            let carried = bundle.carried_over + retain_block - bundle.block_number;
+            if carried > carry_over_blocks ||
+                bundle
+                    .transactions
+                    .iter()
+                    .any(|tx| included.contains(&tx.hash) || is_stale(tx, nonces))
+            {
+                continue
+            }
+            bundle.block_number = retain_block;
+            bundle.carried_over = carried;
+            for transaction in &bundle.transactions {
+                state.transactions.insert(transaction.hash, retain_block);
+            }
+            // NOTE: a bundle sent again for the next block takes the place of the carried bundle
+            let bundles = state.bundles.entry(retain_block).or_default();
+            if let Entry::Vacant(entry) = bundles.entry(bundle.hash) {
+                entry.insert(bundle);
+                carried_over += 1;
+            }
+        }
+        let head = state.head;
+        state.transactions.retain(|_, block_number| *block_number + MAX_REORG_DEPTH > head);
+        metrics::on_bundles_carried_over(carried_over);
+        metrics::on_bundle_pool_size(state.len());
+        origins
+    }
+
+    /// Drop any bundles including or backrunning the private transaction with `tx_hash`, e.g. once
+    /// its sender cancels it, returning the number of bundles dropped
+    pub fn remove_including(&self, tx_hash: &B256) -> usize {
+        let mut state = self.0.lock().expect("can lock");
+        let count = state.len();
+        for bundles in state.bundles.values_mut() {
+            bundles.retain(|_, bundle| {
+                bundle.backrun.as_ref() != Some(tx_hash) &&
+                    !bundle.transactions.iter().any(|tx| &tx.hash == tx_hash)
+            });
+        }
+        let dropped = count - state.len();
+        metrics::on_bundle_pool_size(state.len());
+        dropped
+    }
+
+    /// Drop any bundles targeting blocks after `head` with transactions `included` in the chain,
+    /// which can no longer be executed, returning the number of bundles dropped
+    pub fn on_committed(&self, head: u64, included: &HashSet<B256>) -> usize {
+        let mut state = self.0.lock().expect("can lock");
+        let count = state.len();
+        for (_, bundles) in state.bundles.range_mut(head + 1..) {
+            bundles.retain(|_, bundle| {
+                !bundle.transactions.iter().any(|tx| included.contains(&tx.hash))
+            });
+        }
+        let dropped = count - state.len();
+        metrics::on_bundle_pool_size(state.len());
+        dropped
+    }
+
+    /// Rewind the pool to `head` after a reorg, so bundles can again target the blocks after it
+    pub fn on_reorg(&self, head: u64) {
+        let mut state = self.0.lock().expect("can lock");
+        state.head = head;
+    }
+}
+
+#[derive(Debug)]
+struct PrivateTransaction {
+    transaction: TransactionSignedEcRecovered,
+    max_block_number: u64,
+    origin: Option<Origin>,
+    // NOTE: only transactions whose sender opted into hints may be backrun
+    backrunnable: bool,
+}
+
+#[derive(Debug, Default)]
+struct PrivateState {
+    head: u64,
+    max_transactions: usize,
+    transactions: HashMap<B256, PrivateTransaction>,
+    // transactions included in recent blocks, along with the number of their block
+    included: HashMap<B256, (u64, PrivateTransaction)>,
+    // transactions cancelled by their senders, along with the head when they were cancelled
+    cancelled: HashMap<B256, u64>,
+}
+
+/// Transactions sent privately to the builder, which are never gossiped to the public mempool and
+/// are only included in blocks where they do not revert
+#[derive(Debug, Clone)]
+pub struct PrivateTransactionPool(Arc<Mutex<PrivateState>>);
+
+impl Default for PrivateTransactionPool {
+    fn default() -> Self {
+        Self::with_limit(DEFAULT_MAX_PRIVATE_TRANSACTIONS)
+    }
+}
+
+impl PrivateTransactionPool {
+    /// Create a pool keeping at most `max_transactions` pending transactions
+    pub fn with_limit(max_transactions: usize) -> Self {
+        Self(Arc::new(Mutex::new(PrivateState { max_transactions, ..Default::default() })))
+    }
+
+    /// Add the transaction with `encoding` to the pool, to be considered for blocks up to
+    /// `max_block_number`.
+    ///
+    /// Once the pool is full, the transaction bidding the least is dropped to make room for the
+    /// transaction, if it bids more.
+    pub fn insert(
+        &self,
+        encoding: &Bytes,
+        max_block_number: Option<u64>,
+        origin: Option<Origin>,
+    ) -> Result<B256, Error> {
+        let transaction = decode_transaction(0, encoding)?;
+        let hash = transaction.hash;
+        let mut state = self.0.lock().expect("can lock");
+        let max_block_number =
+            max_block_number.unwrap_or(state.head + DEFAULT_PRIVATE_TRANSACTION_BLOCK_RANGE);
+        if max_block_number <= state.head {
+            return Err(Error::StaleBlock { target: max_block_number, head: state.head })
+        }
+        if state.transactions.contains_key(&hash) {
+            return Err(Error::DuplicateTransaction(hash))
+        }
+        if state.transactions.len() >= state.max_transactions {
+            let lowest = state
+                .transactions
+                .iter()
+                .map(|(hash, private)| (bid(&private.transaction), *hash))
+                .min()
+                .filter(|(lowest_bid, _)| *lowest_bid < bid(&transaction))
+                .ok_or(Error::PrivatePoolFull)?;
+            state.transactions.remove(&lowest.1);
+        }
+        state.transactions.insert(
+            hash,
+            PrivateTransaction { transaction, max_block_number, origin, backrunnable: false },
+        );
+        Ok(hash)
+    }
+
+    /// Allow bundles to backrun the pending transaction with `hash`, once it is hinted to searchers
+    pub fn allow_backruns(&self, hash: &B256) {
+        let mut state = self.0.lock().expect("can lock");
+        if let Some(private) = state.transactions.get_mut(hash) {
+            private.backrunnable = true;
+        }
+    }
+
+    /// Check that a bundle targeting `block_number` can backrun the transaction with `hash`, i.e.
+    /// that it is pending, may be backrun and is still considered for the block
+    pub fn check_backrun(&self, hash: &B256, block_number: u64) -> Result<(), Error> {
+        let state = self.0.lock().expect("can lock");
+        let private = state
+            .transactions
+            .get(hash)
+            .filter(|private| private.backrunnable)
+            .ok_or(Error::NotBackrunnable(*hash))?;
+        if block_number > private.max_block_number {
+            return Err(Error::BeyondMaxBlock {
+                hash: *hash,
+                target: block_number,
+                max_block_number: private.max_block_number,
+            })
+        }
+        Ok(())
+    }
+
+    /// Return `bundle` with the transaction it backruns placed ahead of its transactions, if it is
+    /// a backrun, or `None` if the transaction can no longer be included in the bundle's block
+    pub fn resolve_backrun(&self, mut bundle: Bundle) -> Option<Bundle> {
+        let Some(hash) = bundle.backrun else { return Some(bundle) };
+        let state = self.0.lock().expect("can lock");
+        let private = state.transactions.get(&hash).filter(|private| {
+            private.backrunnable && private.max_block_number >= bundle.block_number
+        })?;
+        bundle.transactions.insert(0, private.transaction.clone());
+        Some(bundle)
+    }
+
+    /// Return the transaction with `hash`, if it is still pending in the pool
+    pub fn pending(&self, hash: &B256) -> Option<TransactionSignedEcRecovered> {
+        let state = self.0.lock().expect("can lock");
+        state.transactions.get(hash).map(|private| private.transaction.clone())
+    }
+
+    /// Indicates if the transaction with `hash` is pending in the pool, or was included in a
+    /// recent block it may return from after a reorg
+    pub fn contains(&self, hash: &B256) -> bool {
+        let state = self.0.lock().expect("can lock");
+        state.transactions.contains_key(hash) || state.included.contains_key(hash)
+    }
+
+    /// Remove the transaction with `hash` from the pool on behalf of `sender`, returning `true` if
+    /// it was present, or an error if it was sent by another account
+    pub fn remove(&self, hash: &B256, sender: Address) -> Result<bool, Error> {
+        let mut state = self.0.lock().expect("can lock");
+        match state.transactions.get(hash) {
+            Some(private) if private.transaction.signer() != sender => Err(Error::NotSender(*hash)),
+            Some(_) => {
+                state.transactions.remove(hash);
+                let head = state.head;
+                state.cancelled.insert(*hash, head);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Indicates if any of the transactions with `hashes` was removed from the pool by its sender
+    /// since the current head, so that blocks including it must no longer be bid
+    pub fn any_cancelled<'a>(&self, mut hashes: impl Iterator<Item = &'a B256>) -> bool {
+        let state = self.0.lock().expect("can lock");
+        !state.cancelled.is_empty() && hashes.any(|hash| state.cancelled.contains_key(hash))
+    }
+
+    /// Return the transactions that can be included in the block with `block_number`, each as a
+    /// bundle that can not revert
+    pub fn bundles_for(&self, block_number: u64) -> Vec<Bundle> {
+        let state = self.0.lock().expect("can lock");
+        state
+            .transactions
+            .values()
+            .filter(|private| private.max_block_number >= block_number)
+            .map(|private| {
+                let mut bundle = Bundle::new(
+                    vec![private.transaction.clone()],
+                    block_number,
+                    None,
+                    None,
+                    Default::default(),
+                );
+                bundle.origin = private.origin.clone();
+                bundle
+            })
+            .collect()
+    }
+
+    /// Drop any transactions included in the block at `head`, or that can no longer be included,
+    /// including those whose nonce the `nonces` of the accounts changed on chain have moved past,
+    /// returning the origins of the included transactions
+    pub fn on_new_block(
+        &self,
+        head: u64,
+        included: impl IntoIterator<Item = B256>,
+        nonces: &HashMap<Address, u64>,
+    ) -> Vec<Origin> {
+        let mut state = self.0.lock().expect("can lock");
+        state.head = state.head.max(head);
+        let mut origins = vec![];
+        for hash in included {
+            if let Some(private) = state.transactions.remove(&hash) {
+                state.included.insert(hash, (head, private));
+            }
+            // NOTE: the transaction may have been removed by an earlier call for the same block
+            if let Some((_, private)) = state.included.get(&hash) {
+                origins.extend(private.origin.clone());
+            }
+        }
+        let head = state.head;
+        state.transactions.retain(|_, private| {
+            private.max_block_number > head && !is_stale(&private.transaction, nonces)
+        });
+        state.included.retain(|_, (block_number, _)| *block_number + MAX_REORG_DEPTH > head);
+        state.cancelled.retain(|_, block_number| *block_number >= head);
+        origins
+    }
+
+    /// Rewind the pool to `head` after a reorg, restoring the transactions included in the
+    /// `reverted` blocks that can still be included, returning the number of transactions restored
+    pub fn on_reorg(&self, head: u64, reverted: impl IntoIterator<Item = B256>) -> usize {
+        let mut state = self.0.lock().expect("can lock");
+        state.head = head;
+        let mut restored = 0;
+        for hash in reverted {
+            match state.included.remove(&hash) {
+                Some((_, private)) if private.max_block_number > head => {
+                    state.transactions.insert(hash, private);
+                    restored += 1;
+                }
+                _ => {}
+            }
+        }
+        restored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use alloy_eips::eip2718::Encodable2718;
+    use reth::primitives::{
+        revm_primitives::{alloy_primitives::Parity, TxKind},
+        Signature, Transaction,
+    };
+
+    fn encoded_transaction(signer: u8, nonce: u64) -> Bytes {
+        encoded_transaction_with_tip(signer, nonce, 0)
+    }
+
+    fn encoded_transaction_with_tip(signer: u8, nonce: u64, tip_per_gas: u128) -> Bytes {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(signer)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            nonce,
+            gas_limit: 21_000,
+            max_fee_per_gas: tip_per_gas,
+            max_priority_fee_per_gas: tip_per_gas,
+            to: TxKind::Call(Address::repeat_byte(2)),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        );
+        transaction.encoded_2718().into()
+    }
+
+    fn bundle(id: u8, block_number: u64) -> Bundle {
+        let mut bundle = Bundle::new(vec![], block_number, None, None, Default::default());
+        bundle.hash = B256::repeat_byte(id);
+        bundle
+    }
+
+    fn hashes(bundles: Vec<Bundle>) -> Vec<B256> {
+        let mut hashes = bundles.into_iter().map(|bundle| bundle.hash).collect::<Vec<_>>();
+        hashes.sort();
+        hashes
+    }
+
+    fn bundle_with_tip(signer: u8, block_number: u64, tip_per_gas: u128) -> Bundle {
+        let encoding = encoded_transaction_with_tip(signer, 0, tip_per_gas);
+        Bundle::try_new(&[encoding], block_number, None, None, Default::default()).unwrap()
+    }
+
+    #[test]
+    fn test_try_new() {
+        assert!(matches!(
+            Bundle::try_new(&[], 1, None, None, Default::default()),
+            Err(Error::Empty)
+        ));
+        let invalid = Bytes::from_static(&[0x02, 0xc0]);
+        assert!(matches!(
+            Bundle::try_new(
+                &[encoded_transaction(1, 0), invalid],
+                1,
+                None,
+                None,
+                Default::default()
+            ),
+            Err(Error::InvalidTransaction(1))
+        ));
+
+        let encodings = [encoded_transaction(1, 0), encoded_transaction(1, 1)];
+        let bundle = Bundle::try_new(&encodings, 1, None, None, Default::default()).unwrap();
+        assert_eq!(bundle.transactions.len(), 2);
+        assert!(bundle
+            .transactions
+            .iter()
+            .all(|tx| tx.signer() == bundle.transactions[0].signer()));
+        let hash =
+            keccak256(bundle.transactions.iter().flat_map(|tx| tx.hash.0).collect::<Vec<_>>());
+        assert_eq!(bundle.hash, hash);
+    }
+
+    #[test]
+    fn test_is_eligible() {
+        let mut bundle = bundle(1, 10);
+        assert!(bundle.is_eligible(10, 100));
+        assert!(!bundle.is_eligible(11, 100));
+
+        // NOTE: a bound of `0` is no bound
+        bundle.min_timestamp = Some(0);
+        bundle.max_timestamp = Some(0);
+        assert!(bundle.is_eligible(10, 100));
+
+        bundle.min_timestamp = Some(100);
+        bundle.max_timestamp = Some(200);
+        assert!(!bundle.is_eligible(10, 99));
+        assert!(bundle.is_eligible(10, 100));
+        assert!(bundle.is_eligible(10, 200));
+        assert!(!bundle.is_eligible(10, 201));
+    }
+
+    #[test]
+    fn test_insert() {
+        let pool = BundlePool::with_limits(0, 2, 3);
+        pool.on_new_head(9, &Default::default(), &Default::default());
+        assert!(matches!(pool.insert(bundle(1, 9)), Err(Error::StaleBlock { target: 9, head: 9 })));
+
+        let low = pool.insert(bundle_with_tip(1, 10, 1)).unwrap();
+        let high = pool.insert(bundle_with_tip(2, 10, 3)).unwrap();
+        // NOTE: a bundle sent again replaces itself rather than another bundle
+        assert_eq!(pool.insert(bundle_with_tip(1, 10, 1)).unwrap(), low);
+        assert_eq!(hashes(pool.bundles_for(10, 0)).len(), 2);
+
+        // NOTE: a full block drops the bundle bidding the least, unless the new bundle bids less
+        assert!(matches!(pool.insert(bundle_with_tip(3, 10, 1)), Err(Error::PoolFull)));
+        let higher = pool.insert(bundle_with_tip(3, 10, 2)).unwrap();
+        let mut expected = vec![high, higher];
+        expected.sort();
+        assert_eq!(hashes(pool.bundles_for(10, 0)), expected);
+
+        // NOTE: a full pool drops the bundle bidding the least across all blocks
+        let next = pool.insert(bundle_with_tip(4, 11, 5)).unwrap();
+        let last = pool.insert(bundle_with_tip(5, 11, 4)).unwrap();
+        assert_eq!(hashes(pool.bundles_for(10, 0)), vec![high]);
+        let mut expected = vec![next, last];
+        expected.sort();
+        assert_eq!(hashes(pool.bundles_for(11, 0)), expected);
+        assert!(matches!(pool.insert(bundle_with_tip(6, 12, 1)), Err(Error::PoolFull)));
+    }
+
+    #[test]
+    fn test_on_new_head() {
+        let pool = BundlePool::new(1);
+        let origin = Origin { provider: "searcher".to_string(), priority: false };
+        let included = bundle_with_tip(1, 10, 1).with_origin(origin.clone());
+        let included_hashes = included.transactions.iter().map(|tx| tx.hash).collect();
+        pool.insert(included).unwrap();
+        let stale = bundle_with_tip(2, 10, 1);
+        pool.insert(stale).unwrap();
+        let carried = pool.insert(bundle_with_tip(3, 10, 1)).unwrap();
+        pool.insert(bundle(4, 11)).unwrap();
+
+        // NOTE: the bundle whose transactions all landed is credited to its origin, and bundles
+        // whose transactions can no longer be executed are not carried over
+        let nonces = HashMap::from([(
+            PrivateKeySigner::from_bytes(&B256::repeat_byte(2)).unwrap().address(),
+            1,
+        )]);
+        let origins = pool.on_new_head(10, &included_hashes, &nonces);
+        assert_eq!(origins, vec![origin]);
+        let mut expected = vec![B256::repeat_byte(4), carried];
+        expected.sort();
+        assert_eq!(hashes(pool.bundles_for(11, 0)), expected);
+        assert!(matches!(
+            pool.insert(bundle(5, 10)),
+            Err(Error::StaleBlock { target: 10, head: 10 })
+        ));
+    }
+
+    #[test]
+    fn test_carry_over() {
+        let pool = BundlePool::new(2);
+        pool.insert(bundle(1, 10)).unwrap();
+        pool.insert(bundle(2, 11)).unwrap();
+        let no_nonces = HashMap::default();
+
+        pool.on_new_head(10, &Default::default(), &no_nonces);
+        assert_eq!(hashes(pool.bundles_for(11, 0)), [B256::repeat_byte(1), B256::repeat_byte(2)]);
+
+        // NOTE: a bundle is carried over for at most two blocks past the block it targets
+        pool.on_new_head(11, &Default::default(), &no_nonces);
+        pool.on_new_head(12, &Default::default(), &no_nonces);
+        assert_eq!(hashes(pool.bundles_for(13, 0)), [B256::repeat_byte(2)]);
+        pool.on_new_head(13, &Default::default(), &no_nonces);
+        assert!(pool.bundles_for(14, 0).is_empty());
+
+        let pool = BundlePool::default();
+        pool.insert(bundle(1, 10)).unwrap();
+        pool.on_new_head(10, &Default::default(), &no_nonces);
+        assert!(pool.bundles_for(11, 0).is_empty());
+    }
+
+    #[test]
+    fn test_backrun() {
+        let pool = BundlePool::default();
+        let private_transactions = PrivateTransactionPool::default();
+        let tx_hash =
+            private_transactions.insert(&encoded_transaction(1, 0), Some(5), None).unwrap();
+
+        // NOTE: a transaction may only be backrun once its sender opted into hints
+        assert!(matches!(
+            private_transactions.check_backrun(&tx_hash, 1),
+            Err(Error::NotBackrunnable(..))
+        ));
+        private_transactions.allow_backruns(&tx_hash);
+        assert!(private_transactions.check_backrun(&tx_hash, 5).is_ok());
+        assert!(matches!(
+            private_transactions.check_backrun(&tx_hash, 6),
+            Err(Error::BeyondMaxBlock { max_block_number: 5, .. })
+        ));
+
+        let backrun = Bundle::try_backrun(tx_hash, &[encoded_transaction(3, 0)], 1).unwrap();
+        assert_eq!(backrun.transactions.len(), 1);
+        pool.insert(backrun).unwrap();
+        let resolved = pool
+            .bundles_for(1, 0)
+            .into_iter()
+            .filter_map(|bundle| private_transactions.resolve_backrun(bundle))
+            .collect::<Vec<_>>();
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].transactions.len(), 2);
+        assert_eq!(resolved[0].transactions[0].hash, tx_hash);
+
+        // NOTE: a backrun is never built once the transaction it backruns is cancelled
+        let sender = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap().address();
+        assert!(private_transactions.remove(&tx_hash, sender).unwrap());
+        let bundle = pool.bundles_for(1, 0).pop().unwrap();
+        assert!(private_transactions.resolve_backrun(bundle).is_none());
+        assert_eq!(pool.remove_including(&tx_hash), 1);
+        assert!(pool.bundles_for(1, 0).is_empty());
+    }
+
+    #[test]
+    fn test_with_prerequisites() {
+        let bundle =
+            Bundle::try_new(&[encoded_transaction(1, 2)], 1, None, None, Default::default())
+                .unwrap();
+        let prerequisites = [encoded_transaction(1, 0), encoded_transaction(1, 1)]
+            .iter()
+            .enumerate()
+            .map(|(index, encoding)| decode_transaction(index, encoding).unwrap())
+            .collect::<Vec<_>>();
+        let hashes = prerequisites.iter().map(|tx| tx.hash).collect::<Vec<_>>();
+
+        let unchanged = bundle.clone().with_prerequisites(vec![]);
+        assert_eq!(unchanged.hash, bundle.hash);
+        let resolved = bundle.clone().with_prerequisites(prerequisites);
+        let nonces = resolved.transactions.iter().map(|tx| tx.nonce()).collect::<Vec<_>>();
+        assert_eq!(nonces, vec![0, 1, 2]);
+        let hash = keccak256(
+            hashes
+                .iter()
+                .chain(std::iter::once(&bundle.hash))
+                .flat_map(|hash| hash.0)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(resolved.hash, hash);
+    }
+
+    #[test]
+    fn test_bundle_transactions_are_known_past_inclusion() {
+        let pool = BundlePool::default();
+        let bundle =
+            Bundle::try_new(&[encoded_transaction(1, 0)], 1, None, None, Default::default())
+                .unwrap();
+        let tx_hash = bundle.transactions[0].hash;
+        pool.insert(bundle).unwrap();
+        assert!(pool.contains_transaction(&tx_hash));
+
+        // NOTE: the transaction is still known once its bundle is included, in case of a reorg
+        let included = HashSet::from([tx_hash]);
+        pool.on_new_head(1, &included, &Default::default());
+        assert!(pool.bundles_for(2, 0).is_empty());
+        assert!(pool.contains_transaction(&tx_hash));
+
+        pool.on_new_head(MAX_REORG_DEPTH, &Default::default(), &Default::default());
+        assert!(pool.contains_transaction(&tx_hash));
+        pool.on_new_head(MAX_REORG_DEPTH + 1, &Default::default(), &Default::default());
+        assert!(!pool.contains_transaction(&tx_hash));
+    }
+
+    #[test]
+    fn test_private_transaction_cancelled_by_sender() {
+        let private_transactions = PrivateTransactionPool::default();
+        let tx_hash = private_transactions.insert(&encoded_transaction(1, 0), None, None).unwrap();
+        let sender = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap().address();
+        let other = PrivateKeySigner::from_bytes(&B256::repeat_byte(3)).unwrap().address();
+
+        assert!(matches!(private_transactions.remove(&tx_hash, other), Err(Error::NotSender(..))));
+        assert!(private_transactions.contains(&tx_hash));
+        assert!(!private_transactions.any_cancelled([tx_hash].iter()));
+        assert!(private_transactions.remove(&tx_hash, sender).unwrap());
+        assert!(private_transactions.any_cancelled([tx_hash].iter()));
+        assert!(!private_transactions.remove(&tx_hash, sender).unwrap());
+    }
+
+    #[test]
+    fn test_private_transaction_pool_limit() {
+        let private_transactions = PrivateTransactionPool::with_limit(2);
+        let low = private_transactions
+            .insert(&encoded_transaction_with_tip(1, 0, 1), None, None)
+            .unwrap();
+        let high = private_transactions
+            .insert(&encoded_transaction_with_tip(3, 0, 3), None, None)
+            .unwrap();
+
+        // NOTE: a full pool drops the transaction bidding the least, unless the new one bids less
+        assert!(matches!(
+            private_transactions.insert(&encoded_transaction_with_tip(4, 0, 1), None, None),
+            Err(Error::PrivatePoolFull)
+        ));
+        let higher = private_transactions
+            .insert(&encoded_transaction_with_tip(5, 0, 2), None, None)
+            .unwrap();
+        assert!(!private_transactions.contains(&low));
+        assert!(private_transactions.contains(&high));
+        assert!(private_transactions.contains(&higher));
+    }
+}
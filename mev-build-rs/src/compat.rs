@@ -6,7 +6,7 @@ use ethereum_consensus::{
     ssz::prelude::{ByteList, ByteVector, SimpleSerializeError, U256},
     Fork,
 };
-use mev_rs::types::{BlobsBundle, ExecutionPayload};
+use mev_rs::types::{BlobsBundle, ExecutionPayload, ExecutionPayloadHeader};
 use reth::primitives::{
     revm_primitives::{alloy_primitives::Bloom, Address, B256},
     BlobTransactionSidecar, SealedBlock,
@@ -77,6 +77,23 @@ pub fn to_execution_payload(value: &SealedBlock, fork: Fork) -> Result<Execution
     }
 }
 
+pub fn to_execution_payload_header(
+    value: &ExecutionPayload,
+) -> Result<ExecutionPayloadHeader, Error> {
+    let header = match value {
+        ExecutionPayload::Bellatrix(payload) => ExecutionPayloadHeader::Bellatrix(
+            payload.try_into().map_err(|err| Error::Consensus(err.into()))?,
+        ),
+        ExecutionPayload::Capella(payload) => ExecutionPayloadHeader::Capella(
+            payload.try_into().map_err(|err| Error::Consensus(err.into()))?,
+        ),
+        ExecutionPayload::Deneb(payload) => ExecutionPayloadHeader::Deneb(
+            payload.try_into().map_err(|err| Error::Consensus(err.into()))?,
+        ),
+    };
+    Ok(header)
+}
+
 pub fn to_blobs_bundle(sidecars: &[BlobTransactionSidecar]) -> Result<BlobsBundle, Error> {
     let mut commitments = vec![];
     let mut proofs = vec![];
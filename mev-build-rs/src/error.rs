@@ -1,5 +1,6 @@
 use alloy::signers::local::LocalSignerError;
 use ethereum_consensus::{Error as ConsensusError, Fork};
+use rayon::ThreadPoolBuildError;
 use reth::payload::PayloadBuilderError;
 use thiserror::Error;
 
@@ -13,4 +14,12 @@ pub enum Error {
     PayloadBuilderError(#[from] PayloadBuilderError),
     #[error(transparent)]
     SignerError(#[from] LocalSignerError),
+    #[error("could not sign with builder key: {0}")]
+    SigningFailed(#[from] mev_rs::signer::Error),
+    #[error(transparent)]
+    ThreadPool(#[from] ThreadPoolBuildError),
+    #[error("treasury fee of {0} basis points is more than the whole profit of a block")]
+    InvalidTreasuryFee(u64),
+    #[error("could not load subsidy ledger: {0}")]
+    SubsidyLedger(#[from] crate::payload::subsidy::Error),
 }
@@ -0,0 +1,52 @@
+//! Execution extension streaming the chain's committed and reverted blocks into the builder's
+//! bundle pools
+
+use crate::bundles::{changed_nonces, BundlePool, PrivateTransactionPool};
+use futures_util::TryStreamExt;
+use reth::{api::FullNodeComponents, primitives::revm_primitives::B256, providers::Chain};
+use reth_exex::{ExExContext, ExExEvent};
+use std::collections::HashSet;
+use tracing::{debug, info};
+
+pub const EXEX_ID: &str = "mev-builder/bundles";
+
+fn transaction_hashes(chain: &Chain) -> HashSet<B256> {
+    chain.blocks_iter().flat_map(|block| block.body.transactions.iter().map(|tx| tx.hash)).collect()
+}
+
+/// Keep the bundle pools in line with the chain, dropping bundles whose transactions land in any
+/// committed block and restoring the private transactions of blocks reverted by a reorg
+pub async fn run<Node: FullNodeComponents>(
+    mut ctx: ExExContext<Node>,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+) -> eyre::Result<()> {
+    while let Some(notification) = ctx.notifications.try_next().await? {
+        let committed = notification.committed_chain();
+        if let Some(reverted) = notification.reverted_chain() {
+            // NOTE: the chain is rewound to the block before the first reverted block, unless the
+            // reorg commits a new chain in its place
+            let head = committed
+                .as_ref()
+                .map_or(reverted.first().number.saturating_sub(1), |chain| chain.tip().number);
+            bundles.on_reorg(head);
+            let restored = private_transactions.on_reorg(head, transaction_hashes(&reverted));
+            info!(head, reverted = reverted.len(), restored, "rewound bundle pools after reorg");
+        }
+        if let Some(committed) = committed {
+            let tip = committed.tip();
+            let nonces = changed_nonces(committed.execution_outcome());
+            for block in committed.blocks_iter() {
+                private_transactions.on_new_block(
+                    block.number,
+                    block.body.transactions.iter().map(|tx| tx.hash),
+                    &nonces,
+                );
+            }
+            let dropped = bundles.on_committed(tip.number, &transaction_hashes(&committed));
+            debug!(head = tip.number, dropped, "dropped bundles with transactions on chain");
+            ctx.events.send(ExExEvent::FinishedHeight(tip.num_hash()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,328 @@
+//! Hints about the private transactions held by the builder, streamed to searchers so they can
+//! backrun the transactions without seeing them
+
+use crate::{
+    bundles::{Bundle, BundlePool, Error as BundleError, PrivateTransactionPool},
+    metrics,
+    rpc::SendBundleResponse,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Router,
+};
+use beacon_api_client::ApiError;
+use mev_rs::blinded_block_relayer::API_KEY_HEADER;
+use reth::primitives::{
+    revm_primitives::{alloy_primitives::U64, Address, Bytes, Log, B256},
+    TransactionSignedEcRecovered,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio::{sync::broadcast, task::JoinHandle};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tracing::{debug, error, info, trace, warn};
+
+// Number of hints buffered for each subscriber before a slow subscriber misses hints
+const DEFAULT_HINT_CHANNEL_SIZE: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HintKind {
+    /// The address the transaction calls
+    ContractAddress,
+    /// The first four bytes of the transaction's calldata
+    FunctionSelector,
+    /// The transaction's whole calldata
+    Calldata,
+    /// The logs the transaction emits when executed on top of the latest block
+    Logs,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    /// Address to serve the hints on; if missing, they are only served to the local host
+    pub host: Option<Ipv4Addr>,
+    pub port: u16,
+    /// Details of a private transaction the builder may share in its hint, along with its hash;
+    /// each transaction is only hinted with the details its sender opts into
+    #[serde(default)]
+    pub share: Vec<HintKind>,
+    /// Keys searchers send in the `x-api-key` header of each request; if empty, any searcher
+    /// that can reach `host` is served
+    #[serde(default)]
+    pub api_keys: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("missing or unknown API key")]
+    Unauthorized,
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        let code = match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::Bundle(..) => StatusCode::BAD_REQUEST,
+        };
+        (code, Json(ApiError::ErrorMessage { code, message })).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HintedLog {
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HintedTransaction {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub function_selector: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub call_data: Option<Bytes>,
+}
+
+/// What the builder shares about a private transaction, following the shape of MEV-Share's hints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hint {
+    pub hash: B256,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub logs: Vec<HintedLog>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub txs: Vec<HintedTransaction>,
+}
+
+/// A bundle backrunning a hinted transaction, whose transactions are placed right after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBackrunRequest {
+    pub tx_hash: B256,
+    pub txs: Vec<Bytes>,
+    pub block_number: U64,
+}
+
+/// Emits a hint for each private transaction to any subscribed searchers
+#[derive(Debug, Clone)]
+pub struct Hints {
+    share: Arc<HashSet<HintKind>>,
+    hints: broadcast::Sender<Hint>,
+}
+
+impl Hints {
+    pub fn new(share: &[HintKind]) -> Self {
+        let (hints, _) = broadcast::channel(DEFAULT_HINT_CHANNEL_SIZE);
+        Self { share: Arc::new(share.iter().copied().collect()), hints }
+    }
+
+    /// Return the details shared in the hint of a transaction whose sender opted into `requested`,
+    /// or `None` if the transaction is not hinted at all
+    pub fn shared(&self, requested: &[HintKind]) -> Option<HashSet<HintKind>> {
+        // NOTE: a transaction is only hinted, and so only backrun, if its sender opts in
+        if requested.is_empty() {
+            return None
+        }
+        Some(requested.iter().filter(|kind| self.share.contains(kind)).copied().collect())
+    }
+
+    /// Emit the hint sharing `shared` about `transaction`, which emitted `logs` when executed
+    pub fn emit(
+        &self,
+        transaction: &TransactionSignedEcRecovered,
+        shared: &HashSet<HintKind>,
+        logs: Vec<Log>,
+    ) {
+        let input = transaction.input();
+        let hinted_transaction = HintedTransaction {
+            to: transaction.to().filter(|_| shared.contains(&HintKind::ContractAddress)),
+            function_selector: (shared.contains(&HintKind::FunctionSelector) && input.len() >= 4)
+                .then(|| input.slice(..4)),
+            call_data: shared.contains(&HintKind::Calldata).then(|| input.clone()),
+        };
+        let logs = if shared.contains(&HintKind::Logs) {
+            logs.into_iter()
+                .map(|log| HintedLog {
+                    address: log.address,
+                    topics: log.topics().to_vec(),
+                    data: log.data.data,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let hint = Hint { hash: transaction.hash, logs, txs: vec![hinted_transaction] };
+        metrics::on_hint_emitted();
+        // NOTE: there may be no subscribers, in which case the hint is dropped
+        let _ = self.hints.send(hint);
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<Hint> {
+        self.hints.subscribe()
+    }
+}
+
+#[derive(Clone)]
+struct HintServer {
+    hints: Hints,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    api_keys: Arc<HashSet<String>>,
+}
+
+impl HintServer {
+    fn authenticate(&self, headers: &HeaderMap) -> Result<(), Error> {
+        if self.api_keys.is_empty() {
+            return Ok(())
+        }
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|api_key| self.api_keys.contains(*api_key))
+            .map(|_| ())
+            .ok_or(Error::Unauthorized)
+    }
+}
+
+async fn handle_stream_hints(
+    State(server): State<HintServer>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, Error> {
+    server.authenticate(&headers)?;
+    trace!("streaming hints");
+    let events = BroadcastStream::new(server.hints.subscribe()).filter_map(|hint| match hint {
+        Ok(hint) => Some(Event::default().json_data(hint)),
+        Err(err) => {
+            // NOTE: a slow subscriber misses hints, rather than holding up the builder
+            warn!(%err, "subscriber lagged behind stream of hints");
+            None
+        }
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+async fn handle_send_backrun(
+    State(server): State<HintServer>,
+    headers: HeaderMap,
+    Json(request): Json<SendBackrunRequest>,
+) -> Result<Json<SendBundleResponse>, Error> {
+    server.authenticate(&headers)?;
+    let SendBackrunRequest { tx_hash, txs, block_number } = request;
+    let block_number = block_number.to();
+    // NOTE: a backrun may only target blocks its transaction is still considered for
+    server.private_transactions.check_backrun(&tx_hash, block_number)?;
+    let bundle = Bundle::try_backrun(tx_hash, &txs, block_number)?;
+    let bundle_hash = server.bundles.insert(bundle)?;
+    metrics::on_backrun_received();
+    debug!(%bundle_hash, %tx_hash, block_number, "received backrun");
+    Ok(Json(SendBundleResponse { bundle_hash }))
+}
+
+/// Serve the stream of `hints` to searchers, along with an endpoint accepting bundles that
+/// backrun the hinted transactions, which are added to `bundles`
+pub fn spawn(
+    config: &Config,
+    hints: Hints,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+) -> JoinHandle<()> {
+    let api_keys = Arc::new(config.api_keys.iter().cloned().collect());
+    let server = HintServer { hints, bundles, private_transactions, api_keys };
+    let router = Router::new()
+        .route("/hints/v1/stream", get(handle_stream_hints))
+        .route("/hints/v1/backruns", post(handle_send_backrun))
+        .with_state(server);
+    let addr = SocketAddr::from((config.host.unwrap_or(Ipv4Addr::LOCALHOST), config.port));
+    let server = axum::Server::bind(&addr).serve(router.into_make_service());
+    tokio::spawn(async move {
+        info!(%addr, "serving hints...");
+        if let Err(err) = server.await {
+            error!(%err, "error while serving hints")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use axum::http::HeaderValue;
+    use reth::primitives::{
+        revm_primitives::{alloy_primitives::Parity, TxKind},
+        Signature, Transaction, TransactionSigned,
+    };
+
+    #[test]
+    fn test_hints_are_opt_in() {
+        let hints = Hints::new(&[HintKind::FunctionSelector, HintKind::Logs]);
+        assert!(hints.shared(&[]).is_none());
+        let shared = hints.shared(&[HintKind::FunctionSelector, HintKind::Calldata]).unwrap();
+        assert_eq!(shared, HashSet::from([HintKind::FunctionSelector]));
+
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            to: TxKind::Call(Address::repeat_byte(2)),
+            input: Bytes::from_static(&[1, 2, 3, 4, 5]),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let transaction = TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        )
+        .into_ecrecovered()
+        .unwrap();
+        let mut subscriber = hints.subscribe();
+        hints.emit(&transaction, &shared, vec![Log::empty()]);
+        let hint = subscriber.try_recv().unwrap();
+        assert_eq!(hint.hash, transaction.hash);
+        assert!(hint.logs.is_empty());
+        assert_eq!(hint.txs[0].function_selector, Some(Bytes::from_static(&[1, 2, 3, 4])));
+        assert!(hint.txs[0].to.is_none() && hint.txs[0].call_data.is_none());
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let server = |api_keys: &[&str]| HintServer {
+            hints: Hints::new(&[]),
+            bundles: Default::default(),
+            private_transactions: Default::default(),
+            api_keys: Arc::new(api_keys.iter().map(|key| key.to_string()).collect()),
+        };
+        let mut headers = HeaderMap::new();
+        assert!(server(&[]).authenticate(&headers).is_ok());
+        assert!(matches!(server(&["key"]).authenticate(&headers), Err(Error::Unauthorized)));
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("other"));
+        assert!(matches!(server(&["key"]).authenticate(&headers), Err(Error::Unauthorized)));
+        headers.insert(API_KEY_HEADER, HeaderValue::from_static("key"));
+        assert!(server(&["key"]).authenticate(&headers).is_ok());
+    }
+
+    #[test]
+    fn test_backrun_request_serde() {
+        let json = r#"{"txHash":"0x0101010101010101010101010101010101010101010101010101010101010101","txs":["0x02"],"blockNumber":"0x1"}"#;
+        let request: SendBackrunRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(request.tx_hash, B256::repeat_byte(1));
+        assert_eq!(request.block_number, U64::from(1));
+        assert_eq!(serde_json::to_string(&request).unwrap(), json);
+    }
+}
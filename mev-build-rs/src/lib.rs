@@ -1,10 +1,41 @@
 mod auctioneer;
 mod bidder;
+mod blocklist;
+mod bundles;
 mod compat;
 mod error;
+mod exex;
+mod hints;
+mod metrics;
 mod node;
+mod orderflow;
 mod payload;
+mod pool;
+mod reload;
+mod reputation;
+mod rpc;
 mod service;
+mod user_operations;
 
 pub use crate::error::Error;
+pub use blocklist::{Blocklist, Error as BlocklistError};
+pub use bundles::{
+    Bundle, BundleCandidate, BundlePool, Error as BundleError, Origin, PrivateTransactionPool,
+    StateAccess,
+};
+pub use hints::{
+    Config as HintsConfig, Hint, HintKind, HintedLog, HintedTransaction, SendBackrunRequest,
+};
+pub use orderflow::{
+    Config as OrderflowConfig, Inclusion, Order, OrderAck, ProviderConfig as OrderflowProvider,
+    SendTransactionResponse,
+};
+pub use payload::ordering::{BundleOrdering, Greedy, OrderingKind, ProfitMaximizing};
+pub use reputation::{Config as ReputationConfig, Reputation};
+pub use rpc::{
+    CallBundleRequest, CallBundleResponse, CallBundleTransactionResult,
+    CancelPrivateTransactionRequest, PrivacyPreferences, PrivateTransactionPreferences,
+    SendBundleRequest, SendBundleResponse, SendPrivateTransactionRequest,
+};
 pub use service::{launch, Config};
+pub use user_operations::{Error as UserOperationsError, ENTRY_POINT_V06, ENTRY_POINT_V07};
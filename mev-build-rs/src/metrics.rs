@@ -0,0 +1,198 @@
+use crate::payload::treasury::RevenueSplit;
+use metrics::{counter, gauge, histogram};
+use reth::{primitives::revm_primitives::U256, prometheus_exporter::install_prometheus_recorder};
+use std::time::Duration;
+
+const BLOCKLIST_EXCLUSIONS: &str = "builder_blocklist_excluded_transactions_total";
+const REVERTED_TRANSACTIONS: &str = "builder_reverted_transactions_skipped_total";
+const BUILD_DURATION: &str = "builder_build_duration_seconds";
+const BUILD_PHASE_DURATION: &str = "builder_build_phase_duration_seconds";
+const BUILDS_SKIPPED: &str = "builder_builds_skipped_total";
+const SIMULATIONS: &str = "builder_bundle_simulations_total";
+const PAYLOADS_BUILT: &str = "builder_payloads_built_total";
+const PAYLOADS_WITHDRAWN: &str = "builder_payloads_withdrawn_total";
+const AUCTIONS_OPENED: &str = "builder_auctions_opened_total";
+const AUCTIONS_WON: &str = "builder_auctions_won_total";
+const AUCTIONS_SKIPPED: &str = "builder_auctions_skipped_total";
+const BID_VALUE: &str = "builder_bid_value_wei";
+const SUBMISSIONS_ACCEPTED: &str = "builder_submissions_accepted_total";
+const SUBMISSIONS_REJECTED: &str = "builder_submissions_rejected_total";
+const BUNDLE_POOL_SIZE: &str = "builder_bundle_pool_size";
+const BUNDLES_CARRIED_OVER: &str = "builder_bundles_carried_over_total";
+const BUNDLES_EVICTED: &str = "builder_bundles_evicted_total";
+const RELAY_READY: &str = "builder_relay_ready";
+const AUCTIONS_COMPARED: &str = "builder_auctions_compared_total";
+const BID_VALUE_GAP: &str = "builder_bid_value_gap_wei";
+const BID_SHORTFALL: &str = "builder_bid_shortfall_wei";
+const ORDERFLOW_RECEIVED: &str = "builder_orderflow_received_total";
+const ORDERFLOW_INCLUDED: &str = "builder_orderflow_included_total";
+const ORDERFLOW_RATE_LIMITED: &str = "builder_orderflow_rate_limited_total";
+const ORDERFLOW_SUCCESS_RATE: &str = "builder_orderflow_simulation_success_rate";
+const ORDERFLOW_LANDED_RATE: &str = "builder_orderflow_landed_rate";
+const HINTS_EMITTED: &str = "builder_hints_emitted_total";
+const BACKRUNS_RECEIVED: &str = "builder_backruns_received_total";
+const USER_OPERATIONS_RECEIVED: &str = "builder_user_operations_received_total";
+const BLOCK_REVENUE: &str = "builder_block_revenue_wei";
+const SUBSIDY_AVAILABLE: &str = "builder_subsidy_available_wei";
+const EXTERNAL_BLOCKS: &str = "builder_external_blocks_received_total";
+
+// NOTE: `source` is where the excluded transaction came from, e.g. the mempool or a bundle
+pub fn on_blocklist_exclusion(source: &'static str) {
+    counter!(BLOCKLIST_EXCLUSIONS, "source" => source).increment(1);
+}
+
+pub fn on_reverted_transaction() {
+    counter!(REVERTED_TRANSACTIONS).increment(1);
+}
+
+// NOTE: records each build of a payload, whether or not it was better than the previous one
+pub fn on_build(duration: Duration) {
+    histogram!(BUILD_DURATION).record(duration.as_secs_f64());
+}
+
+// NOTE: `phase` is the part of building and submitting a payload that took `duration`: taking a
+// `pool_snapshot` of the bundles and transactions, simulating bundles and `ordering` them,
+// `execution` of the block's transactions, `sealing` the block, `finalization` with the payment to
+// the proposer, or `submission` to the relays
+pub fn on_build_phase(phase: &'static str, duration: Duration) {
+    histogram!(BUILD_PHASE_DURATION, "phase" => phase).record(duration.as_secs_f64());
+}
+
+// NOTE: `reason` is why the block was not sealed: its value was `below_minimum` or `not_better`
+// than the best block already built for the payload
+pub fn on_build_skipped(reason: &'static str) {
+    counter!(BUILDS_SKIPPED, "reason" => reason).increment(1);
+}
+
+// NOTE: `cached` indicates if the outcome of the simulation was reused from an earlier build
+pub fn on_simulation_cache(cached: bool) {
+    counter!(SIMULATIONS, "cached" => if cached { "true" } else { "false" }).increment(1);
+}
+
+pub fn on_payload_built() {
+    counter!(PAYLOADS_BUILT).increment(1);
+}
+
+pub fn on_payload_withdrawn() {
+    counter!(PAYLOADS_WITHDRAWN).increment(1);
+}
+
+pub fn on_auction_opened() {
+    counter!(AUCTIONS_OPENED).increment(1);
+}
+
+// NOTE: counts slots the builder did not build for, as no relay listed their proposer
+pub fn on_auction_skipped() {
+    counter!(AUCTIONS_SKIPPED).increment(1);
+}
+
+// NOTE: the win rate of the builder is the ratio of auctions won to auctions opened
+pub fn on_auction_won() {
+    counter!(AUCTIONS_WON).increment(1);
+}
+
+fn to_f64(value: U256) -> f64 {
+    u128::try_from(value).unwrap_or(u128::MAX) as f64
+}
+
+pub fn on_bid(value: U256) {
+    gauge!(BID_VALUE).set(to_f64(value));
+}
+
+// NOTE: `outcome` is how the builder's best bid for a slot fared against the payload delivered for
+// the slot, e.g. `won` or `lost`
+pub fn on_auction_compared(outcome: &'static str) {
+    counter!(AUCTIONS_COMPARED, "outcome" => outcome).increment(1);
+}
+
+// NOTE: the gap is negative when the delivered payload was worth more than the builder's bid
+pub fn on_bid_value_gap(value: U256, delivered_value: U256) {
+    gauge!(BID_VALUE_GAP).set(to_f64(value) - to_f64(delivered_value));
+    if delivered_value > value {
+        histogram!(BID_SHORTFALL).record(to_f64(delivered_value - value));
+    }
+}
+
+// NOTE: records the share of the revenue of each block won taken by each party, so the builder's
+// profit over time is the sum of the `builder` share
+pub fn on_revenue_split(split: &RevenueSplit) {
+    histogram!(BLOCK_REVENUE, "share" => "proposer").record(to_f64(split.proposer));
+    histogram!(BLOCK_REVENUE, "share" => "treasury").record(to_f64(split.treasury));
+    histogram!(BLOCK_REVENUE, "share" => "builder").record(to_f64(split.builder()));
+}
+
+// NOTE: the subsidy left to spend before reaching any cap on subsidy
+pub fn on_subsidy_available(value: U256) {
+    gauge!(SUBSIDY_AVAILABLE).set(to_f64(value));
+}
+
+pub fn on_submission_accepted(relay: &str) {
+    counter!(SUBMISSIONS_ACCEPTED, "relay" => relay.to_string()).increment(1);
+}
+
+// NOTE: `reason` is how the relay responded to the submission, e.g. `too_late`
+pub fn on_submission_rejected(relay: &str, reason: &'static str) {
+    counter!(SUBMISSIONS_REJECTED, "relay" => relay.to_string(), "reason" => reason).increment(1);
+}
+
+pub fn on_relay_readiness(relay: &str, ready: bool) {
+    gauge!(RELAY_READY, "relay" => relay.to_string()).set(if ready { 1.0 } else { 0.0 });
+}
+
+// NOTE: `kind` is what the provider sent over the exclusive orderflow endpoint, e.g. a `bundle`
+pub fn on_orderflow_received(provider: &str, kind: &'static str) {
+    counter!(ORDERFLOW_RECEIVED, "provider" => provider.to_string(), "kind" => kind).increment(1);
+}
+
+pub fn on_orderflow_included(provider: &str) {
+    counter!(ORDERFLOW_INCLUDED, "provider" => provider.to_string()).increment(1);
+}
+
+pub fn on_orderflow_rate_limited(provider: &str) {
+    counter!(ORDERFLOW_RATE_LIMITED, "provider" => provider.to_string()).increment(1);
+}
+
+// NOTE: the rates are the shares of the provider's bundles simulated against the top of a block
+// that could be included and that landed in the builder's blocks, see `Reputation`
+pub fn on_orderflow_reputation(provider: &str, success_rate: f64, landed_rate: f64) {
+    gauge!(ORDERFLOW_SUCCESS_RATE, "provider" => provider.to_string()).set(success_rate);
+    gauge!(ORDERFLOW_LANDED_RATE, "provider" => provider.to_string()).set(landed_rate);
+}
+
+pub fn on_user_operations_received(provider: &str, count: usize) {
+    counter!(USER_OPERATIONS_RECEIVED, "provider" => provider.to_string()).increment(count as u64);
+}
+
+pub fn on_external_block(pipeline: &str, accepted: bool) {
+    let accepted = if accepted { "true" } else { "false" };
+    counter!(EXTERNAL_BLOCKS, "pipeline" => pipeline.to_string(), "accepted" => accepted)
+        .increment(1);
+}
+
+pub fn on_hint_emitted() {
+    counter!(HINTS_EMITTED).increment(1);
+}
+
+pub fn on_backrun_received() {
+    counter!(BACKRUNS_RECEIVED).increment(1);
+}
+
+pub fn on_bundles_carried_over(count: usize) {
+    counter!(BUNDLES_CARRIED_OVER).increment(count as u64);
+}
+
+pub fn on_bundle_evicted() {
+    counter!(BUNDLES_EVICTED).increment(1);
+}
+
+pub fn on_bundle_pool_size(size: usize) {
+    gauge!(BUNDLE_POOL_SIZE).set(size as f64);
+}
+
+/// Records the builder's metrics with the Prometheus recorder of `reth`, so they are served along
+/// with the node's own metrics at the endpoint `reth` serves with `--metrics`
+pub fn install() {
+    // NOTE: only one global recorder can be installed; `reth` installs its own at most once and
+    // hands out the same one to any later caller
+    install_prometheus_recorder();
+}
@@ -1,7 +1,10 @@
 //! Customized types for the builder to configuring reth
 
-use crate::payload::{
-    attributes::BuilderPayloadBuilderAttributes, service_builder::PayloadServiceBuilder,
+use crate::{
+    payload::{
+        attributes::BuilderPayloadBuilderAttributes, service_builder::PayloadServiceBuilder,
+    },
+    pool::BuilderPoolBuilder,
 };
 use reth::{
     api::{EngineTypes, FullNodeTypes, PayloadTypes},
@@ -15,19 +18,21 @@ use reth::{
 };
 use reth_node_ethereum::node::{
     EthereumConsensusBuilder, EthereumEngineValidatorBuilder, EthereumExecutorBuilder,
-    EthereumNetworkBuilder, EthereumPoolBuilder,
+    EthereumNetworkBuilder,
 };
 
 #[derive(Debug, Default, Clone, Copy)]
 pub struct BuilderNode;
 
 impl BuilderNode {
-    /// Returns a [ComponentsBuilder] configured for a regular Ethereum node.
+    /// Returns a [ComponentsBuilder] configured for a regular Ethereum node, whose transaction pool
+    /// never propagates transactions only sent to the builder.
     pub fn components_with<Node>(
+        pool_builder: BuilderPoolBuilder,
         payload_service_builder: PayloadServiceBuilder,
     ) -> ComponentsBuilder<
         Node,
-        EthereumPoolBuilder,
+        BuilderPoolBuilder,
         PayloadServiceBuilder,
         EthereumNetworkBuilder,
         EthereumExecutorBuilder,
@@ -41,7 +46,7 @@ impl BuilderNode {
     {
         ComponentsBuilder::default()
             .node_types::<Node>()
-            .pool(EthereumPoolBuilder::default())
+            .pool(pool_builder)
             .payload(payload_service_builder)
             .network(EthereumNetworkBuilder::default())
             .executor(EthereumExecutorBuilder::default())
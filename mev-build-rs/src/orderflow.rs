@@ -0,0 +1,418 @@
+//! Authenticated endpoint for orderflow providers to send bundles and transactions exclusively to
+//! the builder
+
+use crate::{
+    bundles::{Bundle, BundlePool, Error as BundleError, Origin, PrivateTransactionPool},
+    metrics,
+    reputation::{Config as ReputationConfig, Reputation},
+    rpc::{SendBundleRequest, SendBundleResponse, SendPrivateTransactionRequest},
+    user_operations::{self, Error as UserOperationsError},
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Json, State,
+    },
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Router,
+};
+use beacon_api_client::ApiError;
+use mev_rs::blinded_block_relayer::API_KEY_HEADER;
+use reth::primitives::revm_primitives::{Address, B256};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, trace, warn};
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Inclusion {
+    /// Order the provider's bundles along with every other bundle
+    #[default]
+    BestEffort,
+    /// Include the provider's bundles ahead of any bundles without priority
+    Priority,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProviderConfig {
+    /// Name of the provider, used to label its orderflow in logs and metrics
+    pub name: String,
+    /// Key the provider sends in the `x-api-key` header of each request
+    pub api_key: String,
+    #[serde(default)]
+    pub inclusion: Inclusion,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    /// Address to serve the endpoint on; if missing, it is only served to the local host, e.g. to
+    /// a proxy terminating TLS in front of it, as the API keys of providers are sent in the clear
+    pub host: Option<Ipv4Addr>,
+    pub port: u16,
+    pub providers: Vec<ProviderConfig>,
+    /// `EntryPoint` contracts accepted in bundles of user operations; if missing, the canonical
+    /// deployments of v0.6 and v0.7 are accepted
+    #[serde(default = "user_operations::default_entry_points")]
+    pub entry_points: Vec<Address>,
+    /// Limits on the orderflow of providers whose bundles mostly fail to simulate; if missing,
+    /// orderflow is accepted from every provider without limit
+    pub reputation: Option<ReputationConfig>,
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("missing or unknown API key")]
+    Unauthorized,
+    #[error("too many orders for the next block from a provider whose bundles mostly fail")]
+    RateLimited,
+    #[error(transparent)]
+    Bundle(#[from] BundleError),
+    #[error(transparent)]
+    UserOperations(#[from] UserOperationsError),
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let message = self.to_string();
+        let code = match self {
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            Self::Bundle(..) | Self::UserOperations(..) => StatusCode::BAD_REQUEST,
+        };
+        (code, Json(ApiError::ErrorMessage { code, message })).into_response()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTransactionResponse {
+    pub tx_hash: B256,
+}
+
+/// Orderflow sent over the stream, each as a JSON text message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Order {
+    Bundle(SendBundleRequest),
+    Transaction(SendPrivateTransactionRequest),
+    UserOperations(SendBundleRequest),
+}
+
+/// The builder's response to each order sent over the stream, carrying the hash of the bundle or
+/// transaction if it was accepted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderAck {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<B256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+struct Orderflow {
+    // origin of the orderflow of each provider, by its API key
+    providers: Arc<HashMap<String, Origin>>,
+    entry_points: Arc<Vec<Address>>,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    reputation: Reputation,
+    limits: Option<Arc<ReputationConfig>>,
+}
+
+impl Orderflow {
+    fn authenticate(&self, headers: &HeaderMap) -> Result<Origin, Error> {
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|api_key| self.providers.get(api_key))
+            .cloned()
+            .ok_or(Error::Unauthorized)
+    }
+
+    // NOTE: a provider whose bundles mostly fail to simulate is limited in the orderflow it sends
+    // for each block, so that it can not take up the builder's simulation capacity
+    fn admit(&self, origin: &Origin) -> Result<(), Error> {
+        let Some(limits) = self.limits.as_ref() else { return Ok(()) };
+        if self.reputation.admit(&origin.provider, limits) {
+            Ok(())
+        } else {
+            metrics::on_orderflow_rate_limited(&origin.provider);
+            Err(Error::RateLimited)
+        }
+    }
+
+    fn send_bundle(&self, origin: Origin, request: SendBundleRequest) -> Result<B256, Error> {
+        metrics::on_orderflow_received(&origin.provider, "bundle");
+        self.admit(&origin)?;
+        let provider = origin.provider.clone();
+        let bundle = Bundle::try_from(request)?.with_origin(origin);
+        let block_number = bundle.block_number;
+        let bundle_hash = self.bundles.insert(bundle)?;
+        debug!(%bundle_hash, block_number, provider, "received exclusive bundle");
+        Ok(bundle_hash)
+    }
+
+    // NOTE: user operations are only accepted from bundlers as `handleOps` transactions, which
+    // the builder includes like any other bundle, so a bundle that reverts is never included
+    fn send_user_operations(
+        &self,
+        origin: Origin,
+        request: SendBundleRequest,
+    ) -> Result<B256, Error> {
+        metrics::on_orderflow_received(&origin.provider, "user_operations");
+        self.admit(&origin)?;
+        let provider = origin.provider.clone();
+        let bundle = Bundle::try_from(request)?.with_origin(origin);
+        let user_operations = user_operations::check_bundle(&bundle, &self.entry_points)?;
+        let block_number = bundle.block_number;
+        let bundle_hash = self.bundles.insert(bundle)?;
+        metrics::on_user_operations_received(&provider, user_operations);
+        debug!(%bundle_hash, block_number, provider, user_operations, "received bundle of user operations");
+        Ok(bundle_hash)
+    }
+
+    fn send_transaction(
+        &self,
+        origin: Origin,
+        request: SendPrivateTransactionRequest,
+    ) -> Result<B256, Error> {
+        metrics::on_orderflow_received(&origin.provider, "transaction");
+        self.admit(&origin)?;
+        // NOTE: exclusive transactions are never hinted
+        let SendPrivateTransactionRequest { tx, max_block_number, .. } = request;
+        let max_block_number = max_block_number.map(|number| number.to());
+        let provider = origin.provider.clone();
+        let tx_hash = self.private_transactions.insert(&tx, max_block_number, Some(origin))?;
+        debug!(%tx_hash, ?max_block_number, provider, "received exclusive transaction");
+        Ok(tx_hash)
+    }
+}
+
+async fn handle_send_bundle(
+    State(orderflow): State<Orderflow>,
+    headers: HeaderMap,
+    Json(request): Json<SendBundleRequest>,
+) -> Result<Json<SendBundleResponse>, Error> {
+    let origin = orderflow.authenticate(&headers)?;
+    let bundle_hash = orderflow.send_bundle(origin, request)?;
+    Ok(Json(SendBundleResponse { bundle_hash }))
+}
+
+async fn handle_send_user_operations(
+    State(orderflow): State<Orderflow>,
+    headers: HeaderMap,
+    Json(request): Json<SendBundleRequest>,
+) -> Result<Json<SendBundleResponse>, Error> {
+    let origin = orderflow.authenticate(&headers)?;
+    let bundle_hash = orderflow.send_user_operations(origin, request)?;
+    Ok(Json(SendBundleResponse { bundle_hash }))
+}
+
+async fn handle_send_transaction(
+    State(orderflow): State<Orderflow>,
+    headers: HeaderMap,
+    Json(request): Json<SendPrivateTransactionRequest>,
+) -> Result<Json<SendTransactionResponse>, Error> {
+    let origin = orderflow.authenticate(&headers)?;
+    let tx_hash = orderflow.send_transaction(origin, request)?;
+    Ok(Json(SendTransactionResponse { tx_hash }))
+}
+
+async fn handle_stream(
+    State(orderflow): State<Orderflow>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, Error> {
+    let origin = orderflow.authenticate(&headers)?;
+    trace!(provider = origin.provider, "opening stream of orderflow");
+    Ok(upgrade.on_upgrade(move |socket| stream_orders(orderflow, origin, socket)))
+}
+
+// Accept JSON-encoded orders in text messages, responding to each with an `OrderAck`. The provider
+// authenticated when opening the stream is the origin of every order sent over it.
+async fn stream_orders(orderflow: Orderflow, origin: Origin, mut socket: WebSocket) {
+    while let Some(message) = socket.recv().await {
+        let order = match message {
+            Ok(Message::Text(text)) => serde_json::from_str::<Order>(&text),
+            Ok(Message::Close(..)) => break,
+            Ok(..) => continue,
+            Err(err) => {
+                trace!(%err, provider = origin.provider, "closing stream of orderflow");
+                break
+            }
+        };
+        let result = match order {
+            Ok(Order::Bundle(request)) => {
+                orderflow.send_bundle(origin.clone(), request).map_err(|err| err.to_string())
+            }
+            Ok(Order::UserOperations(request)) => orderflow
+                .send_user_operations(origin.clone(), request)
+                .map_err(|err| err.to_string()),
+            Ok(Order::Transaction(request)) => {
+                orderflow.send_transaction(origin.clone(), request).map_err(|err| err.to_string())
+            }
+            Err(err) => Err(err.to_string()),
+        };
+        let ack = match result {
+            Ok(hash) => OrderAck { hash: Some(hash), error: None },
+            Err(err) => OrderAck { hash: None, error: Some(err) },
+        };
+        let ack = serde_json::to_string(&ack).expect("can serialize");
+        if socket.send(Message::Text(ack)).await.is_err() {
+            break
+        }
+    }
+}
+
+/// Serve the exclusive orderflow endpoint, adding the orderflow of each provider to `bundles` and
+/// `private_transactions`, so it is never gossiped to the public mempool
+pub fn spawn(
+    config: Config,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    reputation: Reputation,
+) -> JoinHandle<()> {
+    let providers = config
+        .providers
+        .into_iter()
+        .filter(|provider| {
+            // NOTE: an empty key would let in any request sending an empty `x-api-key` header
+            if provider.api_key.is_empty() {
+                warn!(provider = provider.name, "ignoring orderflow provider without an API key");
+            }
+            !provider.api_key.is_empty()
+        })
+        .map(|provider| {
+            let priority = provider.inclusion == Inclusion::Priority;
+            (provider.api_key, Origin { provider: provider.name, priority })
+        })
+        .collect::<HashMap<_, _>>();
+    let provider_count = providers.len();
+    if provider_count == 0 {
+        warn!("no orderflow providers are configured, so all orderflow is rejected");
+    }
+    let orderflow = Orderflow {
+        providers: Arc::new(providers),
+        entry_points: Arc::new(config.entry_points),
+        bundles,
+        private_transactions,
+        reputation,
+        limits: config.reputation.map(Arc::new),
+    };
+    let router = Router::new()
+        .route("/orderflow/v1/bundles", post(handle_send_bundle))
+        .route("/orderflow/v1/transactions", post(handle_send_transaction))
+        .route("/orderflow/v1/user_operations", post(handle_send_user_operations))
+        .route("/orderflow/v1/stream", get(handle_stream))
+        .with_state(orderflow);
+    let addr = SocketAddr::from((config.host.unwrap_or(Ipv4Addr::LOCALHOST), config.port));
+    let server = axum::Server::bind(&addr).serve(router.into_make_service());
+    tokio::spawn(async move {
+        info!(%addr, providers = provider_count, "serving exclusive orderflow...");
+        if let Err(err) = server.await {
+            error!(%err, "error while serving exclusive orderflow")
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderName, HeaderValue};
+    use reth::primitives::revm_primitives::{alloy_primitives::U64, Bytes};
+
+    fn orderflow() -> Orderflow {
+        let origin = Origin { provider: "wallet".to_string(), priority: true };
+        Orderflow {
+            providers: Arc::new(HashMap::from([("secret".to_string(), origin)])),
+            entry_points: Arc::new(user_operations::default_entry_points()),
+            bundles: BundlePool::new(0),
+            private_transactions: Default::default(),
+            reputation: Default::default(),
+            limits: None,
+        }
+    }
+
+    fn headers(api_key: &'static str) -> HeaderMap {
+        let api_key = HeaderValue::from_static(api_key);
+        HeaderMap::from_iter([(HeaderName::from_static(API_KEY_HEADER), api_key)])
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        let orderflow = orderflow();
+        assert!(matches!(orderflow.authenticate(&HeaderMap::new()), Err(Error::Unauthorized)));
+        assert!(matches!(orderflow.authenticate(&headers("")), Err(Error::Unauthorized)));
+        assert!(matches!(orderflow.authenticate(&headers("guess")), Err(Error::Unauthorized)));
+        let origin = orderflow.authenticate(&headers("secret")).unwrap();
+        assert_eq!(origin, Origin { provider: "wallet".to_string(), priority: true });
+
+        // NOTE: requests are authenticated before their orderflow is looked at
+        let request = SendBundleRequest {
+            txs: vec![],
+            block_number: U64::from(1),
+            min_timestamp: None,
+            max_timestamp: None,
+            reverting_tx_hashes: vec![],
+        };
+        let response =
+            handle_send_bundle(State(orderflow.clone()), headers("guess"), Json(request.clone()))
+                .await
+                .into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = handle_send_bundle(State(orderflow), headers("secret"), Json(request))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_order_encoding() {
+        let order = r#"{"type":"bundle","txs":["0x01"],"blockNumber":"0xa"}"#;
+        let Order::Bundle(request) = serde_json::from_str(order).unwrap() else {
+            panic!("order is a bundle")
+        };
+        assert_eq!(request.txs, vec![Bytes::from_static(&[1])]);
+        assert_eq!(request.block_number, U64::from(10));
+
+        let order = r#"{"type":"transaction","tx":"0x01","maxBlockNumber":"0xa"}"#;
+        let Order::Transaction(request) = serde_json::from_str(order).unwrap() else {
+            panic!("order is a transaction")
+        };
+        assert_eq!(request.max_block_number, Some(U64::from(10)));
+
+        let order = r#"{"type":"userOperations","txs":["0x01"],"blockNumber":"0xa"}"#;
+        assert!(matches!(serde_json::from_str(order).unwrap(), Order::UserOperations(..)));
+        assert!(serde_json::from_str::<Order>(r#"{"type":"unknown"}"#).is_err());
+
+        let ack = OrderAck { hash: Some(B256::ZERO), error: None };
+        let expected = serde_json::json!({ "hash": B256::ZERO });
+        assert_eq!(serde_json::to_value(ack).unwrap(), expected);
+        let ack = OrderAck { hash: None, error: Some(BundleError::Empty.to_string()) };
+        assert_eq!(serde_json::to_value(ack).unwrap()["error"], BundleError::Empty.to_string());
+    }
+
+    #[test]
+    fn test_config() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "port": 28550,
+            "providers": [
+                { "name": "wallet", "api_key": "secret", "inclusion": "priority" },
+                { "name": "searcher", "api_key": "other" },
+            ],
+        }))
+        .unwrap();
+        assert_eq!(config.host, None);
+        assert_eq!(config.providers[0].inclusion, Inclusion::Priority);
+        assert_eq!(config.providers[1].inclusion, Inclusion::BestEffort);
+        assert_eq!(config.entry_points, user_operations::default_entry_points());
+    }
+}
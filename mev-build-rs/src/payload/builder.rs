@@ -1,7 +1,27 @@
-use crate::payload::{attributes::BuilderPayloadBuilderAttributes, job::PayloadFinalizerConfig};
+use crate::{
+    blocklist::Blocklist,
+    bundles::{Bundle, BundleCandidate, BundlePool, PrivateTransactionPool, StateAccess},
+    metrics,
+    payload::{
+        attributes::BuilderPayloadBuilderAttributes,
+        dependencies::{Dependency, NonceDependencies, Prerequisites},
+        job::PayloadFinalizerConfig,
+        ordering::BundleOrdering,
+        pre_execution::{Exclusion, PreExecutions},
+        simulation_cache::{Prefix, SimulationCache},
+        state_cache::{PrestateDb, StateCache},
+        subsidy::SubsidyLedger,
+        treasury::{RevenueSplit, Treasury},
+    },
+    reputation::Reputation,
+};
 use alloy::signers::{local::PrivateKeySigner, SignerSync};
 use alloy_consensus::TxEip1559;
 use mev_rs::compute_preferred_gas_limit;
+use rayon::{
+    iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator},
+    ThreadPool,
+};
 use reth::{
     api::PayloadBuilderAttributes,
     chainspec::{ChainSpec, EthereumHardforks},
@@ -13,21 +33,24 @@ use reth::{
         proofs,
         revm_primitives::{
             alloy_primitives::{ChainId, Parity},
-            calc_excess_blob_gas, Address, BlockEnv, CfgEnvWithHandlerCfg, TxEnv, TxKind, U256,
+            calc_excess_blob_gas, Address, BlockEnv, CfgEnvWithHandlerCfg, TxEnv, TxKind, B256,
+            U256,
         },
         transaction::FillTxEnv,
         Block, BlockBody, Header, Receipt, Receipts, SealedBlock, Signature, Transaction,
         TransactionSigned, TransactionSignedEcRecovered, EMPTY_OMMER_ROOT_HASH,
     },
-    providers::{ExecutionOutcome, StateProviderFactory},
+    providers::{ExecutionOutcome, ProviderError, StateProviderFactory},
     revm::{
         self,
         database::StateProviderDatabase,
-        db::states::bundle_state::BundleRetention,
-        primitives::{EVMError, EnvWithHandlerCfg, InvalidTransaction, ResultAndState},
-        DatabaseCommit, State,
+        db::{states::bundle_state::BundleRetention, BundleState},
+        primitives::{
+            EVMError, EnvWithHandlerCfg, ExecutionResult, InvalidTransaction, ResultAndState,
+        },
+        Database, DatabaseCommit, State,
     },
-    transaction_pool::{BestTransactionsAttributes, TransactionPool},
+    transaction_pool::{BestTransactionsAttributes, TransactionPool, ValidPoolTransaction},
 };
 use reth_basic_payload_builder::{
     commit_withdrawals, is_better_payload, BuildArguments, BuildOutcome, PayloadConfig,
@@ -36,9 +59,10 @@ use reth_basic_payload_builder::{
 use reth_evm::{system_calls::SystemCaller, ConfigureEvm, ConfigureEvmEnv, NextBlockEnvAttributes};
 use reth_node_ethereum::EthEvmConfig;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     ops::Deref,
     sync::{Arc, Mutex},
+    time::Instant,
 };
 use thiserror::Error;
 use tokio::sync::mpsc::Sender;
@@ -48,15 +72,26 @@ use tracing::{debug, trace, warn};
 pub enum Error {
     #[error("block gas used {gas_used} exceeded block gas limit {gas_limit}")]
     BlockGasLimitExceeded { gas_used: u64, gas_limit: u64 },
+    #[error("bundle {0} could not be executed after a successful simulation")]
+    BundleExecutionMismatch(B256),
+    #[error("payment account {address} has balance {available} but needs {required} to pay the proposer")]
+    InsufficientPaymentFunds { address: Address, required: U256, available: U256 },
+    #[error("payment transaction {0} to the proposer reverted")]
+    PaymentReverted(B256),
 }
 
 pub const BASE_TX_GAS_LIMIT: u64 = 21000;
 
 pub const PAYMENT_TO_CONTRACT_GAS_LIMIT: u64 = 100_000;
 
+// Number of blocks the transactions taken from the mempool for each build can fill, so that a
+// large mempool does not slow down every build; the pool yields its best transactions first, so
+// only those paying the least are left out
+const POOL_SNAPSHOT_BLOCKS: u64 = 4;
+
 fn make_payment_transaction(
     signer: &PrivateKeySigner,
-    config: &PayloadFinalizerConfig,
+    to: Address,
     chain_id: ChainId,
     nonce: u64,
     gas_limit: u64,
@@ -69,7 +104,7 @@ fn make_payment_transaction(
         gas_limit,
         max_fee_per_gas,
         max_priority_fee_per_gas: 0,
-        to: TxKind::Call(config.proposer_fee_recipient),
+        to: TxKind::Call(to),
         value,
         access_list: Default::default(),
         input: Default::default(),
@@ -83,119 +118,6 @@ fn make_payment_transaction(
     Ok(TransactionSignedEcRecovered::from_signed_transaction(signed_transaction, signer.address()))
 }
 
-fn append_payment<Client: StateProviderFactory>(
-    client: Client,
-    execution_outcome: ExecutionOutcome,
-    signer: &PrivateKeySigner,
-    config: &PayloadFinalizerConfig,
-    chain_id: ChainId,
-    block: SealedBlock,
-    value: U256,
-) -> Result<SealedBlock, PayloadBuilderError> {
-    let state_provider = client.state_by_block_hash(block.header.header().parent_hash)?;
-    let state = StateProviderDatabase::new(&state_provider);
-    // TODO: use cached reads
-    let mut db = State::builder()
-        .with_database_ref(state)
-        // TODO skip clone here...
-        .with_bundle_prestate(execution_outcome.state().clone())
-        .with_bundle_update()
-        .build();
-
-    let signer_account = db.load_cache_account(signer.address())?;
-    let nonce = signer_account.account_info().map(|account| account.nonce).unwrap_or_default();
-
-    let proposer_fee_recipient_account = db.load_cache_account(config.proposer_fee_recipient)?;
-    let is_empty_code_hash = proposer_fee_recipient_account
-        .account_info()
-        .map(|account| account.is_empty_code_hash())
-        .unwrap_or_default();
-
-    // Use a fixed gas limit for the payment transaction reflecting the recipient's status
-    // as smart contract or EOA.
-    let gas_limit =
-        if is_empty_code_hash { BASE_TX_GAS_LIMIT } else { PAYMENT_TO_CONTRACT_GAS_LIMIT };
-
-    // SAFETY: cast to bigger type always succeeds
-    let max_fee_per_gas = block.header().base_fee_per_gas.unwrap_or_default() as u128;
-    let payment_tx = make_payment_transaction(
-        signer,
-        config,
-        chain_id,
-        nonce,
-        gas_limit,
-        max_fee_per_gas,
-        value,
-    )?;
-
-    // TODO: skip clones here
-    let mut tx_env = TxEnv::default();
-    payment_tx.fill_tx_env(&mut tx_env, signer.address());
-    let mut env: EnvWithHandlerCfg = EnvWithHandlerCfg::new_with_cfg_env(
-        config.cfg_env.clone(),
-        config.block_env.clone(),
-        tx_env,
-    );
-    // NOTE: adjust gas limit to allow for payment transaction
-    env.block.gas_limit += U256::from(gas_limit);
-    let mut evm = revm::Evm::builder().with_db(&mut db).with_env_with_handler_cfg(env).build();
-
-    let ResultAndState { result, state } =
-        evm.transact().map_err(PayloadBuilderError::EvmExecutionError)?;
-
-    drop(evm);
-    db.commit(state);
-
-    let Block { mut header, mut body } = block.unseal();
-
-    // Verify we reserved the correct amount of gas for the payment transaction
-    let gas_limit = header.gas_limit + result.gas_used();
-    let cumulative_gas_used = header.gas_used + result.gas_used();
-    if cumulative_gas_used > gas_limit {
-        return Err(PayloadBuilderError::Other(Box::new(Error::BlockGasLimitExceeded {
-            gas_used: cumulative_gas_used,
-            gas_limit: header.gas_limit,
-        })))
-    }
-    let receipt = Receipt {
-        tx_type: payment_tx.tx_type(),
-        success: result.is_success(),
-        cumulative_gas_used,
-        logs: result.into_logs().into_iter().map(Into::into).collect(),
-    };
-
-    body.transactions.push(payment_tx.into_signed());
-
-    db.merge_transitions(BundleRetention::PlainState);
-
-    let block_number = header.number;
-    // TODO skip clone here
-    let mut receipts = execution_outcome.receipts_by_block(block_number).to_vec();
-    receipts.push(Some(receipt));
-
-    let receipts = Receipts::from(vec![receipts]);
-
-    // TODO: final parameter is for EIP-7685 requests
-    let execution_outcome = ExecutionOutcome::new(db.take_bundle(), receipts, block_number, vec![]);
-
-    let receipts_root =
-        execution_outcome.receipts_root_slow(block_number).expect("Number is in range");
-    let logs_bloom = execution_outcome.block_logs_bloom(block_number).expect("Number is in range");
-    let state_root = state_provider.state_root(execution_outcome.hash_state_slow())?;
-    let transactions_root = proofs::calculate_transaction_root(&body.transactions);
-
-    header.state_root = state_root;
-    header.transactions_root = transactions_root;
-    header.receipts_root = receipts_root;
-    header.logs_bloom = logs_bloom;
-    header.gas_used = cumulative_gas_used;
-    header.gas_limit = gas_limit;
-
-    let block = Block { header, body };
-
-    Ok(block.seal_slow())
-}
-
 #[derive(Debug, Clone)]
 pub struct PayloadBuilder(Arc<Inner>);
 
@@ -207,36 +129,125 @@ impl Deref for PayloadBuilder {
     }
 }
 
+/// How the builder selects the bundles and transactions to include in a block
+#[derive(Debug, Clone)]
+pub struct SelectionConfig {
+    pub blocklist: Blocklist,
+    // gas reserved for bundles at the top of the block, if any
+    pub top_of_block_gas: Option<u64>,
+    // minimum effective priority fee per gas of transactions from the mempool, if any
+    pub min_priority_fee: Option<u128>,
+    // minimum value of a block to the builder for it to be sealed and bid, if any, i.e. the
+    // minimum bid of the local proposer when it is the only one bid to
+    pub min_block_value: Option<U256>,
+    // NOTE: the first ordering is used by default, and a candidate payload is built with each
+    // ordering when building candidates in parallel
+    pub orderings: Vec<Arc<dyn BundleOrdering>>,
+    // threads to simulate bundles and pre-execute transactions from the mempool on in parallel,
+    // if any; otherwise, bundles are simulated one after another on the thread building the
+    // payload and transactions are only executed once selected
+    pub simulation_threads: Option<Arc<ThreadPool>>,
+    // reputation of the orderflow providers, deciding which bundles are simulated first
+    pub reputation: Reputation,
+}
+
+/// How the builder is paid for the blocks it builds, and how it pays the proposer
+#[derive(Debug, Clone)]
+pub struct PaymentConfig {
+    // account sending the payment to the proposer at the end of each block
+    pub signer: PrivateKeySigner,
+    // address receiving the fees of built blocks
+    pub fee_recipient: Address,
+    // treasury paid a share of the profit of each block, if any
+    pub treasury: Option<Treasury>,
+    // subsidy spent on blocks won, checked against caps over time, if any
+    pub subsidy: Option<SubsidyLedger>,
+}
+
 #[derive(Debug)]
 pub struct Inner {
     bids: Sender<EthBuiltPayload>,
     signer: PrivateKeySigner,
     fee_recipient: Address,
+    treasury: Option<Treasury>,
+    subsidy: Option<Mutex<SubsidyLedger>>,
     chain_id: ChainId,
-    execution_outcomes: Mutex<HashMap<PayloadId, ExecutionOutcome>>,
+    // execution outcomes of built payloads, by block hash, for use when finalizing them
+    execution_outcomes: Mutex<HashMap<B256, ExecutionOutcome>>,
+    // split of the revenue of finalized payloads, by block hash, along with their number
+    revenue_splits: Mutex<HashMap<B256, (u64, RevenueSplit)>>,
+    // reads of the state of each parent block built on, by its hash, along with its number
+    state_caches: Mutex<HashMap<B256, (u64, StateCache)>>,
+    // outcomes of simulating bundles for each payload, along with the number of its parent block
+    simulation_caches: Mutex<HashMap<PayloadId, (u64, SimulationCache<BundleSimulation>)>>,
     evm_config: EthEvmConfig,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    selection: SelectionConfig,
 }
 
 impl PayloadBuilder {
     pub fn new(
         bids: Sender<EthBuiltPayload>,
-        signer: PrivateKeySigner,
-        fee_recipient: Address,
-        chain_id: ChainId,
+        payment: PaymentConfig,
         chain_spec: Arc<ChainSpec>,
+        bundles: BundlePool,
+        private_transactions: PrivateTransactionPool,
+        selection: SelectionConfig,
     ) -> Self {
+        assert!(!selection.orderings.is_empty(), "at least one ordering is required");
+        let chain_id = chain_spec.chain().id();
         let evm_config = EthEvmConfig::new(chain_spec);
+        let PaymentConfig { signer, fee_recipient, treasury, subsidy } = payment;
         let inner = Inner {
             bids,
             signer,
             fee_recipient,
+            treasury,
+            subsidy: subsidy.map(Mutex::new),
             chain_id,
             execution_outcomes: Default::default(),
+            revenue_splits: Default::default(),
+            state_caches: Default::default(),
+            simulation_caches: Default::default(),
             evm_config,
+            bundles,
+            private_transactions,
+            selection,
         };
         Self(Arc::new(inner))
     }
 
+    /// Address receiving the fees of built blocks
+    pub fn fee_recipient(&self) -> Address {
+        self.fee_recipient
+    }
+
+    pub fn orderings(&self) -> &[Arc<dyn BundleOrdering>] {
+        &self.selection.orderings
+    }
+
+    pub fn bundle_pool(&self) -> &BundlePool {
+        &self.bundles
+    }
+
+    pub fn private_transaction_pool(&self) -> &PrivateTransactionPool {
+        &self.private_transactions
+    }
+
+    pub fn reputation(&self) -> &Reputation {
+        &self.selection.reputation
+    }
+
+    /// Gas reserved at the end of each block for the payments to the proposer and any treasury
+    // NOTE: reserve enough gas for each payment transaction, regardless of EOA or smart contract
+    // TODO: check recipients ahead of time to determine this, rather than leave some gas on the
+    // table
+    fn reserved_payment_gas(&self) -> u64 {
+        let payments = 1 + self.treasury.is_some() as u64;
+        payments * PAYMENT_TO_CONTRACT_GAS_LIMIT
+    }
+
     pub fn cfg_and_block_env(
         &self,
         payload_config: &PayloadConfig<BuilderPayloadBuilderAttributes>,
@@ -256,20 +267,54 @@ impl PayloadBuilder {
                 proposal_attributes.proposer_gas_limit,
                 payload_config.parent_block.gas_limit,
             );
-            // NOTE: reserve enough gas for the final payment transaction,
-            // regardless of EOA or smart contract
-            // TODO: check recipient ahead of time to determine this here, rather than leave some
-            // gas on the table
-            block_env.gas_limit = U256::from(gas_limit) - U256::from(PAYMENT_TO_CONTRACT_GAS_LIMIT);
+            // NOTE: reserve enough gas for the final payment transactions
+            block_env.gas_limit = U256::from(gas_limit - self.reserved_payment_gas());
         }
         block_env.coinbase = self.0.fee_recipient;
 
         (cfg_env, block_env)
     }
 
-    pub fn get_build_execution_outcome(&self, payload_id: PayloadId) -> Option<ExecutionOutcome> {
+    pub fn get_build_execution_outcome(&self, block_hash: &B256) -> Option<ExecutionOutcome> {
+        let mut outcomes = self.execution_outcomes.lock().expect("can lock");
+        outcomes.remove(block_hash)
+    }
+
+    /// Drop the execution outcomes of payloads that can no longer be included once the chain is at
+    /// `head`
+    pub fn prune_execution_outcomes(&self, head: u64) {
         let mut outcomes = self.execution_outcomes.lock().expect("can lock");
-        outcomes.remove(&payload_id)
+        outcomes.retain(|_, outcome| outcome.first_block() > head);
+    }
+
+    /// Return the cache of reads of the state of the `parent` block, shared by every build on it
+    pub fn state_cache(&self, parent: &SealedBlock) -> StateCache {
+        let mut caches = self.state_caches.lock().expect("can lock");
+        let (_, cache) =
+            caches.entry(parent.hash()).or_insert_with(|| (parent.number, Default::default()));
+        cache.clone()
+    }
+
+    /// Drop the caches of state of blocks that can no longer be built on once the chain is at
+    /// `head`
+    pub fn prune_state_caches(&self, head: u64) {
+        let mut caches = self.state_caches.lock().expect("can lock");
+        caches.retain(|_, (number, _)| *number >= head);
+        let mut caches = self.simulation_caches.lock().expect("can lock");
+        caches.retain(|_, (number, _)| *number >= head);
+    }
+
+    /// Return the cache of outcomes of simulating bundles for the payload with `payload_id`, built
+    /// on the `parent` block, shared by every build of the payload
+    fn simulation_cache(
+        &self,
+        payload_id: PayloadId,
+        parent: &SealedBlock,
+    ) -> SimulationCache<BundleSimulation> {
+        let mut caches = self.simulation_caches.lock().expect("can lock");
+        let (_, cache) =
+            caches.entry(payload_id).or_insert_with(|| (parent.number, Default::default()));
+        cache.clone()
     }
 
     pub async fn finalize_payload_and_dispatch<Client: StateProviderFactory>(
@@ -280,13 +325,17 @@ impl PayloadBuilder {
         config: &PayloadFinalizerConfig,
     ) {
         let blob_sidecars = payload.sidecars().to_vec();
-        match self.finalize_payload(
+        let start = Instant::now();
+        let finalized = self.finalize_payload(
             payload.id(),
             client,
             payload.block().clone(),
+            payload.fees(),
             payment_amount,
             config,
-        ) {
+        );
+        metrics::on_build_phase("finalization", start.elapsed());
+        match finalized {
             Ok(mut payload) => {
                 payload.extend_sidecars(blob_sidecars);
                 if let Err(err) = self.bids.send(payload).await {
@@ -305,23 +354,238 @@ impl PayloadBuilder {
         payload_id: PayloadId,
         client: Client,
         block: SealedBlock,
+        revenue: U256,
         payment_amount: U256,
         config: &PayloadFinalizerConfig,
     ) -> Result<EthBuiltPayload, PayloadBuilderError> {
         let execution_outcome = self
-            .get_build_execution_outcome(payload_id)
+            .get_build_execution_outcome(&block.hash())
             .ok_or_else(|| PayloadBuilderError::Other("missing build state for payload".into()))?;
-        let block = append_payment(
-            client,
-            execution_outcome,
-            &self.signer,
-            config,
-            self.chain_id,
-            block,
-            payment_amount,
-        )?;
+        let payment_amount = self.cap_subsidy(block.timestamp, revenue, payment_amount);
+        let mut payments = vec![(config.proposer_fee_recipient, payment_amount)];
+        // NOTE: the treasury is only paid when the block leaves the builder a profit
+        let split = match self.treasury.as_ref() {
+            Some(treasury) => {
+                let split = treasury.split(revenue, payment_amount);
+                if split.treasury > U256::ZERO {
+                    payments.push((treasury.address, split.treasury));
+                }
+                split
+            }
+            None => RevenueSplit { revenue, proposer: payment_amount, treasury: U256::ZERO },
+        };
+        let block = self.append_payments(client, execution_outcome, config, block, &payments)?;
+        let mut splits = self.revenue_splits.lock().expect("can lock");
+        splits.insert(block.hash(), (block.number, split));
         Ok(EthBuiltPayload::new(payload_id, block, payment_amount, None))
     }
+
+    /// Lower `payment_amount` for a block with the given `revenue` at `timestamp` so that any
+    /// subsidy it spends stays within the caps on subsidy, if any
+    fn cap_subsidy(&self, timestamp: u64, revenue: U256, payment_amount: U256) -> U256 {
+        let Some(ledger) = self.subsidy.as_ref() else { return payment_amount };
+        let subsidy = payment_amount.saturating_sub(revenue);
+        if subsidy == U256::ZERO {
+            return payment_amount
+        }
+        let available = ledger.lock().expect("can lock").available(timestamp);
+        if subsidy <= available {
+            return payment_amount
+        }
+        warn!(%revenue, %payment_amount, %subsidy, %available, "subsidy cap reached; lowering payment to proposer");
+        revenue + available
+    }
+
+    /// Record the subsidy spent on a block won at `timestamp`, whose revenue was split as in
+    /// `split`
+    pub fn record_subsidy(&self, timestamp: u64, split: &RevenueSplit) {
+        let Some(ledger) = self.subsidy.as_ref() else { return };
+        let mut ledger = ledger.lock().expect("can lock");
+        ledger.record(timestamp, split.subsidy());
+        metrics::on_subsidy_available(ledger.available(timestamp));
+    }
+
+    /// Return how the revenue of the payload finalized with the given `block_hash` was split, if
+    /// the builder finalized it
+    pub fn take_revenue_split(&self, block_hash: &B256) -> Option<RevenueSplit> {
+        let mut splits = self.revenue_splits.lock().expect("can lock");
+        splits.remove(block_hash).map(|(_, split)| split)
+    }
+
+    /// Drop the splits of revenue of payloads that can no longer be included once the chain is
+    /// at `head`
+    pub fn prune_revenue_splits(&self, head: u64) {
+        let mut splits = self.revenue_splits.lock().expect("can lock");
+        splits.retain(|_, (number, _)| *number > head);
+    }
+
+    /// Append a transaction from the builder's signer for each of the `payments` to the end of
+    /// `block`, built on top of its `execution_outcome`
+    fn append_payments<Client: StateProviderFactory>(
+        &self,
+        client: Client,
+        execution_outcome: ExecutionOutcome,
+        config: &PayloadFinalizerConfig,
+        block: SealedBlock,
+        payments: &[(Address, U256)],
+    ) -> Result<SealedBlock, PayloadBuilderError> {
+        let signer = &self.signer;
+        let state_provider = client.state_by_block_hash(block.header.header().parent_hash)?;
+        let state = StateProviderDatabase::new(&state_provider);
+        // TODO: use cached reads
+        let mut db = State::builder()
+            .with_database_ref(state)
+            // TODO skip clone here...
+            .with_bundle_prestate(execution_outcome.state().clone())
+            .with_bundle_update()
+            .build();
+
+        // NOTE: the payment account may also send transactions included earlier in the block, so
+        // its nonce and balance are read from the state after those transactions
+        let signer_account = db.load_cache_account(signer.address())?;
+        let (mut nonce, balance) = signer_account
+            .account_info()
+            .map(|account| (account.nonce, account.balance))
+            .unwrap_or_default();
+
+        // SAFETY: cast to bigger type always succeeds
+        let max_fee_per_gas = block.header().base_fee_per_gas.unwrap_or_default() as u128;
+
+        let mut payment_txs = Vec::with_capacity(payments.len());
+        let mut required = U256::ZERO;
+        for &(to, value) in payments {
+            let recipient_account = db.load_cache_account(to)?;
+            let is_empty_code_hash = recipient_account
+                .account_info()
+                .map(|account| account.is_empty_code_hash())
+                .unwrap_or_default();
+
+            // Use a fixed gas limit for the payment transaction reflecting the recipient's status
+            // as smart contract or EOA.
+            let gas_limit =
+                if is_empty_code_hash { BASE_TX_GAS_LIMIT } else { PAYMENT_TO_CONTRACT_GAS_LIMIT };
+
+            required = required
+                .saturating_add(value)
+                .saturating_add(U256::from(max_fee_per_gas) * U256::from(gas_limit));
+            payment_txs.push(make_payment_transaction(
+                signer,
+                to,
+                self.chain_id,
+                nonce,
+                gas_limit,
+                max_fee_per_gas,
+                value,
+            )?);
+            nonce += 1;
+        }
+        if balance < required {
+            return Err(PayloadBuilderError::Other(Box::new(Error::InsufficientPaymentFunds {
+                address: signer.address(),
+                required,
+                available: balance,
+            })))
+        }
+
+        let Block { mut header, mut body } = block.unseal();
+
+        // Restore the block's gas limit to the one preferred for the proposer, see
+        // `cfg_and_block_env`, and verify enough gas was reserved for the payment transactions
+        let gas_limit = header.gas_limit + self.reserved_payment_gas();
+        let block_number = header.number;
+        // TODO skip clone here
+        let mut receipts = execution_outcome.receipts_by_block(block_number).to_vec();
+        let mut cumulative_gas_used = header.gas_used;
+        for payment_tx in payment_txs {
+            // TODO: skip clones here
+            let mut tx_env = TxEnv::default();
+            payment_tx.fill_tx_env(&mut tx_env, signer.address());
+            let mut env: EnvWithHandlerCfg = EnvWithHandlerCfg::new_with_cfg_env(
+                config.cfg_env.clone(),
+                config.block_env.clone(),
+                tx_env,
+            );
+            env.block.gas_limit = U256::from(gas_limit);
+            let mut evm =
+                revm::Evm::builder().with_db(&mut db).with_env_with_handler_cfg(env).build();
+
+            let ResultAndState { result, state } =
+                evm.transact().map_err(PayloadBuilderError::EvmExecutionError)?;
+
+            drop(evm);
+            if !result.is_success() {
+                return Err(PayloadBuilderError::Other(Box::new(Error::PaymentReverted(
+                    payment_tx.hash,
+                ))))
+            }
+            db.commit(state);
+
+            cumulative_gas_used += result.gas_used();
+            if cumulative_gas_used > gas_limit {
+                return Err(PayloadBuilderError::Other(Box::new(Error::BlockGasLimitExceeded {
+                    gas_used: cumulative_gas_used,
+                    gas_limit,
+                })))
+            }
+            receipts.push(Some(Receipt {
+                tx_type: payment_tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                logs: result.into_logs().into_iter().map(Into::into).collect(),
+            }));
+            body.transactions.push(payment_tx.into_signed());
+        }
+
+        db.merge_transitions(BundleRetention::PlainState);
+
+        let receipts = Receipts::from(vec![receipts]);
+
+        // TODO: final parameter is for EIP-7685 requests
+        let execution_outcome =
+            ExecutionOutcome::new(db.take_bundle(), receipts, block_number, vec![]);
+
+        let receipts_root =
+            execution_outcome.receipts_root_slow(block_number).expect("Number is in range");
+        let logs_bloom =
+            execution_outcome.block_logs_bloom(block_number).expect("Number is in range");
+        let state_root = state_provider.state_root(execution_outcome.hash_state_slow())?;
+        let transactions_root = proofs::calculate_transaction_root(&body.transactions);
+
+        header.state_root = state_root;
+        header.transactions_root = transactions_root;
+        header.receipts_root = receipts_root;
+        header.logs_bloom = logs_bloom;
+        header.gas_used = cumulative_gas_used;
+        header.gas_limit = gas_limit;
+
+        let block = Block { header, body };
+
+        Ok(block.seal_slow())
+    }
+
+    /// Build a payload from `args`, ordering any bundles with `ordering`
+    pub fn try_build_with<Pool, Client>(
+        &self,
+        args: BuildArguments<Pool, Client, BuilderPayloadBuilderAttributes, EthBuiltPayload>,
+        ordering: &dyn BundleOrdering,
+    ) -> Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>
+    where
+        Client: StateProviderFactory,
+        Pool: TransactionPool,
+    {
+        let (cfg_env, block_env) = self.cfg_and_block_env(&args.config);
+        let start = Instant::now();
+        let (outcome, execution_outcome) =
+            default_ethereum_payload_builder(self, cfg_env, block_env, ordering, args)?;
+        metrics::on_build(start.elapsed());
+        if let (BuildOutcome::Better { payload, .. }, Some(execution_outcome)) =
+            (&outcome, execution_outcome)
+        {
+            let mut execution_outcomes = self.execution_outcomes.lock().expect("can lock");
+            execution_outcomes.insert(payload.block().hash(), execution_outcome);
+        }
+        Ok(outcome)
+    }
 }
 
 impl<Pool, Client> reth_basic_payload_builder::PayloadBuilder<Pool, Client> for PayloadBuilder
@@ -336,15 +600,7 @@ where
         &self,
         args: BuildArguments<Pool, Client, Self::Attributes, Self::BuiltPayload>,
     ) -> Result<BuildOutcome<Self::BuiltPayload>, PayloadBuilderError> {
-        let payload_id = args.config.payload_id();
-        let (cfg_env, block_env) = self.cfg_and_block_env(&args.config);
-        let (outcome, bundle) =
-            default_ethereum_payload_builder(self.evm_config.clone(), cfg_env, block_env, args)?;
-        if let Some(bundle) = bundle {
-            let mut execution_outcomes = self.execution_outcomes.lock().expect("can lock");
-            execution_outcomes.insert(payload_id, bundle);
-        }
-        Ok(outcome)
+        self.try_build_with(args, self.orderings[0].as_ref())
     }
 
     fn build_empty_payload(
@@ -372,7 +628,13 @@ where
 
         let base_fee = block_env.basefee.to::<u64>();
         let block_number = block_env.number.to::<u64>();
-        let block_gas_limit: u64 = block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+        let mut block_gas_limit: u64 = block_env.gas_limit.try_into().unwrap_or(u64::MAX);
+        // NOTE: an empty payload carries no payment transaction, so it takes the whole gas limit
+        // preferred for the proposer, rather than leaving room for the payment; see
+        // `cfg_and_block_env`
+        if attributes.proposal.is_some() {
+            block_gas_limit += self.reserved_payment_gas();
+        }
 
         let mut system_caller = SystemCaller::new(&self.evm_config, chain_spec.clone());
 
@@ -458,16 +720,99 @@ where
     }
 }
 
-/// Constructs an Ethereum transaction payload using the best transactions from the pool.
+/// Execute the transactions of `bundle` in order, committing each to `db` and recording their
+/// state access to `access`, if provided.
+///
+/// Returns `None` if the bundle can not be included, i.e. one of its transactions is invalid or
+/// reverts without being listed in the bundle's reverting transaction hashes.
+fn execute_bundle<DB>(
+    evm_config: &EthEvmConfig,
+    db: &mut State<DB>,
+    cfg_env: &CfgEnvWithHandlerCfg,
+    block_env: &BlockEnv,
+    bundle: &Bundle,
+    mut access: Option<&mut StateAccess>,
+) -> Result<Option<Vec<ExecutionResult>>, PayloadBuilderError>
+where
+    DB: Database<Error = ProviderError>,
+{
+    let mut results = Vec::with_capacity(bundle.transactions.len());
+    for tx in &bundle.transactions {
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            cfg_env.clone(),
+            block_env.clone(),
+            evm_config.tx_env(tx),
+        );
+        let mut evm = evm_config.evm_with_env(&mut *db, env);
+        let ResultAndState { result, state } = match evm.transact() {
+            Ok(res) => res,
+            Err(EVMError::Transaction(err)) => {
+                trace!(target: "payload_builder", %err, bundle = %bundle.hash, tx = %tx.hash, "skipping bundle with invalid transaction");
+                return Ok(None)
+            }
+            Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+        };
+        drop(evm);
+        if !result.is_success() && !bundle.can_revert(&tx.hash) {
+            trace!(target: "payload_builder", bundle = %bundle.hash, tx = %tx.hash, "skipping bundle with reverting transaction");
+            return Ok(None)
+        }
+        if let Some(access) = access.as_mut() {
+            access.record(&state, block_env.coinbase);
+        }
+        db.commit(state);
+        results.push(result);
+    }
+    Ok(Some(results))
+}
+
+#[derive(Debug, Clone)]
+struct BundleSimulation {
+    gas_used: u64,
+    coinbase_payment: U256,
+    access: StateAccess,
+}
+
+/// Execute `bundle` against `db` to determine if it can be included and what it pays the
+/// block's coinbase, counting both priority fees and any direct transfers.
+fn simulate_bundle<DB>(
+    evm_config: &EthEvmConfig,
+    db: &mut State<DB>,
+    cfg_env: &CfgEnvWithHandlerCfg,
+    block_env: &BlockEnv,
+    bundle: &Bundle,
+) -> Result<Option<BundleSimulation>, PayloadBuilderError>
+where
+    DB: Database<Error = ProviderError>,
+{
+    let coinbase = block_env.coinbase;
+    let balance_before = db.basic(coinbase)?.map(|account| account.balance).unwrap_or_default();
+    let mut access = StateAccess::default();
+    let Some(results) =
+        execute_bundle(evm_config, db, cfg_env, block_env, bundle, Some(&mut access))?
+    else {
+        return Ok(None)
+    };
+    let balance_after = db.basic(coinbase)?.map(|account| account.balance).unwrap_or_default();
+    Ok(Some(BundleSimulation {
+        gas_used: results.iter().map(ExecutionResult::gas_used).sum(),
+        coinbase_payment: balance_after.saturating_sub(balance_before),
+        access,
+    }))
+}
+
+/// Constructs an Ethereum transaction payload using the given searcher bundles and then the best
+/// transactions from the pool.
 ///
 /// Given build arguments including an Ethereum client, transaction pool,
 /// and configuration, this function creates a transaction payload. Returns
 /// a result indicating success with the payload or an error in case of failure.
 #[inline]
 pub fn default_ethereum_payload_builder<Pool, Client>(
-    evm_config: EthEvmConfig,
+    builder: &PayloadBuilder,
     cfg_env: CfgEnvWithHandlerCfg,
     block_env: BlockEnv,
+    ordering: &dyn BundleOrdering,
     args: BuildArguments<Pool, Client, BuilderPayloadBuilderAttributes, EthBuiltPayload>,
 ) -> Result<(BuildOutcome<EthBuiltPayload>, Option<ExecutionOutcome>), PayloadBuilderError>
 where
@@ -475,16 +820,32 @@ where
     Pool: TransactionPool,
 {
     let BuildArguments { client, pool, mut cached_reads, config, cancel, best_payload } = args;
+    let evm_config = builder.evm_config.clone();
+    let SelectionConfig {
+        blocklist,
+        top_of_block_gas,
+        min_priority_fee,
+        min_block_value,
+        simulation_threads,
+        reputation,
+        ..
+    } = &builder.selection;
 
     let state_provider = client.state_by_block_hash(config.parent_block.hash())?;
-    let state = StateProviderDatabase::new(&state_provider);
+    // NOTE: reads of the parent state are shared by every build on it, see `StateCache`
+    let state_cache = builder.state_cache(&config.parent_block);
+    // NOTE: the outcomes of simulating bundles are shared by every build of the payload, see
+    // `SimulationCache`
+    let simulations =
+        builder.simulation_cache(config.attributes.payload_id(), &config.parent_block);
+    let state = state_cache.as_db(StateProviderDatabase::new(&state_provider));
     let mut db =
         State::builder().with_database_ref(cached_reads.as_db(&state)).with_bundle_update().build();
     let PayloadConfig { parent_block, extra_data, attributes } = config;
 
     let chain_spec = evm_config.chain_spec();
 
-    debug!(target: "payload_builder", id=%attributes.payload_id(), parent_hash = ?parent_block.hash(), parent_number = parent_block.number, "building new payload");
+    debug!(target: "payload_builder", id=%attributes.payload_id(), parent_hash = ?parent_block.hash(), parent_number = parent_block.number, cached_state = state_cache.size(), cached_simulations = simulations.size(), "building new payload");
     let mut cumulative_gas_used = 0;
     let mut sum_blob_gas_used = 0;
     let block_gas_limit: u64 = block_env.gas_limit.try_into().unwrap_or(u64::MAX);
@@ -492,14 +853,61 @@ where
 
     let mut executed_txs = Vec::new();
 
-    let mut best_txs = pool.best_transactions_with_attributes(BestTransactionsAttributes::new(
-        base_fee,
-        block_env.get_blob_gasprice().map(|gasprice| gasprice as u64),
-    ));
+    let block_number = block_env.number.to::<u64>();
 
-    let mut total_fees = U256::ZERO;
+    // NOTE: the time spent in each phase of the build is reported, see `metrics::on_build_phase`
+    let phase_start = Instant::now();
+    // NOTE: a backrun is only built while the private transaction it backruns can be included
+    let mut bundles = builder
+        .bundles
+        .bundles_for(block_number, attributes.timestamp())
+        .into_iter()
+        .filter_map(|bundle| builder.private_transactions.resolve_backrun(bundle))
+        .collect::<Vec<_>>();
+    // NOTE: private transactions are built as bundles of a single transaction that can not
+    // revert, so they are never included when they would
+    bundles.extend(builder.private_transactions.bundles_for(block_number));
+    // NOTE: bundles from the most reputable providers are simulated first, so that bundles from
+    // providers whose bundles mostly fail are the ones left out when the build is cancelled
+    reputation.prioritize(&mut bundles);
+    let snapshot_gas_limit = block_gas_limit.saturating_mul(POOL_SNAPSHOT_BLOCKS);
+    let mut snapshot_gas = 0u64;
+    let pool_txs = pool
+        .best_transactions_with_attributes(BestTransactionsAttributes::new(
+            base_fee,
+            block_env.get_blob_gasprice().map(|gasprice| gasprice as u64),
+        ))
+        .take_while(|pool_tx| {
+            let within_limit = snapshot_gas < snapshot_gas_limit;
+            snapshot_gas = snapshot_gas.saturating_add(pool_tx.gas_limit());
+            within_limit
+        })
+        .collect::<Vec<_>>();
+    // NOTE: a bundle with transactions following transactions of their sender still in the mempool
+    // is built along with those transactions, placed ahead of the bundle, see `Prerequisites`
+    let prerequisites = Prerequisites::new(
+        pool_txs
+            .iter()
+            .filter(|pool_tx| !pool_tx.is_eip4844())
+            .map(|pool_tx| (pool_tx.sender(), pool_tx.nonce(), pool_tx.clone())),
+    );
+    let bundles = bundles
+        .into_iter()
+        .map(|bundle| {
+            let mut senders = HashSet::new();
+            let bundle_prerequisites = bundle
+                .transactions
+                .iter()
+                .filter(|tx| senders.insert(tx.signer()))
+                .flat_map(|tx| prerequisites.before(&tx.signer(), tx.nonce()))
+                .map(|pool_tx| pool_tx.to_recovered_transaction())
+                .collect();
+            bundle.with_prerequisites(bundle_prerequisites)
+        })
+        .collect::<Vec<_>>();
+    metrics::on_build_phase("pool_snapshot", phase_start.elapsed());
 
-    let block_number = block_env.number.to::<u64>();
+    let mut total_fees = U256::ZERO;
 
     let mut system_caller = SystemCaller::new(&evm_config, chain_spec.clone());
 
@@ -515,13 +923,249 @@ where
             })?;
 
     let mut receipts = Vec::new();
-    while let Some(pool_tx) = best_txs.next() {
+
+    let phase_start = Instant::now();
+    // NOTE: bundles are placed ahead of any transactions from the mempool. Each bundle is first
+    // simulated on its own against the top of the block, so the configured ordering can select
+    // the bundles to include, see `BundleOrdering`. As these simulations do not depend on one
+    // another, they run in parallel on the simulation threads, if any.
+    db.merge_transitions(BundleRetention::PlainState);
+    let top_of_block = &db.bundle_state;
+    // Simulate `bundle` against the state after `prefix`, on top of `prestate`, unless it was
+    // simulated there before
+    let simulate_after = |prefix: Prefix,
+                          prestate: &BundleState,
+                          bundle: &Bundle|
+     -> Result<Option<BundleSimulation>, PayloadBuilderError> {
+        if let Some(simulation) = simulations.get(prefix, bundle) {
+            metrics::on_simulation_cache(true);
+            return Ok(simulation)
+        }
+        metrics::on_simulation_cache(false);
+        let mut simulation_db = State::builder()
+            .with_database_ref(PrestateDb::new(
+                prestate,
+                state_cache.as_db(StateProviderDatabase::new(&state_provider)),
+            ))
+            .build();
+        let simulation =
+            simulate_bundle(&evm_config, &mut simulation_db, &cfg_env, &block_env, bundle)?;
+        simulations.insert(prefix, bundle, simulation.clone());
+        if let Some(origin) = bundle.origin.as_ref().filter(|_| prefix == Prefix::default()) {
+            reputation.on_simulated(&origin.provider, simulation.is_some());
+        }
+        Ok(simulation)
+    };
+    let simulate = |bundle: Bundle| -> Result<Option<BundleCandidate>, PayloadBuilderError> {
+        if cancel.is_cancelled() {
+            return Ok(None)
+        }
+        let Some(BundleSimulation { gas_used, coinbase_payment, access }) =
+            simulate_after(Prefix::default(), top_of_block, &bundle)?
+        else {
+            return Ok(None)
+        };
+        if let Some(address) = blocklist.find(access.accounts()) {
+            trace!(target: "payload_builder", bundle = %bundle.hash, %address, "skipping bundle touching blocklisted address");
+            metrics::on_blocklist_exclusion("bundle");
+            return Ok(None)
+        }
+        Ok(Some(BundleCandidate { bundle, payment: coinbase_payment, gas_used, access }))
+    };
+    let candidates = match simulation_threads {
+        Some(threads) => threads
+            .install(|| bundles.into_par_iter().map(simulate).collect::<Result<Vec<_>, _>>())?,
+        None => bundles.into_iter().map(simulate).collect::<Result<Vec<_>, _>>()?,
+    };
+    if cancel.is_cancelled() {
+        return Ok((BuildOutcome::Cancelled, None))
+    }
+    let candidates = candidates.into_iter().flatten().collect::<Vec<_>>();
+
+    // NOTE: bundles are only included within the gas reserved for them at the top of the block,
+    // if any, and transactions from the mempool fill the remainder of the block
+    let bundle_gas_limit =
+        top_of_block_gas.map_or(block_gas_limit, |gas_limit| gas_limit.min(block_gas_limit));
+
+    // NOTE: bundles from orderflow providers with priority inclusion are placed ahead of any other
+    // bundles, ordered among themselves with the same ordering
+    let (priority_candidates, candidates): (Vec<_>, Vec<_>) =
+        candidates.into_iter().partition(|candidate| candidate.bundle.has_priority());
+    let selected =
+        ordering.order(priority_candidates).into_iter().chain(ordering.order(candidates));
+    metrics::on_build_phase("ordering", phase_start.elapsed());
+
+    let phase_start = Instant::now();
+
+    // NOTE: conflict detection is not exhaustive, so each selected bundle is simulated again on a
+    // copy of the state before it is included so that a failing bundle leaves no trace in the
+    // block. Later builds selecting the same bundles in the same order reuse these simulations.
+    let mut prefix = Prefix::default();
+    for bundle in selected {
+        if cancel.is_cancelled() {
+            return Ok((BuildOutcome::Cancelled, None))
+        }
+
+        db.merge_transitions(BundleRetention::PlainState);
+        let Some(simulation) = simulate_after(prefix, &db.bundle_state, &bundle)? else { continue };
+        // NOTE: the bundle may reach different accounts on top of the bundles selected before it
+        if let Some(address) = blocklist.find(simulation.access.accounts()) {
+            trace!(target: "payload_builder", bundle = %bundle.hash, %address, "skipping bundle touching blocklisted address");
+            metrics::on_blocklist_exclusion("bundle");
+            continue
+        }
+        if cumulative_gas_used + simulation.gas_used > bundle_gas_limit {
+            trace!(target: "payload_builder", bundle = %bundle.hash, gas_used = simulation.gas_used, "skipping bundle that does not fit in the gas reserved for bundles");
+            continue
+        }
+
+        let results = execute_bundle(&evm_config, &mut db, &cfg_env, &block_env, &bundle, None)?
+            .ok_or_else(|| {
+                PayloadBuilderError::Other(Box::new(Error::BundleExecutionMismatch(bundle.hash)))
+            })?;
+        prefix = prefix.then(&bundle);
+        for (tx, result) in bundle.transactions.into_iter().zip(results) {
+            cumulative_gas_used += result.gas_used();
+            receipts.push(Some(Receipt {
+                tx_type: tx.tx_type(),
+                success: result.is_success(),
+                cumulative_gas_used,
+                logs: result.into_logs().into_iter().map(Into::into).collect(),
+            }));
+            executed_txs.push(tx.into_signed());
+        }
+        total_fees += simulation.coinbase_payment;
+        let provider = bundle.origin.as_ref().map(|origin| origin.provider.as_str());
+        debug!(target: "payload_builder", bundle = %bundle.hash, value = %simulation.coinbase_payment, ?provider, "included bundle");
+    }
+
+    // NOTE: transactions from the mempool are ordered by the sequences of transactions from their
+    // sender they unlock, see `NonceDependencies`, leaving out those already included in bundles
+    let included = executed_txs.iter().map(|tx| tx.hash()).collect::<HashSet<_>>();
+    let dependency = |pool_tx: &Arc<ValidPoolTransaction<Pool::Transaction>>| Dependency {
+        sender: pool_tx.sender(),
+        nonce: pool_tx.nonce(),
+        tip_per_gas: pool_tx.effective_tip_per_gas(base_fee).unwrap_or_default(),
+        gas_limit: pool_tx.gas_limit(),
+    };
+    let pool_txs = pool_txs
+        .into_iter()
+        .filter(|pool_tx| !included.contains(pool_tx.hash()))
+        .collect::<Vec<_>>();
+
+    // NOTE: the outcome of each transaction from the mempool depends on the transactions selected
+    // before it, so they are selected one after another. Ahead of that, the transactions are
+    // executed in parallel against the state after the bundles on the simulation threads, if any,
+    // so the transactions left out of the block are skipped without executing them again, see
+    // `PreExecutions`.
+    db.merge_transitions(BundleRetention::PlainState);
+    let after_bundles = &db.bundle_state;
+    let pre_execute = |pool_tx: &Arc<ValidPoolTransaction<Pool::Transaction>>| -> Result<
+        Option<(B256, Exclusion, StateAccess)>,
+        PayloadBuilderError,
+    > {
+        if cancel.is_cancelled() {
+            return Ok(None)
+        }
+        let tx = pool_tx.to_recovered_transaction();
+        let mut pre_execution_db = State::builder()
+            .with_database_ref(PrestateDb::new(
+                after_bundles,
+                state_cache.as_db(StateProviderDatabase::new(&state_provider)),
+            ))
+            .build();
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            cfg_env.clone(),
+            block_env.clone(),
+            evm_config.tx_env(&tx),
+        );
+        let mut evm = evm_config.evm_with_env(&mut pre_execution_db, env);
+        let ResultAndState { result, state } = match evm.transact() {
+            Ok(res) => res,
+            // NOTE: an invalid transaction, e.g. one following transactions of its sender that are
+            // not selected yet, is only executed once selected
+            Err(EVMError::Transaction(_)) => return Ok(None),
+            Err(err) => return Err(PayloadBuilderError::EvmExecutionError(err)),
+        };
+        let exclusion = if !result.is_success() {
+            Exclusion::Reverted
+        } else if let Some(address) = blocklist.touched_by(tx.signer(), &state) {
+            Exclusion::Blocklisted(address)
+        } else {
+            return Ok(None)
+        };
+        let mut access = StateAccess::default();
+        access.record(&state, block_env.coinbase);
+        Ok(Some((tx.hash, exclusion, access)))
+    };
+    let mut pre_executions = match simulation_threads {
+        Some(threads) => {
+            let excluded = threads
+                .install(|| pool_txs.par_iter().map(pre_execute).collect::<Result<Vec<_>, _>>())?;
+            PreExecutions::new(excluded.into_iter().flatten())
+        }
+        None => PreExecutions::default(),
+    };
+    if cancel.is_cancelled() {
+        return Ok((BuildOutcome::Cancelled, None))
+    }
+
+    let mut best_txs =
+        NonceDependencies::new(pool_txs.into_iter().map(|pool_tx| (dependency(&pool_tx), pool_tx)));
+
+    // NOTE: blob space is scarce, so blob transactions are set aside, along with the later
+    // transactions of their sender, and only included after the other transactions, in order of
+    // the priority fees they pay per unit of blob gas. Blob fees are burned, so priority fees are
+    // all the builder earns from them, like any transaction.
+    let mut blob_txs_released = false;
+    loop {
+        let Some((pool_tx, tip_per_gas)) = best_txs.next() else {
+            if blob_txs_released {
+                break
+            }
+            blob_txs_released = true;
+            let mut ranks = HashMap::new();
+            for (sender, pool_tx) in best_txs.set_aside_transactions() {
+                let tx = pool_tx.to_recovered_transaction();
+                let blob_gas = tx.transaction.as_eip4844().map(|blob_tx| blob_tx.blob_gas());
+                // NOTE: priority fees are paid on the gas used, which is only known once the
+                // transaction is executed on top of the block so far
+                let env = EnvWithHandlerCfg::new_with_cfg_env(
+                    cfg_env.clone(),
+                    block_env.clone(),
+                    evm_config.tx_env(&tx),
+                );
+                let mut evm = evm_config.evm_with_env(&mut db, env);
+                let gas_used = match evm.transact() {
+                    Ok(ResultAndState { result, .. }) => result.gas_used(),
+                    Err(_) => 0,
+                };
+                let fees =
+                    pool_tx.effective_tip_per_gas(base_fee).unwrap_or_default() * gas_used as u128;
+                ranks.insert(sender, fees / blob_gas.unwrap_or_default().max(1) as u128);
+            }
+            best_txs.release(&ranks);
+            continue
+        };
+        if !blob_txs_released && pool_tx.is_eip4844() {
+            best_txs.set_aside(dependency(&pool_tx), pool_tx);
+            continue
+        }
+
+        // skip transactions paying too little to be worth the time to select them, counting the
+        // later transactions of the sender they unlock
+        if min_priority_fee.is_some_and(|min_priority_fee| tip_per_gas < min_priority_fee) {
+            trace!(target: "payload_builder", tx = %pool_tx.hash(), "skipping transaction below the minimum priority fee");
+            best_txs.mark_invalid(&pool_tx.sender());
+            continue
+        }
+
         // ensure we still have capacity for this transaction
         if cumulative_gas_used + pool_tx.gas_limit() > block_gas_limit {
             // we can't fit this transaction into the block, so we need to mark it as invalid
             // which also removes all dependent transaction from the iterator before we can
             // continue
-            best_txs.mark_invalid(&pool_tx);
+            best_txs.mark_invalid(&pool_tx.sender());
             continue
         }
 
@@ -543,11 +1187,21 @@ where
                 // the iterator. This is similar to the gas limit condition
                 // for regular transactions above.
                 trace!(target: "payload_builder", tx=?tx.hash, ?sum_blob_gas_used, ?tx_blob_gas, "skipping blob transaction because it would exceed the max data gas per block");
-                best_txs.mark_invalid(&pool_tx);
+                best_txs.mark_invalid(&pool_tx.sender());
                 continue
             }
         }
 
+        if let Some(exclusion) = pre_executions.exclusion(&tx.hash) {
+            trace!(target: "payload_builder", tx = %tx.hash, ?exclusion, "skipping transaction left out when executed ahead of selection, along with its descendants");
+            match exclusion {
+                Exclusion::Reverted => metrics::on_reverted_transaction(),
+                Exclusion::Blocklisted(_) => metrics::on_blocklist_exclusion("mempool"),
+            }
+            best_txs.mark_invalid(&pool_tx.sender());
+            continue
+        }
+
         let env = EnvWithHandlerCfg::new_with_cfg_env(
             cfg_env.clone(),
             block_env.clone(),
@@ -569,7 +1223,7 @@ where
                             // if the transaction is invalid, we can skip it and all of its
                             // descendants
                             trace!(target: "payload_builder", %err, ?tx, "skipping invalid transaction and its descendants");
-                            best_txs.mark_invalid(&pool_tx);
+                            best_txs.mark_invalid(&pool_tx.sender());
                         }
 
                         continue
@@ -583,7 +1237,27 @@ where
         };
         // drop evm so db is released.
         drop(evm);
+
+        // NOTE: a reverting transaction still uses gas, so reverting spam would take up space in
+        // the block while paying little in priority fees
+        if !result.is_success() {
+            // skip the transaction and all of its descendants, as they depend on its nonce
+            trace!(target: "payload_builder", tx = %tx.hash, "skipping reverting transaction and its descendants");
+            metrics::on_reverted_transaction();
+            best_txs.mark_invalid(&pool_tx.sender());
+            continue
+        }
+
+        if let Some(address) = blocklist.touched_by(tx.signer(), &state) {
+            // skip the transaction and all of its descendants, without committing its changes
+            trace!(target: "payload_builder", tx = %tx.hash, %address, "skipping transaction touching blocklisted address");
+            metrics::on_blocklist_exclusion("mempool");
+            best_txs.mark_invalid(&pool_tx.sender());
+            continue
+        }
+
         // commit changes
+        pre_executions.on_committed(&state, block_env.coinbase);
         db.commit(state);
 
         // add to the total blob gas used if the transaction successfully executed
@@ -591,9 +1265,10 @@ where
             let tx_blob_gas = blob_tx.blob_gas();
             sum_blob_gas_used += tx_blob_gas;
 
-            // if we've reached the max data gas per block, we can skip blob txs entirely
+            // if we've reached the max data gas per block, no more blob transactions fit and they
+            // are the only transactions left
             if sum_blob_gas_used == MAX_DATA_GAS_PER_BLOCK {
-                best_txs.skip_blobs();
+                break
             }
         }
 
@@ -622,12 +1297,26 @@ where
         executed_txs.push(tx.into_signed());
     }
 
-    // check if we have a better block
-    if !is_better_payload(best_payload.as_ref(), total_fees) {
-        // can skip building the block
+    metrics::on_build_phase("execution", phase_start.elapsed());
+
+    // NOTE: the value of the block is known once its transactions are executed, so a block that
+    // would not be bid is dropped here, before the state root is computed and the block sealed. As
+    // bids only grow with the value of the block, a block worth no more than the best one built
+    // for this payload would not be bid above the builder's previous bid.
+    let skip_reason = if min_block_value.is_some_and(|min_value| total_fees < min_value) {
+        Some("below_minimum")
+    } else if !is_better_payload(best_payload.as_ref(), total_fees) {
+        Some("not_better")
+    } else {
+        None
+    };
+    if let Some(reason) = skip_reason {
+        trace!(target: "payload_builder", value = %total_fees, reason, "skipping sealing of block");
+        metrics::on_build_skipped(reason);
         return Ok((BuildOutcome::Aborted { fees: total_fees, cached_reads }, None))
     }
 
+    let phase_start = Instant::now();
     let WithdrawalsOutcome { withdrawals_root, withdrawals } = commit_withdrawals(
         &mut db,
         chain_spec,
@@ -718,6 +1407,7 @@ where
 
     // extend the payload with the blob sidecars from the executed txs
     payload.extend_sidecars(blob_sidecars);
+    metrics::on_build_phase("sealing", phase_start.elapsed());
 
     Ok((BuildOutcome::Better { payload, cached_reads }, Some(execution_outcome)))
 }
@@ -0,0 +1,261 @@
+use reth::primitives::revm_primitives::Address;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet, VecDeque},
+};
+
+/// Where a transaction stands among the transactions of its sender, and what it pays
+#[derive(Debug, Clone, Copy)]
+pub struct Dependency {
+    pub sender: Address,
+    pub nonce: u64,
+    // effective priority fee per gas
+    pub tip_per_gas: u128,
+    pub gas_limit: u64,
+}
+
+#[derive(Debug)]
+struct Dependent<T> {
+    nonce: u64,
+    tip_per_gas: u128,
+    gas_limit: u64,
+    // position of the transaction in the pool's ordering, to break ties
+    position: usize,
+    transaction: T,
+}
+
+/// Transactions from the mempool, each linked to the later transactions of its sender, which can
+/// only be included after it.
+///
+/// Rather than by its own priority fee, a transaction is ordered by the best priority fee per gas
+/// of any sequence of its sender's transactions starting with it, so that a transaction paying
+/// little is still selected early when it unlocks a transaction paying more.
+///
+/// A transaction can be set aside along with the later transactions of its sender, which are then
+/// only taken once every other transaction is, one sender after the other.
+#[derive(Debug)]
+pub struct NonceDependencies<T> {
+    senders: HashMap<Address, VecDeque<Dependent<T>>>,
+    // next transaction of each sender, by the priority fee of the best sequence it starts
+    ready: BinaryHeap<(u128, Reverse<usize>, Address)>,
+    // senders whose transactions are set aside until released
+    set_aside: HashSet<Address>,
+    // senders released after being set aside, in the order their transactions are taken
+    released: VecDeque<Address>,
+}
+
+impl<T> NonceDependencies<T> {
+    /// Link `transactions`, given in the pool's ordering, to the transactions of the same sender
+    pub fn new(transactions: impl IntoIterator<Item = (Dependency, T)>) -> Self {
+        let mut senders = HashMap::<_, VecDeque<_>>::new();
+        for (position, (dependency, transaction)) in transactions.into_iter().enumerate() {
+            let Dependency { sender, nonce, tip_per_gas, gas_limit } = dependency;
+            let dependent = Dependent { nonce, tip_per_gas, gas_limit, position, transaction };
+            senders.entry(sender).or_default().push_back(dependent);
+        }
+        let mut ready = BinaryHeap::with_capacity(senders.len());
+        for (sender, dependents) in senders.iter_mut() {
+            dependents.make_contiguous().sort_by_key(|dependent| dependent.nonce);
+            ready.push(Self::schedule(*sender, dependents));
+        }
+        Self { senders, ready, set_aside: Default::default(), released: Default::default() }
+    }
+
+    fn schedule(
+        sender: Address,
+        dependents: &VecDeque<Dependent<T>>,
+    ) -> (u128, Reverse<usize>, Address) {
+        let (mut fees, mut gas, mut best_tip_per_gas) = (0u128, 0u128, 0);
+        for dependent in dependents {
+            fees = fees
+                .saturating_add(dependent.tip_per_gas.saturating_mul(dependent.gas_limit as u128));
+            gas += dependent.gas_limit as u128;
+            best_tip_per_gas = best_tip_per_gas.max(fees / gas.max(1));
+        }
+        let position = dependents.front().map(|dependent| dependent.position).unwrap_or_default();
+        (best_tip_per_gas, Reverse(position), sender)
+    }
+
+    /// Drop the remaining transactions of `sender`, e.g. once one of them can not be included
+    pub fn mark_invalid(&mut self, sender: &Address) {
+        // NOTE: the sender's entry in `ready` is skipped once it is reached
+        self.senders.remove(sender);
+    }
+
+    /// Set aside `transaction`, just taken with `dependency`, along with the later transactions of
+    /// its sender, until they are released
+    pub fn set_aside(&mut self, dependency: Dependency, transaction: T) {
+        let Dependency { sender, nonce, tip_per_gas, gas_limit } = dependency;
+        let dependent = Dependent { nonce, tip_per_gas, gas_limit, position: 0, transaction };
+        self.senders.entry(sender).or_default().push_front(dependent);
+        self.set_aside.insert(sender);
+    }
+
+    /// The first transaction set aside for each sender
+    pub fn set_aside_transactions(&self) -> impl Iterator<Item = (Address, &T)> + '_ {
+        self.set_aside.iter().filter_map(|sender| {
+            let dependent = self.senders.get(sender)?.front()?;
+            Some((*sender, &dependent.transaction))
+        })
+    }
+
+    /// Release the senders set aside, so that their transactions are taken once every other
+    /// transaction is, from the sender with the highest rank in `ranks` down
+    pub fn release(&mut self, ranks: &HashMap<Address, u128>) {
+        let mut released = self.set_aside.drain().collect::<Vec<_>>();
+        released.sort_by_key(|sender| Reverse(ranks.get(sender).copied().unwrap_or_default()));
+        self.released.extend(released);
+    }
+}
+
+impl<T> Iterator for NonceDependencies<T> {
+    /// The next transaction, along with the priority fee per gas of the best sequence it starts
+    type Item = (T, u128);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((tip_per_gas, _, sender)) = self.ready.pop() {
+            if self.set_aside.contains(&sender) {
+                continue
+            }
+            let Some(dependents) = self.senders.get_mut(&sender) else { continue };
+            let Some(dependent) = dependents.pop_front() else { continue };
+            if dependents.is_empty() {
+                self.senders.remove(&sender);
+            } else {
+                self.ready.push(Self::schedule(sender, dependents));
+            }
+            return Some((dependent.transaction, tip_per_gas))
+        }
+        // NOTE: the transactions of a released sender are taken in order, before the next sender
+        while let Some(&sender) = self.released.front() {
+            let next = self.senders.get_mut(&sender).and_then(VecDeque::pop_front);
+            match next {
+                Some(dependent) => return Some((dependent.transaction, dependent.tip_per_gas)),
+                None => {
+                    self.senders.remove(&sender);
+                    self.released.pop_front();
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Transactions from the mempool, by sender and nonce, that a bundle may depend on.
+///
+/// A bundle may include a transaction whose nonce follows transactions of its sender that are
+/// still in the mempool, which must then be included ahead of the bundle for it to be valid.
+#[derive(Debug)]
+pub struct Prerequisites<T> {
+    senders: HashMap<Address, BTreeMap<u64, T>>,
+}
+
+impl<T: Clone> Prerequisites<T> {
+    pub fn new(transactions: impl IntoIterator<Item = (Address, u64, T)>) -> Self {
+        let mut senders = HashMap::<_, BTreeMap<_, _>>::new();
+        for (sender, nonce, transaction) in transactions {
+            senders.entry(sender).or_default().insert(nonce, transaction);
+        }
+        Self { senders }
+    }
+
+    /// The transactions of `sender` to include, in order, ahead of its transaction with `nonce`,
+    /// or none if the sender's transactions in the mempool leave a gap before `nonce`, as the
+    /// transaction can then not be included
+    pub fn before(&self, sender: &Address, nonce: u64) -> Vec<T> {
+        let Some(transactions) = self.senders.get(sender) else { return vec![] };
+        let earlier = transactions.range(..nonce).collect::<Vec<_>>();
+        let contiguous = earlier.last().is_some_and(|(&last, _)| last + 1 == nonce) &&
+            earlier.windows(2).all(|pair| pair[0].0 + 1 == *pair[1].0);
+        if !contiguous {
+            return vec![]
+        }
+        earlier.into_iter().map(|(_, transaction)| transaction.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(sender: u8, nonce: u64, tip_per_gas: u128) -> (Dependency, (u8, u64)) {
+        let dependency = Dependency {
+            sender: Address::repeat_byte(sender),
+            nonce,
+            tip_per_gas,
+            gas_limit: 21_000,
+        };
+        (dependency, (sender, nonce))
+    }
+
+    #[test]
+    fn test_transactions_unlocking_higher_fees_come_first() {
+        let mut dependencies = NonceDependencies::new([
+            dependency(1, 0, 5),
+            dependency(2, 0, 1),
+            dependency(1, 1, 4),
+            dependency(2, 1, 19),
+            dependency(3, 0, 1),
+        ]);
+        assert_eq!(dependencies.next(), Some(((2, 0), 10)));
+        assert_eq!(dependencies.next(), Some(((2, 1), 19)));
+        assert_eq!(dependencies.next(), Some(((1, 0), 5)));
+
+        dependencies.mark_invalid(&Address::repeat_byte(1));
+        assert_eq!(dependencies.next(), Some(((3, 0), 1)));
+        assert_eq!(dependencies.next(), None);
+    }
+
+    #[test]
+    fn test_set_aside_transactions_keep_their_sender_in_order() {
+        let mut dependencies = NonceDependencies::new([
+            dependency(1, 0, 5),
+            dependency(1, 1, 5),
+            dependency(1, 2, 5),
+            dependency(2, 0, 3),
+            dependency(3, 0, 4),
+            dependency(3, 1, 4),
+        ]);
+        assert_eq!(dependencies.next(), Some(((1, 0), 5)));
+        // NOTE: setting aside a transaction also holds back the later transactions of its sender
+        let (set_aside, _) = dependency(1, 1, 5);
+        assert_eq!(dependencies.next(), Some(((1, 1), 5)));
+        dependencies.set_aside(set_aside, (1, 1));
+        let (set_aside, _) = dependency(3, 0, 4);
+        assert_eq!(dependencies.next(), Some(((3, 0), 4)));
+        dependencies.set_aside(set_aside, (3, 0));
+        assert_eq!(dependencies.next(), Some(((2, 0), 3)));
+        assert_eq!(dependencies.next(), None);
+
+        let mut set_aside =
+            dependencies.set_aside_transactions().map(|(_, tx)| *tx).collect::<Vec<_>>();
+        set_aside.sort();
+        assert_eq!(set_aside, vec![(1, 1), (3, 0)]);
+        let ranks = HashMap::from([(Address::repeat_byte(1), 1), (Address::repeat_byte(3), 2)]);
+        dependencies.release(&ranks);
+        assert_eq!(dependencies.next(), Some(((3, 0), 4)));
+        assert_eq!(dependencies.next(), Some(((3, 1), 4)));
+        assert_eq!(dependencies.next(), Some(((1, 1), 5)));
+        dependencies.mark_invalid(&Address::repeat_byte(1));
+        assert_eq!(dependencies.next(), None);
+    }
+
+    #[test]
+    fn test_prerequisites() {
+        let (sender, other) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let prerequisites = Prerequisites::new([
+            (sender, 4, "a4"),
+            (sender, 5, "a5"),
+            (sender, 6, "a6"),
+            (other, 1, "b1"),
+            (other, 3, "b3"),
+        ]);
+        assert_eq!(prerequisites.before(&sender, 6), vec!["a4", "a5"]);
+        assert_eq!(prerequisites.before(&sender, 8), Vec::<&str>::new());
+        // NOTE: a transaction from the mempool is not its own prerequisite
+        assert_eq!(prerequisites.before(&sender, 4), Vec::<&str>::new());
+        assert_eq!(prerequisites.before(&other, 2), vec!["b1"]);
+        assert_eq!(prerequisites.before(&other, 4), Vec::<&str>::new());
+        assert_eq!(prerequisites.before(&Address::repeat_byte(3), 1), Vec::<&str>::new());
+    }
+}
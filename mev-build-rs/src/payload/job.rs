@@ -1,25 +1,30 @@
-use crate::payload::{attributes::BuilderPayloadBuilderAttributes, builder::PayloadBuilder};
+use crate::{
+    bundles::PrivateTransactionPool,
+    metrics,
+    payload::{attributes::BuilderPayloadBuilderAttributes, builder::PayloadBuilder},
+};
 use futures_util::{Future, FutureExt};
 use reth::{
     payload::{
         self, database::CachedReads, EthBuiltPayload, KeepPayloadJobAlive, PayloadBuilderError,
     },
     primitives::revm_primitives::{Address, BlockEnv, CfgEnvWithHandlerCfg, U256},
-    providers::StateProviderFactory,
+    providers::{BlockNumReader, StateProviderFactory},
     tasks::TaskSpawner,
     transaction_pool::TransactionPool,
 };
 use reth_basic_payload_builder::{
-    BuildArguments, BuildOutcome, Cancelled, PayloadBuilder as _, PayloadConfig, PayloadTaskGuard,
+    is_better_payload, BuildArguments, BuildOutcome, Cancelled, PayloadConfig, PayloadTaskGuard,
     PendingPayload, ResolveBestPayload,
 };
 use std::{
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tokio::{
-    sync::oneshot::{self, error::RecvError},
-    time::{Interval, Sleep},
+    sync::{mpsc, oneshot},
+    time::{Instant, Interval, Sleep},
 };
 use tracing::{debug, error, trace, warn};
 
@@ -40,12 +45,30 @@ pub struct PayloadJob<Client, Pool, Tasks> {
     pub deadline: Pin<Box<Sleep>>,
     pub interval: Interval,
     pub best_payload: Option<EthBuiltPayload>,
-    pub pending_block: Option<PendingPayload<EthBuiltPayload>>,
+    // NOTE: one pending build for each ordering of the builder, built concurrently
+    pub pending_blocks: Vec<PendingPayload<EthBuiltPayload>>,
     pub payload_task_guard: PayloadTaskGuard,
     pub cached_reads: Option<CachedReads>,
     // TODO: consider moving shared state here, rather than builder
     pub builder: PayloadBuilder,
     pub pending_bid_update: Option<BidUpdate>,
+    // when the current round of builds started, if any are pending
+    pub build_started: Option<Instant>,
+    // how long the last round of builds took, to avoid starting builds that can not finish in time
+    pub last_build_duration: Duration,
+}
+
+// Indicates if `payload` includes a private transaction its sender cancelled, so that it must no
+// longer be bid
+// NOTE: the base fee of a block and the validity of the bundles in it are fixed by its parent, so
+// only a cancellation can take back a payload built for the same parent; a bundle including or
+// backrunning the cancelled transaction carries its hash, so it is caught here as well
+fn includes_cancelled(
+    payload: &EthBuiltPayload,
+    private_transactions: &PrivateTransactionPool,
+) -> bool {
+    let transactions = payload.block().body.transactions.iter().map(|tx| &tx.hash);
+    private_transactions.any_cancelled(transactions)
 }
 
 impl<Client, Pool, Tasks> payload::PayloadJob for PayloadJob<Client, Pool, Tasks>
@@ -78,7 +101,8 @@ where
 
     fn resolve(&mut self) -> (Self::ResolvePayloadFuture, KeepPayloadJobAlive) {
         let best_payload = self.best_payload.take();
-        let maybe_better = self.pending_block.take();
+        // NOTE: only wait on the build with the builder's default ordering
+        let maybe_better = std::mem::take(&mut self.pending_blocks).into_iter().next();
         let mut empty_payload = None;
 
         if best_payload.is_none() {
@@ -144,150 +168,261 @@ where
             return Poll::Ready(Ok(()))
         }
 
-        // poll for pending bids
-        if let Some(mut fut) = this.pending_bid_update.take() {
-            match fut.poll_unpin(cx) {
-                Poll::Pending => {
-                    this.pending_bid_update = Some(fut);
-                }
-                Poll::Ready(Ok(maybe_dispatch)) => {
-                    if let Some((payload, value_to_bid)) = maybe_dispatch {
-                        // TODO: handle the pending block, esp if this is the last bid
-                        if let Some(proposal) = this.config.attributes.proposal.as_ref() {
-                            let (cfg_env, block_env) = this.builder.cfg_and_block_env(&this.config);
-                            let config = PayloadFinalizerConfig {
-                                proposer_fee_recipient: proposal.proposer_fee_recipient,
-                                cfg_env,
-                                block_env,
-                            };
-                            let client = this.client.clone();
-                            let builder = this.builder.clone();
-                            this.executor.spawn_blocking(Box::pin(async move {
-                                // TODO: - track proposer payment, revenue
-                                builder
-                                    .finalize_payload_and_dispatch(
-                                        client,
-                                        payload,
-                                        value_to_bid,
-                                        &config,
-                                    )
-                                    .await
-                            }));
-                        } else {
-                            error!(?payload, "attempt to finalize payload for an auction that is missing proposal attributes");
-                        }
+        // poll for pending bids, including any raised bids for the same payload
+        while let Some(bid_update) = this.pending_bid_update.as_mut() {
+            match bid_update.poll_value(cx) {
+                Poll::Pending => break,
+                Poll::Ready(Some((payload, value_to_bid))) => {
+                    // TODO: handle the pending block, esp if this is the last bid
+                    if let Some(proposal) = this.config.attributes.proposal.as_ref() {
+                        let (cfg_env, block_env) = this.builder.cfg_and_block_env(&this.config);
+                        let config = PayloadFinalizerConfig {
+                            proposer_fee_recipient: proposal.proposer_fee_recipient,
+                            cfg_env,
+                            block_env,
+                        };
+                        let client = this.client.clone();
+                        let builder = this.builder.clone();
+                        this.executor.spawn_blocking(Box::pin(async move {
+                            // TODO: - track proposer payment, revenue
+                            builder
+                                .finalize_payload_and_dispatch(
+                                    client,
+                                    payload,
+                                    value_to_bid,
+                                    &config,
+                                )
+                                .await
+                        }));
+                    } else {
+                        error!(?payload, "attempt to finalize payload for an auction that is missing proposal attributes");
                     }
                 }
                 // bidder has terminated, so we terminate this job
-                Poll::Ready(Err(_)) => return Poll::Ready(Ok(())),
+                Poll::Ready(None) => return Poll::Ready(Ok(())),
             }
         }
 
         // check if the interval is reached
         while this.interval.poll_tick(cx).is_ready() {
-            // start a new job if there is no pending block and we haven't reached the deadline
-            if this.pending_block.is_none() {
-                trace!(target: "payload_builder", "spawn new payload build task");
-                let (tx, rx) = oneshot::channel();
-                let client = this.client.clone();
-                let pool = this.pool.clone();
-                let cancel = Cancelled::default();
-                let _cancel = cancel.clone();
-                let guard = this.payload_task_guard.clone();
-                let payload_config = this.config.clone();
-                let best_payload = this.best_payload.clone();
-                let cached_reads = this.cached_reads.take().unwrap_or_default();
-                let builder = this.builder.clone();
-                this.executor.spawn_blocking(Box::pin(async move {
-                    // acquire the permit for executing the task
-                    let _permit = guard.acquire().await;
-                    let args = BuildArguments {
-                        client,
-                        pool,
-                        cached_reads,
-                        config: payload_config,
-                        cancel,
-                        best_payload,
-                    };
-                    let result = builder.try_build(args);
-                    let _ = tx.send(result);
-                }));
-
-                this.pending_block = Some(PendingPayload::new(_cancel, rx));
+            this.withdraw_cancelled_payload();
+
+            // NOTE: a block for this payload's height is already on chain, so the slot has passed
+            let block_number = this.config.parent_block.number + 1;
+            if this.client.best_block_number().is_ok_and(|best| best >= block_number) {
+                trace!(target: "payload_builder", block_number, "terminating payload job for a slot that has passed");
+                return Poll::Ready(Ok(()))
+            }
+
+            // start new builds if there are no pending builds and they can finish by the deadline
+            let deadline = this.deadline.deadline();
+            if this.pending_blocks.is_empty() &&
+                Instant::now() + this.last_build_duration >= deadline
+            {
+                trace!(target: "payload_builder", build_duration = ?this.last_build_duration, "not enough time to build again before the deadline");
+            } else if this.pending_blocks.is_empty() {
+                this.build_started = Some(Instant::now());
+                let orderings = this.builder.orderings().len();
+                for index in 0..orderings {
+                    trace!(target: "payload_builder", ordering = index, "spawn new payload build task");
+                    let (tx, rx) = oneshot::channel();
+                    let client = this.client.clone();
+                    let pool = this.pool.clone();
+                    let cancel = Cancelled::default();
+                    let _cancel = cancel.clone();
+                    let guard = this.payload_task_guard.clone();
+                    let payload_config = this.config.clone();
+                    let best_payload = this.best_payload.clone();
+                    // NOTE: cached reads can only be given to one of the builds
+                    let cached_reads = this.cached_reads.take().unwrap_or_default();
+                    let builder = this.builder.clone();
+                    this.executor.spawn_blocking(Box::pin(async move {
+                        // acquire the permit for executing the task
+                        let _permit = guard.acquire().await;
+                        let args = BuildArguments {
+                            client,
+                            pool,
+                            cached_reads,
+                            config: payload_config,
+                            cancel,
+                            best_payload,
+                        };
+                        let ordering = builder.orderings()[index].clone();
+                        let result = builder.try_build_with(args, ordering.as_ref());
+                        let _ = tx.send(result);
+                    }));
+
+                    this.pending_blocks.push(PendingPayload::new(_cancel, rx));
+                }
             }
         }
 
-        // poll the pending block
-        if let Some(mut fut) = this.pending_block.take() {
-            match fut.poll_unpin(cx) {
-                Poll::Ready(Ok(outcome)) => {
-                    this.interval.reset();
-                    match outcome {
-                        BuildOutcome::Better { payload, cached_reads } => {
-                            this.cached_reads = Some(cached_reads);
-                            debug!(target: "payload_builder", value = %payload.fees(), "built better payload");
-                            // TODO: consider reworking this code path...
-                            // If it stays, then at least skip clone here...
-                            this.best_payload = Some(payload.clone());
-
-                            if let Some(proposal) = this.config.attributes.proposal.as_ref() {
-                                let (value_tx, value_rx) = oneshot::channel();
-                                let fees = payload.fees();
-                                let bidder = proposal.bidder.clone();
-                                this.executor.spawn(Box::pin(async move {
-                                    if bidder.is_closed() {
-                                        return
-                                    }
-                                    if bidder.send((fees, value_tx)).await.is_err() {
-                                        warn!("could not send fees to bidder");
-                                    }
-                                }));
-                                this.pending_bid_update =
-                                    Some(BidUpdate { value_rx, payload: Some(payload) });
-                            }
-                        }
-                        BuildOutcome::Aborted { fees, cached_reads } => {
-                            this.cached_reads = Some(cached_reads);
-                            trace!(target: "payload_builder", worse_fees = %fees, "skipped payload build of worse block");
-                        }
-                        BuildOutcome::Cancelled => {
-                            unreachable!("the cancel signal never fired")
-                        }
-                    }
+        // poll the pending blocks
+        let mut outcomes = Vec::new();
+        this.pending_blocks.retain_mut(|fut| match fut.poll_unpin(cx) {
+            Poll::Ready(outcome) => {
+                outcomes.push(outcome);
+                false
+            }
+            Poll::Pending => true,
+        });
+        if !outcomes.is_empty() {
+            this.interval.reset();
+        }
+        if this.pending_blocks.is_empty() {
+            if let Some(build_started) = this.build_started.take() {
+                this.last_build_duration = build_started.elapsed();
+            }
+        }
+        for outcome in outcomes {
+            this.on_build_outcome(outcome);
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<Client, Pool, Tasks> PayloadJob<Client, Pool, Tasks>
+where
+    Tasks: TaskSpawner,
+{
+    // NOTE: a private transaction cancelled by its sender must not land through a block built
+    // before it was cancelled, so the best payload is dropped and a replacement, without the
+    // transaction, is built right away and bid even if it is worth less. Relays accepting
+    // cancellations replace the earlier bid with it, while other relays keep the earlier bid.
+    fn withdraw_cancelled_payload(&mut self) {
+        let Some(payload) = self.best_payload.as_ref() else { return };
+        if !includes_cancelled(payload, self.builder.private_transaction_pool()) {
+            return
+        }
+        debug!(target: "payload_builder", block_hash = %payload.block().hash(), value = %payload.fees(), "withdrawing payload including a cancelled private transaction");
+        metrics::on_payload_withdrawn();
+        self.best_payload = None;
+        // NOTE: a bid for the payload that is still pending is dropped along with it
+        self.pending_bid_update = None;
+        // NOTE: builds in flight must beat the withdrawn payload and may include the cancelled
+        // transaction, so they are cancelled in favor of builds of the replacement, which start
+        // right away as no builds are pending
+        self.pending_blocks.clear();
+        self.build_started = None;
+    }
+
+    fn on_build_outcome(
+        &mut self,
+        outcome: Result<BuildOutcome<EthBuiltPayload>, PayloadBuilderError>,
+    ) {
+        match outcome {
+            Ok(BuildOutcome::Better { payload, cached_reads }) => {
+                self.cached_reads = Some(cached_reads);
+                if includes_cancelled(&payload, self.builder.private_transaction_pool()) {
+                    debug!(target: "payload_builder", block_hash = %payload.block().hash(), "skipped candidate payload including a cancelled private transaction");
+                    return
                 }
-                Poll::Ready(Err(error)) => {
-                    // job failed, but we simply try again next interval
-                    debug!(target: "payload_builder", %error, "payload build attempt failed");
+                // NOTE: a candidate built concurrently may have been better still
+                if !is_better_payload(self.best_payload.as_ref(), payload.fees()) {
+                    trace!(target: "payload_builder", worse_fees = %payload.fees(), "skipped candidate payload worse than another candidate");
+                    return
                 }
-                Poll::Pending => {
-                    this.pending_block = Some(fut);
+                debug!(target: "payload_builder", value = %payload.fees(), "built better payload");
+                metrics::on_payload_built();
+                // TODO: consider reworking this code path...
+                // If it stays, then at least skip clone here...
+                self.best_payload = Some(payload.clone());
+
+                if let Some(proposal) = self.config.attributes.proposal.as_ref() {
+                    // NOTE: the bidder may raise its bid for the payload after its first bid
+                    let (value_tx, value_rx) = mpsc::channel(1);
+                    let fees = payload.fees();
+                    let bidder = proposal.bidder.clone();
+                    self.executor.spawn(Box::pin(async move {
+                        if bidder.is_closed() {
+                            return
+                        }
+                        if bidder.send((fees, value_tx)).await.is_err() {
+                            warn!("could not send fees to bidder");
+                        }
+                    }));
+                    self.pending_bid_update = Some(BidUpdate { value_rx, payload });
                 }
             }
+            Ok(BuildOutcome::Aborted { fees, cached_reads }) => {
+                self.cached_reads = Some(cached_reads);
+                trace!(target: "payload_builder", worse_fees = %fees, "skipped payload build of worse block");
+            }
+            Ok(BuildOutcome::Cancelled) => {
+                unreachable!("the cancel signal never fired")
+            }
+            Err(error) => {
+                // job failed, but we simply try again next interval
+                debug!(target: "payload_builder", %error, "payload build attempt failed");
+            }
         }
-
-        Poll::Pending
     }
 }
 
 pub struct BidUpdate {
-    value_rx: oneshot::Receiver<Option<U256>>,
+    value_rx: mpsc::Receiver<U256>,
     // TODO: consider payload store, to skip shuttling data around
-    payload: Option<EthBuiltPayload>,
+    payload: EthBuiltPayload,
 }
 
-impl Future for BidUpdate {
-    type Output = Result<Option<(EthBuiltPayload, U256)>, RecvError>;
+impl BidUpdate {
+    /// Poll for the next value to bid for the payload, or `None` once the bidder is done with the
+    /// auction
+    fn poll_value(&mut self, cx: &mut Context<'_>) -> Poll<Option<(EthBuiltPayload, U256)>> {
+        self.value_rx.poll_recv(cx).map(|value| value.map(|value| (self.payload.clone(), value)))
+    }
+}
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.get_mut();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use alloy_eips::eip2718::Encodable2718;
+    use reth::{
+        payload::PayloadId,
+        primitives::{
+            revm_primitives::{alloy_primitives::Parity, TxKind, B256},
+            SealedBlock, Signature, Transaction, TransactionSigned,
+        },
+    };
 
-        match this.value_rx.poll_unpin(cx) {
-            Poll::Pending => Poll::Pending,
-            Poll::Ready(Ok(maybe_value)) => {
-                Poll::Ready(Ok(maybe_value
-                    .map(|value| (this.payload.take().expect("only called once"), value))))
-            }
-            Poll::Ready(Err(err)) => Poll::Ready(Err(err)),
-        }
+    fn signed_transaction(nonce: u64) -> TransactionSigned {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            nonce,
+            to: TxKind::Call(Address::repeat_byte(2)),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        )
+    }
+
+    #[test]
+    fn test_payload_including_cancelled_transaction_is_withdrawn() {
+        let private_transactions = PrivateTransactionPool::default();
+        let (private, public) = (signed_transaction(0), signed_transaction(1));
+        private_transactions.insert(&private.encoded_2718().into(), None, None).unwrap();
+
+        let mut block = SealedBlock::default();
+        block.body.transactions = vec![private.clone(), public.clone()];
+        let payload = EthBuiltPayload::new(PayloadId::new([0; 8]), block, U256::from(1), None);
+        assert!(!includes_cancelled(&payload, &private_transactions));
+
+        // NOTE: only a transaction cancelled by its sender withdraws the payload
+        let sender = private.recover_signer().unwrap();
+        assert!(!private_transactions.remove(&public.hash, sender).unwrap());
+        assert!(!includes_cancelled(&payload, &private_transactions));
+        assert!(private_transactions.remove(&private.hash, sender).unwrap());
+        assert!(includes_cancelled(&payload, &private_transactions));
+
+        // NOTE: cancellations are forgotten once the chain moves past the head they were made at
+        private_transactions.on_new_block(1, Vec::new(), &Default::default());
+        assert!(!includes_cancelled(&payload, &private_transactions));
     }
 }
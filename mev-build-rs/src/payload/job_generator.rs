@@ -1,5 +1,9 @@
-use crate::payload::{builder::PayloadBuilder, job::PayloadJob};
-use ethereum_consensus::clock::duration_until;
+use crate::{
+    bundles::changed_nonces,
+    metrics,
+    payload::{builder::PayloadBuilder, job::PayloadJob},
+};
+use ethereum_consensus::clock::{duration_since_unix_epoch, duration_until};
 use reth::{
     api::PayloadBuilderAttributes,
     payload::{self, database::CachedReads, PayloadBuilderError},
@@ -12,7 +16,8 @@ use reth::{
     transaction_pool::TransactionPool,
 };
 use reth_basic_payload_builder::{PayloadConfig, PayloadTaskGuard, PrecachedState};
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tracing::info;
 
 #[derive(Debug, Clone)]
 pub struct PayloadJobGeneratorConfig {
@@ -22,6 +27,8 @@ pub struct PayloadJobGeneratorConfig {
     pub interval: Duration,
     pub deadline: Duration,
     pub max_payload_tasks: usize,
+    // amount of time into the slot after which jobs for the slot stop building, if provided
+    pub cutoff: Option<Duration>,
 }
 
 #[derive(Debug)]
@@ -66,7 +73,12 @@ impl<Client, Pool, Tasks> PayloadJobGenerator<Client, Pool, Tasks> {
 
     #[inline]
     fn job_deadline(&self, unix_timestamp: u64) -> tokio::time::Instant {
-        tokio::time::Instant::now() + self.max_job_duration(unix_timestamp)
+        let mut job_duration = self.max_job_duration(unix_timestamp);
+        if let Some(cutoff) = self.config.cutoff {
+            let cutoff = Duration::from_secs(unix_timestamp) + cutoff;
+            job_duration = job_duration.min(cutoff.saturating_sub(duration_since_unix_epoch()));
+        }
+        tokio::time::Instant::now() + job_duration
     }
 
     fn maybe_pre_cached(&self, parent: B256) -> Option<CachedReads> {
@@ -123,11 +135,13 @@ where
             deadline,
             interval: tokio::time::interval(self.config.interval),
             best_payload: None,
-            pending_block: None,
+            pending_blocks: Vec::new(),
             cached_reads,
             payload_task_guard: self.payload_task_guard.clone(),
             builder: self.builder.clone(),
             pending_bid_update: None,
+            build_started: None,
+            last_build_duration: Duration::ZERO,
         })
     }
 
@@ -148,5 +162,43 @@ where
         }
 
         self.pre_cached = Some(PrecachedState { block: committed.tip().hash(), cached });
+
+        let tip = committed.tip();
+        let won = tip.beneficiary == self.builder.fee_recipient();
+        if won {
+            metrics::on_auction_won();
+            if let Some(split) = self.builder.take_revenue_split(&tip.hash()) {
+                info!(
+                    block_number = tip.number,
+                    block_hash = %tip.hash(),
+                    revenue = %split.revenue,
+                    proposer = %split.proposer,
+                    treasury = %split.treasury,
+                    builder = %split.builder(),
+                    "split revenue of block won"
+                );
+                metrics::on_revenue_split(&split);
+                self.builder.record_subsidy(tip.timestamp, &split);
+            }
+        }
+        self.builder.prune_execution_outcomes(tip.number);
+        self.builder.prune_revenue_splits(tip.number);
+        self.builder.prune_state_caches(tip.number);
+        let included = tip.body.transactions.iter().map(|tx| tx.hash).collect::<HashSet<_>>();
+        let nonces = changed_nonces(execution_outcome);
+        let mut origins = self.builder.bundle_pool().on_new_head(tip.number, &included, &nonces);
+        origins.extend(
+            self.builder.private_transaction_pool().on_new_block(tip.number, included, &nonces),
+        );
+        // NOTE: orderflow is only accounted to its provider when it lands in a block built by
+        // this builder
+        let reputation = self.builder.reputation();
+        if won {
+            for origin in origins {
+                metrics::on_orderflow_included(&origin.provider);
+                reputation.on_landed(&origin.provider);
+            }
+        }
+        reputation.on_new_head();
     }
 }
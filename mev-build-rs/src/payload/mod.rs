@@ -2,6 +2,13 @@
 
 pub mod attributes;
 pub mod builder;
+pub mod dependencies;
 pub mod job;
 pub mod job_generator;
+pub mod ordering;
+pub mod pre_execution;
 pub mod service_builder;
+pub mod simulation_cache;
+pub mod state_cache;
+pub mod subsidy;
+pub mod treasury;
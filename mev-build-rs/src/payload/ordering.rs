@@ -0,0 +1,132 @@
+use crate::bundles::{Bundle, BundleCandidate};
+use reth::primitives::revm_primitives::U256;
+use serde::Deserialize;
+use std::{fmt, sync::Arc};
+
+/// Orders the bundles included at the top of a block
+pub trait BundleOrdering: fmt::Debug + Send + Sync {
+    /// Select the bundles to include in a block from `candidates`, in order of inclusion.
+    ///
+    /// NOTE: each bundle is simulated again before it is included, so an ordering may return
+    /// bundles that turn out to conflict; they are left out of the block.
+    fn order(&self, candidates: Vec<BundleCandidate>) -> Vec<Bundle>;
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderingKind {
+    Greedy,
+    #[default]
+    ProfitMaximizing,
+}
+
+impl OrderingKind {
+    pub fn build(self) -> Arc<dyn BundleOrdering> {
+        match self {
+            Self::Greedy => Arc::new(Greedy),
+            Self::ProfitMaximizing => Arc::new(ProfitMaximizing),
+        }
+    }
+}
+
+fn price_per_gas(candidate: &BundleCandidate) -> U256 {
+    candidate.payment / U256::from(candidate.gas_used.max(1))
+}
+
+/// Orders bundles by their effective priority fee, i.e. their payment per unit of gas
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Greedy;
+
+impl BundleOrdering for Greedy {
+    fn order(&self, mut candidates: Vec<BundleCandidate>) -> Vec<Bundle> {
+        candidates.sort_by_cached_key(|candidate| std::cmp::Reverse(price_per_gas(candidate)));
+        candidates.into_iter().map(|candidate| candidate.bundle).collect()
+    }
+}
+
+/// Orders bundles greedily from the highest payment down, skipping any that conflict with a
+/// bundle already selected
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ProfitMaximizing;
+
+impl BundleOrdering for ProfitMaximizing {
+    fn order(&self, mut candidates: Vec<BundleCandidate>) -> Vec<Bundle> {
+        candidates.sort_by(|a, b| b.payment.cmp(&a.payment));
+        let mut selected: Vec<BundleCandidate> = Vec::with_capacity(candidates.len());
+        for candidate in candidates {
+            if selected.iter().any(|other| other.access.conflicts_with(&candidate.access)) {
+                continue
+            }
+            selected.push(candidate);
+        }
+        selected.into_iter().map(|candidate| candidate.bundle).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundles::{StateAccess, StateKey};
+    use reth::primitives::revm_primitives::{Address, B256};
+
+    fn candidate(
+        id: u8,
+        payment: u64,
+        gas_used: u64,
+        reads: &[StateKey],
+        writes: &[StateKey],
+    ) -> BundleCandidate {
+        BundleCandidate {
+            bundle: Bundle {
+                hash: B256::repeat_byte(id),
+                transactions: vec![],
+                block_number: 1,
+                min_timestamp: None,
+                max_timestamp: None,
+                reverting_tx_hashes: Default::default(),
+                origin: None,
+                carried_over: 0,
+            },
+            payment: U256::from(payment),
+            gas_used,
+            access: StateAccess {
+                reads: reads.iter().copied().collect(),
+                writes: writes.iter().copied().collect(),
+            },
+        }
+    }
+
+    fn hashes(bundles: Vec<Bundle>) -> Vec<B256> {
+        bundles.into_iter().map(|bundle| bundle.hash).collect()
+    }
+
+    #[test]
+    fn test_orderings() {
+        let pool = Address::repeat_byte(1);
+        let reserve = StateKey::Storage(pool, U256::ZERO);
+        let searcher = StateKey::Account(Address::repeat_byte(2));
+        let other_searcher = StateKey::Account(Address::repeat_byte(3));
+
+        let candidates = || {
+            vec![
+                candidate(1, 100, 10, &[reserve], &[]),
+                candidate(2, 300, 100, &[reserve, searcher], &[reserve, searcher]),
+                candidate(3, 200, 40, &[other_searcher], &[other_searcher]),
+                candidate(4, 50, 1, &[searcher], &[searcher]),
+            ]
+        };
+        assert_eq!(
+            hashes(ProfitMaximizing.order(candidates())),
+            vec![B256::repeat_byte(2), B256::repeat_byte(3)]
+        );
+        assert_eq!(
+            hashes(Greedy.order(candidates())),
+            vec![
+                B256::repeat_byte(4),
+                B256::repeat_byte(1),
+                B256::repeat_byte(3),
+                B256::repeat_byte(2)
+            ]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::bundles::StateAccess;
+use reth::primitives::revm_primitives::{Address, EvmState, B256};
+use std::collections::HashMap;
+
+/// Why a transaction from the mempool is left out of the block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exclusion {
+    Reverted,
+    Blocklisted(Address),
+}
+
+/// Transactions from the mempool found to be left out of the block when each is executed on its
+/// own against the state after the bundles, along with the state each accessed.
+///
+/// The transactions are executed in parallel ahead of the pass selecting transactions into the
+/// block one after another. As long as none of the state a transaction accessed is changed by the
+/// transactions selected before it, the transaction is left out again without executing it.
+#[derive(Debug, Default)]
+pub struct PreExecutions {
+    excluded: HashMap<B256, (Exclusion, StateAccess)>,
+    // state changed by the transactions selected since the transactions were executed
+    committed: StateAccess,
+}
+
+impl PreExecutions {
+    pub fn new(excluded: impl IntoIterator<Item = (B256, Exclusion, StateAccess)>) -> Self {
+        let excluded =
+            excluded.into_iter().map(|(hash, exclusion, access)| (hash, (exclusion, access)));
+        Self { excluded: excluded.collect(), committed: Default::default() }
+    }
+
+    /// Record the state changes `state` of a transaction selected into the block
+    pub fn on_committed(&mut self, state: &EvmState, coinbase: Address) {
+        if !self.excluded.is_empty() {
+            self.committed.record(state, coinbase);
+        }
+    }
+
+    /// Why the transaction with `hash` is left out of the block, if it is known without executing
+    /// it again
+    pub fn exclusion(&self, hash: &B256) -> Option<Exclusion> {
+        let (exclusion, access) = self.excluded.get(hash)?;
+        (!self.committed.conflicts_with(access)).then_some(*exclusion)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reth::primitives::revm_primitives::{Account, EvmStorageSlot, U256};
+
+    fn state(address: Address, slot: u64, changed: bool) -> EvmState {
+        let mut account = Account::default();
+        account.info.code_hash = B256::repeat_byte(1);
+        let value = if changed { U256::from(1) } else { U256::ZERO };
+        account.storage.insert(U256::from(slot), EvmStorageSlot::new_changed(U256::ZERO, value));
+        EvmState::from_iter([(address, account)])
+    }
+
+    #[test]
+    fn test_exclusions_hold_until_their_state_changes() {
+        let (contract, coinbase) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        let (reverted, blocklisted) = (B256::repeat_byte(1), B256::repeat_byte(2));
+        let access = |slot| {
+            let mut access = StateAccess::default();
+            access.record(&state(contract, slot, false), coinbase);
+            access
+        };
+        let mut pre_executions = PreExecutions::new([
+            (reverted, Exclusion::Reverted, access(1)),
+            (blocklisted, Exclusion::Blocklisted(contract), access(2)),
+        ]);
+        assert_eq!(pre_executions.exclusion(&reverted), Some(Exclusion::Reverted));
+        assert_eq!(pre_executions.exclusion(&B256::ZERO), None);
+
+        // NOTE: changes to unrelated state or to the coinbase keep the transactions left out
+        pre_executions.on_committed(&state(contract, 3, true), coinbase);
+        pre_executions.on_committed(&state(coinbase, 1, true), coinbase);
+        assert_eq!(pre_executions.exclusion(&reverted), Some(Exclusion::Reverted));
+
+        pre_executions.on_committed(&state(contract, 1, true), coinbase);
+        assert_eq!(pre_executions.exclusion(&reverted), None);
+        assert_eq!(pre_executions.exclusion(&blocklisted), Some(Exclusion::Blocklisted(contract)));
+    }
+}
@@ -1,22 +1,29 @@
 use crate::{
+    blocklist::Blocklist,
+    bundles::{BundlePool, PrivateTransactionPool},
     node::BuilderEngineTypes,
     payload::{
-        builder::PayloadBuilder,
+        builder::{PayloadBuilder, PaymentConfig, SelectionConfig},
         job_generator::{PayloadJobGenerator, PayloadJobGeneratorConfig},
+        subsidy::SubsidyLedger,
+        treasury::Treasury,
     },
+    reputation::Reputation,
     service::BuilderConfig as Config,
     Error,
 };
 use alloy::signers::local::{coins_bip39::English, MnemonicBuilder, PrivateKeySigner};
+use rayon::ThreadPoolBuilder;
 use reth::{
     builder::{node::FullNodeTypes, BuilderContext, NodeTypesWithEngine},
     chainspec::ChainSpec,
     cli::config::PayloadBuilderConfig,
     payload::{EthBuiltPayload, PayloadBuilderHandle, PayloadBuilderService},
-    primitives::revm_primitives::{Address, Bytes},
+    primitives::revm_primitives::{Address, Bytes, U256},
     providers::CanonStateSubscriptions,
     transaction_pool::TransactionPool,
 };
+use std::{sync::Arc, time::Duration};
 use tokio::sync::mpsc::Sender;
 
 fn signer_from_mnemonic(mnemonic: &str) -> Result<PrivateKeySigner, Error> {
@@ -26,18 +33,83 @@ fn signer_from_mnemonic(mnemonic: &str) -> Result<PrivateKeySigner, Error> {
 #[derive(Debug, Clone)]
 pub struct PayloadServiceBuilder {
     extra_data: Option<Bytes>,
-    signer: PrivateKeySigner,
-    fee_recipient: Address,
+    payment: PaymentConfig,
     bid_tx: Sender<EthBuiltPayload>,
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    selection: SelectionConfig,
+    job_cutoff: Option<Duration>,
 }
 
-impl TryFrom<(&Config, Sender<EthBuiltPayload>)> for PayloadServiceBuilder {
+impl
+    TryFrom<(
+        &Config,
+        Option<U256>,
+        Sender<EthBuiltPayload>,
+        BundlePool,
+        PrivateTransactionPool,
+        Blocklist,
+        Reputation,
+    )> for PayloadServiceBuilder
+{
     type Error = Error;
 
-    fn try_from((value, bid_tx): (&Config, Sender<EthBuiltPayload>)) -> Result<Self, Self::Error> {
+    fn try_from(
+        (value, min_block_value, bid_tx, bundles, private_transactions, blocklist, reputation): (
+            &Config,
+            Option<U256>,
+            Sender<EthBuiltPayload>,
+            BundlePool,
+            PrivateTransactionPool,
+            Blocklist,
+            Reputation,
+        ),
+    ) -> Result<Self, Self::Error> {
         let signer = signer_from_mnemonic(&value.execution_mnemonic)?;
         let fee_recipient = value.fee_recipient.unwrap_or_else(|| signer.address());
-        Ok(Self { extra_data: value.extra_data.clone(), signer, fee_recipient, bid_tx })
+        let simulation_threads = value
+            .simulation_threads
+            .filter(|&threads| threads > 1)
+            .map(|threads| {
+                ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|index| format!("mev-builder-simulation-{index}"))
+                    .build()
+                    .map(Arc::new)
+            })
+            .transpose()?;
+        Ok(Self {
+            extra_data: value.extra_data.clone(),
+            payment: PaymentConfig {
+                signer,
+                fee_recipient,
+                treasury: value.treasury.as_ref().map(Treasury::new).transpose()?.flatten(),
+                subsidy: value.subsidy_limits.clone().map(SubsidyLedger::load).transpose()?,
+            },
+            bid_tx,
+            bundles,
+            private_transactions,
+            selection: SelectionConfig {
+                blocklist,
+                top_of_block_gas: value.top_of_block_gas,
+                min_priority_fee: value.min_priority_fee_wei,
+                min_block_value,
+                orderings: std::iter::once(value.ordering)
+                    .chain(value.candidate_orderings.iter().copied())
+                    .map(|ordering| ordering.build())
+                    .collect(),
+                simulation_threads,
+                reputation,
+            },
+            job_cutoff: value.job_cutoff_ms.map(Duration::from_millis),
+        })
+    }
+}
+
+impl PayloadServiceBuilder {
+    /// Address receiving the fees of built blocks
+    pub fn fee_recipient(&self) -> Address {
+        self.payment.fee_recipient
     }
 }
 
@@ -54,7 +126,6 @@ where
         ctx: &BuilderContext<Node>,
         pool: Pool,
     ) -> eyre::Result<PayloadBuilderHandle<<Node::Types as NodeTypesWithEngine>::Engine>> {
-        let chain_id = ctx.chain_spec().chain().id();
         let conf = ctx.payload_builder_config();
 
         let extradata = if let Some(extra_data) = self.extra_data {
@@ -68,6 +139,7 @@ where
             interval: conf.interval(),
             deadline: conf.deadline(),
             max_payload_tasks: conf.max_payload_tasks(),
+            cutoff: self.job_cutoff,
         };
 
         let payload_generator = PayloadJobGenerator::with_builder(
@@ -77,10 +149,11 @@ where
             payload_job_config,
             PayloadBuilder::new(
                 self.bid_tx,
-                self.signer,
-                self.fee_recipient,
-                chain_id,
+                self.payment,
                 ctx.chain_spec().clone(),
+                self.bundles,
+                self.private_transactions,
+                self.selection,
             ),
         );
 
@@ -0,0 +1,103 @@
+use crate::bundles::Bundle;
+use reth::primitives::revm_primitives::{keccak256, B256};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
+};
+
+/// Identifies the state a bundle is simulated against: the top of the block, after any bundles
+/// included ahead of it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Prefix(B256);
+
+impl Prefix {
+    /// The state once `bundle` is included on top of this state
+    pub fn then(self, bundle: &Bundle) -> Self {
+        let mut preimage = [0u8; 64];
+        preimage[..32].copy_from_slice(self.0.as_slice());
+        preimage[32..].copy_from_slice(bundle.hash.as_slice());
+        Self(keccak256(preimage))
+    }
+}
+
+#[derive(Debug)]
+struct Simulation<T> {
+    reverting_tx_hashes: HashSet<B256>,
+    outcome: Option<T>,
+}
+
+/// Outcomes of simulating bundles while building on top of a parent block for some payload
+/// attributes, shared by every build of the payload.
+///
+/// The transactions of a bundle execute the same way against the same state, so each bundle is
+/// only simulated once against the top of the block, and once after each sequence of bundles
+/// included ahead of it, however many times the payload is built and with whichever ordering.
+#[derive(Debug)]
+pub struct SimulationCache<T>(Arc<RwLock<HashMap<(Prefix, B256), Simulation<T>>>>);
+
+impl<T> Clone for SimulationCache<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for SimulationCache<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<T: Clone> SimulationCache<T> {
+    /// Return the outcome of simulating `bundle` against the state after `prefix`, if it was
+    /// simulated before, where `None` indicates the bundle can not be included
+    pub fn get(&self, prefix: Prefix, bundle: &Bundle) -> Option<Option<T>> {
+        let simulations = self.0.read().expect("can read");
+        // NOTE: a bundle sent again with the same transactions may allow different transactions
+        // to revert, which changes whether it can be included
+        simulations
+            .get(&(prefix, bundle.hash))
+            .filter(|simulation| simulation.reverting_tx_hashes == bundle.reverting_tx_hashes)
+            .map(|simulation| simulation.outcome.clone())
+    }
+
+    pub fn insert(&self, prefix: Prefix, bundle: &Bundle, outcome: Option<T>) {
+        let simulation =
+            Simulation { reverting_tx_hashes: bundle.reverting_tx_hashes.clone(), outcome };
+        self.0.write().expect("can write").insert((prefix, bundle.hash), simulation);
+    }
+
+    /// Number of simulations cached
+    pub fn size(&self) -> usize {
+        self.0.read().expect("can read").len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(id: u8) -> Bundle {
+        let mut bundle = Bundle::new(vec![], 1, None, None, Default::default());
+        bundle.hash = B256::repeat_byte(id);
+        bundle
+    }
+
+    #[test]
+    fn test_simulations_by_prefix() {
+        let cache = SimulationCache::<u64>::default();
+        let (first, mut second) = (bundle(1), bundle(2));
+        let top = Prefix::default();
+        cache.insert(top, &first, Some(21_000));
+        cache.insert(top, &second, None);
+        cache.insert(top.then(&first), &second, Some(42_000));
+
+        assert_eq!(cache.get(top, &first), Some(Some(21_000)));
+        assert_eq!(cache.get(top, &second), Some(None));
+        assert_eq!(cache.get(top.then(&first), &second), Some(Some(42_000)));
+        assert_eq!(cache.get(top.then(&second), &first), None);
+
+        second.reverting_tx_hashes.insert(B256::repeat_byte(3));
+        assert_eq!(cache.get(top, &second), None);
+        assert_eq!(cache.size(), 3);
+    }
+}
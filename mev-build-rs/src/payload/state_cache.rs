@@ -0,0 +1,209 @@
+use reth::{
+    primitives::revm_primitives::{Address, B256, U256},
+    revm::{
+        db::BundleState,
+        primitives::{AccountInfo, Bytecode},
+        DatabaseRef,
+    },
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+#[derive(Debug, Default)]
+struct Reads {
+    accounts: HashMap<Address, Option<AccountInfo>>,
+    storage: HashMap<(Address, U256), U256>,
+    contracts: HashMap<B256, Bytecode>,
+    block_hashes: HashMap<u64, B256>,
+}
+
+/// Reads of the state of a parent block, shared by every build on top of it.
+///
+/// Unlike the `CachedReads` handed from one build to the next, the cache is shared by the builds
+/// for each ordering and by the simulations of bundles, so each account and storage slot is only
+/// read from the database once for all of the builds of a slot.
+// NOTE: the state of a block never changes, so entries are never invalidated, and the cache is
+// dropped once the block can no longer be built on
+#[derive(Debug, Clone, Default)]
+pub struct StateCache(Arc<RwLock<Reads>>);
+
+impl StateCache {
+    /// Wrap `db`, serving reads from the cache and caching any reads it serves
+    pub fn as_db<DB>(&self, db: DB) -> CachedStateDb<'_, DB> {
+        CachedStateDb { db, cache: self }
+    }
+
+    /// Number of accounts, storage slots and contracts read so far
+    pub fn size(&self) -> usize {
+        let reads = self.0.read().expect("can read");
+        reads.accounts.len() + reads.storage.len() + reads.contracts.len()
+    }
+
+    fn get_or_read<K, V, E>(
+        &self,
+        key: K,
+        entries: impl Fn(&Reads) -> &HashMap<K, V>,
+        entries_mut: impl Fn(&mut Reads) -> &mut HashMap<K, V>,
+        read: impl FnOnce(&K) -> Result<V, E>,
+    ) -> Result<V, E>
+    where
+        K: std::hash::Hash + Eq,
+        V: Clone,
+    {
+        if let Some(value) = entries(&self.0.read().expect("can read")).get(&key) {
+            return Ok(value.clone())
+        }
+        let value = read(&key)?;
+        entries_mut(&mut self.0.write().expect("can write")).insert(key, value.clone());
+        Ok(value)
+    }
+}
+
+/// A database serving reads from a [`StateCache`] before the database it wraps
+#[derive(Debug)]
+pub struct CachedStateDb<'a, DB> {
+    db: DB,
+    cache: &'a StateCache,
+}
+
+impl<'a, DB: DatabaseRef> DatabaseRef for CachedStateDb<'a, DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.cache.get_or_read(
+            address,
+            |reads| &reads.accounts,
+            |reads| &mut reads.accounts,
+            |address| self.db.basic_ref(*address),
+        )
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.cache.get_or_read(
+            code_hash,
+            |reads| &reads.contracts,
+            |reads| &mut reads.contracts,
+            |code_hash| self.db.code_by_hash_ref(*code_hash),
+        )
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.cache.get_or_read(
+            (address, index),
+            |reads| &reads.storage,
+            |reads| &mut reads.storage,
+            |(address, index)| self.db.storage_ref(*address, *index),
+        )
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.cache.get_or_read(
+            number,
+            |reads| &reads.block_hashes,
+            |reads| &mut reads.block_hashes,
+            |number| self.db.block_hash_ref(*number),
+        )
+    }
+}
+
+/// A database serving the state changed in a [`BundleState`] before the database it wraps, so
+/// bundles can be simulated on top of the changes without a copy of them for each bundle
+#[derive(Debug)]
+pub struct PrestateDb<'a, DB> {
+    prestate: &'a BundleState,
+    db: DB,
+}
+
+impl<'a, DB> PrestateDb<'a, DB> {
+    pub fn new(prestate: &'a BundleState, db: DB) -> Self {
+        Self { prestate, db }
+    }
+}
+
+impl<'a, DB: DatabaseRef> DatabaseRef for PrestateDb<'a, DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        match self.prestate.account(&address) {
+            Some(account) => Ok(account.account_info()),
+            None => self.db.basic_ref(address),
+        }
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        match self.prestate.bytecode(&code_hash) {
+            Some(code) => Ok(code),
+            None => self.db.code_by_hash_ref(code_hash),
+        }
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        // NOTE: the storage of an account created or destroyed in the prestate is fully known
+        match self.prestate.account(&address).and_then(|account| account.storage_slot(index)) {
+            Some(value) => Ok(value),
+            None => self.db.storage_ref(address, index),
+        }
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::Cell, convert::Infallible};
+
+    #[derive(Default)]
+    struct CountingDb {
+        reads: Cell<usize>,
+    }
+
+    impl DatabaseRef for CountingDb {
+        type Error = Infallible;
+
+        fn basic_ref(&self, _address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(Some(AccountInfo { nonce: 1, ..Default::default() }))
+        }
+
+        fn code_by_hash_ref(&self, _code_hash: B256) -> Result<Bytecode, Self::Error> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(Bytecode::default())
+        }
+
+        fn storage_ref(&self, _address: Address, index: U256) -> Result<U256, Self::Error> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(index)
+        }
+
+        fn block_hash_ref(&self, _number: u64) -> Result<B256, Self::Error> {
+            self.reads.set(self.reads.get() + 1);
+            Ok(B256::ZERO)
+        }
+    }
+
+    #[test]
+    fn test_reads_are_shared_across_builds() {
+        let cache = StateCache::default();
+        let address = Address::repeat_byte(1);
+
+        let first = CountingDb::default();
+        let db = cache.as_db(&first);
+        assert_eq!(db.basic_ref(address).unwrap().unwrap().nonce, 1);
+        assert_eq!(db.storage_ref(address, U256::from(7)).unwrap(), U256::from(7));
+        assert_eq!(first.reads.get(), 2);
+
+        // NOTE: a later build only reads what no earlier build read
+        let second = CountingDb::default();
+        let db = cache.as_db(&second);
+        assert_eq!(db.basic_ref(address).unwrap().unwrap().nonce, 1);
+        assert_eq!(db.storage_ref(address, U256::from(7)).unwrap(), U256::from(7));
+        assert_eq!(db.storage_ref(address, U256::from(8)).unwrap(), U256::from(8));
+        assert_eq!(second.reads.get(), 1);
+        assert_eq!(cache.size(), 3);
+    }
+}
@@ -0,0 +1,160 @@
+use reth::primitives::revm_primitives::U256;
+use serde::Deserialize;
+use std::{collections::VecDeque, fs, io, path::PathBuf};
+use thiserror::Error;
+use tracing::warn;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const SECONDS_PER_WEEK: u64 = 7 * SECONDS_PER_DAY;
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    /// Most subsidy in wei spent on blocks won over any 24 hours; if missing, it is not capped
+    pub daily_wei: Option<U256>,
+    /// Most subsidy in wei spent on blocks won over any 7 days; if missing, it is not capped
+    pub weekly_wei: Option<U256>,
+    /// File the subsidy spent is saved to after each block won and loaded from at launch, so the
+    /// caps hold across restarts; if missing, the subsidy spent is only kept in memory
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Encoding(#[from] serde_json::Error),
+}
+
+/// Subsidy spent on the blocks the builder won, i.e. what it paid proposers beyond the revenue of
+/// each block, checked against caps over rolling windows of time
+#[derive(Debug, Clone)]
+pub struct SubsidyLedger {
+    config: Config,
+    // subsidy spent on each block won, along with its timestamp, over the last week
+    spent: VecDeque<(u64, U256)>,
+}
+
+impl SubsidyLedger {
+    /// Load the subsidy spent from the file in `config`, if any, starting empty if it does not
+    /// exist yet
+    pub fn load(config: Config) -> Result<Self, Error> {
+        let spent = match config.path.as_ref().map(fs::read) {
+            Some(Ok(contents)) => serde_json::from_slice(&contents)?,
+            Some(Err(err)) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => Default::default(),
+        };
+        Ok(Self { config, spent })
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let Some(path) = self.config.path.as_ref() else { return Ok(()) };
+        // NOTE: write to a separate file first, so a crash while saving can not leave a partial
+        // ledger that fails to load at the next launch
+        let pending = path.with_extension("pending");
+        fs::write(&pending, serde_json::to_vec(&self.spent)?)?;
+        fs::rename(pending, path)?;
+        Ok(())
+    }
+
+    /// Record `subsidy` spent on the block won at `timestamp`
+    pub fn record(&mut self, timestamp: u64, subsidy: U256) {
+        let count = self.spent.len();
+        if subsidy > U256::ZERO {
+            self.spent.push_back((timestamp, subsidy));
+        }
+        let mut pruned = false;
+        while self
+            .spent
+            .front()
+            .is_some_and(|&(spent_at, _)| spent_at.saturating_add(SECONDS_PER_WEEK) <= timestamp)
+        {
+            self.spent.pop_front();
+            pruned = true;
+        }
+        if pruned || self.spent.len() != count {
+            if let Err(err) = self.save() {
+                warn!(%err, path = ?self.config.path, "could not save subsidy ledger");
+            }
+        }
+    }
+
+    /// Subsidy spent over the `window` of seconds up to `timestamp`
+    pub fn spent_within(&self, timestamp: u64, window: u64) -> U256 {
+        let start = timestamp.saturating_sub(window);
+        self.spent
+            .iter()
+            .filter(|&&(spent_at, _)| spent_at > start && spent_at <= timestamp)
+            .fold(U256::ZERO, |total, &(_, subsidy)| total.saturating_add(subsidy))
+    }
+
+    /// Most subsidy that can be spent on a block at `timestamp` without exceeding any cap
+    pub fn available(&self, timestamp: u64) -> U256 {
+        [(self.config.daily_wei, SECONDS_PER_DAY), (self.config.weekly_wei, SECONDS_PER_WEEK)]
+            .into_iter()
+            .filter_map(|(cap, window)| {
+                cap.map(|cap| cap.saturating_sub(self.spent_within(timestamp, window)))
+            })
+            .min()
+            .unwrap_or(U256::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_caps_over_rolling_windows() {
+        let mut ledger = SubsidyLedger::load(Config {
+            daily_wei: Some(U256::from(100)),
+            weekly_wei: Some(U256::from(250)),
+            path: None,
+        })
+        .unwrap();
+        assert_eq!(ledger.available(0), U256::from(100));
+
+        ledger.record(12, U256::from(60));
+        ledger.record(24, U256::from(30));
+        assert_eq!(ledger.available(36), U256::from(10));
+
+        // NOTE: a day later, only the weekly cap is left to bind
+        let next_day = 12 + SECONDS_PER_DAY;
+        assert_eq!(ledger.available(next_day), U256::from(70));
+        ledger.record(next_day, U256::from(70));
+        ledger.record(next_day + SECONDS_PER_DAY, U256::from(90));
+        assert_eq!(ledger.available(next_day + SECONDS_PER_DAY), U256::ZERO);
+
+        // NOTE: subsidy spent over a week ago no longer counts against the weekly cap
+        let next_week = 24 + SECONDS_PER_WEEK;
+        ledger.record(next_week, U256::ZERO);
+        assert_eq!(ledger.spent_within(next_week, SECONDS_PER_WEEK), U256::from(160));
+        assert_eq!(ledger.available(next_week), U256::from(90));
+    }
+
+    #[test]
+    fn test_uncapped() {
+        let mut ledger = SubsidyLedger::load(Config::default()).unwrap();
+        ledger.record(12, U256::from(1000));
+        assert_eq!(ledger.available(12), U256::MAX);
+    }
+
+    #[test]
+    fn test_ledger_persists() {
+        let path = std::env::temp_dir().join(format!("subsidy-ledger-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let config =
+            Config { daily_wei: Some(U256::from(100)), weekly_wei: None, path: Some(path.clone()) };
+        let mut ledger = SubsidyLedger::load(config.clone()).unwrap();
+        assert_eq!(ledger.available(12), U256::from(100));
+        ledger.record(12, U256::from(60));
+
+        // NOTE: a builder launched again keeps counting the subsidy spent before
+        let ledger = SubsidyLedger::load(config.clone()).unwrap();
+        assert_eq!(ledger.available(24), U256::from(40));
+
+        fs::write(&path, "not a ledger").unwrap();
+        assert!(SubsidyLedger::load(config).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
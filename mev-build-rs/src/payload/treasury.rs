@@ -0,0 +1,116 @@
+use crate::Error;
+use reth::primitives::revm_primitives::{Address, U256};
+use serde::Deserialize;
+
+const BASIS_POINTS: u64 = 10_000;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Address receiving the treasury's share of the profit of each block
+    pub address: Address,
+    /// Fixed amount of the profit of each block paid to the treasury; if missing, none is paid
+    pub fee_wei: Option<U256>,
+    /// Share of the profit of each block, after any `fee_wei`, paid to the treasury, in basis
+    /// points; if missing, none is paid
+    pub fee_bps: Option<u64>,
+}
+
+/// How the revenue of a block is split between the proposer, the treasury and the builder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RevenueSplit {
+    pub revenue: U256,
+    pub proposer: U256,
+    pub treasury: U256,
+}
+
+impl RevenueSplit {
+    /// Amount the builder paid the proposer beyond the revenue of the block
+    pub fn subsidy(&self) -> U256 {
+        self.proposer.saturating_sub(self.revenue)
+    }
+
+    /// The builder's profit on the block, after paying the proposer and the treasury
+    pub fn builder(&self) -> U256 {
+        self.revenue.saturating_sub(self.proposer).saturating_sub(self.treasury)
+    }
+}
+
+/// Pays the treasury a share of the profit of each block
+#[derive(Debug, Clone)]
+pub struct Treasury {
+    pub address: Address,
+    fee_wei: U256,
+    fee_bps: u64,
+}
+
+impl Treasury {
+    /// The treasury of `config`, if it is paid anything, so that no gas is reserved in blocks for
+    /// a payment that is never made
+    pub fn new(config: &Config) -> Result<Option<Self>, Error> {
+        let fee_wei = config.fee_wei.unwrap_or_default();
+        let fee_bps = config.fee_bps.unwrap_or_default();
+        if fee_bps > BASIS_POINTS {
+            return Err(Error::InvalidTreasuryFee(fee_bps))
+        }
+        if fee_wei == U256::ZERO && fee_bps == 0 {
+            return Ok(None)
+        }
+        Ok(Some(Self { address: config.address, fee_wei, fee_bps }))
+    }
+
+    /// Split the `revenue` of a block paying `proposer_payment` to the proposer
+    // NOTE: the treasury is only paid from the profit of the block, so a block the builder
+    // subsidizes pays nothing to the treasury
+    pub fn split(&self, revenue: U256, proposer_payment: U256) -> RevenueSplit {
+        let profit = revenue.saturating_sub(proposer_payment);
+        let remainder = profit.saturating_sub(self.fee_wei);
+        let share = remainder.saturating_mul(U256::from(self.fee_bps)) / U256::from(BASIS_POINTS);
+        let treasury = self.fee_wei.saturating_add(share).min(profit);
+        RevenueSplit { revenue, proposer: proposer_payment, treasury }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let treasury = Treasury::new(&Config {
+            address: Address::ZERO,
+            fee_wei: Some(U256::from(10)),
+            fee_bps: Some(5_000),
+        })
+        .unwrap()
+        .unwrap();
+        for (revenue, proposer_payment, treasury_share) in
+            [(1000, 900, 55), (1000, 995, 5), (1000, 1000, 0), (1000, 1200, 0)]
+        {
+            let split = treasury.split(U256::from(revenue), U256::from(proposer_payment));
+            assert_eq!(split.treasury, U256::from(treasury_share));
+            assert_eq!(
+                split.builder(),
+                U256::from(revenue).saturating_sub(U256::from(proposer_payment + treasury_share))
+            );
+        }
+    }
+
+    #[test]
+    fn test_new() {
+        let config = |fee_wei: u64, fee_bps: u64| Config {
+            address: Address::ZERO,
+            fee_wei: Some(U256::from(fee_wei)),
+            fee_bps: Some(fee_bps),
+        };
+        assert!(Treasury::new(&config(0, 10_000)).unwrap().is_some());
+        assert!(Treasury::new(&config(1, 0)).unwrap().is_some());
+        // NOTE: a treasury paid nothing takes no payment, nor any gas for it
+        assert!(Treasury::new(&config(0, 0)).unwrap().is_none());
+        let config = Config { address: Address::ZERO, fee_wei: None, fee_bps: None };
+        assert!(Treasury::new(&config).unwrap().is_none());
+        assert!(matches!(
+            Treasury::new(&Config { fee_bps: Some(10_001), ..config }),
+            Err(Error::InvalidTreasuryFee(10_001))
+        ));
+    }
+}
@@ -0,0 +1,266 @@
+//! `reth`'s transaction pool, configured so that transactions only sent to the builder are never
+//! announced to peers
+//!
+//! Bundles and private transactions are kept apart from the pool, so they only reach it if a block
+//! including them is reorged out of the chain, when `reth` returns the transactions of the reverted
+//! blocks to the pool. As the pool's validator decides which transactions are propagated, it is
+//! wrapped to flag any of the builder's own orderflow as builder-only.
+
+use crate::bundles::{BundlePool, PrivateTransactionPool};
+use reth::{
+    api::FullNodeTypes,
+    builder::{components::PoolBuilder, BuilderContext, NodeTypesWithEngine},
+    chainspec::ChainSpec,
+    primitives::{revm_primitives::B256, SealedBlock},
+    providers::CanonStateSubscriptions,
+    transaction_pool::{
+        blobstore::DiskFileBlobStore, maintain, CoinbaseTipOrdering, EthPooledTransaction,
+        EthTransactionValidator, Pool, PoolTransaction, TransactionOrigin,
+        TransactionValidationOutcome, TransactionValidationTaskExecutor, TransactionValidator,
+    },
+};
+use std::future::Future;
+use tracing::{debug, info, trace};
+
+/// Transactions sent only to the builder, in bundles or privately, which must never be gossiped
+#[derive(Debug, Clone)]
+pub struct BuilderOnly {
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+}
+
+impl BuilderOnly {
+    pub fn new(bundles: BundlePool, private_transactions: PrivateTransactionPool) -> Self {
+        Self { bundles, private_transactions }
+    }
+
+    /// Indicates if the transaction with `hash` was only sent to the builder
+    pub fn contains(&self, hash: &B256) -> bool {
+        self.private_transactions.contains(hash) || self.bundles.contains_transaction(hash)
+    }
+}
+
+/// Validates transactions with `V`, never propagating those only sent to the builder
+#[derive(Debug, Clone)]
+pub struct BuilderOnlyValidator<V> {
+    validator: V,
+    builder_only: BuilderOnly,
+}
+
+impl<V: TransactionValidator> BuilderOnlyValidator<V> {
+    pub fn new(validator: V, builder_only: BuilderOnly) -> Self {
+        Self { validator, builder_only }
+    }
+
+    fn withhold(
+        &self,
+        mut outcome: TransactionValidationOutcome<V::Transaction>,
+    ) -> TransactionValidationOutcome<V::Transaction> {
+        if let TransactionValidationOutcome::Valid { transaction, propagate, .. } = &mut outcome {
+            let hash = transaction.transaction().hash();
+            if *propagate && self.builder_only.contains(hash) {
+                trace!(%hash, "withholding builder-only transaction from peers");
+                *propagate = false;
+            }
+        }
+        outcome
+    }
+}
+
+impl<V: TransactionValidator> TransactionValidator for BuilderOnlyValidator<V> {
+    type Transaction = V::Transaction;
+
+    async fn validate_transaction(
+        &self,
+        origin: TransactionOrigin,
+        transaction: Self::Transaction,
+    ) -> TransactionValidationOutcome<Self::Transaction> {
+        let outcome = self.validator.validate_transaction(origin, transaction).await;
+        self.withhold(outcome)
+    }
+
+    fn validate_transactions(
+        &self,
+        transactions: Vec<(TransactionOrigin, Self::Transaction)>,
+    ) -> impl Future<Output = Vec<TransactionValidationOutcome<Self::Transaction>>> + Send {
+        let outcomes = self.validator.validate_transactions(transactions);
+        async move { outcomes.await.into_iter().map(|outcome| self.withhold(outcome)).collect() }
+    }
+
+    fn on_new_head_block(&self, new_tip_block: &SealedBlock) {
+        self.validator.on_new_head_block(new_tip_block)
+    }
+}
+
+pub type BuilderTransactionPool<Client> = Pool<
+    BuilderOnlyValidator<
+        TransactionValidationTaskExecutor<EthTransactionValidator<Client, EthPooledTransaction>>,
+    >,
+    CoinbaseTipOrdering<EthPooledTransaction>,
+    DiskFileBlobStore,
+>;
+
+/// Builds `reth`'s Ethereum transaction pool, with its validator wrapped in a
+/// [`BuilderOnlyValidator`]
+#[derive(Debug, Clone)]
+pub struct BuilderPoolBuilder {
+    builder_only: BuilderOnly,
+}
+
+impl BuilderPoolBuilder {
+    pub fn new(builder_only: BuilderOnly) -> Self {
+        Self { builder_only }
+    }
+}
+
+// NOTE: follows `EthereumPoolBuilder`, only wrapping the pool's validator
+impl<Node> PoolBuilder<Node> for BuilderPoolBuilder
+where
+    Node: FullNodeTypes<Types: NodeTypesWithEngine<ChainSpec = ChainSpec>>,
+{
+    type Pool = BuilderTransactionPool<Node::Provider>;
+
+    async fn build_pool(self, ctx: &BuilderContext<Node>) -> eyre::Result<Self::Pool> {
+        let data_dir = ctx.config().datadir();
+        let pool_config = ctx.pool_config();
+        let blob_store = DiskFileBlobStore::open(data_dir.blobstore(), Default::default())?;
+        let validator = TransactionValidationTaskExecutor::eth_builder(ctx.chain_spec())
+            .with_head_timestamp(ctx.head().timestamp)
+            .kzg_settings(ctx.kzg_settings()?)
+            .with_local_transactions_config(pool_config.local_transactions_config.clone())
+            .with_additional_tasks(ctx.config().txpool.additional_validation_tasks)
+            .build_with_tasks(
+                ctx.provider().clone(),
+                ctx.task_executor().clone(),
+                blob_store.clone(),
+            );
+        let validator = BuilderOnlyValidator::new(validator, self.builder_only);
+        let transaction_pool =
+            Pool::new(validator, CoinbaseTipOrdering::default(), blob_store, pool_config);
+        info!(target: "reth::cli", "Transaction pool initialized");
+
+        let transactions_path = data_dir.txpool_transactions();
+        let pool = transaction_pool.clone();
+        let chain_events = ctx.provider().canonical_state_stream();
+        let client = ctx.provider().clone();
+        let transactions_backup_config =
+            maintain::LocalTransactionBackupConfig::with_local_txs_backup(transactions_path);
+        ctx.task_executor().spawn_critical_with_graceful_shutdown_signal(
+            "local transactions backup task",
+            |shutdown| {
+                maintain::backup_local_transactions_task(
+                    shutdown,
+                    pool.clone(),
+                    transactions_backup_config,
+                )
+            },
+        );
+        ctx.task_executor().spawn_critical(
+            "txpool maintenance task",
+            maintain::maintain_transaction_pool_future(
+                client,
+                pool,
+                chain_events,
+                ctx.task_executor().clone(),
+                Default::default(),
+            ),
+        );
+        debug!(target: "reth::cli", "Spawned txpool maintenance task");
+
+        Ok(transaction_pool)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use alloy_eips::eip2718::Encodable2718;
+    use reth::{
+        primitives::{
+            revm_primitives::{alloy_primitives::Parity, Address, TxKind, U256},
+            Signature, Transaction, TransactionSigned,
+        },
+        transaction_pool::ValidTransaction,
+    };
+
+    // Validates every transaction, always asking for it to be propagated
+    struct Propagating;
+
+    impl TransactionValidator for Propagating {
+        type Transaction = EthPooledTransaction;
+
+        async fn validate_transaction(
+            &self,
+            _origin: TransactionOrigin,
+            transaction: Self::Transaction,
+        ) -> TransactionValidationOutcome<Self::Transaction> {
+            TransactionValidationOutcome::Valid {
+                balance: U256::MAX,
+                state_nonce: 0,
+                transaction: ValidTransaction::Valid(transaction),
+                propagate: true,
+            }
+        }
+    }
+
+    fn signed_transaction(nonce: u64) -> TransactionSigned {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            nonce,
+            to: TxKind::Call(Address::repeat_byte(2)),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        )
+    }
+
+    async fn propagates(
+        validator: &impl TransactionValidator<Transaction = EthPooledTransaction>,
+        transaction: &TransactionSigned,
+    ) -> bool {
+        let recovered = transaction.clone().into_ecrecovered().unwrap();
+        let transaction = EthPooledTransaction::new(recovered, transaction.encode_2718_len());
+        match validator.validate_transaction(TransactionOrigin::External, transaction).await {
+            TransactionValidationOutcome::Valid { propagate, .. } => propagate,
+            _ => panic!("transaction is valid"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_builder_only_transactions_are_never_propagated() {
+        let bundles = BundlePool::default();
+        let private_transactions = PrivateTransactionPool::default();
+        let validator = BuilderOnlyValidator::new(
+            Propagating,
+            BuilderOnly::new(bundles.clone(), private_transactions.clone()),
+        );
+        let (public, private, bundled) =
+            (signed_transaction(0), signed_transaction(1), signed_transaction(2));
+
+        private_transactions.insert(&private.encoded_2718().into(), None, None).unwrap();
+        let bundle = crate::Bundle::try_new(
+            &[bundled.encoded_2718().into()],
+            1,
+            None,
+            None,
+            Default::default(),
+        )
+        .unwrap();
+        bundles.insert(bundle).unwrap();
+
+        assert!(propagates(&validator, &public).await);
+        assert!(!propagates(&validator, &private).await);
+        assert!(!propagates(&validator, &bundled).await);
+
+        // NOTE: a private transaction stays builder-only once included, as a reorg may return it
+        // to the pool
+        private_transactions.on_new_block(1, [private.hash], &Default::default());
+        assert!(!propagates(&validator, &private).await);
+    }
+}
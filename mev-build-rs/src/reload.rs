@@ -0,0 +1,71 @@
+#[cfg(unix)]
+use crate::blocklist::Blocklist;
+use crate::{auctioneer::RelayConfig, bidder::Config as BidderConfig, service::Config};
+#[cfg(unix)]
+use serde::Deserialize;
+#[cfg(unix)]
+use std::path::PathBuf;
+#[cfg(unix)]
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+#[cfg(unix)]
+use tracing::{error, info, warn};
+
+/// Settings of the auctioneer that can be changed without restarting the builder
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub bidder: BidderConfig,
+    pub relays: Vec<RelayConfig>,
+}
+
+impl From<&Config> for Settings {
+    fn from(config: &Config) -> Self {
+        Self { bidder: config.bidder.clone(), relays: config.auctioneer.relays.clone() }
+    }
+}
+
+// NOTE: the configuration file holds the configuration of every `mev-rs` component, so only the
+// `builder` table is read
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct ConfigFile {
+    builder: Option<Config>,
+}
+
+/// Reload the [`Settings`] and the `blocklist` from the configuration file at `path` each time the
+/// process receives `SIGHUP`, sending the settings to the auctioneer over `settings`; any other
+/// changes to the file only take effect after a restart
+#[cfg(unix)]
+pub fn spawn(path: PathBuf, blocklist: Blocklist, settings: Sender<Settings>) -> JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                error!(%err, "could not listen for SIGHUP; configuration will not be reloaded");
+                return
+            }
+        };
+        while hangups.recv().await.is_some() {
+            info!(path = %path.display(), "reloading configuration");
+            let config = match mev_rs::config::from_toml_file::<_, ConfigFile>(&path) {
+                Ok(ConfigFile { builder: Some(config) }) => config,
+                Ok(_) => {
+                    warn!("missing builder config from file; keeping current settings");
+                    continue
+                }
+                Err(err) => {
+                    warn!(%err, "could not load configuration; keeping current settings");
+                    continue
+                }
+            };
+            if let Err(err) = blocklist.reload(config.builder.blocklist.as_deref()).await {
+                warn!(%err, "could not reload blocklist; keeping current blocklist");
+            }
+            if settings.send(Settings::from(&config)).await.is_err() {
+                warn!("auctioneer is not running; configuration will not be reloaded");
+                return
+            }
+        }
+    })
+}
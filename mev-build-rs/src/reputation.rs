@@ -0,0 +1,158 @@
+//! Reputation of orderflow providers, from how the bundles they send fare in the builder's blocks
+
+use crate::{bundles::Bundle, metrics};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+// Successful simulations a provider is credited with up front, so a new provider starts out with
+// a clean record that its first failing bundles only lower gradually
+const PRIOR_SIMULATIONS: f64 = 10.0;
+// Simulations remembered for each provider, beyond which its record is halved so that its recent
+// bundles weigh the most
+const MAX_SIMULATIONS: u64 = 10_000;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Share of a provider's bundles that must be includable when simulated against the top of a
+    /// block for the provider to send orderflow without limit
+    pub min_success_rate: f64,
+    /// Number of bundles and transactions a provider below `min_success_rate` may send between
+    /// two blocks; any more are rejected
+    pub max_orders_per_block: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Record {
+    simulated: u64,
+    succeeded: u64,
+    landed: u64,
+    // orders received since the last block
+    received: usize,
+}
+
+impl Record {
+    fn success_rate(&self) -> f64 {
+        (self.succeeded as f64 + PRIOR_SIMULATIONS) / (self.simulated as f64 + PRIOR_SIMULATIONS)
+    }
+
+    fn landed_rate(&self) -> f64 {
+        (self.landed as f64 / (self.simulated as f64 + PRIOR_SIMULATIONS)).min(1.0)
+    }
+
+    fn score(&self) -> f64 {
+        self.success_rate() + self.landed_rate()
+    }
+}
+
+/// Record of the orderflow of each provider, by its name, i.e. of each API key of the exclusive
+/// orderflow endpoint.
+///
+/// The builder tracks the share of each provider's bundles that can be included when simulated
+/// against the top of a block, and the share that lands in the builder's blocks. Bundles are
+/// simulated in order of the reputation of their provider, and providers whose bundles mostly fail
+/// can be limited in the orderflow they send, so that they can not take up the builder's
+/// simulation capacity.
+#[derive(Debug, Clone, Default)]
+pub struct Reputation(Arc<Mutex<HashMap<String, Record>>>);
+
+impl Reputation {
+    /// Record the outcome of simulating a bundle from `provider` against the top of a block
+    pub fn on_simulated(&self, provider: &str, success: bool) {
+        let mut records = self.0.lock().expect("can lock");
+        let record = records.entry(provider.to_string()).or_default();
+        record.simulated += 1;
+        record.succeeded += success as u64;
+        if record.simulated > MAX_SIMULATIONS {
+            record.simulated /= 2;
+            record.succeeded /= 2;
+            record.landed /= 2;
+        }
+    }
+
+    /// Record orderflow from `provider` landing in a block built by the builder
+    pub fn on_landed(&self, provider: &str) {
+        let mut records = self.0.lock().expect("can lock");
+        records.entry(provider.to_string()).or_default().landed += 1;
+    }
+
+    /// Indicates if an order from `provider` is accepted within the limits of `config`, counting it
+    /// towards the provider's orders for the next block if so
+    pub fn admit(&self, provider: &str, config: &Config) -> bool {
+        let mut records = self.0.lock().expect("can lock");
+        let record = records.entry(provider.to_string()).or_default();
+        if record.success_rate() < config.min_success_rate &&
+            record.received >= config.max_orders_per_block
+        {
+            return false
+        }
+        record.received += 1;
+        true
+    }
+
+    /// Order `bundles` from the most reputable provider down, where bundles without a provider are
+    /// ranked like those of a new provider
+    pub fn prioritize(&self, bundles: &mut [Bundle]) {
+        let records = self.0.lock().expect("can lock");
+        let score = |bundle: &Bundle| {
+            bundle
+                .origin
+                .as_ref()
+                .and_then(|origin| records.get(&origin.provider))
+                .copied()
+                .unwrap_or_default()
+                .score()
+        };
+        bundles.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    }
+
+    /// Start counting the orders of each provider for the next block
+    pub fn on_new_head(&self) {
+        let mut records = self.0.lock().expect("can lock");
+        for (provider, record) in records.iter_mut() {
+            record.received = 0;
+            metrics::on_orderflow_reputation(provider, record.success_rate(), record.landed_rate());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundles::Origin;
+    use reth::primitives::revm_primitives::B256;
+
+    fn bundle(id: u8, provider: Option<&str>) -> Bundle {
+        let mut bundle = Bundle::new(vec![], 1, None, None, Default::default());
+        bundle.hash = B256::repeat_byte(id);
+        bundle.origin =
+            provider.map(|provider| Origin { provider: provider.to_string(), priority: false });
+        bundle
+    }
+
+    #[test]
+    fn test_reputation() {
+        let reputation = Reputation::default();
+        let config = Config { min_success_rate: 0.5, max_orders_per_block: 2 };
+        for _ in 0..20 {
+            reputation.on_simulated("spammer", false);
+            reputation.on_simulated("searcher", true);
+        }
+        reputation.on_landed("searcher");
+
+        let mut bundles =
+            vec![bundle(1, Some("spammer")), bundle(2, None), bundle(3, Some("searcher"))];
+        reputation.prioritize(&mut bundles);
+        let hashes = bundles.iter().map(|bundle| bundle.hash).collect::<Vec<_>>();
+        assert_eq!(hashes, vec![B256::repeat_byte(3), B256::repeat_byte(2), B256::repeat_byte(1)]);
+
+        assert!((0..3).all(|_| reputation.admit("searcher", &config)));
+        assert!(reputation.admit("spammer", &config));
+        assert!(reputation.admit("spammer", &config));
+        assert!(!reputation.admit("spammer", &config));
+        reputation.on_new_head();
+        assert!(reputation.admit("spammer", &config));
+    }
+}
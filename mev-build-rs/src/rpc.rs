@@ -0,0 +1,548 @@
+//! Searcher-facing JSON-RPC methods served alongside `reth`'s own RPC modules
+
+use crate::{
+    bundles::{Bundle, BundlePool, Error as BundleError, PrivateTransactionPool},
+    hints::{HintKind, Hints},
+};
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::{
+        error::{INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+        ErrorObjectOwned,
+    },
+};
+use reth::{
+    primitives::{
+        revm_primitives::{
+            alloy_primitives::{SignatureError, U64},
+            Address, Bytes, Log, B256, U256,
+        },
+        BlockNumberOrTag, SealedHeader, Signature, TransactionSignedEcRecovered,
+    },
+    providers::{BlockReaderIdExt, ProviderError, StateProviderBox, StateProviderFactory},
+    revm::{
+        database::StateProviderDatabase,
+        primitives::{
+            BlockEnv, CfgEnvWithHandlerCfg, EnvWithHandlerCfg, ExecutionResult, ResultAndState,
+        },
+        Database, DatabaseCommit, State,
+    },
+};
+use reth_evm::{ConfigureEvm, ConfigureEvmEnv, NextBlockEnvAttributes};
+use reth_node_ethereum::EthEvmConfig;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBundleRequest {
+    pub txs: Vec<Bytes>,
+    pub block_number: U64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reverting_tx_hashes: Vec<B256>,
+}
+
+impl TryFrom<SendBundleRequest> for Bundle {
+    type Error = BundleError;
+
+    fn try_from(request: SendBundleRequest) -> Result<Self, Self::Error> {
+        let SendBundleRequest {
+            txs,
+            block_number,
+            min_timestamp,
+            max_timestamp,
+            reverting_tx_hashes,
+        } = request;
+        Bundle::try_new(
+            &txs,
+            block_number.to(),
+            min_timestamp,
+            max_timestamp,
+            reverting_tx_hashes.into_iter().collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendBundleResponse {
+    pub bundle_hash: B256,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleRequest {
+    pub txs: Vec<Bytes>,
+    pub block_number: U64,
+    /// Block whose post-state the bundle is simulated against, defaulting to the latest block
+    #[serde(default)]
+    pub state_block_number: BlockNumberOrTag,
+    /// Timestamp of the simulated block, defaulting to one slot after the state block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleTransactionResult {
+    pub tx_hash: B256,
+    pub from_address: Address,
+    pub to_address: Option<Address>,
+    pub gas_used: u64,
+    pub gas_price: U256,
+    /// Priority fees paid to the coinbase
+    pub gas_fees: U256,
+    /// Change in the balance of the coinbase
+    pub coinbase_diff: U256,
+    /// Value transferred to the coinbase directly, on top of `gas_fees`
+    pub eth_sent_to_coinbase: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert: Option<Bytes>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallBundleResponse {
+    pub bundle_hash: B256,
+    pub bundle_gas_price: U256,
+    pub coinbase_diff: U256,
+    pub eth_sent_to_coinbase: U256,
+    pub gas_fees: U256,
+    pub results: Vec<CallBundleTransactionResult>,
+    pub state_block_number: u64,
+    pub total_gas_used: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SendPrivateTransactionRequest {
+    pub tx: Bytes,
+    /// Last block the transaction is considered for, defaulting to a small number of blocks
+    /// after the current block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_block_number: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferences: Option<PrivateTransactionPreferences>,
+}
+
+/// How the sender of a private transaction allows the builder to share it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivateTransactionPreferences {
+    #[serde(default)]
+    pub privacy: PrivacyPreferences,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrivacyPreferences {
+    /// Details of the transaction shared with searchers in its hint, if the builder shares them;
+    /// if empty, the transaction is neither hinted nor backrun
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hints: Vec<HintKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelPrivateTransactionRequest {
+    pub tx_hash: B256,
+    /// Signature of `tx_hash` by the sender of the transaction, as an EIP-191 message
+    pub signature: Bytes,
+}
+
+impl CancelPrivateTransactionRequest {
+    /// Recover the account that signed the cancellation
+    pub fn signer(&self) -> Result<Address, SignatureError> {
+        Signature::try_from(self.signature.as_ref())?.recover_address_from_msg(self.tx_hash)
+    }
+}
+
+#[rpc(server, namespace = "eth")]
+pub trait BundleApi {
+    #[method(name = "sendBundle")]
+    fn send_bundle(&self, request: SendBundleRequest) -> RpcResult<SendBundleResponse>;
+
+    #[method(name = "callBundle", blocking)]
+    fn call_bundle(&self, request: CallBundleRequest) -> RpcResult<CallBundleResponse>;
+
+    #[method(name = "sendPrivateTransaction", blocking)]
+    fn send_private_transaction(&self, request: SendPrivateTransactionRequest) -> RpcResult<B256>;
+
+    #[method(name = "cancelPrivateTransaction")]
+    fn cancel_private_transaction(
+        &self,
+        request: CancelPrivateTransactionRequest,
+    ) -> RpcResult<bool>;
+}
+
+fn invalid_params(err: impl ToString) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INVALID_PARAMS_CODE, err.to_string(), None::<()>)
+}
+
+fn internal_error(err: impl ToString) -> ErrorObjectOwned {
+    ErrorObjectOwned::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<()>)
+}
+
+fn balance_of<DB: Database<Error = ProviderError>>(
+    db: &mut State<DB>,
+    address: Address,
+) -> Result<U256, ErrorObjectOwned> {
+    let account = db.basic(address).map_err(internal_error)?;
+    Ok(account.map(|account| account.balance).unwrap_or_default())
+}
+
+fn price_per_gas(value: U256, gas_used: u64) -> U256 {
+    if gas_used == 0 {
+        U256::ZERO
+    } else {
+        value / U256::from(gas_used)
+    }
+}
+
+// State and environment to execute transactions in a block on top of `parent`
+struct SimulationEnv {
+    parent: SealedHeader,
+    db: State<StateProviderDatabase<StateProviderBox>>,
+    cfg_env: CfgEnvWithHandlerCfg,
+    block_env: BlockEnv,
+}
+
+pub struct BundleRpc<Provider> {
+    bundles: BundlePool,
+    private_transactions: PrivateTransactionPool,
+    provider: Provider,
+    evm_config: EthEvmConfig,
+    fee_recipient: Address,
+    // emits a hint for each private transaction, if enabled
+    hints: Option<Hints>,
+    // used to derive the timestamp of the simulated block if `eth_callBundle` does not provide one
+    seconds_per_slot: u64,
+}
+
+impl<Provider> BundleRpc<Provider> {
+    pub fn new(
+        bundles: BundlePool,
+        private_transactions: PrivateTransactionPool,
+        provider: Provider,
+        evm_config: EthEvmConfig,
+        fee_recipient: Address,
+        hints: Option<Hints>,
+        seconds_per_slot: u64,
+    ) -> Self {
+        Self {
+            bundles,
+            private_transactions,
+            provider,
+            evm_config,
+            fee_recipient,
+            hints,
+            seconds_per_slot,
+        }
+    }
+}
+
+impl<Provider> BundleRpc<Provider>
+where
+    Provider: StateProviderFactory + BlockReaderIdExt,
+{
+    fn simulation_env(
+        &self,
+        state_block_number: BlockNumberOrTag,
+        timestamp: Option<u64>,
+    ) -> Result<SimulationEnv, ErrorObjectOwned> {
+        let parent = self
+            .provider
+            .sealed_header_by_number_or_tag(state_block_number)
+            .map_err(internal_error)?
+            .ok_or_else(|| invalid_params(format!("unknown state block {state_block_number}")))?;
+        let state_provider =
+            self.provider.state_by_block_hash(parent.hash()).map_err(internal_error)?;
+        let db = State::builder().with_database(StateProviderDatabase::new(state_provider)).build();
+
+        let next_attributes = NextBlockEnvAttributes {
+            timestamp: timestamp.unwrap_or(parent.timestamp + self.seconds_per_slot),
+            suggested_fee_recipient: self.fee_recipient,
+            prev_randao: B256::ZERO,
+        };
+        let (cfg_env, mut block_env) =
+            self.evm_config.next_cfg_and_block_env(parent.header(), next_attributes);
+        block_env.coinbase = self.fee_recipient;
+        Ok(SimulationEnv { parent, db, cfg_env, block_env })
+    }
+
+    // Execute `transaction` on top of the latest block, returning the logs it emits
+    fn simulate_logs(
+        &self,
+        transaction: &TransactionSignedEcRecovered,
+    ) -> Result<Vec<Log>, ErrorObjectOwned> {
+        let SimulationEnv { mut db, cfg_env, block_env, .. } =
+            self.simulation_env(BlockNumberOrTag::Latest, None)?;
+        let env = EnvWithHandlerCfg::new_with_cfg_env(
+            cfg_env,
+            block_env,
+            self.evm_config.tx_env(transaction),
+        );
+        let mut evm = self.evm_config.evm_with_env(&mut db, env);
+        let ResultAndState { result, .. } = evm.transact().map_err(internal_error)?;
+        Ok(result.into_logs())
+    }
+}
+
+impl<Provider> BundleApiServer for BundleRpc<Provider>
+where
+    Provider: StateProviderFactory + BlockReaderIdExt + Send + Sync + 'static,
+{
+    fn send_bundle(&self, request: SendBundleRequest) -> RpcResult<SendBundleResponse> {
+        let bundle = Bundle::try_from(request).map_err(invalid_params)?;
+        let block_number = bundle.block_number;
+        let bundle_hash = self.bundles.insert(bundle).map_err(invalid_params)?;
+        debug!(%bundle_hash, block_number, "received bundle");
+        Ok(SendBundleResponse { bundle_hash })
+    }
+
+    fn call_bundle(&self, request: CallBundleRequest) -> RpcResult<CallBundleResponse> {
+        let CallBundleRequest { txs, block_number, state_block_number, timestamp } = request;
+        let bundle = Bundle::try_new(&txs, block_number.to(), None, None, Default::default())
+            .map_err(invalid_params)?;
+
+        let SimulationEnv { parent, mut db, cfg_env, mut block_env } =
+            self.simulation_env(state_block_number, timestamp)?;
+        block_env.number = U256::from(bundle.block_number);
+        let base_fee = block_env.basefee.to::<u64>();
+
+        let mut results = Vec::with_capacity(bundle.transactions.len());
+        for tx in &bundle.transactions {
+            let coinbase_before = balance_of(&mut db, self.fee_recipient)?;
+            let env = EnvWithHandlerCfg::new_with_cfg_env(
+                cfg_env.clone(),
+                block_env.clone(),
+                self.evm_config.tx_env(tx),
+            );
+            let mut evm = self.evm_config.evm_with_env(&mut db, env);
+            let ResultAndState { result, state } = evm.transact().map_err(|err| {
+                invalid_params(format!("could not execute transaction {}: {err}", tx.hash))
+            })?;
+            drop(evm);
+            db.commit(state);
+            let coinbase_diff =
+                balance_of(&mut db, self.fee_recipient)?.saturating_sub(coinbase_before);
+
+            let gas_used = result.gas_used();
+            let priority_fee = tx.effective_tip_per_gas(Some(base_fee)).unwrap_or_default();
+            let gas_fees = U256::from(priority_fee) * U256::from(gas_used);
+            let (value, error, revert) = match result {
+                ExecutionResult::Success { output, .. } => (Some(output.into_data()), None, None),
+                ExecutionResult::Revert { output, .. } => {
+                    (None, Some("execution reverted".to_string()), Some(output))
+                }
+                ExecutionResult::Halt { reason, .. } => (None, Some(format!("{reason:?}")), None),
+            };
+            results.push(CallBundleTransactionResult {
+                tx_hash: tx.hash,
+                from_address: tx.signer(),
+                to_address: tx.to(),
+                gas_used,
+                gas_price: price_per_gas(coinbase_diff, gas_used),
+                gas_fees,
+                coinbase_diff,
+                eth_sent_to_coinbase: coinbase_diff.saturating_sub(gas_fees),
+                value,
+                error,
+                revert,
+            });
+        }
+
+        let total_gas_used = results.iter().map(|result| result.gas_used).sum();
+        let coinbase_diff = results.iter().map(|result| result.coinbase_diff).sum();
+        let gas_fees = results.iter().map(|result| result.gas_fees).sum();
+        let eth_sent_to_coinbase = results.iter().map(|result| result.eth_sent_to_coinbase).sum();
+        Ok(CallBundleResponse {
+            bundle_hash: bundle.hash,
+            bundle_gas_price: price_per_gas(coinbase_diff, total_gas_used),
+            coinbase_diff,
+            eth_sent_to_coinbase,
+            gas_fees,
+            results,
+            state_block_number: parent.number,
+            total_gas_used,
+        })
+    }
+
+    fn send_private_transaction(&self, request: SendPrivateTransactionRequest) -> RpcResult<B256> {
+        let SendPrivateTransactionRequest { tx, max_block_number, preferences } = request;
+        let max_block_number = max_block_number.map(|number| number.to());
+        let tx_hash = self
+            .private_transactions
+            .insert(&tx, max_block_number, None)
+            .map_err(invalid_params)?;
+        debug!(%tx_hash, ?max_block_number, "received private transaction");
+        let requested =
+            preferences.map(|preferences| preferences.privacy.hints).unwrap_or_default();
+        if let Some((hints, shared)) =
+            self.hints.as_ref().and_then(|hints| Some((hints, hints.shared(&requested)?)))
+        {
+            if let Some(transaction) = self.private_transactions.pending(&tx_hash) {
+                // NOTE: a transaction that can not be executed is hinted without its logs
+                let logs = if shared.contains(&HintKind::Logs) {
+                    self.simulate_logs(&transaction).unwrap_or_else(|err| {
+                        debug!(%tx_hash, ?err, "could not execute private transaction for its hint");
+                        vec![]
+                    })
+                } else {
+                    vec![]
+                };
+                self.private_transactions.allow_backruns(&tx_hash);
+                hints.emit(&transaction, &shared, logs);
+            }
+        }
+        Ok(tx_hash)
+    }
+
+    fn cancel_private_transaction(
+        &self,
+        request: CancelPrivateTransactionRequest,
+    ) -> RpcResult<bool> {
+        let tx_hash = request.tx_hash;
+        let sender = request.signer().map_err(invalid_params)?;
+        let removed = self.private_transactions.remove(&tx_hash, sender).map_err(invalid_params)?;
+        // NOTE: bundles including or backrunning the transaction are dropped along with it
+        let bundles = self.bundles.remove_including(&tx_hash);
+        debug!(%tx_hash, removed, bundles, "cancelled private transaction");
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use reth::{
+        chainspec::MAINNET,
+        primitives::{
+            revm_primitives::{alloy_primitives::Parity, TxKind},
+            Header, Transaction, TransactionSigned,
+        },
+    };
+    use reth_provider::test_utils::{ExtendedAccount, MockEthProvider};
+
+    const PARENT_TIMESTAMP: u64 = 1_720_000_000;
+
+    fn bundle_rpc(seconds_per_slot: u64) -> BundleRpc<MockEthProvider> {
+        let provider = MockEthProvider::default();
+        let parent = Header {
+            number: 1,
+            timestamp: PARENT_TIMESTAMP,
+            gas_limit: 30_000_000,
+            base_fee_per_gas: Some(7),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::ZERO),
+            ..Default::default()
+        };
+        provider.add_header(parent.hash_slow(), parent);
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        provider.add_account(signer.address(), ExtendedAccount::new(0, U256::from(10u128.pow(18))));
+        BundleRpc::new(
+            BundlePool::new(0),
+            PrivateTransactionPool::default(),
+            provider,
+            EthEvmConfig::new(MAINNET.clone()),
+            Address::repeat_byte(9),
+            None,
+            seconds_per_slot,
+        )
+    }
+
+    fn payment(to: Address, value: u64) -> TransactionSigned {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            gas_limit: 21_000,
+            max_fee_per_gas: 1_000_000_000,
+            max_priority_fee_per_gas: 2,
+            to: TxKind::Call(to),
+            value: U256::from(value),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        )
+    }
+
+    #[test]
+    fn test_simulated_block_follows_slot_duration() {
+        for seconds_per_slot in [6, 12] {
+            let rpc = bundle_rpc(seconds_per_slot);
+            let env = rpc.simulation_env(BlockNumberOrTag::Number(1), None).unwrap();
+            assert_eq!(env.parent.number, 1);
+            assert_eq!(env.block_env.timestamp, U256::from(PARENT_TIMESTAMP + seconds_per_slot));
+            assert_eq!(env.block_env.coinbase, Address::repeat_byte(9));
+        }
+        // NOTE: a timestamp given with the request is kept
+        let rpc = bundle_rpc(12);
+        let env =
+            rpc.simulation_env(BlockNumberOrTag::Number(1), Some(PARENT_TIMESTAMP + 1)).unwrap();
+        assert_eq!(env.block_env.timestamp, U256::from(PARENT_TIMESTAMP + 1));
+        assert!(rpc.simulation_env(BlockNumberOrTag::Number(2), None).is_err());
+    }
+
+    #[test]
+    fn test_call_bundle() {
+        let rpc = bundle_rpc(12);
+        let tx = payment(Address::repeat_byte(9), 1_000);
+        let request = CallBundleRequest {
+            txs: vec![tx.envelope_encoded()],
+            block_number: U64::from(2),
+            state_block_number: BlockNumberOrTag::Number(1),
+            timestamp: None,
+        };
+        let response = rpc.call_bundle(request).unwrap();
+        assert_eq!(response.state_block_number, 1);
+        assert_eq!(response.total_gas_used, 21_000);
+        assert_eq!(response.gas_fees, U256::from(2 * 21_000));
+        assert_eq!(response.eth_sent_to_coinbase, U256::from(1_000));
+        assert_eq!(response.coinbase_diff, U256::from(1_000 + 2 * 21_000));
+        assert_eq!(response.bundle_gas_price, U256::from((1_000 + 2 * 21_000) / 21_000));
+        let [result] = response.results.as_slice() else { panic!("one result per transaction") };
+        assert_eq!(result.tx_hash, tx.hash);
+        assert_eq!(result.to_address, Some(Address::repeat_byte(9)));
+        assert!(result.error.is_none());
+
+        // NOTE: a bundle the sender can not pay for is rejected
+        let request = CallBundleRequest {
+            txs: vec![payment(Address::repeat_byte(9), u64::MAX).envelope_encoded()],
+            block_number: U64::from(2),
+            state_block_number: BlockNumberOrTag::Number(1),
+            timestamp: None,
+        };
+        assert!(rpc.call_bundle(request).is_err());
+    }
+
+    #[test]
+    fn test_cancellation_signer() {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx_hash = B256::repeat_byte(2);
+        let signature = signer.sign_message_sync(tx_hash.as_slice()).unwrap();
+        let request = CancelPrivateTransactionRequest {
+            tx_hash,
+            signature: signature.as_bytes().to_vec().into(),
+        };
+        assert_eq!(request.signer().unwrap(), signer.address());
+
+        // NOTE: a signature of another transaction recovers to another account
+        let request = CancelPrivateTransactionRequest { tx_hash: B256::repeat_byte(3), ..request };
+        assert_ne!(request.signer().unwrap(), signer.address());
+        let request = CancelPrivateTransactionRequest { signature: Bytes::new(), ..request };
+        assert!(request.signer().is_err());
+    }
+}
@@ -1,10 +1,23 @@
 use crate::{
-    auctioneer::{Config as AuctioneerConfig, Service as Auctioneer},
+    auctioneer::{
+        BlockValidator, Config as AuctioneerConfig, ExecutionValidator, Service as Auctioneer,
+    },
     bidder::{Config as BidderConfig, Service as Bidder},
+    blocklist::Blocklist,
+    bundles::{BundlePool, PrivateTransactionPool},
+    exex,
+    hints::{self, Config as HintsConfig, Hints},
+    metrics,
     node::BuilderNode,
+    orderflow::{self, Config as OrderflowConfig},
     payload::{
-        attributes::BuilderPayloadBuilderAttributes, service_builder::PayloadServiceBuilder,
+        attributes::BuilderPayloadBuilderAttributes, ordering::OrderingKind,
+        service_builder::PayloadServiceBuilder, subsidy::Config as SubsidyConfig,
+        treasury::Config as TreasuryConfig,
     },
+    pool::{BuilderOnly, BuilderPoolBuilder},
+    reputation::Reputation,
+    rpc::{BundleApiServer, BundleRpc},
 };
 use ethereum_consensus::{
     clock::SystemClock,
@@ -12,20 +25,23 @@ use ethereum_consensus::{
     primitives::{Epoch, Slot},
     state_transition::Context,
 };
-use eyre::OptionExt;
-use mev_rs::{get_genesis_time, Error};
+use eyre::{eyre, OptionExt};
+use mev_rs::{get_genesis_time, get_genesis_validators_root};
 use reth::{
     api::EngineTypes,
     builder::{NodeBuilder, WithLaunchContext},
     chainspec::{ChainSpec, NamedChain},
     payload::{EthBuiltPayload, PayloadBuilderHandle},
-    primitives::revm_primitives::{Address, Bytes},
+    primitives::{
+        constants::MAXIMUM_EXTRA_DATA_SIZE,
+        revm_primitives::{Address, Bytes},
+    },
     tasks::TaskExecutor,
 };
 use reth_db::DatabaseEnv;
-use reth_node_ethereum::node::EthereumAddOns;
-use serde::Deserialize;
-use std::{path::PathBuf, sync::Arc};
+use reth_node_ethereum::{node::EthereumAddOns, EthEvmConfig};
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::{path::PathBuf, str::FromStr, sync::Arc};
 use tokio::sync::{
     broadcast::{self, Sender},
     mpsc,
@@ -35,11 +51,77 @@ use tracing::warn;
 
 pub const DEFAULT_COMPONENT_CHANNEL_SIZE: usize = 16;
 
+// Read the extra data of built blocks from either `0x`-prefixed hex or a plain string, e.g. the
+// builder's name, rejecting any that does not fit in a block header
+fn deserialize_extra_data<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Bytes>, D::Error> {
+    let Some(value) = Option::<String>::deserialize(deserializer)? else { return Ok(None) };
+    let extra_data = if value.starts_with("0x") {
+        Bytes::from_str(&value).map_err(D::Error::custom)?
+    } else {
+        Bytes::from(value.into_bytes())
+    };
+    if extra_data.len() > MAXIMUM_EXTRA_DATA_SIZE {
+        return Err(D::Error::custom(format!(
+            "extra data is {} bytes but at most {MAXIMUM_EXTRA_DATA_SIZE} bytes fit in a block",
+            extra_data.len()
+        )))
+    }
+    Ok(Some(extra_data))
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 pub struct BuilderConfig {
     pub fee_recipient: Option<Address>,
+    // Extra data of built blocks, identifying the builder, as hex or a plain string of at most 32
+    // bytes
+    // if missing, `reth`'s `--builder.extradata` is used
+    #[serde(default, deserialize_with = "deserialize_extra_data")]
     pub extra_data: Option<Bytes>,
     pub execution_mnemonic: String,
+    // Path or URL of a JSON array of addresses; transactions touching any of them are never
+    // included in built blocks
+    pub blocklist: Option<String>,
+    // Amount of gas at the top of each block reserved for bundles, which are only included within
+    // it while transactions from the mempool fill the remainder of the block
+    // if missing, bundles may use the whole block
+    pub top_of_block_gas: Option<u64>,
+    // Ordering of the bundles at the top of each block, either "greedy" by effective priority fee
+    // or "profit_maximizing" with conflict resolution
+    #[serde(default)]
+    pub ordering: OrderingKind,
+    // Further orderings to build candidate payloads with, concurrently with `ordering`, so that
+    // the most valuable candidate is bid
+    #[serde(default)]
+    pub candidate_orderings: Vec<OrderingKind>,
+    // Amount of time in milliseconds into the slot after which build jobs for the slot stop, e.g.
+    // ahead of the relays' cutoffs for submissions
+    // if missing, jobs stop after the payload builder's deadline past the start of the slot
+    pub job_cutoff_ms: Option<u64>,
+    // Minimum effective priority fee per gas, in wei, of transactions from the mempool to consider
+    // for inclusion
+    // if missing, transactions are considered regardless of their priority fee
+    pub min_priority_fee_wei: Option<u128>,
+    // Number of threads to simulate bundles and pre-execute transactions from the mempool on in
+    // parallel for each block; if missing, bundles are simulated one after another
+    pub simulation_threads: Option<usize>,
+    // Number of blocks past the block a bundle targets that it keeps being considered for while
+    // it is not included and its transactions remain valid
+    // if missing, bundles are only considered for the block they target
+    pub carry_over_blocks: Option<u64>,
+    // Treasury paid a share of the builder's profit on each block, after the proposer is paid,
+    // with a transaction at the end of the block
+    // if missing, the builder keeps all of its profit
+    pub treasury: Option<TreasuryConfig>,
+    // Caps on the subsidy spent on blocks won, i.e. what the builder pays proposers beyond the
+    // revenue of each block, over any day and any week; bids are lowered to stay within them
+    // if missing, subsidy is only limited per block by the bidder's `max_loss_wei`
+    pub subsidy_limits: Option<SubsidyConfig>,
+    // Keep the bundle pools in line with the chain through an execution extension, dropping
+    // bundles as soon as their transactions land and restoring private transactions on reorgs
+    #[serde(default)]
+    pub exex: bool,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -50,6 +132,15 @@ pub struct Config {
 
     // Used to get genesis time, if one can't be found without a network call
     pub beacon_node_url: Option<String>,
+    // Additional beacon nodes to get genesis time from, tried in order if `beacon_node_url` can
+    // not be reached
+    #[serde(default)]
+    pub fallback_beacon_node_urls: Vec<String>,
+    // Authenticated endpoint for orderflow providers to send bundles and transactions exclusively
+    // to the builder, served if provided
+    pub orderflow: Option<OrderflowConfig>,
+    // Stream of hints about private transactions for searchers to backrun them, served if provided
+    pub hints: Option<HintsConfig>,
 }
 
 pub struct Services<
@@ -69,15 +160,30 @@ pub async fn construct_services<
             BuiltPayload = EthBuiltPayload,
         > + 'static,
 >(
-    network: Network,
-    config: Config,
+    context: Arc<Context>,
+    mut config: Config,
     task_executor: TaskExecutor,
     payload_builder: PayloadBuilderHandle<Engine>,
     bid_rx: mpsc::Receiver<EthBuiltPayload>,
-) -> Result<Services<Engine>, Error> {
-    let context = Arc::new(Context::try_from(network)?);
-
-    let genesis_time = get_genesis_time(&context, config.beacon_node_url.as_ref(), None).await;
+    block_validator: Arc<dyn BlockValidator>,
+) -> eyre::Result<Services<Engine>> {
+    let beacon_node_urls = config
+        .beacon_node_url
+        .iter()
+        .chain(&config.fallback_beacon_node_urls)
+        .cloned()
+        .collect::<Vec<_>>();
+    let genesis_time = get_genesis_time(&context, &beacon_node_urls, None).await;
+    if let Some(local_proposer) = config.auctioneer.local_proposer.as_mut() {
+        if local_proposer.genesis_validators_root.is_none() {
+            // NOTE: the signatures of local validators on their blocks can not be verified
+            // without it, and no payload is revealed to them unverified
+            let genesis_validators_root = get_genesis_validators_root(&beacon_node_urls)
+                .await
+                .ok_or_eyre("could not get genesis validators root for local proposer; set it in the configuration or provide a beacon node")?;
+            local_proposer.genesis_validators_root = Some(genesis_validators_root);
+        }
+    }
 
     let clock = context.clock_at(genesis_time);
 
@@ -92,7 +198,8 @@ pub async fn construct_services<
         config.auctioneer,
         context,
         genesis_time,
-    );
+        block_validator,
+    )?;
 
     Ok(Services { auctioneer, clock, clock_tx })
 }
@@ -107,18 +214,9 @@ pub async fn launch(
     node_builder: WithLaunchContext<NodeBuilder<Arc<DatabaseEnv>, ChainSpec>>,
     custom_chain_config_directory: Option<PathBuf>,
     config: Config,
+    config_file: Option<PathBuf>,
 ) -> eyre::Result<()> {
-    let (bid_tx, bid_rx) = mpsc::channel(DEFAULT_COMPONENT_CHANNEL_SIZE);
-    let payload_builder = PayloadServiceBuilder::try_from((&config.builder, bid_tx))?;
-
-    let handle = node_builder
-        .with_types::<BuilderNode>()
-        .with_components(BuilderNode::components_with(payload_builder))
-        .with_add_ons(EthereumAddOns::default())
-        .launch()
-        .await?;
-
-    let chain = handle.node.config.chain.chain;
+    let chain = node_builder.config().chain.chain;
     let network = if let Some(chain) = chain.named() {
         match chain {
             NamedChain::Mainnet => Network::Mainnet,
@@ -135,11 +233,111 @@ pub async fn launch(
             .ok_or_eyre("missing custom chain configuration when expected")?;
         custom_network_from_config_directory(path)
     };
+    let context = Arc::new(Context::try_from(network)?);
+    // NOTE: bids for a chain other than the one `reth` builds on would never be valid
+    if context.deposit_chain_id as u64 != chain.id() {
+        return Err(eyre!(
+            "consensus configuration is for chain {} but `reth` is configured for chain {}",
+            context.deposit_chain_id,
+            chain.id()
+        ));
+    }
+
+    let (bid_tx, bid_rx) = mpsc::channel(DEFAULT_COMPONENT_CHANNEL_SIZE);
+    let bundles = BundlePool::new(config.builder.carry_over_blocks.unwrap_or_default());
+    let private_transactions = PrivateTransactionPool::default();
+    let reputation = Reputation::default();
+    let blocklist = match config.builder.blocklist.as_ref() {
+        Some(source) => Blocklist::load(source).await?,
+        None => Blocklist::default(),
+    };
+    // NOTE: a builder only bidding to local validators never serves a bid below their minimum, so
+    // blocks worth less are not worth sealing
+    let min_block_value = match config.auctioneer.local_proposer.as_ref() {
+        Some(local_proposer) if config.auctioneer.relays.is_empty() => {
+            Some(local_proposer.min_bid_wei)
+        }
+        _ => None,
+    };
+    let payload_builder = PayloadServiceBuilder::try_from((
+        &config.builder,
+        min_block_value,
+        bid_tx,
+        bundles.clone(),
+        private_transactions.clone(),
+        blocklist.clone(),
+        reputation.clone(),
+    ))?;
+    let fee_recipient = payload_builder.fee_recipient();
+    let pool_builder =
+        BuilderPoolBuilder::new(BuilderOnly::new(bundles.clone(), private_transactions.clone()));
+
+    if let Some(orderflow_config) = config.orderflow.clone() {
+        orderflow::spawn(
+            orderflow_config,
+            bundles.clone(),
+            private_transactions.clone(),
+            reputation,
+        );
+    }
+    let hints = config.hints.as_ref().map(|hints_config| {
+        let hints = Hints::new(&hints_config.share);
+        hints::spawn(hints_config, hints.clone(), bundles.clone(), private_transactions.clone());
+        hints
+    });
+    let seconds_per_slot = context.seconds_per_slot;
+
+    let handle = node_builder
+        .with_types::<BuilderNode>()
+        .with_components(BuilderNode::components_with(pool_builder, payload_builder))
+        .with_add_ons(EthereumAddOns::default())
+        .install_exex_if(config.builder.exex, exex::EXEX_ID, {
+            let bundles = bundles.clone();
+            let private_transactions = private_transactions.clone();
+            move |ctx| async move { Ok(exex::run(ctx, bundles, private_transactions)) }
+        })
+        .extend_rpc_modules(move |ctx| {
+            let evm_config = EthEvmConfig::new(ctx.config().chain.clone());
+            let rpc = BundleRpc::new(
+                bundles,
+                private_transactions,
+                ctx.provider().clone(),
+                evm_config,
+                fee_recipient,
+                hints,
+                seconds_per_slot,
+            );
+            ctx.modules.merge_configured(rpc.into_rpc())?;
+            Ok(())
+        })
+        .launch()
+        .await?;
+
+    metrics::install();
 
     let task_executor = handle.node.task_executor.clone();
     let payload_builder = handle.node.payload_builder.clone();
-    let Services { auctioneer, clock, clock_tx } =
-        construct_services(network, config, task_executor, payload_builder, bid_rx).await?;
+    let block_validator = Arc::new(ExecutionValidator::new(
+        handle.node.provider.clone(),
+        handle.node.block_executor.clone(),
+        fee_recipient,
+    ));
+    let Services { auctioneer, clock, clock_tx } = construct_services(
+        context,
+        config,
+        task_executor,
+        payload_builder,
+        bid_rx,
+        block_validator,
+    )
+    .await?;
+
+    if let Some(path) = config_file {
+        #[cfg(unix)]
+        crate::reload::spawn(path, blocklist, auctioneer.settings_handle());
+        #[cfg(not(unix))]
+        warn!(path = %path.display(), "not reloading configuration as `SIGHUP` is only supported on unix");
+    }
 
     handle.node.task_executor.spawn_critical_blocking("mev-builder/auctioneer", auctioneer.spawn());
     handle.node.task_executor.spawn_critical("mev-builder/clock", async move {
@@ -184,3 +382,34 @@ pub enum ClockMessage {
     NewSlot(Slot),
     NewEpoch(Epoch),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Deserialize)]
+    struct ExtraData {
+        #[serde(default, deserialize_with = "deserialize_extra_data")]
+        extra_data: Option<Bytes>,
+    }
+
+    fn parse(value: &str) -> Result<Option<Bytes>, serde_json::Error> {
+        serde_json::from_str::<ExtraData>(value).map(|config| config.extra_data)
+    }
+
+    #[test]
+    fn test_extra_data() {
+        assert_eq!(parse("{}").unwrap(), None);
+        assert_eq!(
+            parse(r#"{"extra_data": "0x68656c6c6f"}"#).unwrap(),
+            Some(Bytes::from_static(b"hello"))
+        );
+        assert_eq!(
+            parse(r#"{"extra_data": "hello"}"#).unwrap(),
+            Some(Bytes::from_static(b"hello"))
+        );
+        assert!(parse(r#"{"extra_data": "0xzz"}"#).is_err());
+        let too_long = "a".repeat(MAXIMUM_EXTRA_DATA_SIZE + 1);
+        assert!(parse(&format!(r#"{{"extra_data": "{too_long}"}}"#)).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Bundles of ERC-4337 user operations from external bundlers, each a transaction calling
+//! `handleOps` on an accepted `EntryPoint`
+
+use crate::bundles::Bundle;
+use reth::primitives::{
+    revm_primitives::{address, keccak256, Address, B256, U256},
+    TransactionSignedEcRecovered,
+};
+use thiserror::Error;
+
+/// `EntryPoint` v0.6, at the same address on every chain
+pub const ENTRY_POINT_V06: Address = address!("5FF137D4b0FDCD49DcA30c7CF57E578a026d2789");
+/// `EntryPoint` v0.7, at the same address on every chain
+pub const ENTRY_POINT_V07: Address = address!("0000000071727De22E5E9d8BAf0edAc6f37da032");
+
+// Signatures of `handleOps` for each version of `EntryPoint`, taking the user operations and the
+// beneficiary of the gas refunds
+const HANDLE_OPS_SIGNATURES: [&str; 2] = [
+    "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)",
+    "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)",
+];
+
+pub fn default_entry_points() -> Vec<Address> {
+    vec![ENTRY_POINT_V06, ENTRY_POINT_V07]
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("transaction {0} does not call an accepted entry point")]
+    UnknownEntryPoint(B256),
+    #[error("transaction {0} does not call `handleOps` with well-formed user operations")]
+    NotHandleOps(B256),
+}
+
+/// Return the number of user operations `transaction` hands to one of the `entry_points`
+pub fn count_user_operations(
+    transaction: &TransactionSignedEcRecovered,
+    entry_points: &[Address],
+) -> Result<usize, Error> {
+    let hash = transaction.hash;
+    if !transaction.to().is_some_and(|to| entry_points.contains(&to)) {
+        return Err(Error::UnknownEntryPoint(hash))
+    }
+    let input = transaction.input();
+    let is_handle_ops = input.len() >= 4 &&
+        HANDLE_OPS_SIGNATURES
+            .iter()
+            .any(|signature| keccak256(signature.as_bytes())[..4] == input[..4]);
+    if !is_handle_ops {
+        return Err(Error::NotHandleOps(hash))
+    }
+    // NOTE: the first argument is the offset of the array of user operations, which starts with
+    // its length
+    let arguments = &input[4..];
+    let word = |offset: usize| {
+        arguments
+            .get(offset..offset.checked_add(32)?)
+            .map(U256::from_be_slice)
+            .and_then(|word| usize::try_from(word).ok())
+    };
+    let count =
+        word(0).and_then(word).filter(|&count| count > 0).ok_or(Error::NotHandleOps(hash))?;
+    Ok(count)
+}
+
+/// Return the number of user operations in `bundle`, if every transaction in it hands user
+/// operations to one of the `entry_points`
+pub fn check_bundle(bundle: &Bundle, entry_points: &[Address]) -> Result<usize, Error> {
+    bundle
+        .transactions
+        .iter()
+        .map(|transaction| count_user_operations(transaction, entry_points))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+    use alloy_consensus::TxEip1559;
+    use reth::primitives::{
+        revm_primitives::{alloy_primitives::Parity, Bytes, TxKind},
+        Signature, Transaction, TransactionSigned,
+    };
+
+    fn transaction_to(to: Address, input: Vec<u8>) -> TransactionSignedEcRecovered {
+        let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let tx = Transaction::Eip1559(TxEip1559 {
+            chain_id: 1,
+            to: TxKind::Call(to),
+            input: Bytes::from(input),
+            ..Default::default()
+        });
+        let signature = signer.sign_hash_sync(&tx.signature_hash()).unwrap();
+        let signed = TransactionSigned::from_transaction_and_signature(
+            tx,
+            Signature::new(signature.r(), signature.s(), Parity::Parity(signature.v().y_parity())),
+        );
+        TransactionSignedEcRecovered::from_signed_transaction(signed, signer.address())
+    }
+
+    fn handle_ops_input(signature: &str, count: u64) -> Vec<u8> {
+        let mut input = keccak256(signature.as_bytes())[..4].to_vec();
+        input.extend(U256::from(64).to_be_bytes::<32>());
+        input.extend([0u8; 32]);
+        input.extend(U256::from(count).to_be_bytes::<32>());
+        input
+    }
+
+    #[test]
+    fn test_count_user_operations() {
+        let entry_points = default_entry_points();
+        for signature in HANDLE_OPS_SIGNATURES {
+            let tx = transaction_to(ENTRY_POINT_V07, handle_ops_input(signature, 3));
+            assert_eq!(count_user_operations(&tx, &entry_points).unwrap(), 3);
+        }
+
+        let input = handle_ops_input(HANDLE_OPS_SIGNATURES[0], 3);
+        let tx = transaction_to(Address::repeat_byte(1), input.clone());
+        assert!(matches!(
+            count_user_operations(&tx, &entry_points),
+            Err(Error::UnknownEntryPoint(..))
+        ));
+
+        let tx = transaction_to(ENTRY_POINT_V06, input[..40].to_vec());
+        assert!(matches!(count_user_operations(&tx, &entry_points), Err(Error::NotHandleOps(..))));
+
+        let tx = transaction_to(ENTRY_POINT_V06, vec![0xde, 0xad, 0xbe, 0xef]);
+        assert!(matches!(count_user_operations(&tx, &entry_points), Err(Error::NotHandleOps(..))));
+    }
+}
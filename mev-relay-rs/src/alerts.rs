@@ -0,0 +1,157 @@
+use mev_rs::types::BidTrace;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use tracing::{debug, warn};
+use url::Url;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// URL to `POST` an alert to for each payload that fails to be delivered
+    pub webhook_url: String,
+    /// Format each alert as a Slack message, for use with a Slack incoming webhook
+    #[serde(default)]
+    pub slack: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryFailure {
+    /// The builder did not upload the payload for a header-only bid
+    PayloadNotUploaded,
+    /// The beacon node rejected the unblinded block
+    BlockRejected,
+    /// The beacon node rejected the unblinded block although its payload passed simulation, so
+    /// the part of the block signed by the proposer is at fault, e.g. as the proposer equivocated
+    ProposerBlockRejected,
+    /// The block was published but is not on chain at its slot
+    BlockMissing,
+}
+
+impl DeliveryFailure {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PayloadNotUploaded => "payload_not_uploaded",
+            Self::BlockRejected => "block_rejected",
+            Self::ProposerBlockRejected => "proposer_block_rejected",
+            Self::BlockMissing => "block_missing",
+        }
+    }
+}
+
+// NOTE: serialized as its name in metrics, so alerts and metrics label failures the same way
+impl Serialize for DeliveryFailure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryAlert {
+    pub failure: DeliveryFailure,
+    #[serde(flatten)]
+    pub bid_trace: BidTrace,
+}
+
+impl fmt::Display for DeliveryAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bid_trace = &self.bid_trace;
+        write!(
+            f,
+            "could not deliver payload in slot {} ({}): block {} from builder {} for proposer {}",
+            bid_trace.slot,
+            self.failure.as_str(),
+            bid_trace.block_hash,
+            bid_trace.builder_public_key,
+            bid_trace.proposer_public_key,
+        )
+    }
+}
+
+#[derive(Serialize)]
+struct SlackMessage {
+    text: String,
+}
+
+/// Sends an alert to a webhook for each payload the relay fails to deliver
+#[derive(Clone)]
+pub struct Alerter {
+    client: reqwest::Client,
+    webhook_url: Url,
+    slack: bool,
+}
+
+impl Alerter {
+    pub fn new(config: Config) -> Result<Self, url::ParseError> {
+        let webhook_url = config.webhook_url.parse()?;
+        Ok(Self { client: reqwest::Client::new(), webhook_url, slack: config.slack })
+    }
+
+    async fn post(&self, alert: &DeliveryAlert) -> Result<(), reqwest::Error> {
+        let request = self.client.post(self.webhook_url.clone());
+        let request = if self.slack {
+            request.json(&SlackMessage { text: alert.to_string() })
+        } else {
+            request.json(alert)
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    /// Send `alert` on a separate task
+    pub fn alert(&self, alert: DeliveryAlert) {
+        let alerter = self.clone();
+        tokio::spawn(async move {
+            let slot = alert.bid_trace.slot;
+            match alerter.post(&alert).await {
+                Ok(()) => debug!(slot, failure = alert.failure.as_str(), "sent delivery alert"),
+                Err(err) => warn!(%err, slot, "could not send delivery alert"),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use std::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    fn alert() -> DeliveryAlert {
+        let bid_trace = BidTrace { slot: 7, ..Default::default() };
+        DeliveryAlert { failure: DeliveryFailure::BlockMissing, bid_trace }
+    }
+
+    #[test]
+    fn test_alert_encoding() {
+        let encoded = serde_json::to_value(alert()).unwrap();
+        assert_eq!(encoded["failure"], "block_missing");
+        assert_eq!(encoded["slot"], "7");
+        assert!(alert()
+            .to_string()
+            .starts_with("could not deliver payload in slot 7 (block_missing)"));
+    }
+
+    #[tokio::test]
+    async fn test_alert_webhook() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (alerts_tx, mut alerts) = mpsc::unbounded_channel();
+        let handler = |State(alerts_tx): State<mpsc::UnboundedSender<serde_json::Value>>,
+                       Json(alert): Json<serde_json::Value>| async move {
+            alerts_tx.send(alert).unwrap();
+        };
+        let router = Router::new().route("/alerts", post(handler)).with_state(alerts_tx);
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+
+        let webhook_url = format!("http://{addr}/alerts");
+        let alerter =
+            Alerter::new(Config { webhook_url: webhook_url.clone(), slack: false }).unwrap();
+        alerter.alert(alert());
+        assert_eq!(alerts.recv().await.unwrap()["failure"], "block_missing");
+
+        let alerter = Alerter::new(Config { webhook_url, slack: true }).unwrap();
+        alerter.alert(alert());
+        assert_eq!(alerts.recv().await.unwrap()["text"], alert().to_string());
+    }
+}
@@ -0,0 +1,129 @@
+use ethereum_consensus::primitives::{BlsPublicKey, Hash32, Slot, U256};
+use mev_rs::types::block_submission::data_api::{
+    AuctionAnalytics, BuilderAnalytics, PayloadTrace, SubmissionTrace,
+};
+use std::collections::{HashMap, HashSet};
+
+// Identifies an auction by its slot, parent and proposer
+type AuctionKey<'a> = (Slot, &'a Hash32, &'a BlsPublicKey);
+
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Summarize the auctions given by their `submissions` and `delivered_payloads`, where a bid was
+/// served to the proposer in `auctions_served` of them
+pub fn summarize(
+    submissions: &[SubmissionTrace],
+    delivered_payloads: &[PayloadTrace],
+    auctions_served: usize,
+) -> AuctionAnalytics {
+    let mut first_submissions = HashMap::<AuctionKey, &SubmissionTrace>::new();
+    let mut participants = HashMap::<&BlsPublicKey, HashSet<AuctionKey>>::new();
+    for trace in submissions {
+        let key = (trace.slot, &trace.parent_hash, &trace.proposer_public_key);
+        first_submissions
+            .entry(key)
+            .and_modify(|first| {
+                if trace.timestamp_ms < first.timestamp_ms {
+                    *first = trace;
+                }
+            })
+            .or_insert(trace);
+        participants.entry(&trace.builder_public_key).or_default().insert(key);
+    }
+
+    let mut wins = HashMap::<&BlsPublicKey, usize>::new();
+    let mut total_uplift = U256::ZERO;
+    let mut uplift_count = 0u64;
+    for trace in delivered_payloads {
+        *wins.entry(&trace.builder_public_key).or_default() += 1;
+        let key = (trace.slot, &trace.parent_hash, &trace.proposer_public_key);
+        if let Some(first) = first_submissions.get(&key) {
+            total_uplift = total_uplift.saturating_add(trace.value.saturating_sub(first.value));
+            uplift_count += 1;
+        }
+    }
+    let average_value_uplift =
+        if uplift_count == 0 { U256::ZERO } else { total_uplift / U256::from(uplift_count) };
+
+    let mut builders = participants
+        .into_iter()
+        .map(|(builder_public_key, auctions)| {
+            let wins = wins.get(builder_public_key).copied().unwrap_or_default();
+            BuilderAnalytics {
+                builder_public_key: builder_public_key.clone(),
+                auctions: auctions.len(),
+                wins,
+                win_rate: ratio(wins, auctions.len()),
+            }
+        })
+        .collect::<Vec<_>>();
+    builders.sort_by(|a, b| b.wins.cmp(&a.wins).then(b.auctions.cmp(&a.auctions)));
+
+    AuctionAnalytics {
+        auctions: first_submissions.len(),
+        auctions_served,
+        payloads_delivered: delivered_payloads.len(),
+        delivery_success_rate: ratio(delivered_payloads.len(), auctions_served),
+        average_value_uplift,
+        builders,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::crypto::SecretKey;
+
+    #[test]
+    fn test_summarize_auctions() {
+        let builder_a = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let builder_b = SecretKey::key_gen(&[2u8; 32]).unwrap().public_key();
+        let submission =
+            |slot, builder_public_key: &BlsPublicKey, value, timestamp_ms| SubmissionTrace {
+                slot,
+                builder_public_key: builder_public_key.clone(),
+                value: U256::from(value),
+                timestamp_ms,
+                ..Default::default()
+            };
+        let submissions = [
+            submission(1, &builder_a, 10, 100),
+            submission(1, &builder_b, 30, 200),
+            submission(2, &builder_a, 20, 100),
+        ];
+        let delivered_payloads = [PayloadTrace {
+            slot: 1,
+            builder_public_key: builder_b.clone(),
+            value: U256::from(30),
+            ..Default::default()
+        }];
+
+        let analytics = summarize(&submissions, &delivered_payloads, 2);
+        assert_eq!(analytics.auctions, 2);
+        assert_eq!(analytics.delivery_success_rate, 0.5);
+        assert_eq!(analytics.average_value_uplift, U256::from(20));
+        assert_eq!(
+            analytics.builders,
+            vec![
+                BuilderAnalytics {
+                    builder_public_key: builder_b,
+                    auctions: 1,
+                    wins: 1,
+                    win_rate: 1.0,
+                },
+                BuilderAnalytics {
+                    builder_public_key: builder_a,
+                    auctions: 2,
+                    wins: 0,
+                    win_rate: 0.0,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use ethereum_consensus::primitives::BlsPublicKey;
+use mev_rs::RelayError;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// An API key issued to a builder, to accompany each of its submissions
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    pub key: String,
+    /// Public key of the builder allowed to submit with this key
+    #[serde(rename = "builder_pubkey")]
+    pub builder_public_key: BlsPublicKey,
+    /// Maximum number of submissions accepted with this key in each slot, if any
+    pub submissions_per_slot: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Reject submissions that do not carry a valid API key; otherwise only submissions that do
+    /// carry a key are checked
+    pub required: bool,
+    /// Keys currently issued to builders; revoke a key by removing it
+    pub keys: Vec<ApiKey>,
+}
+
+/// Checks the API keys accompanying builder submissions and counts the submissions made with each
+/// key in the current slot
+#[derive(Debug, Default)]
+pub struct ApiKeys {
+    required: bool,
+    keys: HashMap<String, ApiKey>,
+    counts: Mutex<HashMap<String, usize>>,
+}
+
+impl ApiKeys {
+    pub fn new(config: Config) -> Self {
+        let keys = config.keys.into_iter().map(|api_key| (api_key.key.clone(), api_key)).collect();
+        Self { required: config.required, keys, counts: Default::default() }
+    }
+
+    /// Count a submission from `builder_public_key` made with `api_key`, returning an error if the
+    /// key is missing when required, is not issued to the builder or is over its limit
+    pub fn authenticate(
+        &self,
+        builder_public_key: &BlsPublicKey,
+        api_key: Option<&str>,
+    ) -> Result<(), RelayError> {
+        let Some(api_key) = api_key else {
+            if self.required {
+                return Err(RelayError::InvalidApiKey(builder_public_key.clone()))
+            }
+            return Ok(())
+        };
+        let issued_key = self
+            .keys
+            .get(api_key)
+            .filter(|issued_key| &issued_key.builder_public_key == builder_public_key)
+            .ok_or_else(|| RelayError::InvalidApiKey(builder_public_key.clone()))?;
+        let mut counts = self.counts.lock();
+        let count = counts.entry(issued_key.key.clone()).or_default();
+        if let Some(limit) = issued_key.submissions_per_slot {
+            if *count >= limit {
+                return Err(RelayError::ApiKeyRateLimited(limit))
+            }
+        }
+        *count += 1;
+        Ok(())
+    }
+
+    /// Reset all counts at the start of a new slot
+    pub fn on_slot(&self) {
+        self.counts.lock().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::crypto::SecretKey;
+
+    #[test]
+    fn test_api_keys() {
+        let builder = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let other_builder = SecretKey::key_gen(&[2u8; 32]).unwrap().public_key();
+        let api_keys = ApiKeys::new(Config {
+            required: true,
+            keys: vec![ApiKey {
+                key: "secret".to_string(),
+                builder_public_key: builder.clone(),
+                submissions_per_slot: Some(1),
+            }],
+        });
+
+        assert!(api_keys.authenticate(&builder, None).is_err());
+        assert!(api_keys.authenticate(&builder, Some("wrong")).is_err());
+        // keys are bound to the builder they were issued to
+        assert!(api_keys.authenticate(&other_builder, Some("secret")).is_err());
+        assert!(api_keys.authenticate(&builder, Some("secret")).is_ok());
+        assert!(api_keys.authenticate(&builder, Some("secret")).is_err());
+
+        api_keys.on_slot();
+        assert!(api_keys.authenticate(&builder, Some("secret")).is_ok());
+    }
+}
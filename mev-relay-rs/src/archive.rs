@@ -0,0 +1,114 @@
+use crate::auction_context::AuctionContext;
+use ethereum_consensus::primitives::Slot;
+use mev_rs::types::{BidTrace, BlobsBundle, ExecutionPayload};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, warn};
+use url::Url;
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Serde(#[from] serde_json::Error),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct Config {
+    /// Base URL of object storage that accepts `PUT` requests for objects under it, e.g. GCS with
+    /// an OAuth token as `auth_token`, or a gateway in front of S3; requests are not signed with
+    /// AWS Signature Version 4, so S3 itself can not be written to directly
+    pub bucket_url: String,
+    /// Optional prefix prepended to every object key
+    #[serde(default)]
+    pub key_prefix: String,
+    /// Number of slots grouped under a single key partition, e.g. to align with retention rules
+    /// configured on the bucket; defaults to one partition per epoch
+    pub slots_per_partition: Option<Slot>,
+    /// Optional bearer token sent in the `Authorization` header of each upload
+    pub auth_token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct ArchivedPayload<'a> {
+    bid_trace: &'a BidTrace,
+    execution_payload: &'a ExecutionPayload,
+    blobs_bundle: Option<&'a BlobsBundle>,
+}
+
+/// Writes the full contents of delivered payloads to object storage so they can be retrieved
+/// long after they have been pruned from the relay's in-memory state
+#[derive(Clone)]
+pub struct Archiver {
+    client: reqwest::Client,
+    bucket_url: Url,
+    key_prefix: String,
+    slots_per_partition: Slot,
+    auth_token: Option<String>,
+}
+
+impl Archiver {
+    pub fn new(config: Config, slots_per_epoch: Slot) -> Result<Self, url::ParseError> {
+        let mut bucket_url: Url = config.bucket_url.parse()?;
+        // ensure object keys are resolved relative to the full bucket path
+        if !bucket_url.path().ends_with('/') {
+            let path = format!("{}/", bucket_url.path());
+            bucket_url.set_path(&path);
+        }
+        let key_prefix = config.key_prefix.trim_matches('/').to_string();
+        let slots_per_partition = config.slots_per_partition.unwrap_or(slots_per_epoch).max(1);
+        Ok(Self {
+            client: reqwest::Client::new(),
+            bucket_url,
+            key_prefix,
+            slots_per_partition,
+            auth_token: config.auth_token,
+        })
+    }
+
+    fn object_key(&self, bid_trace: &BidTrace) -> String {
+        let partition = bid_trace.slot / self.slots_per_partition;
+        let key =
+            format!("partition={partition}/slot={}/{}.json", bid_trace.slot, bid_trace.block_hash);
+        if self.key_prefix.is_empty() {
+            key
+        } else {
+            format!("{}/{key}", self.key_prefix)
+        }
+    }
+
+    async fn upload(&self, auction_context: &AuctionContext) -> Result<String, Error> {
+        let bid_trace = auction_context.bid_trace();
+        let archived_payload = ArchivedPayload {
+            bid_trace,
+            execution_payload: auction_context.execution_payload(),
+            blobs_bundle: auction_context.blobs_bundle(),
+        };
+        let body = serde_json::to_vec(&archived_payload)?;
+        let key = self.object_key(bid_trace);
+        let target = self.bucket_url.join(&key)?;
+        let mut request =
+            self.client.put(target).header("content-type", "application/json").body(body);
+        if let Some(token) = self.auth_token.as_ref() {
+            request = request.bearer_auth(token);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(key)
+    }
+
+    /// Archive the payload in `auction_context` on a separate task
+    pub fn archive(&self, auction_context: Arc<AuctionContext>) {
+        let archiver = self.clone();
+        tokio::spawn(async move {
+            let slot = auction_context.bid_trace().slot;
+            match archiver.upload(&auction_context).await {
+                Ok(key) => debug!(slot, key, "archived delivered payload"),
+                Err(err) => warn!(%err, slot, "could not archive delivered payload"),
+            }
+        });
+    }
+}
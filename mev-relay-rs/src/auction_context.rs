@@ -1,22 +1,34 @@
+use crate::bid_adjustment::Config as BidAdjustment;
 use ethereum_consensus::{
+    crypto::KzgCommitment,
     primitives::{BlsPublicKey, U256},
     ssz::prelude::*,
     state_transition::Context,
 };
 use mev_rs::{
-    signing::{sign_builder_message, SecretKey},
+    signer::Signer,
     types::{
         auction_contents, builder_bid, AuctionContents, BidTrace, BlobsBundle, BuilderBid,
         ExecutionPayload, ExecutionPayloadHeader, SignedBidSubmission, SignedBuilderBid,
+        SignedHeaderSubmission,
     },
     Error,
 };
 use std::{
     hash::{Hash, Hasher},
+    sync::Arc,
     time::Duration,
 };
+use tokio::sync::watch;
 
-fn to_header(execution_payload: &ExecutionPayload) -> Result<ExecutionPayloadHeader, Error> {
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::deneb::mainnet::MAX_BLOB_COMMITMENTS_PER_BLOCK;
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::deneb::minimal::MAX_BLOB_COMMITMENTS_PER_BLOCK;
+
+pub(crate) fn to_header(
+    execution_payload: &ExecutionPayload,
+) -> Result<ExecutionPayloadHeader, Error> {
     let header = match execution_payload {
         ExecutionPayload::Bellatrix(payload) => {
             ExecutionPayloadHeader::Bellatrix(payload.try_into()?)
@@ -27,6 +39,34 @@ fn to_header(execution_payload: &ExecutionPayload) -> Result<ExecutionPayloadHea
     Ok(header)
 }
 
+pub(crate) async fn sign_builder_bid(
+    header: ExecutionPayloadHeader,
+    blob_kzg_commitments: Option<List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>>,
+    value: U256,
+    signer: &Signer,
+    context: &Context,
+) -> Result<SignedBuilderBid, Error> {
+    let public_key = signer.public_key().clone();
+    let bid = match header {
+        header @ ExecutionPayloadHeader::Bellatrix(_) => {
+            BuilderBid::Bellatrix(builder_bid::bellatrix::BuilderBid { header, value, public_key })
+        }
+        header @ ExecutionPayloadHeader::Capella(_) => {
+            BuilderBid::Capella(builder_bid::capella::BuilderBid { header, value, public_key })
+        }
+        header @ ExecutionPayloadHeader::Deneb(_) => {
+            BuilderBid::Deneb(builder_bid::deneb::BuilderBid {
+                header,
+                blob_kzg_commitments: blob_kzg_commitments.unwrap_or_default(),
+                value,
+                public_key,
+            })
+        }
+    };
+    let signature = signer.sign_builder_message(&bid, context).await?;
+    Ok(SignedBuilderBid { message: bid, signature })
+}
+
 pub mod bellatrix {
     use super::*;
 
@@ -97,11 +137,11 @@ pub enum AuctionContext {
 }
 
 impl AuctionContext {
-    pub fn new(
+    pub async fn new(
         signed_submission: SignedBidSubmission,
         receive_duration: Duration,
-        relay_public_key: BlsPublicKey,
-        relay_secret_key: &SecretKey,
+        bid_adjustment: &BidAdjustment,
+        signer: &Signer,
         context: &Context,
     ) -> Result<Self, Error> {
         let builder_public_key = signed_submission.message().builder_public_key.clone();
@@ -111,33 +151,16 @@ impl AuctionContext {
 
         let value = signed_submission.message().value;
 
-        let bid = match signed_submission {
-            SignedBidSubmission::Bellatrix(_) => {
-                BuilderBid::Bellatrix(builder_bid::bellatrix::BuilderBid {
-                    header: execution_payload_header,
-                    value,
-                    public_key: relay_public_key,
-                })
-            }
-            SignedBidSubmission::Capella(_) => {
-                BuilderBid::Capella(builder_bid::capella::BuilderBid {
-                    header: execution_payload_header,
-                    value,
-                    public_key: relay_public_key,
-                })
-            }
-            SignedBidSubmission::Deneb(ref submission) => {
-                BuilderBid::Deneb(builder_bid::deneb::BuilderBid {
-                    header: execution_payload_header,
-                    blob_kzg_commitments: submission.blobs_bundle.commitments.clone(),
-                    value,
-                    public_key: relay_public_key,
-                })
-            }
-        };
-
-        let signature = sign_builder_message(&bid, relay_secret_key, context)?;
-        let signed_builder_bid = SignedBuilderBid { message: bid, signature };
+        let blob_kzg_commitments =
+            signed_submission.blobs_bundle().map(|bundle| bundle.commitments.clone());
+        let signed_builder_bid = sign_builder_bid(
+            execution_payload_header,
+            blob_kzg_commitments,
+            bid_adjustment.apply(value)?,
+            signer,
+            context,
+        )
+        .await?;
 
         let auction_context = match signed_submission {
             SignedBidSubmission::Bellatrix(submission) => {
@@ -228,6 +251,11 @@ impl AuctionContext {
         }
     }
 
+    // Value of the bid served to the proposer, after any adjustment made by the relay.
+    pub fn adjusted_value(&self) -> U256 {
+        self.signed_builder_bid().message.value()
+    }
+
     pub fn to_auction_contents(&self) -> AuctionContents {
         match self {
             Self::Bellatrix(context) => {
@@ -243,3 +271,99 @@ impl AuctionContext {
         }
     }
 }
+
+/// A bid made from only the header of a block, until the builder uploads the full payload
+#[derive(Debug)]
+pub struct DeferredBid {
+    pub bid_trace: BidTrace,
+    pub receive_duration: Duration,
+    pub signed_builder_bid: SignedBuilderBid,
+    // full payload of the block, once uploaded by the builder and validated
+    payload: watch::Sender<Option<Arc<AuctionContext>>>,
+}
+
+impl DeferredBid {
+    pub async fn new(
+        signed_submission: &SignedHeaderSubmission,
+        receive_duration: Duration,
+        bid_adjustment: &BidAdjustment,
+        signer: &Signer,
+        context: &Context,
+    ) -> Result<Self, Error> {
+        let bid_trace = signed_submission.message().clone();
+        let signed_builder_bid = sign_builder_bid(
+            signed_submission.header().clone(),
+            signed_submission.blob_kzg_commitments().cloned(),
+            bid_adjustment.apply(bid_trace.value)?,
+            signer,
+            context,
+        )
+        .await?;
+        let (payload, _) = watch::channel(None);
+        Ok(Self { bid_trace, receive_duration, signed_builder_bid, payload })
+    }
+
+    /// Indicates if `auction_context` holds the payload of the block this bid was made for, with
+    /// the blobs the proposer was offered
+    pub fn is_resolved_by(&self, auction_context: &AuctionContext) -> bool {
+        let (bid, uploaded) =
+            (&self.signed_builder_bid.message, &auction_context.signed_builder_bid().message);
+        auction_context.builder_public_key() == &self.bid_trace.builder_public_key &&
+            bid.header() == uploaded.header() &&
+            bid.blob_kzg_commitments() == uploaded.blob_kzg_commitments()
+    }
+
+    /// Resolve the bid with the payload in `auction_context`, waking any request waiting on it
+    pub fn resolve(&self, auction_context: Arc<AuctionContext>) {
+        self.payload.send_replace(Some(auction_context));
+    }
+
+    /// Wait up to `timeout` for the bid to be resolved with its payload
+    pub async fn payload(&self, timeout: Duration) -> Option<Arc<AuctionContext>> {
+        let mut payload = self.payload.subscribe();
+        // NOTE: the sender lives as long as the bid, so the channel is never closed here
+        tokio::time::timeout(timeout, payload.wait_for(Option::is_some))
+            .await
+            .ok()?
+            .ok()
+            .and_then(|payload| payload.clone())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Bid {
+    Full(Arc<AuctionContext>),
+    Deferred(Arc<DeferredBid>),
+}
+
+impl Bid {
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Full(a), Self::Full(b)) => Arc::ptr_eq(a, b),
+            (Self::Deferred(a), Self::Deferred(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    pub fn bid_trace(&self) -> &BidTrace {
+        match self {
+            Self::Full(context) => context.bid_trace(),
+            Self::Deferred(bid) => &bid.bid_trace,
+        }
+    }
+
+    pub fn builder_public_key(&self) -> &BlsPublicKey {
+        &self.bid_trace().builder_public_key
+    }
+
+    pub fn signed_builder_bid(&self) -> &SignedBuilderBid {
+        match self {
+            Self::Full(context) => context.signed_builder_bid(),
+            Self::Deferred(bid) => &bid.signed_builder_bid,
+        }
+    }
+
+    pub fn value(&self) -> U256 {
+        self.bid_trace().value
+    }
+}
@@ -0,0 +1,58 @@
+use ethereum_consensus::primitives::U256;
+use mev_rs::RelayError;
+use serde::Deserialize;
+
+const BASIS_POINTS: u64 = 10_000;
+
+/// Adjustment the relay applies to the value of every bid it serves to proposers
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Multiplier applied to the value of each bid, in basis points
+    pub scale_bps: u64,
+    /// Amount in wei added to the value of each bid, paid to the proposer from the relay
+    /// operator's funds
+    pub subsidy_wei: U256,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { scale_bps: BASIS_POINTS, subsidy_wei: U256::ZERO }
+    }
+}
+
+impl Config {
+    /// The value served to proposers for a bid with `value`, rejecting a bid whose adjusted value
+    /// can not be represented rather than serving a value unrelated to the bid
+    pub fn apply(&self, value: U256) -> Result<U256, RelayError> {
+        let overflow = || RelayError::BidAdjustmentOverflow(value);
+        let scaled = value.checked_mul(U256::from(self.scale_bps)).ok_or_else(overflow)? /
+            U256::from(BASIS_POINTS);
+        scaled.checked_add(self.subsidy_wei).ok_or_else(overflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_bid_adjustment() {
+        let value = U256::from(1_000_000);
+        assert_eq!(Config::default().apply(value).unwrap(), value);
+
+        let adjustment = Config { scale_bps: 9_000, subsidy_wei: U256::from(5) };
+        assert_eq!(adjustment.apply(value).unwrap(), U256::from(900_005));
+
+        let adjustment = Config { scale_bps: 20_000, subsidy_wei: U256::ZERO };
+        let value = U256::MAX / U256::from(20_000);
+        assert_eq!(adjustment.apply(value).unwrap(), value * U256::from(2));
+        assert!(matches!(
+            adjustment.apply(U256::MAX),
+            Err(RelayError::BidAdjustmentOverflow(value)) if value == U256::MAX
+        ));
+
+        let adjustment = Config { scale_bps: BASIS_POINTS, subsidy_wei: U256::from(1) };
+        assert!(adjustment.apply(U256::MAX).is_err());
+    }
+}
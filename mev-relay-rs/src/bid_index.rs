@@ -0,0 +1,112 @@
+use crate::auction_context::{Bid, DeferredBid};
+use ethereum_consensus::primitives::{BlsPublicKey, Hash32, Slot};
+use mev_rs::types::AuctionRequest;
+use parking_lot::RwLock;
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(Debug, Default)]
+struct AuctionBids {
+    // latest bid from each builder
+    builder_bids: HashMap<BlsPublicKey, Bid>,
+    // highest value bid across `builder_bids`
+    top_bid: Option<Bid>,
+    // every header-only bid, by block hash, as a proposer may have signed the header of one even
+    // once a later bid from its builder replaced it
+    deferred_bids: HashMap<Hash32, Arc<DeferredBid>>,
+}
+
+impl AuctionBids {
+    fn insert(&mut self, bid: Bid) -> Option<Bid> {
+        if let Bid::Deferred(deferred_bid) = &bid {
+            let block_hash = deferred_bid.bid_trace.block_hash.clone();
+            self.deferred_bids.insert(block_hash, deferred_bid.clone());
+        }
+        let builder_public_key = bid.builder_public_key().clone();
+        let replaced = self.builder_bids.insert(builder_public_key, bid.clone());
+
+        let top_was_replaced = match (self.top_bid.as_ref(), replaced.as_ref()) {
+            (Some(top_bid), Some(replaced)) => top_bid.ptr_eq(replaced),
+            _ => false,
+        };
+        if top_was_replaced {
+            // the builder may have lowered their bid, so find the new top bid
+            self.top_bid = self.builder_bids.values().max_by_key(|bid| bid.value()).cloned();
+        } else {
+            let is_top_bid =
+                self.top_bid.as_ref().map_or(true, |top_bid| bid.value() >= top_bid.value());
+            if is_top_bid {
+                self.top_bid = Some(bid);
+            }
+        }
+        replaced
+    }
+
+    fn remove(&mut self, builder_public_key: &BlsPublicKey, block_hash: &Hash32) -> Option<Bid> {
+        let bid = self.builder_bids.get(builder_public_key)?;
+        if &bid.bid_trace().block_hash != block_hash {
+            return None
+        }
+        let removed = self.builder_bids.remove(builder_public_key)?;
+        if self.top_bid.as_ref().is_some_and(|top_bid| top_bid.ptr_eq(&removed)) {
+            self.top_bid = self.builder_bids.values().max_by_key(|bid| bid.value()).cloned();
+        }
+        Some(removed)
+    }
+}
+
+/// Index of the latest bid from each builder and the current top bid, keyed by auction
+#[derive(Debug, Default)]
+pub struct BidIndex {
+    auctions: RwLock<HashMap<AuctionRequest, AuctionBids>>,
+}
+
+impl BidIndex {
+    pub fn top_bid(&self, auction_request: &AuctionRequest) -> Option<Bid> {
+        let auctions = self.auctions.read();
+        auctions.get(auction_request).and_then(|bids| bids.top_bid.clone())
+    }
+
+    // Insert `bid` as the latest bid from its builder, replacing (and returning) any previous bid
+    // from the same builder, even if it had a higher value.
+    pub fn insert(&self, auction_request: AuctionRequest, bid: Bid) -> Option<Bid> {
+        let mut auctions = self.auctions.write();
+        auctions.entry(auction_request).or_default().insert(bid)
+    }
+
+    // Remove the latest bid from the builder with `builder_public_key` if it is for the block with
+    // `block_hash`, so the top bid falls back to the best bid from another builder.
+    pub fn remove(
+        &self,
+        auction_request: &AuctionRequest,
+        builder_public_key: &BlsPublicKey,
+        block_hash: &Hash32,
+    ) -> Option<Bid> {
+        let mut auctions = self.auctions.write();
+        auctions.get_mut(auction_request)?.remove(builder_public_key, block_hash)
+    }
+
+    pub fn deferred_bid(
+        &self,
+        auction_request: &AuctionRequest,
+        block_hash: &Hash32,
+    ) -> Option<Arc<DeferredBid>> {
+        let auctions = self.auctions.read();
+        auctions.get(auction_request).and_then(|bids| bids.deferred_bids.get(block_hash).cloned())
+    }
+
+    // Return the latest bid from every builder across all auctions.
+    pub fn latest_bids(&self) -> Vec<(AuctionRequest, Bid)> {
+        let auctions = self.auctions.read();
+        auctions
+            .iter()
+            .flat_map(|(auction_request, bids)| {
+                bids.builder_bids.values().map(|bid| (auction_request.clone(), bid.clone()))
+            })
+            .collect()
+    }
+
+    pub fn retain(&self, retain_slot: Slot) {
+        let mut auctions = self.auctions.write();
+        auctions.retain(|auction_request, _| auction_request.slot >= retain_slot);
+    }
+}
@@ -0,0 +1,96 @@
+use alloy_consensus::TxEnvelope;
+use alloy_eips::{
+    eip2718::Decodable2718,
+    eip4844::{env_settings::EnvKzgSettings, kzg_to_versioned_hash},
+};
+use c_kzg::{Blob, Bytes48, KzgProof};
+use mev_rs::{
+    types::{BlobsBundle, ExecutionPayload},
+    RelayError,
+};
+
+const BLOB_TX_TYPE: u8 = 0x03;
+
+// Ensure the `blobs_bundle` is consistent with the `execution_payload` it was submitted with:
+// the bundle must be well-formed, its commitments must correspond to the blob transactions in
+// the payload and each blob must verify against its commitment and proof.
+pub fn verify_blobs_bundle(
+    execution_payload: &ExecutionPayload,
+    blobs_bundle: &BlobsBundle,
+) -> Result<(), RelayError> {
+    let commitments = blobs_bundle.commitments.len();
+    let proofs = blobs_bundle.proofs.len();
+    let blobs = blobs_bundle.blobs.len();
+    if commitments != proofs || commitments != blobs {
+        return Err(RelayError::InvalidBlobsBundleLength { commitments, proofs, blobs })
+    }
+
+    let versioned_hashes = blob_versioned_hashes(execution_payload)?;
+    if versioned_hashes.len() != commitments {
+        return Err(RelayError::InvalidBlobCommitments)
+    }
+    let commitments_match = blobs_bundle.commitments.iter().zip(versioned_hashes.iter()).all(
+        |(commitment, versioned_hash)| {
+            kzg_to_versioned_hash(commitment.as_ref()).0 == *versioned_hash
+        },
+    );
+    if !commitments_match {
+        return Err(RelayError::InvalidBlobCommitments)
+    }
+
+    if blobs == 0 {
+        return Ok(())
+    }
+
+    let blobs = blobs_bundle
+        .blobs
+        .iter()
+        .map(|blob| Blob::from_bytes(blob.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| RelayError::InvalidBlobProofs)?;
+    let commitments = blobs_bundle
+        .commitments
+        .iter()
+        .map(|commitment| Bytes48::from_bytes(commitment.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| RelayError::InvalidBlobProofs)?;
+    let proofs = blobs_bundle
+        .proofs
+        .iter()
+        .map(|proof| Bytes48::from_bytes(proof.as_ref()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| RelayError::InvalidBlobProofs)?;
+    let valid = KzgProof::verify_blob_kzg_proof_batch(
+        &blobs,
+        &commitments,
+        &proofs,
+        EnvKzgSettings::Default.get(),
+    )
+    .map_err(|_| RelayError::InvalidBlobProofs)?;
+    if valid {
+        Ok(())
+    } else {
+        Err(RelayError::InvalidBlobProofs)
+    }
+}
+
+// Collect the versioned hashes referenced by all blob transactions in the `execution_payload`,
+// in the order they appear in the block.
+fn blob_versioned_hashes(
+    execution_payload: &ExecutionPayload,
+) -> Result<Vec<[u8; 32]>, RelayError> {
+    let mut versioned_hashes = vec![];
+    for transaction in execution_payload.transactions().iter() {
+        let mut encoding = transaction.as_ref();
+        if encoding.first() != Some(&BLOB_TX_TYPE) {
+            continue
+        }
+        let envelope = TxEnvelope::decode_2718(&mut encoding)
+            .map_err(|_| RelayError::InvalidBlobCommitments)?;
+        if let TxEnvelope::Eip4844(signed_transaction) = envelope {
+            let blob_versioned_hashes = &signed_transaction.tx().tx().blob_versioned_hashes;
+            versioned_hashes.extend(blob_versioned_hashes.iter().map(|hash| hash.0));
+        }
+    }
+    Ok(versioned_hashes)
+}
@@ -0,0 +1,186 @@
+use crate::relay::Relay;
+use futures::{Stream, StreamExt};
+use mev_rs::types::{AuctionRequest, SignedBidSubmission, SignedValidatorRegistration};
+use redis::{aio::MultiplexedConnection, AsyncCommands, Client, RedisError};
+use serde::{Deserialize, Serialize};
+use std::{pin::pin, time::Duration};
+use tracing::warn;
+
+// How long the claim on the payload delivered in a slot is kept, well past the slot itself
+const DELIVERY_CLAIM_TTL_SECS: u64 = 60 * 60;
+// How long to wait before subscribing again once the subscription to other nodes is lost
+const RESUBSCRIBE_DELAY: Duration = Duration::from_secs(1);
+
+fn default_namespace() -> String {
+    "mev-relay".to_string()
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// URL of the Redis instance shared by every node of the relay
+    pub redis_url: String,
+    /// Prefix of the keys and channels used by the nodes, so several relays can share one instance
+    #[serde(default = "default_namespace")]
+    pub namespace: String,
+}
+
+/// Updates one node of the relay shares with every other node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// A submission accepted by the node, to be inserted as a bid by every node
+    Submission {
+        auction_request: AuctionRequest,
+        signed_submission: SignedBidSubmission,
+        receive_duration: Duration,
+    },
+    /// Registrations that updated the node's validator registry
+    Registrations { registrations: Vec<SignedValidatorRegistration> },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    // node the event was published by, so nodes can skip their own events
+    node: u64,
+    event: Event,
+}
+
+/// Coordinates the nodes of a relay deployed across several processes behind a load balancer.
+///
+/// Every accepted submission and validator registration is published to the other nodes over
+/// Redis pub/sub, so that each node serves the same top bid and can deliver the payload for it.
+/// Before a node delivers a payload, it claims the slot in Redis so that no other node can release
+/// a different payload for the same slot.
+#[derive(Clone)]
+pub struct Cluster {
+    client: Client,
+    connection: MultiplexedConnection,
+    namespace: String,
+    node: u64,
+}
+
+impl Cluster {
+    pub async fn connect(config: Config) -> Result<Self, RedisError> {
+        let client = Client::open(config.redis_url.as_str())?;
+        let connection = client.get_multiplexed_tokio_connection().await?;
+        Ok(Self { client, connection, namespace: config.namespace, node: rand::random() })
+    }
+
+    fn events_channel(&self) -> String {
+        format!("{}:events", self.namespace)
+    }
+
+    fn delivery_key(&self, auction_request: &AuctionRequest) -> String {
+        format!("{}:delivered:{}", self.namespace, auction_request.slot)
+    }
+
+    /// Publish `event` to the other nodes on a separate task
+    pub fn publish(&self, event: Event) {
+        let message = match serde_json::to_string(&Message { node: self.node, event }) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!(%err, "could not encode event for other relay nodes");
+                return
+            }
+        };
+        let mut connection = self.connection.clone();
+        let channel = self.events_channel();
+        tokio::spawn(async move {
+            if let Err(err) = connection.publish::<_, _, ()>(channel, message).await {
+                warn!(%err, "could not publish event to other relay nodes");
+            }
+        });
+    }
+
+    /// Events published by the other nodes, until the connection to Redis is lost
+    pub async fn subscribe(&self) -> Result<impl Stream<Item = Event>, RedisError> {
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(self.events_channel()).await?;
+        let node = self.node;
+        let events = pubsub.into_on_message().filter_map(move |message| async move {
+            let payload = message
+                .get_payload::<String>()
+                .inspect_err(|err| warn!(%err, "could not read event from relay node"))
+                .ok()?;
+            let message = serde_json::from_str::<Message>(&payload)
+                .inspect_err(|err| warn!(%err, "could not decode event from relay node"))
+                .ok()?;
+            (message.node != node).then_some(message.event)
+        });
+        Ok(events)
+    }
+
+    /// Claim the delivery of the payload with `block_hash` for `auction_request`, returning `false`
+    /// if any node already delivered a different payload for the slot
+    pub async fn claim_delivery(
+        &self,
+        auction_request: &AuctionRequest,
+        block_hash: &str,
+    ) -> Result<bool, RedisError> {
+        let key = self.delivery_key(auction_request);
+        let mut connection = self.connection.clone();
+        let claimed = redis::cmd("SET")
+            .arg(&key)
+            .arg(block_hash)
+            .arg("NX")
+            .arg("EX")
+            .arg(DELIVERY_CLAIM_TTL_SECS)
+            .query_async::<_, Option<String>>(&mut connection)
+            .await?;
+        if claimed.is_some() {
+            return Ok(true)
+        }
+        // NOTE: the same payload can be delivered again, e.g. if the proposer retries the request
+        let delivered = connection.get::<_, Option<String>>(&key).await?;
+        Ok(delivered.as_deref() == Some(block_hash))
+    }
+}
+
+/// Apply the events published by the other nodes to `relay`, subscribing again whenever the
+/// connection to Redis is lost
+// NOTE: events published while the subscription is down are missed
+pub fn spawn(cluster: Cluster, relay: Relay) {
+    tokio::spawn(async move {
+        loop {
+            match cluster.subscribe().await {
+                Ok(events) => {
+                    let mut events = pin!(events);
+                    while let Some(event) = events.next().await {
+                        relay.on_cluster_event(event).await;
+                    }
+                    warn!("lost subscription to other relay nodes");
+                }
+                Err(err) => warn!(%err, "could not subscribe to other relay nodes"),
+            }
+            tokio::time::sleep(RESUBSCRIBE_DELAY).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::{
+        builder::ValidatorRegistration, crypto::SecretKey, state_transition::Context,
+    };
+    use mev_rs::signing::sign_builder_message;
+
+    #[test]
+    fn test_message_round_trip() {
+        let context = Context::for_mainnet();
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let message =
+            ValidatorRegistration { public_key: secret_key.public_key(), ..Default::default() };
+        let signature = sign_builder_message(&message, &secret_key, &context).unwrap();
+        let registration = SignedValidatorRegistration { message, signature };
+        let message =
+            Message { node: 7, event: Event::Registrations { registrations: vec![registration] } };
+        let encoded = serde_json::to_string(&message).unwrap();
+        let decoded = serde_json::from_str::<Message>(&encoded).unwrap();
+        assert_eq!(decoded.node, 7);
+        let Event::Registrations { registrations } = decoded.event else {
+            panic!("decoded registrations")
+        };
+        assert_eq!(registrations.len(), 1);
+    }
+}
@@ -0,0 +1,69 @@
+use ethereum_consensus::primitives::{BlsPublicKey, U256};
+use mev_rs::types::block_submission::data_api::BuilderCollateral;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use tracing::warn;
+
+#[derive(Debug)]
+struct Account {
+    balance: U256,
+    // set after a failed optimistic submission, until the relay is restarted
+    suspended: bool,
+}
+
+/// Tracks collateral credited to each builder, which bounds the value of the bids a builder may
+/// have accepted before they are simulated
+// NOTE: balances are credited from the relay's configuration and not yet observed on-chain
+#[derive(Debug, Default)]
+pub struct Collateral {
+    accounts: RwLock<HashMap<BlsPublicKey, Account>>,
+}
+
+impl Collateral {
+    pub fn new(balances: HashMap<BlsPublicKey, U256>) -> Self {
+        let accounts = balances
+            .into_iter()
+            .map(|(builder_public_key, balance)| {
+                (builder_public_key, Account { balance, suspended: false })
+            })
+            .collect();
+        Self { accounts: RwLock::new(accounts) }
+    }
+
+    // Debit `amount` to cover a payload the builder failed to deliver.
+    pub fn debit(&self, builder_public_key: &BlsPublicKey, amount: U256) {
+        let mut accounts = self.accounts.write();
+        if let Some(account) = accounts.get_mut(builder_public_key) {
+            account.balance = account.balance.saturating_sub(amount);
+            warn!(%builder_public_key, %amount, balance = %account.balance, "debited builder collateral");
+        }
+    }
+
+    pub fn suspend(&self, builder_public_key: &BlsPublicKey) {
+        let mut accounts = self.accounts.write();
+        if let Some(account) = accounts.get_mut(builder_public_key) {
+            account.suspended = true;
+            warn!(%builder_public_key, "suspended optimistic submissions from builder");
+        }
+    }
+
+    /// Returns `true` if the builder has enough collateral to cover a bid of `value`
+    pub fn covers(&self, builder_public_key: &BlsPublicKey, value: U256) -> bool {
+        let accounts = self.accounts.read();
+        accounts
+            .get(builder_public_key)
+            .is_some_and(|account| !account.suspended && value <= account.balance)
+    }
+
+    pub fn balances(&self) -> Vec<BuilderCollateral> {
+        let accounts = self.accounts.read();
+        accounts
+            .iter()
+            .map(|(builder_public_key, account)| BuilderCollateral {
+                builder_public_key: builder_public_key.clone(),
+                collateral: account.balance,
+                is_optimistic: !account.suspended,
+            })
+            .collect()
+    }
+}
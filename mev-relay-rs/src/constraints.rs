@@ -0,0 +1,148 @@
+use ethereum_consensus::primitives::{BlsPublicKey, Slot};
+use mev_rs::{
+    types::{Constraints, ExecutionPayload, PreconfCommitment, SignedPreconfCommitment},
+    RelayError,
+};
+use std::{collections::HashSet, time::Duration};
+
+/// A preconfirmation commitment, along with when the relay received it
+#[derive(Debug, Clone)]
+pub struct ReceivedCommitment {
+    pub signed_commitment: SignedPreconfCommitment,
+    // time since the unix epoch the commitment was received at
+    pub receive_duration: Duration,
+}
+
+// Select the commitments of the builder with `builder_public_key` the relay received before
+// `receive_duration`, e.g. when it received a bid; a bid is only held to the commitments made
+// before it, as a later commitment can only be honored by a later bid.
+pub fn commitments_before<'a>(
+    commitments: &'a [ReceivedCommitment],
+    builder_public_key: &'a BlsPublicKey,
+    receive_duration: Duration,
+) -> impl Iterator<Item = &'a PreconfCommitment> + 'a {
+    commitments
+        .iter()
+        .filter(move |commitment| commitment.receive_duration < receive_duration)
+        .map(|commitment| &commitment.signed_commitment.message)
+        .filter(move |commitment| &commitment.builder_public_key == builder_public_key)
+}
+
+// Count the `transactions` not included in the `execution_payload`.
+fn count_missing<'a>(
+    transactions: impl IntoIterator<Item = &'a [u8]>,
+    execution_payload: &ExecutionPayload,
+) -> usize {
+    let included = execution_payload
+        .transactions()
+        .iter()
+        .map(|transaction| transaction.as_ref())
+        .collect::<HashSet<&[u8]>>();
+    transactions.into_iter().filter(|transaction| !included.contains(transaction)).count()
+}
+
+// Ensure every transaction required by the `constraints` is included in the `execution_payload`.
+pub fn verify_constraints(
+    constraints: &Constraints,
+    execution_payload: &ExecutionPayload,
+) -> Result<(), RelayError> {
+    let transactions = constraints.transactions.iter().map(|transaction| transaction.as_ref());
+    let missing = count_missing(transactions, execution_payload);
+    if missing == 0 {
+        Ok(())
+    } else {
+        Err(RelayError::ConstraintsNotSatisfied { slot: constraints.slot, missing })
+    }
+}
+
+// Ensure every transaction the builder committed to for `slot` in `commitments` is included in
+// the `execution_payload`.
+pub fn verify_preconf_commitments<'a>(
+    slot: Slot,
+    commitments: impl IntoIterator<Item = &'a PreconfCommitment>,
+    execution_payload: &ExecutionPayload,
+) -> Result<(), RelayError> {
+    let transactions = commitments.into_iter().flat_map(|commitment| {
+        commitment.transactions.iter().map(|transaction| transaction.as_ref())
+    });
+    let missing = count_missing(transactions, execution_payload);
+    if missing == 0 {
+        Ok(())
+    } else {
+        Err(RelayError::PreconfCommitmentsNotHonored { slot, missing })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::{bellatrix::mainnet as bellatrix, crypto::SecretKey};
+
+    fn commitment(
+        builder: &BlsPublicKey,
+        transaction: &[u8],
+        received_ms: u64,
+    ) -> ReceivedCommitment {
+        let message = PreconfCommitment {
+            slot: 1,
+            builder_public_key: builder.clone(),
+            transactions: vec![transaction.to_vec().try_into().unwrap()].try_into().unwrap(),
+        };
+        ReceivedCommitment {
+            signed_commitment: SignedPreconfCommitment { message, signature: Default::default() },
+            receive_duration: Duration::from_millis(received_ms),
+        }
+    }
+
+    #[test]
+    fn test_verify_constraints() {
+        let execution_payload = ExecutionPayload::Bellatrix(bellatrix::ExecutionPayload {
+            transactions: vec![vec![1u8].try_into().unwrap(), vec![2u8].try_into().unwrap()]
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        });
+        let constraints = |transactions: &[&[u8]]| Constraints {
+            slot: 1,
+            transactions: transactions
+                .iter()
+                .map(|transaction| transaction.to_vec().try_into().unwrap())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+            ..Default::default()
+        };
+
+        assert!(verify_constraints(&constraints(&[]), &execution_payload).is_ok());
+        assert!(verify_constraints(&constraints(&[&[2], &[1]]), &execution_payload).is_ok());
+        // NOTE: a transaction counts as included only if its encoding matches exactly
+        assert!(matches!(
+            verify_constraints(&constraints(&[&[1], &[3], &[1, 2]]), &execution_payload),
+            Err(RelayError::ConstraintsNotSatisfied { slot: 1, missing: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_commitments_before_bid() {
+        let builder = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let other = SecretKey::key_gen(&[2u8; 32]).unwrap().public_key();
+        let commitments = vec![
+            commitment(&builder, &[1], 100),
+            commitment(&other, &[2], 100),
+            commitment(&builder, &[3], 300),
+        ];
+        let execution_payload = ExecutionPayload::Bellatrix(bellatrix::ExecutionPayload {
+            transactions: vec![vec![1u8].try_into().unwrap()].try_into().unwrap(),
+            ..Default::default()
+        });
+
+        // NOTE: a bid is not held to a commitment made after it, nor to other builders' commitments
+        let before = commitments_before(&commitments, &builder, Duration::from_millis(200));
+        assert!(verify_preconf_commitments(1, before, &execution_payload).is_ok());
+        let before = commitments_before(&commitments, &builder, Duration::from_millis(400));
+        assert!(matches!(
+            verify_preconf_commitments(1, before, &execution_payload),
+            Err(RelayError::PreconfCommitmentsNotHonored { slot: 1, missing: 1 })
+        ));
+    }
+}
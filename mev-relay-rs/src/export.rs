@@ -0,0 +1,69 @@
+use ethereum_consensus::primitives::Epoch;
+use mev_rs::types::block_submission::data_api::{PayloadTrace, SubmissionTrace};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// Directory to write a CSV file of each epoch's delivered payloads and submissions to
+    pub directory: PathBuf,
+}
+
+/// Writes the relay's bid traces to CSV files once each epoch has passed, partitioned by table and
+/// epoch so they can be consumed directly by analytics pipelines
+#[derive(Debug, Clone)]
+pub struct Exporter {
+    directory: PathBuf,
+}
+
+impl Exporter {
+    pub fn new(config: Config) -> Self {
+        Self { directory: config.directory }
+    }
+
+    fn write<T: Serialize>(
+        &self,
+        table: &str,
+        epoch: Epoch,
+        records: &[T],
+    ) -> Result<PathBuf, Error> {
+        let directory = self.directory.join(table);
+        std::fs::create_dir_all(&directory)?;
+        let path = directory.join(format!("epoch={epoch}.csv"));
+        let mut writer = csv::Writer::from_path(&path)?;
+        for record in records {
+            writer.serialize(record)?;
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+
+    /// Write the traces of the delivered payloads and submissions for `epoch`
+    pub fn export(
+        &self,
+        epoch: Epoch,
+        delivered_payloads: &[PayloadTrace],
+        submissions: &[SubmissionTrace],
+    ) {
+        let results = [
+            (delivered_payloads.len(), self.write("delivered_payloads", epoch, delivered_payloads)),
+            (submissions.len(), self.write("submissions", epoch, submissions)),
+        ];
+        for (count, result) in results {
+            match result {
+                Ok(path) => debug!(epoch, count, path = %path.display(), "exported bid traces"),
+                Err(err) => warn!(%err, epoch, "could not export bid traces"),
+            }
+        }
+    }
+}
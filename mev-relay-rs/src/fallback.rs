@@ -0,0 +1,423 @@
+use ethereum_consensus::{
+    primitives::{Bytes32, ExecutionAddress, Root, Slot, U256},
+    Fork,
+};
+use mev_rs::types::{AuctionRequest, BlobsBundle, ExecutionPayload};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+use url::Url;
+
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::{capella::mainnet as capella, deneb::mainnet as deneb};
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::{capella::minimal as capella, deneb::minimal as deneb};
+
+const FORKCHOICE_UPDATED_METHOD: &str = "engine_forkchoiceUpdatedV3";
+const GET_PAYLOAD_METHOD: &str = "engine_getPayloadV3";
+const GET_BLOCK_METHOD: &str = "eth_getBlockByNumber";
+// Fields the Engine API encodes as hex quantities and the beacon APIs as decimal strings
+const QUANTITY_FIELDS: &[&str] = &[
+    "block_number",
+    "gas_limit",
+    "gas_used",
+    "timestamp",
+    "base_fee_per_gas",
+    "blob_gas_used",
+    "excess_blob_gas",
+    "index",
+    "validator_index",
+    "amount",
+];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("fork {0} is not supported by the fallback builder")]
+    UnsupportedFork(Fork),
+    #[error("invalid JWT secret")]
+    InvalidJwtSecret,
+    #[error("engine API error: {0}")]
+    Engine(String),
+    #[error("execution node did not start building a payload")]
+    MissingPayloadId,
+    #[error("invalid quantity {0:?} in engine API response")]
+    InvalidQuantity(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// URL of the authenticated Engine API of an execution node
+    pub engine_api_url: String,
+    /// Path to the hex-encoded JWT secret shared with the execution node
+    pub jwt_secret_path: String,
+}
+
+// Payload attributes as provided by the beacon node's `payload_attributes` events
+#[derive(Deserialize)]
+struct BeaconPayloadAttributes {
+    #[serde(with = "ethereum_consensus::serde::as_str")]
+    timestamp: u64,
+    prev_randao: Bytes32,
+    #[serde(default)]
+    withdrawals: Vec<capella::Withdrawal>,
+}
+
+#[derive(Serialize)]
+struct Claims {
+    iat: u64,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    #[serde(default)]
+    result: Value,
+    error: Option<JsonRpcError>,
+}
+
+fn to_quantity(value: u64) -> String {
+    format!("{value:#x}")
+}
+
+fn to_snake_case(key: &str) -> String {
+    let mut snake_case = String::with_capacity(key.len());
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            snake_case.push('_');
+            snake_case.push(c.to_ascii_lowercase());
+        } else {
+            snake_case.push(c);
+        }
+    }
+    snake_case
+}
+
+fn parse_quantity(value: &str) -> Result<U256, Error> {
+    let digits = value.strip_prefix("0x").ok_or_else(|| Error::InvalidQuantity(value.into()))?;
+    U256::from_str_radix(digits, 16).map_err(|_| Error::InvalidQuantity(value.into()))
+}
+
+// Convert a value from the Engine API's encoding to the encoding used by the beacon APIs, so it
+// can be deserialized into the `ethereum-consensus` types.
+fn to_beacon_encoding(value: Value) -> Result<Value, Error> {
+    match value {
+        Value::Object(fields) => {
+            let mut converted = Map::with_capacity(fields.len());
+            for (key, value) in fields {
+                let key = to_snake_case(&key);
+                let value = match value {
+                    Value::String(quantity) if QUANTITY_FIELDS.contains(&key.as_str()) => {
+                        Value::String(parse_quantity(&quantity)?.to_string())
+                    }
+                    value => to_beacon_encoding(value)?,
+                };
+                converted.insert(key, value);
+            }
+            Ok(Value::Object(converted))
+        }
+        Value::Array(values) => {
+            Ok(Value::Array(values.into_iter().map(to_beacon_encoding).collect::<Result<_, _>>()?))
+        }
+        value => Ok(value),
+    }
+}
+
+pub struct BuiltPayload {
+    pub execution_payload: ExecutionPayload,
+    pub blobs_bundle: BlobsBundle,
+    pub value: U256,
+}
+
+/// Builds blocks from the mempool of a connected execution node, so the relay has a bid to serve
+/// when no builder has submitted one
+pub struct FallbackBuilder {
+    client: reqwest::Client,
+    endpoint: Url,
+    jwt_secret: Vec<u8>,
+    payload_ids: Mutex<HashMap<AuctionRequest, String>>,
+}
+
+impl FallbackBuilder {
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let endpoint = config.engine_api_url.parse()?;
+        let jwt_secret = std::fs::read_to_string(&config.jwt_secret_path)?;
+        let jwt_secret = jwt_secret.trim();
+        let jwt_secret = ethereum_consensus::serde::try_bytes_from_hex_str(jwt_secret)
+            .map_err(|_| Error::InvalidJwtSecret)?;
+        if jwt_secret.len() != 32 {
+            return Err(Error::InvalidJwtSecret)
+        }
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            jwt_secret,
+            payload_ids: Default::default(),
+        })
+    }
+
+    fn token(&self) -> Result<String, Error> {
+        let iat = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let key = jsonwebtoken::EncodingKey::from_secret(&self.jwt_secret);
+        Ok(jsonwebtoken::encode(&Default::default(), &Claims { iat }, &key)?)
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, Error> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .bearer_auth(self.token()?)
+            .json(&request)
+            .send()
+            .await?;
+        let response: JsonRpcResponse = response.error_for_status()?.json().await?;
+        match response.error {
+            Some(err) => Err(Error::Engine(err.message)),
+            None => Ok(response.result),
+        }
+    }
+
+    // Hash of the block the execution node holds as `tag`, e.g. `"finalized"`
+    async fn block_hash(&self, tag: &str) -> Result<Value, Error> {
+        let block = self.call(GET_BLOCK_METHOD, json!([tag, false])).await?;
+        // NOTE: until the chain finalizes, the execution node knows no safe or finalized block,
+        // which the zero hash leaves unset in the forkchoice update
+        Ok(block.get("hash").cloned().unwrap_or_else(|| json!(Bytes32::default())))
+    }
+
+    /// Start building a payload for the auction given by `auction_request`, which can later be
+    /// retrieved with `build`
+    pub async fn start(
+        &self,
+        auction_request: AuctionRequest,
+        fork: Fork,
+        fee_recipient: &ExecutionAddress,
+        parent_beacon_block_root: &Root,
+        payload_attributes: Value,
+    ) -> Result<(), Error> {
+        if fork != Fork::Deneb {
+            return Err(Error::UnsupportedFork(fork))
+        }
+        let BeaconPayloadAttributes { timestamp, prev_randao, withdrawals } =
+            serde_json::from_value(payload_attributes)?;
+        let withdrawals = withdrawals
+            .iter()
+            .map(|withdrawal| {
+                json!({
+                    "index": to_quantity(withdrawal.index as u64),
+                    "validatorIndex": to_quantity(withdrawal.validator_index as u64),
+                    "address": withdrawal.address,
+                    "amount": to_quantity(withdrawal.amount),
+                })
+            })
+            .collect::<Vec<_>>();
+        // NOTE: keep the safe and finalized blocks the execution node already follows, so a node
+        // shared with a beacon node is not moved off the forkchoice the beacon node gave it
+        let forkchoice_state = json!({
+            "headBlockHash": auction_request.parent_hash,
+            "safeBlockHash": self.block_hash("safe").await?,
+            "finalizedBlockHash": self.block_hash("finalized").await?,
+        });
+        let payload_attributes = json!({
+            "timestamp": to_quantity(timestamp),
+            "prevRandao": prev_randao,
+            "suggestedFeeRecipient": fee_recipient,
+            "withdrawals": withdrawals,
+            "parentBeaconBlockRoot": parent_beacon_block_root,
+        });
+        let result = self
+            .call(FORKCHOICE_UPDATED_METHOD, json!([forkchoice_state, payload_attributes]))
+            .await?;
+        let payload_id = result["payloadId"].as_str().ok_or(Error::MissingPayloadId)?.to_string();
+        self.payload_ids.lock().insert(auction_request, payload_id);
+        Ok(())
+    }
+
+    /// Fetch the best payload built so far for the auction given by `auction_request`, if the
+    /// build was started
+    pub async fn build(
+        &self,
+        auction_request: &AuctionRequest,
+    ) -> Result<Option<BuiltPayload>, Error> {
+        let Some(payload_id) = self.payload_ids.lock().get(auction_request).cloned() else {
+            return Ok(None)
+        };
+        let mut result = self.call(GET_PAYLOAD_METHOD, json!([payload_id])).await?;
+        let value = result["blockValue"]
+            .as_str()
+            .ok_or_else(|| Error::InvalidQuantity(result["blockValue"].to_string()))?;
+        let value = parse_quantity(value)?;
+        let execution_payload = to_beacon_encoding(result["executionPayload"].take())?;
+        let execution_payload: deneb::ExecutionPayload = serde_json::from_value(execution_payload)?;
+        let blobs_bundle = serde_json::from_value(result["blobsBundle"].take())?;
+        Ok(Some(BuiltPayload {
+            execution_payload: ExecutionPayload::Deneb(execution_payload),
+            blobs_bundle,
+            value,
+        }))
+    }
+
+    pub fn retain(&self, slot: Slot) {
+        self.payload_ids.lock().retain(|auction_request, _| auction_request.slot >= slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use std::{net::TcpListener, sync::Arc};
+
+    // Serve an Engine API that only knows a safe block, recording the forkchoice state it is sent
+    fn spawn_engine(forkchoice_state: Arc<Mutex<Option<Value>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler = move |Json(request): Json<Value>| {
+            let forkchoice_state = forkchoice_state.clone();
+            async move {
+                let result = match request["method"].as_str() {
+                    Some(GET_BLOCK_METHOD) if request["params"][0] == "safe" => {
+                        json!({ "hash": Bytes32::try_from([1u8; 32].as_ref()).unwrap() })
+                    }
+                    Some(FORKCHOICE_UPDATED_METHOD) => {
+                        *forkchoice_state.lock() = Some(request["params"][0].clone());
+                        json!({ "payloadId": "0x0000000000000001" })
+                    }
+                    _ => Value::Null,
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": 1, "result": result }))
+            }
+        };
+        let router = Router::new().route("/", post(handler));
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn test_to_snake_case() {
+        assert_eq!(to_snake_case("blockHash"), "block_hash");
+        assert_eq!(to_snake_case("excessBlobGas"), "excess_blob_gas");
+        assert_eq!(to_snake_case("index"), "index");
+    }
+
+    #[test]
+    fn test_parse_quantity() {
+        assert_eq!(parse_quantity("0x0").unwrap(), U256::ZERO);
+        assert_eq!(parse_quantity("0x1b").unwrap(), U256::from(27));
+        assert!(matches!(parse_quantity("27"), Err(Error::InvalidQuantity(_))));
+        assert!(matches!(parse_quantity("0xzz"), Err(Error::InvalidQuantity(_))));
+    }
+
+    #[test]
+    fn test_to_beacon_encoding() {
+        let hash = format!("0x{}", "11".repeat(32));
+        let engine_payload = json!({
+            "parentHash": hash,
+            "feeRecipient": format!("0x{}", "22".repeat(20)),
+            "stateRoot": hash,
+            "receiptsRoot": hash,
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "prevRandao": hash,
+            "blockNumber": "0x10",
+            "gasLimit": "0x1c9c380",
+            "gasUsed": "0x5208",
+            "timestamp": "0x6553f100",
+            "extraData": "0x",
+            "baseFeePerGas": "0x7",
+            "blockHash": hash,
+            "transactions": ["0x02"],
+            "withdrawals": [{
+                "index": "0x1",
+                "validatorIndex": "0x2",
+                "address": format!("0x{}", "33".repeat(20)),
+                "amount": "0x3",
+            }],
+            "blobGasUsed": "0x20000",
+            "excessBlobGas": "0x0",
+        });
+        let encoded = to_beacon_encoding(engine_payload).unwrap();
+        assert_eq!(encoded["block_number"], "16");
+        assert_eq!(encoded["withdrawals"][0]["validator_index"], "2");
+        // NOTE: only quantities are converted, while hashes and data keep their hex encoding
+        assert_eq!(encoded["block_hash"], hash.as_str());
+        assert_eq!(encoded["transactions"][0], "0x02");
+
+        let payload: deneb::ExecutionPayload = serde_json::from_value(encoded).unwrap();
+        assert_eq!(payload.gas_limit, 30_000_000);
+        assert_eq!(payload.base_fee_per_gas, U256::from(7));
+        assert_eq!(payload.blob_gas_used, 131_072);
+        assert_eq!(payload.withdrawals[0].amount, 3);
+
+        let invalid = json!({ "gasUsed": "21000" });
+        assert!(matches!(to_beacon_encoding(invalid), Err(Error::InvalidQuantity(_))));
+    }
+
+    #[tokio::test]
+    async fn test_start_keeps_forkchoice() {
+        let forkchoice_state = Arc::new(Mutex::new(None));
+        let jwt_secret_path =
+            std::env::temp_dir().join(format!("fallback-jwt-{}.hex", std::process::id()));
+        std::fs::write(&jwt_secret_path, format!("0x{}", "ab".repeat(32))).unwrap();
+        let builder = FallbackBuilder::new(Config {
+            engine_api_url: spawn_engine(forkchoice_state.clone()),
+            jwt_secret_path: jwt_secret_path.to_str().unwrap().to_string(),
+        })
+        .unwrap();
+        std::fs::remove_file(&jwt_secret_path).unwrap();
+
+        let auction_request = AuctionRequest { slot: 7, ..Default::default() };
+        let payload_attributes = json!({
+            "timestamp": "1700000000",
+            "prev_randao": Bytes32::default(),
+            "withdrawals": [],
+        });
+        let start = builder.start(
+            auction_request.clone(),
+            Fork::Capella,
+            &Default::default(),
+            &Default::default(),
+            payload_attributes.clone(),
+        );
+        assert!(matches!(start.await, Err(Error::UnsupportedFork(Fork::Capella))));
+        builder
+            .start(
+                auction_request.clone(),
+                Fork::Deneb,
+                &Default::default(),
+                &Default::default(),
+                payload_attributes,
+            )
+            .await
+            .unwrap();
+
+        let forkchoice_state = forkchoice_state.lock().take().unwrap();
+        let safe = Bytes32::try_from([1u8; 32].as_ref()).unwrap();
+        assert_eq!(forkchoice_state["safeBlockHash"], json!(safe));
+        // NOTE: a finalized block the execution node does not know is left unset
+        assert_eq!(forkchoice_state["finalizedBlockHash"], json!(Bytes32::default()));
+        assert_eq!(
+            builder.payload_ids.lock().get(&auction_request).map(String::as_str),
+            Some("0x0000000000000001")
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use crate::relay::Relay;
+use mev_rs::{
+    types::{BidTrace, SignedBidSubmission},
+    BlindedBlockDataProvider, BlindedBlockRelayer, SubmissionAuthenticator, API_KEY_HEADER,
+};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    pin::Pin,
+};
+use tokio::task::JoinHandle;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tonic::{transport::Server, Request, Response, Status};
+use tracing::{error, info, trace};
+
+mod proto {
+    tonic::include_proto!("relay");
+}
+
+use proto::{
+    block_relay_server::{BlockRelay, BlockRelayServer},
+    SubmitBidRequest, SubmitBidResponse, SubscribeTopBidsRequest, TopBid,
+};
+
+impl From<&BidTrace> for TopBid {
+    fn from(bid_trace: &BidTrace) -> Self {
+        Self {
+            slot: bid_trace.slot,
+            parent_hash: bid_trace.parent_hash.to_vec(),
+            block_hash: bid_trace.block_hash.to_vec(),
+            builder_pubkey: bid_trace.builder_public_key.to_vec(),
+            proposer_pubkey: bid_trace.proposer_public_key.to_vec(),
+            proposer_fee_recipient: bid_trace.proposer_fee_recipient.to_vec(),
+            gas_limit: bid_trace.gas_limit,
+            gas_used: bid_trace.gas_used,
+            value: bid_trace.value.to_be_bytes::<32>().to_vec(),
+        }
+    }
+}
+
+struct Service {
+    relay: Relay,
+}
+
+#[tonic::async_trait]
+impl BlockRelay for Service {
+    async fn submit_bid(
+        &self,
+        request: Request<SubmitBidRequest>,
+    ) -> Result<Response<SubmitBidResponse>, Status> {
+        let signed_bid_submission =
+            SignedBidSubmission::from_ssz_bytes(&request.get_ref().signed_bid_submission)
+                .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let api_key = request.metadata().get(API_KEY_HEADER).and_then(|value| value.to_str().ok());
+        self.relay
+            .authenticate_submission(&signed_bid_submission.message().builder_public_key, api_key)
+            .map_err(|err| Status::unauthenticated(err.to_string()))?;
+        self.relay
+            .submit_bid(&signed_bid_submission)
+            .await
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        Ok(Response::new(SubmitBidResponse {}))
+    }
+
+    type SubscribeTopBidsStream = Pin<Box<dyn Stream<Item = Result<TopBid, Status>> + Send>>;
+
+    async fn subscribe_top_bids(
+        &self,
+        _request: Request<SubscribeTopBidsRequest>,
+    ) -> Result<Response<Self::SubscribeTopBidsStream>, Status> {
+        trace!("streaming top bids");
+        // NOTE: a slow subscriber misses top bids, rather than holding up the relay
+        let top_bids = BroadcastStream::new(self.relay.subscribe_top_bids())
+            .filter_map(|bid_trace| bid_trace.ok().map(|bid_trace| Ok(TopBid::from(&bid_trace))));
+        Ok(Response::new(Box::pin(top_bids)))
+    }
+}
+
+/// Serves block submission and top bid subscription for builders over gRPC
+pub fn spawn(host: Ipv4Addr, port: u16, relay: Relay) -> JoinHandle<()> {
+    let addr = SocketAddr::from((host, port));
+    let service = BlockRelayServer::new(Service { relay });
+    tokio::spawn(async move {
+        info!("gRPC endpoint listening at {addr}...");
+        if let Err(err) = Server::builder().add_service(service).serve(addr).await {
+            error!(%err, "error while serving gRPC endpoint");
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::{
+        crypto::SecretKey,
+        primitives::{Root, U256},
+        state_transition::Context,
+    };
+    use mev_rs::signer::{Config as SignerConfig, Signer};
+    use tonic::Code;
+
+    #[cfg(not(feature = "minimal-preset"))]
+    use beacon_api_client::mainnet::Client as ApiClient;
+    #[cfg(feature = "minimal-preset")]
+    use beacon_api_client::minimal::Client as ApiClient;
+
+    fn service() -> Service {
+        let beacon_node = ApiClient::new("http://127.0.0.1:0".parse().unwrap());
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let signer = Signer::new(SignerConfig::Local { secret_key }).unwrap();
+        let relay = Relay::new(
+            beacon_node,
+            signer,
+            vec![],
+            Context::for_mainnet(),
+            Root::default(),
+            Default::default(),
+        );
+        Service { relay }
+    }
+
+    #[test]
+    fn test_top_bid_from_bid_trace() {
+        let bid_trace = BidTrace {
+            slot: 12,
+            gas_limit: 30_000_000,
+            value: U256::from(1),
+            ..Default::default()
+        };
+        let top_bid = TopBid::from(&bid_trace);
+        assert_eq!(top_bid.slot, 12);
+        assert_eq!(top_bid.gas_limit, 30_000_000);
+        assert_eq!(top_bid.builder_pubkey.len(), 48);
+        assert_eq!(top_bid.block_hash.len(), 32);
+        let mut value = [0u8; 32];
+        value[31] = 1;
+        assert_eq!(top_bid.value, value.to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_submission_is_rejected() {
+        let request = Request::new(SubmitBidRequest { signed_bid_submission: vec![1, 2, 3] });
+        let status = service().submit_bid(request).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,459 @@
+use crate::{
+    relay::{withdrawals_root, Options, Relay},
+    timing::{AuctionTiming, Config as AuctionTimingConfig},
+};
+use beacon_api_client::{ValidatorStatus, ValidatorSummary};
+use ethereum_consensus::{
+    builder::ValidatorRegistration,
+    clock::duration_since_unix_epoch,
+    crypto::SecretKey,
+    phase0::Validator,
+    primitives::{BlsPublicKey, ExecutionAddress, Hash32, Root, Slot, ValidatorIndex, U256},
+    ssz::prelude::List,
+    state_transition::Context,
+    Error as ConsensusError, Fork,
+};
+use mev_rs::{
+    signer::{Config as SignerConfig, Signer},
+    signing::sign_builder_message,
+    types::{
+        block_submission, AuctionRequest, BidTrace, SignedBidSubmission, SignedBuilderBid,
+        SignedValidatorRegistration,
+    },
+    BlindedBlockProvider, BlindedBlockRelayer,
+};
+use parking_lot::Mutex;
+use std::{
+    collections::HashMap,
+    sync::atomic::{AtomicU64, Ordering},
+};
+use thiserror::Error;
+use url::Url;
+
+#[cfg(not(feature = "minimal-preset"))]
+use beacon_api_client::mainnet::Client as ApiClient;
+#[cfg(feature = "minimal-preset")]
+use beacon_api_client::minimal::Client as ApiClient;
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::{capella::mainnet as capella, deneb::mainnet as deneb};
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::{capella::minimal as capella, deneb::minimal as deneb};
+
+// NOTE: nothing listens here, so any request the relay makes to its beacon node fails right away
+const UNREACHABLE_BEACON_NODE_URL: &str = "http://127.0.0.1:0";
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("proposer with index {0} has not been registered with the harness")]
+    UnknownProposerIndex(ValidatorIndex),
+    #[error("proposer {0:?} has not been registered with the harness")]
+    UnknownProposer(BlsPublicKey),
+    #[error("harness can not build submissions for the {0:?} fork")]
+    UnsupportedFork(Fork),
+    #[error("too many withdrawals in payload attributes")]
+    TooManyWithdrawals,
+    #[error(transparent)]
+    Url(#[from] url::ParseError),
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    #[error(transparent)]
+    Relay(#[from] mev_rs::Error),
+}
+
+/// Payload attributes for an auction, as the beacon node would send them to the relay
+#[derive(Debug, Clone, Default)]
+pub struct PayloadAttributes {
+    pub proposer_index: ValidatorIndex,
+    pub proposal_slot: Slot,
+    pub parent_block_hash: Hash32,
+    pub parent_beacon_block_root: Root,
+    pub withdrawals: Vec<capella::Withdrawal>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    proposers: HashMap<ValidatorIndex, ValidatorRegistration>,
+    withdrawals: HashMap<AuctionRequest, Vec<capella::Withdrawal>>,
+}
+
+/// Runs a [`Relay`] in-process, without a beacon node or any network, so that builders can test
+/// their submissions against it
+///
+/// Auctions are opened by feeding the harness payload attributes directly, and are run without
+/// any timing bounds. The harness treats the UNIX epoch as genesis.
+pub struct RelayHarness {
+    relay: Relay,
+    context: Context,
+    state: Mutex<State>,
+    block_count: AtomicU64,
+}
+
+impl RelayHarness {
+    /// Create a relay accepting submissions from `accepted_builders`
+    pub fn new(context: Context, accepted_builders: Vec<BlsPublicKey>) -> Result<Self, Error> {
+        Self::with_options(context, accepted_builders, Default::default())
+    }
+
+    /// Create a relay accepting submissions from `accepted_builders`, configured with `options`
+    /// other than its auction timing
+    pub fn with_options(
+        context: Context,
+        accepted_builders: Vec<BlsPublicKey>,
+        options: Options,
+    ) -> Result<Self, Error> {
+        let beacon_node = ApiClient::new(Url::parse(UNREACHABLE_BEACON_NODE_URL).expect("valid"));
+        let secret_key = SecretKey::key_gen(&[1u8; 32])?;
+        let signer = Signer::new(SignerConfig::Local { secret_key })?;
+        let auction_timing = AuctionTimingConfig {
+            bid_submission_open_ms: None,
+            bid_submission_close_ms: None,
+            get_header_open_ms: None,
+            get_header_close_ms: None,
+            get_payload_close_ms: None,
+        };
+        let options = Options {
+            auction_timing: AuctionTiming::new(auction_timing, 0, context.seconds_per_slot),
+            ..options
+        };
+        let relay = Relay::new(
+            beacon_node,
+            signer,
+            accepted_builders,
+            context.clone(),
+            Root::default(),
+            options,
+        );
+        Ok(Self { relay, context, state: Default::default(), block_count: AtomicU64::new(0) })
+    }
+
+    /// The relay under test, e.g. to serve it with the builder APIs
+    pub fn relay(&self) -> &Relay {
+        &self.relay
+    }
+
+    /// Add the validator with `secret_key` to the validator set at `index` and register it with
+    /// the relay to receive `fee_recipient` in blocks with `gas_limit`
+    pub async fn register_proposer(
+        &self,
+        index: ValidatorIndex,
+        secret_key: &SecretKey,
+        fee_recipient: ExecutionAddress,
+        gas_limit: u64,
+    ) -> Result<(), Error> {
+        let public_key = secret_key.public_key();
+        self.relay.insert_validators(vec![ValidatorSummary {
+            index,
+            balance: 32_000_000_000,
+            status: ValidatorStatus::ActiveOngoing,
+            validator: Validator { public_key: public_key.clone(), ..Default::default() },
+        }]);
+        let message = ValidatorRegistration {
+            fee_recipient,
+            gas_limit,
+            timestamp: duration_since_unix_epoch().as_secs(),
+            public_key,
+        };
+        let signature = sign_builder_message(&message, secret_key, &self.context)?;
+        let registration = SignedValidatorRegistration { message: message.clone(), signature };
+        self.relay.register_validators(&[registration]).await?;
+        self.state.lock().proposers.insert(index, message);
+        Ok(())
+    }
+
+    /// Open an auction from `payload_attributes`, as if the beacon node had sent them
+    pub async fn open_auction(
+        &self,
+        payload_attributes: PayloadAttributes,
+    ) -> Result<AuctionRequest, Error> {
+        let PayloadAttributes {
+            proposer_index,
+            proposal_slot,
+            parent_block_hash,
+            parent_beacon_block_root,
+            withdrawals,
+        } = payload_attributes;
+        let public_key = self
+            .state
+            .lock()
+            .proposers
+            .get(&proposer_index)
+            .map(|registration| registration.public_key.clone())
+            .ok_or(Error::UnknownProposerIndex(proposer_index))?;
+        let auction_request = AuctionRequest {
+            slot: proposal_slot,
+            parent_hash: parent_block_hash.clone(),
+            public_key,
+        };
+        let withdrawals_root = match self.context.fork_for(proposal_slot) {
+            Fork::Bellatrix => None,
+            _ => Some(withdrawals_root(withdrawals.clone()).ok_or(Error::TooManyWithdrawals)?),
+        };
+        self.relay
+            .open_auction(
+                proposer_index,
+                proposal_slot,
+                parent_block_hash,
+                parent_beacon_block_root,
+                withdrawals_root,
+            )
+            .await?;
+        self.state.lock().withdrawals.insert(auction_request.clone(), withdrawals);
+        Ok(auction_request)
+    }
+
+    /// Build a submission of an empty block from `builder` for the auction of `auction_request`,
+    /// paying `value` to the proposer
+    ///
+    /// Each submission built has a unique block hash, though it is not the hash of a valid block.
+    pub fn build_submission(
+        &self,
+        builder: &SecretKey,
+        auction_request: &AuctionRequest,
+        value: U256,
+    ) -> Result<SignedBidSubmission, Error> {
+        let (registration, withdrawals) = {
+            let state = self.state.lock();
+            let registration = state
+                .proposers
+                .values()
+                .find(|registration| registration.public_key == auction_request.public_key)
+                .cloned()
+                .ok_or_else(|| Error::UnknownProposer(auction_request.public_key.clone()))?;
+            let withdrawals = state.withdrawals.get(auction_request).cloned().unwrap_or_default();
+            (registration, withdrawals)
+        };
+        let withdrawals = List::try_from(withdrawals).map_err(|_| Error::TooManyWithdrawals)?;
+
+        let block_count = self.block_count.fetch_add(1, Ordering::Relaxed);
+        let mut block_hash = [0u8; 32];
+        block_hash[..8].copy_from_slice(&auction_request.slot.to_le_bytes());
+        block_hash[8..16].copy_from_slice(&block_count.to_le_bytes());
+        let block_hash = Hash32::try_from(block_hash.as_ref()).expect("is 32 bytes");
+
+        let message = BidTrace {
+            slot: auction_request.slot,
+            parent_hash: auction_request.parent_hash.clone(),
+            block_hash: block_hash.clone(),
+            builder_public_key: builder.public_key(),
+            proposer_public_key: auction_request.public_key.clone(),
+            proposer_fee_recipient: registration.fee_recipient.clone(),
+            gas_limit: registration.gas_limit,
+            gas_used: 0,
+            value,
+        };
+        let signature = sign_builder_message(&message, builder, &self.context)?;
+        let timestamp = auction_request.slot * self.context.seconds_per_slot;
+        let signed_submission = match self.context.fork_for(auction_request.slot) {
+            Fork::Capella => {
+                let execution_payload = capella::ExecutionPayload {
+                    parent_hash: auction_request.parent_hash.clone(),
+                    fee_recipient: registration.fee_recipient,
+                    gas_limit: registration.gas_limit,
+                    timestamp,
+                    block_hash,
+                    withdrawals,
+                    ..Default::default()
+                };
+                SignedBidSubmission::Capella(block_submission::capella::SignedBidSubmission {
+                    message,
+                    execution_payload,
+                    signature,
+                })
+            }
+            Fork::Deneb => {
+                let execution_payload = deneb::ExecutionPayload {
+                    parent_hash: auction_request.parent_hash.clone(),
+                    fee_recipient: registration.fee_recipient,
+                    gas_limit: registration.gas_limit,
+                    timestamp,
+                    block_hash,
+                    withdrawals,
+                    ..Default::default()
+                };
+                SignedBidSubmission::Deneb(block_submission::deneb::SignedBidSubmission {
+                    message,
+                    execution_payload,
+                    blobs_bundle: Default::default(),
+                    signature,
+                })
+            }
+            fork => return Err(Error::UnsupportedFork(fork)),
+        };
+        Ok(signed_submission)
+    }
+
+    /// Submit `signed_submission` to the relay, as a builder would
+    pub async fn submit_bid(&self, signed_submission: &SignedBidSubmission) -> Result<(), Error> {
+        Ok(self.relay.submit_bid(signed_submission).await?)
+    }
+
+    /// Fetch the bid the relay would serve to the proposer of `auction_request`
+    pub async fn get_header(
+        &self,
+        auction_request: &AuctionRequest,
+    ) -> Result<SignedBuilderBid, Error> {
+        Ok(self.relay.fetch_best_bid(auction_request).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulator::{Config as SimulatorConfig, Simulator};
+    use axum::{routing::post, Json, Router};
+    use std::{net::TcpListener, time::Duration};
+
+    // Serve a simulation endpoint that fails any submission with `failing_value`
+    fn spawn_simulator(failing_value: U256) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let failing_value = failing_value.to_string();
+        let handler = move |Json(request): Json<serde_json::Value>| async move {
+            let response = match request["method"].as_str() {
+                Some("eth_syncing") => serde_json::json!({ "id": 1, "result": false }),
+                _ if request["params"][0]["message"]["value"] == failing_value.as_str() => {
+                    serde_json::json!({ "id": 1, "error": { "message": "invalid block" } })
+                }
+                _ => serde_json::json!({ "id": 1, "result": null }),
+            };
+            Json(response)
+        };
+        let router = Router::new().route("/", post(handler));
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_relay_harness() {
+        let context = Context::for_mainnet();
+        let builder = SecretKey::key_gen(&[2u8; 32]).unwrap();
+        let proposer = SecretKey::key_gen(&[3u8; 32]).unwrap();
+        let harness = RelayHarness::new(context, vec![builder.public_key()]).unwrap();
+        harness
+            .register_proposer(7, &proposer, ExecutionAddress::default(), 30_000_000)
+            .await
+            .unwrap();
+
+        // NOTE: a slot after the Deneb fork on mainnet
+        let proposal_slot = 9_000_000;
+        let auction_request = harness
+            .open_auction(PayloadAttributes {
+                proposer_index: 7,
+                proposal_slot,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let signed_submission =
+            harness.build_submission(&builder, &auction_request, U256::from(100)).unwrap();
+        harness.submit_bid(&signed_submission).await.unwrap();
+
+        let signed_builder_bid = harness.get_header(&auction_request).await.unwrap();
+        assert_eq!(signed_builder_bid.message.value(), U256::from(100));
+    }
+
+    #[tokio::test]
+    async fn test_resubmission_with_changed_value_is_validated() {
+        let context = Context::for_mainnet();
+        let builder = SecretKey::key_gen(&[2u8; 32]).unwrap();
+        let proposer = SecretKey::key_gen(&[3u8; 32]).unwrap();
+        let options = Options { max_gas_limit: Some(30_000_000), ..Default::default() };
+        let harness =
+            RelayHarness::with_options(context.clone(), vec![builder.public_key()], options)
+                .unwrap();
+        harness
+            .register_proposer(7, &proposer, ExecutionAddress::default(), 30_000_000)
+            .await
+            .unwrap();
+        let auction_request = harness
+            .open_auction(PayloadAttributes {
+                proposer_index: 7,
+                proposal_slot: 9_000_000,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let signed_submission =
+            harness.build_submission(&builder, &auction_request, U256::from(100)).unwrap();
+        harness.submit_bid(&signed_submission).await.unwrap();
+        // NOTE: a repeated submission reuses the outcome of the first one
+        harness.submit_bid(&signed_submission).await.unwrap();
+
+        // NOTE: the same block hash, re-signed with a higher value and a gas limit out of bounds
+        let SignedBidSubmission::Deneb(mut submission) = signed_submission else {
+            panic!("submission is for deneb")
+        };
+        submission.message.value = U256::from(1_000);
+        submission.message.gas_limit = 60_000_000;
+        submission.execution_payload.gas_limit = 60_000_000;
+        submission.signature =
+            sign_builder_message(&submission.message, &builder, &context).unwrap();
+        let tampered = SignedBidSubmission::Deneb(submission);
+        assert!(harness.submit_bid(&tampered).await.is_err());
+
+        let signed_builder_bid = harness.get_header(&auction_request).await.unwrap();
+        assert_eq!(signed_builder_bid.message.value(), U256::from(100));
+    }
+
+    #[tokio::test]
+    async fn test_failed_optimistic_submission_falls_back_to_next_best_bid() {
+        let context = Context::for_mainnet();
+        let honest = SecretKey::key_gen(&[2u8; 32]).unwrap();
+        let faulty = SecretKey::key_gen(&[4u8; 32]).unwrap();
+        let proposer = SecretKey::key_gen(&[3u8; 32]).unwrap();
+        let simulator = Simulator::new(SimulatorConfig {
+            endpoints: vec![spawn_simulator(U256::from(1_000))],
+            max_concurrent_simulations: None,
+        })
+        .unwrap();
+        let collateral = U256::from(10_000);
+        let options = Options {
+            simulator: Some(simulator),
+            builder_collateral: HashMap::from([
+                (honest.public_key(), collateral),
+                (faulty.public_key(), collateral),
+            ]),
+            ..Default::default()
+        };
+        let harness = RelayHarness::with_options(
+            context,
+            vec![honest.public_key(), faulty.public_key()],
+            options,
+        )
+        .unwrap();
+        harness
+            .register_proposer(7, &proposer, ExecutionAddress::default(), 30_000_000)
+            .await
+            .unwrap();
+        let auction_request = harness
+            .open_auction(PayloadAttributes {
+                proposer_index: 7,
+                proposal_slot: 9_000_000,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let honest_submission =
+            harness.build_submission(&honest, &auction_request, U256::from(100)).unwrap();
+        harness.submit_bid(&honest_submission).await.unwrap();
+        // NOTE: accepted on the strength of the builder's collateral, before it is simulated
+        let faulty_submission =
+            harness.build_submission(&faulty, &auction_request, U256::from(1_000)).unwrap();
+        harness.submit_bid(&faulty_submission).await.unwrap();
+
+        // NOTE: once the simulation fails, the top bid falls back to the next best bid
+        let mut value = U256::ZERO;
+        for _ in 0..50 {
+            value = harness.get_header(&auction_request).await.unwrap().message.value();
+            if value == U256::from(100) {
+                break
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(value, U256::from(100));
+
+        // NOTE: the failed simulation is recorded, so the same submission is not accepted again
+        assert!(harness.submit_bid(&faulty_submission).await.is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use ethereum_consensus::primitives::Slot;
+use mev_rs::RelayError;
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::fmt;
+
+/// Bounds on how far the relay's view of the chain may fall behind before it is degraded
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Number of slots the relay can go without payload attributes for the next slot
+    pub max_payload_attributes_delay_slots: Slot,
+    /// Number of slots the relay can go without the beacon node's head advancing; allows for a
+    /// run of missed slots
+    pub max_head_delay_slots: Slot,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { max_payload_attributes_delay_slots: 2, max_head_delay_slots: 8 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Degradation {
+    StalePayloadAttributes { slots: Slot },
+    StaleHead { slots: Slot },
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StalePayloadAttributes { slots } => {
+                write!(f, "no payload attributes from the beacon node in {slots} slots")
+            }
+            Self::StaleHead { slots } => {
+                write!(f, "head of the beacon node has not advanced in {slots} slots")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    // slot the relay started tracking the chain at
+    started_at: Option<Slot>,
+    latest_proposal_slot: Option<Slot>,
+    head_block_number: u64,
+    head_advanced_at: Option<Slot>,
+    degradation: Option<Degradation>,
+}
+
+/// Tracks whether the beacon node is keeping the relay up to date with the chain
+#[derive(Debug, Default)]
+pub struct Health {
+    config: Config,
+    state: Mutex<State>,
+}
+
+impl Health {
+    pub fn new(config: Config) -> Self {
+        Self { config, state: Default::default() }
+    }
+
+    pub fn on_payload_attributes(&self, proposal_slot: Slot, parent_block_number: u64) {
+        let mut state = self.state.lock();
+        if state.latest_proposal_slot.map_or(true, |slot| proposal_slot > slot) {
+            state.latest_proposal_slot = Some(proposal_slot);
+        }
+        if parent_block_number > state.head_block_number {
+            state.head_block_number = parent_block_number;
+            state.head_advanced_at = Some(proposal_slot.saturating_sub(1));
+        }
+    }
+
+    /// Check the relay's view of the chain at the start of `slot`, returning the new degradation
+    /// if it changed
+    pub fn on_slot(&self, slot: Slot) -> Option<Option<Degradation>> {
+        let mut state = self.state.lock();
+        let started_at = *state.started_at.get_or_insert(slot);
+        // NOTE: the payload attributes for `slot` are expected during the previous slot
+        let attributes_delay =
+            slot.saturating_sub(state.latest_proposal_slot.unwrap_or(started_at));
+        let head_delay = slot.saturating_sub(state.head_advanced_at.unwrap_or(started_at));
+        let degradation = if attributes_delay > self.config.max_payload_attributes_delay_slots {
+            Some(Degradation::StalePayloadAttributes { slots: attributes_delay })
+        } else if head_delay > self.config.max_head_delay_slots {
+            Some(Degradation::StaleHead { slots: head_delay })
+        } else {
+            None
+        };
+        let changed = degradation.is_some() != state.degradation.is_some();
+        state.degradation = degradation;
+        changed.then_some(degradation)
+    }
+
+    pub fn degradation(&self) -> Option<Degradation> {
+        self.state.lock().degradation
+    }
+
+    /// Returns an error if the relay is degraded
+    pub fn check(&self) -> Result<(), RelayError> {
+        match self.degradation() {
+            Some(degradation) => Err(RelayError::Degraded(degradation.to_string())),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health() {
+        let health = Health::new(Config::default());
+        assert_eq!(health.on_slot(10), None);
+        health.on_payload_attributes(11, 100);
+        assert_eq!(health.on_slot(11), None);
+        assert!(health.check().is_ok());
+
+        // payload attributes stop arriving
+        assert_eq!(health.on_slot(13), None);
+        assert_eq!(
+            health.on_slot(14),
+            Some(Some(Degradation::StalePayloadAttributes { slots: 3 }))
+        );
+        assert!(health.check().is_err());
+
+        // payload attributes resume, but the head does not advance
+        health.on_payload_attributes(15, 100);
+        assert_eq!(health.on_slot(15), Some(None));
+        for slot in 16..=18 {
+            health.on_payload_attributes(slot + 1, 100);
+            assert_eq!(health.on_slot(slot), None);
+        }
+        health.on_payload_attributes(20, 100);
+        assert_eq!(health.on_slot(19), Some(Some(Degradation::StaleHead { slots: 9 })));
+
+        health.on_payload_attributes(21, 101);
+        assert_eq!(health.on_slot(20), Some(None));
+    }
+}
@@ -1,5 +1,49 @@
+mod alerts;
+mod analytics;
+mod api_keys;
+mod archive;
 mod auction_context;
+mod bid_adjustment;
+mod bid_index;
+mod blobs;
+mod cluster;
+mod collateral;
+mod constraints;
+mod export;
+mod fallback;
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "harness")]
+mod harness;
+mod health;
+mod metrics;
+mod mock;
+mod payload_requests;
+mod payload_store;
+mod rate_limit;
 mod relay;
+mod reload;
+mod reputation;
+mod retention;
 mod service;
+mod simulator;
+mod timing;
 
+pub use alerts::Config as DeliveryAlertsConfig;
+pub use api_keys::{ApiKey, Config as ApiKeysConfig};
+pub use archive::Config as ArchiveConfig;
+pub use bid_adjustment::Config as BidAdjustmentConfig;
+pub use cluster::Config as ClusterConfig;
+pub use export::Config as ExportConfig;
+pub use fallback::Config as FallbackBuilderConfig;
+#[cfg(feature = "harness")]
+pub use harness::{Error as HarnessError, PayloadAttributes, RelayHarness};
+pub use health::Config as HealthConfig;
+pub use mev_rs::signer::{Config as SignerConfig, RemoteConfig as RemoteSignerConfig};
+pub use mock::{Config as MockConfig, MockRelay};
+pub use rate_limit::Config as HeaderRateLimitConfig;
+pub use relay::Relay;
+pub use retention::Config as RetentionConfig;
 pub use service::{Config, Service};
+pub use simulator::Config as SimulationConfig;
+pub use timing::Config as AuctionTimingConfig;
@@ -0,0 +1,107 @@
+use axum::{extract::State, routing::get, Router};
+use ethereum_consensus::primitives::{BlsPublicKey, Slot, U256};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+const SUBMISSIONS_RECEIVED: &str = "relay_submissions_received_total";
+const SUBMISSIONS_ACCEPTED: &str = "relay_submissions_accepted_total";
+const SUBMISSIONS_REJECTED: &str = "relay_submissions_rejected_total";
+const SUBMISSION_VALIDATION_DURATION: &str = "relay_submission_validation_duration_seconds";
+const SUBMISSION_SLOT_OFFSET: &str = "relay_submission_slot_offset_seconds";
+const SIMULATION_DURATION: &str = "relay_simulation_duration_seconds";
+const TOP_BID_VALUE: &str = "relay_top_bid_value_wei";
+const TOP_BID_SLOT: &str = "relay_top_bid_slot";
+const GET_HEADER_DURATION: &str = "relay_get_header_duration_seconds";
+const GET_PAYLOAD_DURATION: &str = "relay_get_payload_duration_seconds";
+const PAYLOADS_DELIVERED: &str = "relay_payloads_delivered_total";
+const REQUESTS_REJECTED: &str = "relay_requests_rejected_total";
+const DEGRADED: &str = "relay_degraded";
+const DELIVERY_FAILURES: &str = "relay_delivery_failures_total";
+
+pub fn on_submission_received(builder_public_key: &BlsPublicKey) {
+    counter!(SUBMISSIONS_RECEIVED, "builder" => builder_public_key.to_string()).increment(1);
+}
+
+pub fn on_submission_accepted(builder_public_key: &BlsPublicKey) {
+    counter!(SUBMISSIONS_ACCEPTED, "builder" => builder_public_key.to_string()).increment(1);
+}
+
+pub fn on_submission_rejected(builder_public_key: &BlsPublicKey) {
+    counter!(SUBMISSIONS_REJECTED, "builder" => builder_public_key.to_string()).increment(1);
+}
+
+// NOTE: includes simulation against an execution node, if the relay is configured to do so.
+pub fn on_submission_validated(duration: Duration) {
+    histogram!(SUBMISSION_VALIDATION_DURATION).record(duration.as_secs_f64());
+}
+
+// NOTE: offsets are negative for submissions received before the start of their slot.
+pub fn on_submission_timing(builder_public_key: &BlsPublicKey, slot_offset_secs: f64) {
+    histogram!(SUBMISSION_SLOT_OFFSET, "builder" => builder_public_key.to_string())
+        .record(slot_offset_secs);
+}
+
+pub fn on_simulation(builder_public_key: &BlsPublicKey, duration: Duration) {
+    histogram!(SIMULATION_DURATION, "builder" => builder_public_key.to_string())
+        .record(duration.as_secs_f64());
+}
+
+pub fn on_top_bid(slot: Slot, value: U256) {
+    gauge!(TOP_BID_SLOT).set(slot as f64);
+    gauge!(TOP_BID_VALUE).set(u128::try_from(value).unwrap_or(u128::MAX) as f64);
+}
+
+pub fn on_get_header(duration: Duration) {
+    histogram!(GET_HEADER_DURATION).record(duration.as_secs_f64());
+}
+
+pub fn on_get_payload(duration: Duration) {
+    histogram!(GET_PAYLOAD_DURATION).record(duration.as_secs_f64());
+}
+
+pub fn on_payload_delivered() {
+    counter!(PAYLOADS_DELIVERED).increment(1);
+}
+
+pub fn on_request_rejected(reason: &'static str) {
+    counter!(REQUESTS_REJECTED, "reason" => reason).increment(1);
+}
+
+pub fn on_degraded(is_degraded: bool) {
+    gauge!(DEGRADED).set(if is_degraded { 1.0 } else { 0.0 });
+}
+
+pub fn on_delivery_failure(reason: &'static str) {
+    counter!(DELIVERY_FAILURES, "reason" => reason).increment(1);
+}
+
+async fn handle_get_metrics(State(handle): State<PrometheusHandle>) -> String {
+    handle.render()
+}
+
+/// Installs the global metrics recorder and serves the collected metrics in the Prometheus
+/// text format at `/metrics` on the given `host` and `port`
+pub fn spawn(host: Ipv4Addr, port: u16) -> Option<JoinHandle<()>> {
+    let handle = match PrometheusBuilder::new().install_recorder() {
+        Ok(handle) => handle,
+        Err(err) => {
+            error!(%err, "could not install metrics recorder");
+            return None
+        }
+    };
+    let router = Router::new().route("/metrics", get(handle_get_metrics)).with_state(handle);
+    let addr = SocketAddr::from((host, port));
+    let server = axum::Server::bind(&addr).serve(router.into_make_service());
+    Some(tokio::spawn(async move {
+        info!(%addr, "serving metrics...");
+        if let Err(err) = server.await {
+            error!(%err, "error while serving metrics")
+        }
+    }))
+}
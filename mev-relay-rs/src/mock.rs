@@ -0,0 +1,333 @@
+use crate::auction_context::{sign_builder_bid, to_header};
+use async_trait::async_trait;
+use ethereum_consensus::{
+    builder::ValidatorRegistration,
+    networks::Network,
+    primitives::{BlsPublicKey, BlsSignature, Hash32, Slot, U256},
+    state_transition::Context,
+    Fork,
+};
+use mev_rs::{
+    blinded_block_provider::Server as BlindedBlockProviderServer,
+    signer::Signer,
+    types::{
+        auction_contents, AuctionContents, AuctionRequest, ExecutionPayload,
+        SignedBlindedBeaconBlock, SignedBuilderBid, SignedValidatorRegistration,
+    },
+    BlindedBlockProvider, Error, RelayError,
+};
+use parking_lot::Mutex;
+use serde::Deserialize;
+use std::{collections::HashMap, ops::Deref, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::{
+    bellatrix::mainnet as bellatrix, capella::mainnet as capella, deneb::mainnet as deneb,
+};
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::{
+    bellatrix::minimal as bellatrix, capella::minimal as capella, deneb::minimal as deneb,
+};
+
+// Number of slots to keep served payloads around for
+const PAYLOAD_LIFETIME_SLOTS: Slot = 64;
+
+/// Behavior of the relay when run in mock mode
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Value in wei of every bid served
+    pub bid_value_wei: U256,
+    /// Latency added before responding to each builder API, in milliseconds
+    pub register_validators_delay_ms: u64,
+    pub get_header_delay_ms: u64,
+    pub get_payload_delay_ms: u64,
+    /// Serve bids with an invalid signature
+    pub invalid_signature: bool,
+    /// Return payloads with a block hash that does not match the header served for them
+    pub wrong_block_hash: bool,
+    /// Never return the payload for a signed blinded block
+    pub withhold_payload: bool,
+}
+
+// Returns a block hash unique to the `auction_request`; it is not the hash of a valid block.
+fn mock_block_hash(auction_request: &AuctionRequest) -> Hash32 {
+    let mut block_hash = [0u8; 32];
+    block_hash[..8].copy_from_slice(&auction_request.slot.to_le_bytes());
+    block_hash[8..].copy_from_slice(&auction_request.parent_hash.as_ref()[8..]);
+    Hash32::try_from(block_hash.as_ref()).expect("is 32 bytes")
+}
+
+fn corrupt_block_hash(execution_payload: &mut ExecutionPayload) {
+    let block_hash = match execution_payload {
+        ExecutionPayload::Bellatrix(payload) => &mut payload.block_hash,
+        ExecutionPayload::Capella(payload) => &mut payload.block_hash,
+        ExecutionPayload::Deneb(payload) => &mut payload.block_hash,
+    };
+    let mut bytes = block_hash.to_vec();
+    bytes[0] ^= 0xff;
+    *block_hash = Hash32::try_from(bytes.as_ref()).expect("is 32 bytes");
+}
+
+/// An in-memory relay serving deterministic bids, with optional latency and faults, for testing
+/// consensus clients and `mev-boost-rs` against
+#[derive(Clone)]
+pub struct MockRelay(Arc<Inner>);
+
+impl Deref for MockRelay {
+    type Target = Inner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub struct Inner {
+    signer: Signer,
+    context: Context,
+    config: Config,
+    registrations: Mutex<HashMap<BlsPublicKey, ValidatorRegistration>>,
+    payloads: Mutex<HashMap<Hash32, (Slot, ExecutionPayload)>>,
+}
+
+impl MockRelay {
+    pub fn new(signer: Signer, context: Context, config: Config) -> Self {
+        let inner = Inner {
+            signer,
+            context,
+            config,
+            registrations: Default::default(),
+            payloads: Default::default(),
+        };
+        Self(Arc::new(inner))
+    }
+
+    /// Serves a [`MockRelay`] configured from the `[relay]` section of the configuration
+    pub fn spawn(network: Network, config: crate::Config) -> Result<JoinHandle<()>, Error> {
+        let context = Context::try_from(network)?;
+        let signer =
+            Signer::new(config.signer).map_err(|err| RelayError::SigningFailed(err.to_string()))?;
+        info!(?config.mock, "running relay in mock mode");
+        let relay = Self::new(signer, context, config.mock);
+        Ok(BlindedBlockProviderServer::new(config.host, config.port, relay).spawn())
+    }
+
+    fn build_payload(
+        &self,
+        auction_request: &AuctionRequest,
+        registration: &ValidatorRegistration,
+    ) -> Result<ExecutionPayload, Error> {
+        let parent_hash = auction_request.parent_hash.clone();
+        let block_hash = mock_block_hash(auction_request);
+        let fee_recipient = registration.fee_recipient.clone();
+        let gas_limit = registration.gas_limit;
+        let block_number = auction_request.slot;
+        let execution_payload = match self.context.fork_for(auction_request.slot) {
+            Fork::Bellatrix => ExecutionPayload::Bellatrix(bellatrix::ExecutionPayload {
+                parent_hash,
+                fee_recipient,
+                block_number,
+                gas_limit,
+                block_hash,
+                ..Default::default()
+            }),
+            Fork::Capella => ExecutionPayload::Capella(capella::ExecutionPayload {
+                parent_hash,
+                fee_recipient,
+                block_number,
+                gas_limit,
+                block_hash,
+                ..Default::default()
+            }),
+            Fork::Deneb => ExecutionPayload::Deneb(deneb::ExecutionPayload {
+                parent_hash,
+                fee_recipient,
+                block_number,
+                gas_limit,
+                block_hash,
+                ..Default::default()
+            }),
+            fork => {
+                return Err(Error::InvalidFork { expected: Fork::Deneb, provided: fork });
+            }
+        };
+        Ok(execution_payload)
+    }
+}
+
+#[async_trait]
+impl BlindedBlockProvider for MockRelay {
+    async fn register_validators(
+        &self,
+        registrations: &[SignedValidatorRegistration],
+    ) -> Result<(), Error> {
+        tokio::time::sleep(Duration::from_millis(self.config.register_validators_delay_ms)).await;
+        let mut state = self.registrations.lock();
+        for registration in registrations {
+            let registration = &registration.message;
+            state.insert(registration.public_key.clone(), registration.clone());
+        }
+        Ok(())
+    }
+
+    async fn fetch_best_bid(
+        &self,
+        auction_request: &AuctionRequest,
+    ) -> Result<SignedBuilderBid, Error> {
+        tokio::time::sleep(Duration::from_millis(self.config.get_header_delay_ms)).await;
+        let registration =
+            self.registrations.lock().get(&auction_request.public_key).cloned().ok_or_else(
+                || RelayError::ValidatorNotRegistered(auction_request.public_key.clone()),
+            )?;
+        let execution_payload = self.build_payload(auction_request, &registration)?;
+        let header = to_header(&execution_payload)?;
+        let mut signed_builder_bid =
+            sign_builder_bid(header, None, self.config.bid_value_wei, &self.signer, &self.context)
+                .await?;
+        if self.config.invalid_signature {
+            signed_builder_bid.signature = BlsSignature::default();
+        }
+
+        let slot = auction_request.slot;
+        let block_hash = execution_payload.block_hash().clone();
+        let mut payloads = self.payloads.lock();
+        payloads.retain(|_, (payload_slot, _)| *payload_slot + PAYLOAD_LIFETIME_SLOTS >= slot);
+        payloads.insert(block_hash, (slot, execution_payload));
+        Ok(signed_builder_bid)
+    }
+
+    async fn open_bid(
+        &self,
+        signed_block: &SignedBlindedBeaconBlock,
+    ) -> Result<AuctionContents, Error> {
+        tokio::time::sleep(Duration::from_millis(self.config.get_payload_delay_ms)).await;
+        let block = signed_block.message();
+        let body = block.body();
+        let block_hash = body.execution_payload_header().block_hash().clone();
+        if self.config.withhold_payload {
+            warn!(%block_hash, "withholding payload");
+            return Err(RelayError::PayloadNotUploaded(block_hash).into())
+        }
+        let (_, mut execution_payload) = self
+            .payloads
+            .lock()
+            .get(&block_hash)
+            .cloned()
+            .ok_or(RelayError::InvalidSignedBlindedBeaconBlock)?;
+        if self.config.wrong_block_hash {
+            corrupt_block_hash(&mut execution_payload);
+        }
+        let auction_contents = match execution_payload {
+            execution_payload @ ExecutionPayload::Bellatrix(_) => {
+                AuctionContents::Bellatrix(execution_payload)
+            }
+            execution_payload @ ExecutionPayload::Capella(_) => {
+                AuctionContents::Capella(execution_payload)
+            }
+            execution_payload @ ExecutionPayload::Deneb(_) => {
+                AuctionContents::Deneb(auction_contents::deneb::AuctionContents {
+                    execution_payload,
+                    blobs_bundle: Default::default(),
+                })
+            }
+        };
+        Ok(auction_contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::crypto::SecretKey;
+    use mev_rs::{
+        signer::Config as SignerConfig, signing::verify_signed_builder_data,
+        types::ExecutionPayloadHeader,
+    };
+    use std::time::Instant;
+
+    // NOTE: a slot after the Deneb fork on mainnet
+    const SLOT: Slot = 9_000_000;
+
+    fn mock_relay(config: Config) -> (MockRelay, BlsPublicKey) {
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let public_key = secret_key.public_key();
+        let signer = Signer::new(SignerConfig::Local { secret_key }).unwrap();
+        (MockRelay::new(signer, Context::for_mainnet(), config), public_key)
+    }
+
+    // Register a proposer with `relay`, returning a request for an auction it proposes in
+    async fn register(relay: &MockRelay) -> AuctionRequest {
+        let public_key = SecretKey::key_gen(&[2u8; 32]).unwrap().public_key();
+        let message = ValidatorRegistration {
+            public_key: public_key.clone(),
+            gas_limit: 30_000_000,
+            ..Default::default()
+        };
+        let registration = SignedValidatorRegistration { message, signature: Default::default() };
+        relay.register_validators(&[registration]).await.unwrap();
+        let parent_hash = Hash32::try_from([1u8; 32].as_ref()).unwrap();
+        AuctionRequest { slot: SLOT, parent_hash, public_key }
+    }
+
+    fn signed_block(signed_builder_bid: &SignedBuilderBid) -> SignedBlindedBeaconBlock {
+        let ExecutionPayloadHeader::Deneb(execution_payload_header) =
+            signed_builder_bid.message.header().clone()
+        else {
+            panic!("header is for deneb")
+        };
+        let message = deneb::BlindedBeaconBlock {
+            slot: SLOT,
+            body: deneb::BlindedBeaconBlockBody { execution_payload_header, ..Default::default() },
+            ..Default::default()
+        };
+        let signature = Default::default();
+        SignedBlindedBeaconBlock::Deneb(deneb::SignedBlindedBeaconBlock { message, signature })
+    }
+
+    #[tokio::test]
+    async fn test_mock_relay() {
+        let config = Config {
+            bid_value_wei: U256::from(100),
+            register_validators_delay_ms: 20,
+            ..Default::default()
+        };
+        let (relay, public_key) = mock_relay(config);
+        let unregistered = AuctionRequest { slot: SLOT, ..Default::default() };
+        assert!(relay.fetch_best_bid(&unregistered).await.is_err());
+
+        let start = Instant::now();
+        let auction_request = register(&relay).await;
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let signed_builder_bid = relay.fetch_best_bid(&auction_request).await.unwrap();
+        let SignedBuilderBid { message, signature } = &signed_builder_bid;
+        assert_eq!(message.value(), U256::from(100));
+        assert!(verify_signed_builder_data(message, &public_key, signature, &relay.context).is_ok());
+        let block_hash = message.header().block_hash();
+        assert_eq!(block_hash, &mock_block_hash(&auction_request));
+
+        let auction_contents = relay.open_bid(&signed_block(&signed_builder_bid)).await.unwrap();
+        assert_eq!(auction_contents.execution_payload().block_hash(), block_hash);
+    }
+
+    #[tokio::test]
+    async fn test_mock_relay_faults() {
+        let config =
+            Config { invalid_signature: true, wrong_block_hash: true, ..Default::default() };
+        let (relay, _) = mock_relay(config);
+        let auction_request = register(&relay).await;
+        let signed_builder_bid = relay.fetch_best_bid(&auction_request).await.unwrap();
+        assert_eq!(signed_builder_bid.signature, BlsSignature::default());
+        let auction_contents = relay.open_bid(&signed_block(&signed_builder_bid)).await.unwrap();
+        let block_hash = signed_builder_bid.message.header().block_hash();
+        assert_ne!(auction_contents.execution_payload().block_hash(), block_hash);
+
+        let config = Config { withhold_payload: true, ..Default::default() };
+        let (relay, _) = mock_relay(config);
+        let auction_request = register(&relay).await;
+        let signed_builder_bid = relay.fetch_best_bid(&auction_request).await.unwrap();
+        assert!(relay.open_bid(&signed_block(&signed_builder_bid)).await.is_err());
+    }
+}
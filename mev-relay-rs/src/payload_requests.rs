@@ -0,0 +1,79 @@
+use ethereum_consensus::primitives::BlsPublicKey;
+use mev_rs::types::BidTrace;
+use std::collections::HashMap;
+use tracing::{debug, warn};
+use url::Url;
+
+/// Tells builders that a proposer signed the header of one of their header-only bids, so they
+/// upload the payload for it right away if they have not yet
+#[derive(Clone, Default)]
+pub struct PayloadRequester {
+    client: reqwest::Client,
+    // URL to `POST` the bid trace of each signed header to, for each builder
+    urls: HashMap<BlsPublicKey, Url>,
+}
+
+impl PayloadRequester {
+    pub fn new(urls: HashMap<BlsPublicKey, String>) -> Result<Self, url::ParseError> {
+        let urls = urls
+            .into_iter()
+            .map(|(builder_public_key, url)| Ok((builder_public_key, url.parse()?)))
+            .collect::<Result<_, url::ParseError>>()?;
+        Ok(Self { client: reqwest::Client::new(), urls })
+    }
+
+    async fn post(&self, url: Url, bid_trace: &BidTrace) -> Result<(), reqwest::Error> {
+        self.client.post(url).json(bid_trace).send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    /// Request the payload for `bid_trace` from its builder on a separate task, if the builder
+    /// has a URL configured
+    pub fn request(&self, bid_trace: &BidTrace) {
+        let Some(url) = self.urls.get(&bid_trace.builder_public_key).cloned() else { return };
+        let requester = self.clone();
+        let bid_trace = bid_trace.clone();
+        tokio::spawn(async move {
+            let (slot, block_hash) = (bid_trace.slot, &bid_trace.block_hash);
+            match requester.post(url, &bid_trace).await {
+                Ok(()) => debug!(slot, %block_hash, "requested payload from builder"),
+                Err(err) => {
+                    warn!(%err, slot, %block_hash, "could not request payload from builder")
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::State, routing::post, Json, Router};
+    use ethereum_consensus::crypto::SecretKey;
+    use std::net::TcpListener;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn test_request_payload() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (requests_tx, mut requests) = mpsc::unbounded_channel();
+        let handler = |State(requests_tx): State<mpsc::UnboundedSender<BidTrace>>,
+                       Json(bid_trace): Json<BidTrace>| async move {
+            requests_tx.send(bid_trace).unwrap();
+        };
+        let router = Router::new().route("/payloads", post(handler)).with_state(requests_tx);
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+
+        let builder_public_key = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let urls = HashMap::from([(builder_public_key.clone(), format!("http://{addr}/payloads"))]);
+        let requester = PayloadRequester::new(urls).unwrap();
+
+        // NOTE: builders without a URL are not notified
+        requester.request(&BidTrace { slot: 7, ..Default::default() });
+        let bid_trace = BidTrace { slot: 8, builder_public_key, ..Default::default() };
+        requester.request(&bid_trace);
+        assert_eq!(requests.recv().await.unwrap(), bid_trace);
+    }
+}
@@ -0,0 +1,89 @@
+use ethereum_consensus::primitives::{Hash32, Slot};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+// Upper bound on the payloads kept while finality is delayed, beyond which the oldest are dropped.
+const MAX_STORED_PAYLOADS: usize = 256;
+
+/// Payloads delivered to proposers, along with their blobs, keyed by block hash.
+///
+/// Payloads are kept until their slot is finalized, so a proposer asking again for a block it has
+/// already signed, e.g. after a late `getPayload` or once the block is reorged out, is served the
+/// same payload and blobs even after the auction for the slot has closed.
+#[derive(Debug)]
+pub struct PayloadStore<T>(Mutex<HashMap<Hash32, (Slot, T)>>);
+
+impl<T> Default for PayloadStore<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+impl<T: Clone> PayloadStore<T> {
+    pub fn insert(&self, slot: Slot, block_hash: Hash32, payload: T) {
+        let mut payloads = self.0.lock();
+        payloads.insert(block_hash, (slot, payload));
+        while payloads.len() > MAX_STORED_PAYLOADS {
+            let oldest = payloads
+                .iter()
+                .min_by_key(|(_, (slot, _))| *slot)
+                .map(|(block_hash, _)| block_hash.clone())
+                .expect("store is not empty");
+            payloads.remove(&oldest);
+        }
+    }
+
+    pub fn get(&self, block_hash: &Hash32) -> Option<T> {
+        self.0.lock().get(block_hash).map(|(_, payload)| payload.clone())
+    }
+
+    /// Drop payloads for any slot before `finalized_slot`, returning the number dropped
+    pub fn on_finalized(&self, finalized_slot: Slot) -> usize {
+        let mut payloads = self.0.lock();
+        let count = payloads.len();
+        payloads.retain(|_, (slot, _)| *slot >= finalized_slot);
+        count - payloads.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_hash(id: u64) -> Hash32 {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&id.to_be_bytes());
+        Hash32::try_from(bytes.as_ref()).unwrap()
+    }
+
+    #[test]
+    fn test_payloads_kept_until_finalized() {
+        let store = PayloadStore::default();
+        store.insert(10, block_hash(1), 1);
+        store.insert(11, block_hash(2), 2);
+        // NOTE: a block for the same slot, after the first was reorged out
+        store.insert(11, block_hash(3), 3);
+
+        assert_eq!(store.get(&block_hash(2)), Some(2));
+        assert_eq!(store.get(&block_hash(3)), Some(3));
+        assert_eq!(store.on_finalized(11), 1);
+        assert_eq!(store.get(&block_hash(1)), None);
+        assert_eq!(store.get(&block_hash(2)), Some(2));
+        assert_eq!(store.len(), 2);
+    }
+
+    #[test]
+    fn test_oldest_payloads_dropped_when_full() {
+        let store = PayloadStore::default();
+        for slot in 0..=MAX_STORED_PAYLOADS as Slot {
+            store.insert(slot, block_hash(slot), slot);
+        }
+        assert_eq!(store.len(), MAX_STORED_PAYLOADS);
+        assert_eq!(store.get(&block_hash(0)), None);
+        assert_eq!(store.get(&block_hash(1)), Some(1));
+    }
+}
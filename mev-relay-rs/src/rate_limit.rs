@@ -0,0 +1,101 @@
+use ethereum_consensus::primitives::BlsPublicKey;
+use mev_rs::RelayError;
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
+use std::{collections::HashMap, net::IpAddr};
+
+/// Limits on the `getHeader` requests served in each slot
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Maximum number of requests served for a single proposer from a single IP address
+    pub requests_per_proposer: usize,
+    /// Maximum number of requests served to a single IP address, across all proposers
+    pub requests_per_ip: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { requests_per_proposer: 8, requests_per_ip: 32 }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Counts {
+    by_ip: HashMap<IpAddr, usize>,
+    by_proposer: HashMap<(BlsPublicKey, IpAddr), usize>,
+}
+
+/// Tracks the `getHeader` requests made in the current slot
+// NOTE: requests for a proposer are counted per IP address so that third parties polling for a
+// proposer's public key can not exhaust the allowance of the proposer itself
+#[derive(Debug, Default)]
+pub struct HeaderRateLimiter {
+    config: RwLock<Config>,
+    counts: Mutex<Counts>,
+}
+
+impl HeaderRateLimiter {
+    pub fn new(config: Config) -> Self {
+        Self { config: RwLock::new(config), counts: Default::default() }
+    }
+
+    /// Replace the limits enforced from now on, keeping the counts for the current slot
+    pub fn reconfigure(&self, config: Config) {
+        *self.config.write() = config;
+    }
+
+    /// Count a request for a header for `proposer_public_key` from `peer`, returning an error if
+    /// it exceeds any limit
+    pub fn admit(
+        &self,
+        proposer_public_key: &BlsPublicKey,
+        peer: IpAddr,
+    ) -> Result<(), RelayError> {
+        let config = self.config.read().clone();
+        let mut counts = self.counts.lock();
+        let ip_count = counts.by_ip.get(&peer).copied().unwrap_or_default();
+        if ip_count >= config.requests_per_ip {
+            return Err(RelayError::TooManyHeaderRequests(peer.to_string()))
+        }
+        let key = (proposer_public_key.clone(), peer);
+        let proposer_count = counts.by_proposer.get(&key).copied().unwrap_or_default();
+        if proposer_count >= config.requests_per_proposer {
+            return Err(RelayError::TooManyHeaderRequests(format!("{proposer_public_key:?}")))
+        }
+        counts.by_ip.insert(peer, ip_count + 1);
+        counts.by_proposer.insert(key, proposer_count + 1);
+        Ok(())
+    }
+
+    /// Reset all counts at the start of a new slot
+    pub fn on_slot(&self) {
+        let mut counts = self.counts.lock();
+        counts.by_ip.clear();
+        counts.by_proposer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_header_rate_limits() {
+        let limiter =
+            HeaderRateLimiter::new(Config { requests_per_proposer: 2, requests_per_ip: 3 });
+        let proposer = BlsPublicKey::default();
+        let peer = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let other_peer = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+
+        assert!(limiter.admit(&proposer, peer).is_ok());
+        assert!(limiter.admit(&proposer, peer).is_ok());
+        assert!(limiter.admit(&proposer, peer).is_err());
+        // other peers polling for the same proposer do not share its allowance
+        assert!(limiter.admit(&proposer, other_peer).is_ok());
+
+        limiter.on_slot();
+        assert!(limiter.admit(&proposer, peer).is_ok());
+    }
+}
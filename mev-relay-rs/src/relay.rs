@@ -1,34 +1,77 @@
-use crate::auction_context::AuctionContext;
+use crate::{
+    alerts::{Alerter, DeliveryAlert, DeliveryFailure},
+    analytics,
+    api_keys::{ApiKeys, Config as ApiKeysConfig},
+    archive::Archiver,
+    auction_context::{AuctionContext, Bid, DeferredBid},
+    bid_adjustment::Config as BidAdjustment,
+    bid_index::BidIndex,
+    blobs::verify_blobs_bundle,
+    cluster::{Cluster, Event as ClusterEvent},
+    collateral::Collateral,
+    constraints::{
+        commitments_before, verify_constraints, verify_preconf_commitments, ReceivedCommitment,
+    },
+    export::Exporter,
+    fallback::FallbackBuilder,
+    health::{Config as HealthConfig, Health},
+    metrics,
+    payload_requests::PayloadRequester,
+    payload_store::PayloadStore,
+    rate_limit::{Config as HeaderRateLimits, HeaderRateLimiter},
+    reload::Settings,
+    reputation::BuilderReputations,
+    retention::Config as RetentionConfig,
+    simulator::{Error as SimulationError, SimulationRequest, Simulator},
+    timing::AuctionTiming,
+};
 use async_trait::async_trait;
-use beacon_api_client::{BroadcastValidation, PayloadAttributesEvent, SubmitSignedBeaconBlock};
+use axum::http::StatusCode;
+#[cfg(feature = "harness")]
+use beacon_api_client::ValidatorSummary;
+use beacon_api_client::{
+    ApiError, BlockId, BroadcastValidation, Error as BeaconApiError, PayloadAttributesEvent,
+    StateId, SubmitSignedBeaconBlock,
+};
 use ethereum_consensus::{
     clock::{duration_since_unix_epoch, get_current_unix_time_in_nanos},
-    crypto::SecretKey,
-    primitives::{BlsPublicKey, Epoch, Root, Slot, U256},
-    ssz::prelude::HashTreeRoot,
+    primitives::{BlsPublicKey, Epoch, Hash32, Root, Slot, ValidatorIndex, U256},
+    ssz::prelude::{HashTreeRoot, List},
     state_transition::Context,
     Error as ConsensusError, Fork,
 };
+use futures::future::join_all;
 use mev_rs::{
     blinded_block_relayer::{BlockSubmissionFilter, DeliveredPayloadFilter},
+    signer::Signer,
     signing::{compute_consensus_domain, verify_signed_builder_data, verify_signed_data},
     types::{
-        block_submission::data_api::{PayloadTrace, SubmissionTrace},
-        AuctionContents, AuctionRequest, BidTrace, ExecutionPayload, ExecutionPayloadHeader,
-        ProposerSchedule, SignedBidSubmission, SignedBlindedBeaconBlock, SignedBuilderBid,
+        block_submission::{
+            self,
+            data_api::{AuctionAnalytics, BuilderCollateral, PayloadTrace, SubmissionTrace},
+        },
+        ActiveRegistration, AuctionContents, AuctionRequest, BidTrace, ExecutionPayload,
+        ExecutionPayloadHeader, PreconfCommitment, ProposerSchedule, RegistrationStatus,
+        SignedBidSubmission, SignedBidTrace, SignedBlindedBeaconBlock, SignedBuilderBid,
+        SignedConstraints, SignedHeaderSubmission, SignedPreconfCommitment,
         SignedValidatorRegistration,
     },
-    BlindedBlockDataProvider, BlindedBlockProvider, BlindedBlockRelayer, Error, ProposerScheduler,
-    RelayError, ValidatorRegistry,
+    verify_gas_limit_adjustment, BlindedBlockDataProvider, BlindedBlockProvider,
+    BlindedBlockRelayer, Error, HeaderRequestLimiter, HealthCheck, ProposerScheduler,
+    RejectedRequest, RelayError, RequestRejectionObserver, SubmissionAuthenticator,
+    ValidatorRegistry,
 };
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
+use serde::Deserialize;
 use std::{
-    cmp::Ordering,
+    borrow::Cow,
     collections::{HashMap, HashSet},
+    net::IpAddr,
     ops::Deref,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
+use tokio::sync::broadcast;
 use tracing::{debug, error, info, trace, warn};
 
 #[cfg(not(feature = "minimal-preset"))]
@@ -52,7 +95,132 @@ use ethereum_consensus::{
 
 // Sets the lifetime of an auction with respect to its proposal slot.
 const AUCTION_LIFETIME_SLOTS: Slot = 1;
-const HISTORY_LOOK_BEHIND_EPOCHS: Epoch = 4;
+// Upper bounds on the relay's in-memory state, beyond which new entries are dropped.
+const MAX_OPEN_AUCTIONS: usize = 64;
+const MAX_OTHER_SUBMISSIONS_PER_AUCTION: usize = 4096;
+const MAX_SUBMISSION_OUTCOMES_PER_SLOT: usize = 8192;
+const MAX_PRECONF_COMMITMENTS_PER_BUILDER: usize = 64;
+// Maximum time to wait for a builder to upload the payload for a header-only bid once the proposer
+// has committed to it.
+const DEFERRED_PAYLOAD_TIMEOUT: Duration = Duration::from_secs(1);
+// Number of events buffered for each subscriber before it starts missing events.
+const DELIVERED_PAYLOAD_EVENTS_CAPACITY: usize = 64;
+const TOP_BID_EVENTS_CAPACITY: usize = 1024;
+// Delays between attempts to publish a delivered block to a beacon node that could not be reached.
+const PUBLISH_RETRY_DELAYS: [Duration; 3] =
+    [Duration::from_millis(50), Duration::from_millis(100), Duration::from_millis(200)];
+// Number of slots after a payload is delivered to check that its block is on chain.
+const DELIVERY_CHECK_DELAY_SLOTS: Slot = 2;
+
+// Fields of an execution block declared in both full payloads and their headers, so that
+// submissions of either can be validated against their bid trace.
+trait ExecutionBlockFields {
+    fn gas_limit(&self) -> u64;
+    fn gas_used(&self) -> u64;
+    fn parent_hash(&self) -> &Hash32;
+    fn block_hash(&self) -> &Hash32;
+    fn timestamp(&self) -> u64;
+    // `None` before the Capella fork
+    fn withdrawals_root(&self) -> Option<Root>;
+}
+
+impl ExecutionBlockFields for ExecutionPayload {
+    fn gas_limit(&self) -> u64 {
+        ExecutionPayload::gas_limit(self)
+    }
+
+    fn gas_used(&self) -> u64 {
+        ExecutionPayload::gas_used(self)
+    }
+
+    fn parent_hash(&self) -> &Hash32 {
+        ExecutionPayload::parent_hash(self)
+    }
+
+    fn block_hash(&self) -> &Hash32 {
+        ExecutionPayload::block_hash(self)
+    }
+
+    fn timestamp(&self) -> u64 {
+        ExecutionPayload::timestamp(self)
+    }
+
+    fn withdrawals_root(&self) -> Option<Root> {
+        match self {
+            ExecutionPayload::Bellatrix(_) => None,
+            ExecutionPayload::Capella(payload) => payload.withdrawals.hash_tree_root().ok(),
+            ExecutionPayload::Deneb(payload) => payload.withdrawals.hash_tree_root().ok(),
+        }
+    }
+}
+
+impl ExecutionBlockFields for ExecutionPayloadHeader {
+    fn gas_limit(&self) -> u64 {
+        ExecutionPayloadHeader::gas_limit(self)
+    }
+
+    fn gas_used(&self) -> u64 {
+        ExecutionPayloadHeader::gas_used(self)
+    }
+
+    fn parent_hash(&self) -> &Hash32 {
+        ExecutionPayloadHeader::parent_hash(self)
+    }
+
+    fn block_hash(&self) -> &Hash32 {
+        ExecutionPayloadHeader::block_hash(self)
+    }
+
+    fn timestamp(&self) -> u64 {
+        ExecutionPayloadHeader::timestamp(self)
+    }
+
+    fn withdrawals_root(&self) -> Option<Root> {
+        match self {
+            ExecutionPayloadHeader::Bellatrix(_) => None,
+            ExecutionPayloadHeader::Capella(header) => Some(header.withdrawals_root),
+            ExecutionPayloadHeader::Deneb(header) => Some(header.withdrawals_root),
+        }
+    }
+}
+
+// Withdrawals from the payload attributes sent by the beacon node, which are only present from
+// the Capella fork
+#[derive(Deserialize)]
+struct PayloadAttributesWithdrawals {
+    #[serde(default)]
+    withdrawals: Option<Vec<capella::Withdrawal>>,
+}
+
+fn compute_withdrawals_root(payload_attributes: &serde_json::Value) -> Option<Root> {
+    let PayloadAttributesWithdrawals { withdrawals } =
+        PayloadAttributesWithdrawals::deserialize(payload_attributes).ok()?;
+    withdrawals_root(withdrawals?)
+}
+
+pub(crate) fn withdrawals_root(withdrawals: Vec<capella::Withdrawal>) -> Option<Root> {
+    let withdrawals =
+        List::<capella::Withdrawal, { capella::MAX_WITHDRAWALS_PER_PAYLOAD }>::try_from(
+            withdrawals,
+        )
+        .ok()?;
+    withdrawals.hash_tree_root().ok()
+}
+
+// Returns `true` if the beacon node refused a request, rather than failing to handle it.
+fn is_rejection(err: &BeaconApiError) -> bool {
+    matches!(err, BeaconApiError::Api(ApiError::ErrorMessage { code, .. }) if code.is_client_error())
+}
+
+// Record when a submission for a block was received relative to the start of the block's slot.
+fn record_submission_timing(
+    builder_public_key: &BlsPublicKey,
+    block: &impl ExecutionBlockFields,
+    receive_duration: Duration,
+) {
+    let slot_offset = receive_duration.as_secs_f64() - block.timestamp() as f64;
+    metrics::on_submission_timing(builder_public_key, slot_offset);
+}
 
 fn validate_header_equality(
     local_header: &ExecutionPayloadHeader,
@@ -209,6 +377,45 @@ fn verify_blinded_block_signature(
     .map_err(Into::into)
 }
 
+/// Optional components to extend the core functionality of the [`Relay`]
+#[derive(Default)]
+pub struct Options {
+    /// Additional beacon nodes to publish unblinded blocks to
+    pub broadcast_beacon_nodes: Vec<ApiClient>,
+    /// Archive for delivered payloads
+    pub archiver: Option<Archiver>,
+    /// Sends alerts for payloads that fail to be delivered
+    pub alerter: Option<Alerter>,
+    /// Tells builders when a proposer signs the header of one of their header-only bids
+    pub payload_requester: PayloadRequester,
+    /// Simulates submissions against an execution node before accepting them
+    pub simulator: Option<Simulator>,
+    /// Bids below this value are accepted but never served to proposers
+    pub min_bid_value: U256,
+    /// Submissions with a gas limit outside these bounds are rejected
+    pub min_gas_limit: Option<u64>,
+    pub max_gas_limit: Option<u64>,
+    /// If provided, only these proposers may register with and get bids from the relay
+    pub allowed_proposers: Option<HashSet<BlsPublicKey>>,
+    /// Collateral credited to builders; bids covered by a builder's collateral are accepted
+    /// before they are simulated
+    pub builder_collateral: HashMap<BlsPublicKey, U256>,
+    /// Builds a block from a connected execution node when no builder has submitted a bid
+    pub fallback_builder: Option<FallbackBuilder>,
+    /// Adjustment applied to the value of every bid served to proposers
+    pub bid_adjustment: BidAdjustment,
+    /// Limits on the `getHeader` requests served in each slot
+    pub header_rate_limits: HeaderRateLimits,
+    /// API keys issued to builders, to accompany their submissions
+    pub builder_api_keys: ApiKeysConfig,
+    /// Windows in each slot during which submissions and requests from proposers are served
+    pub auction_timing: AuctionTiming,
+    /// How far the beacon node can fall behind before the relay stops accepting submissions
+    pub health: HealthConfig,
+    /// Coordinates with the other nodes of a relay run across several processes
+    pub cluster: Option<Cluster>,
+}
+
 #[derive(Clone)]
 pub struct Relay(Arc<Inner>);
 
@@ -221,17 +428,82 @@ impl Deref for Relay {
 }
 
 pub struct Inner {
-    secret_key: SecretKey,
-    public_key: BlsPublicKey,
+    signer: Signer,
     validator_registry: ValidatorRegistry,
     proposer_scheduler: ProposerScheduler,
-    builder_registry: HashSet<BlsPublicKey>,
+    builder_registry: RwLock<HashSet<BlsPublicKey>>,
+    bids: BidIndex,
+    builder_reputations: BuilderReputations,
+    collateral: Collateral,
     beacon_node: ApiClient,
+    // additional beacon nodes to publish unblinded blocks to
+    broadcast_beacon_nodes: Vec<ApiClient>,
+    archiver: Option<Archiver>,
+    alerter: Option<Alerter>,
+    payload_requester: PayloadRequester,
+    simulator: Option<Simulator>,
+    fallback_builder: Option<FallbackBuilder>,
+    bid_adjustment: BidAdjustment,
+    header_rate_limiter: HeaderRateLimiter,
+    api_keys: ApiKeys,
+    auction_timing: AuctionTiming,
+    health: Health,
+    cluster: Option<Cluster>,
+    delivered_payload_events: broadcast::Sender<PayloadTrace>,
+    // payloads delivered to proposers, kept by block hash until finalized
+    delivered_blocks: PayloadStore<Arc<AuctionContext>>,
+    top_bid_events: broadcast::Sender<BidTrace>,
+    min_bid_value: RwLock<U256>,
+    min_gas_limit: Option<u64>,
+    max_gas_limit: Option<u64>,
+    allowed_proposers: Option<HashSet<BlsPublicKey>>,
     context: Context,
     state: Mutex<State>,
     genesis_validators_root: Root,
 }
 
+#[derive(Debug, Clone)]
+struct OpenAuction {
+    parent_beacon_block_root: Root,
+    // root of the withdrawals the payload must include, if known from the payload attributes
+    withdrawals_root: Option<Root>,
+    // gas limit of the parent execution block, if it could be fetched from the beacon node
+    parent_gas_limit: Option<u64>,
+}
+
+// How a submission inserted as a bid was simulated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Simulation {
+    // simulated before the bid was inserted, or not at all without a simulator
+    Complete,
+    // simulated after the bid was inserted, on the strength of the builder's collateral
+    Optimistic,
+}
+
+#[derive(Debug, Clone)]
+enum SubmissionOutcome {
+    Accepted,
+    Rejected(String),
+}
+
+impl SubmissionOutcome {
+    // Only record outcomes that would not change if the same block was submitted again.
+    fn from_result<T>(result: &Result<T, Error>) -> Option<Self> {
+        match result {
+            Ok(..) => Some(Self::Accepted),
+            Err(Error::Relay(
+                err @ (RelayError::InvalidBlobsBundleLength { .. } |
+                RelayError::InvalidBlobCommitments |
+                RelayError::InvalidBlobProofs |
+                RelayError::GasLimitOutOfBounds { .. } |
+                RelayError::InvalidGasLimitAdjustment { .. } |
+                RelayError::SimulationFailed(..)),
+            )) => Some(Self::Rejected(err.to_string())),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct State {
     // contains validator public keys that have been updated since we last refreshed
@@ -239,41 +511,114 @@ struct State {
     outstanding_validator_updates: HashSet<BlsPublicKey>,
 
     // auction state
-    open_auctions: HashSet<AuctionRequest>,
-    auctions: HashMap<AuctionRequest, Arc<AuctionContext>>,
-    // keeps set of all submissions that are _NOT_ the current best bid.
-    // the current best bid is stored in `auctions`.
+    open_auctions: HashMap<AuctionRequest, OpenAuction>,
+    // keeps set of all submissions that have been replaced by a later submission from the same
+    // builder; the latest submission from each builder is stored in the relay's `BidIndex`.
     other_submissions: HashMap<AuctionRequest, HashSet<AuctionContext>>,
     delivered_payloads: HashMap<AuctionRequest, Arc<AuctionContext>>,
+    // bid traces of delivered payloads, signed by the relay
+    signed_bid_traces: HashMap<AuctionRequest, SignedBidTrace>,
+    // offset from the start of the slot of the proposer's request for each delivered payload
+    get_payload_offsets: HashMap<AuctionRequest, i64>,
+    // auctions where a bid was served to the proposer
+    served_auctions: HashSet<AuctionRequest>,
+    // outcome of each submission, by the hash tree root of the whole signed submission, so
+    // repeated submissions can be short-circuited
+    submission_outcomes: HashMap<Slot, HashMap<Root, SubmissionOutcome>>,
+    // inclusion constraints signed by the proposer of each slot
+    constraints: HashMap<Slot, SignedConstraints>,
+    // transactions builders have committed to include in their blocks for each slot
+    preconf_commitments: HashMap<Slot, Vec<ReceivedCommitment>>,
+    // hashes of the blocks whose payloads passed simulation in each slot, so a delivered block the
+    // beacon node rejects is only attributed to its builder if the payload was never validated
+    simulated_blocks: HashMap<Slot, HashSet<Hash32>>,
 }
 
 impl Relay {
     pub fn new(
         beacon_node: ApiClient,
-        secret_key: SecretKey,
+        signer: Signer,
         accepted_builders: Vec<BlsPublicKey>,
         context: Context,
         genesis_validators_root: Root,
+        options: Options,
     ) -> Self {
-        let public_key = secret_key.public_key();
+        let Options {
+            broadcast_beacon_nodes,
+            archiver,
+            alerter,
+            payload_requester,
+            simulator,
+            min_bid_value,
+            min_gas_limit,
+            max_gas_limit,
+            allowed_proposers,
+            builder_collateral,
+            fallback_builder,
+            bid_adjustment,
+            header_rate_limits,
+            builder_api_keys,
+            auction_timing,
+            health,
+            cluster,
+        } = options;
         let slots_per_epoch = context.slots_per_epoch;
         let validator_registry = ValidatorRegistry::new(beacon_node.clone(), slots_per_epoch);
         let proposer_scheduler = ProposerScheduler::new(beacon_node.clone(), slots_per_epoch);
         let inner = Inner {
-            secret_key,
-            public_key,
+            signer,
             validator_registry,
             proposer_scheduler,
-            builder_registry: HashSet::from_iter(accepted_builders),
+            builder_registry: RwLock::new(HashSet::from_iter(accepted_builders)),
+            bids: Default::default(),
+            builder_reputations: Default::default(),
+            collateral: Collateral::new(builder_collateral),
             beacon_node,
+            broadcast_beacon_nodes,
+            archiver,
+            alerter,
+            payload_requester,
+            simulator,
+            fallback_builder,
+            bid_adjustment,
+            header_rate_limiter: HeaderRateLimiter::new(header_rate_limits),
+            api_keys: ApiKeys::new(builder_api_keys),
+            auction_timing,
+            health: Health::new(health),
+            cluster,
+            delivered_payload_events: broadcast::channel(DELIVERED_PAYLOAD_EVENTS_CAPACITY).0,
+            delivered_blocks: Default::default(),
+            top_bid_events: broadcast::channel(TOP_BID_EVENTS_CAPACITY).0,
+            min_bid_value: RwLock::new(min_bid_value),
+            min_gas_limit,
+            max_gas_limit,
+            allowed_proposers,
             context,
             state: Default::default(),
             genesis_validators_root,
         };
-        info!(public_key = %inner.public_key, "relay initialized");
+        info!(public_key = %inner.signer.public_key(), "relay initialized");
         Self(Arc::new(inner))
     }
 
+    // Add validators to the known validator set directly, rather than from the beacon node.
+    #[cfg(feature = "harness")]
+    pub(crate) fn insert_validators(&self, summaries: Vec<ValidatorSummary>) {
+        self.validator_registry.insert_validators(summaries);
+    }
+
+    /// Apply `settings` from now on, without closing any open auctions
+    pub fn reload(&self, settings: Settings) {
+        let Settings { accepted_builders, min_bid_value, header_rate_limits, auction_timing } =
+            settings;
+        let builder_count = accepted_builders.len();
+        *self.builder_registry.write() = HashSet::from_iter(accepted_builders);
+        *self.min_bid_value.write() = min_bid_value;
+        self.header_rate_limiter.reconfigure(header_rate_limits);
+        self.auction_timing.reconfigure(auction_timing);
+        info!(builder_count, %min_bid_value, "reloaded relay settings");
+    }
+
     pub async fn on_epoch(&self, epoch: Epoch) {
         info!(epoch, "processing");
 
@@ -281,14 +626,206 @@ impl Relay {
             error!(%err, epoch, "could not update validator registry");
         }
         self.refresh_proposer_schedule(epoch).await;
+        self.clean_up(epoch);
+        self.prune_finalized_blocks().await;
+    }
+
+    // Drop delivered payloads once their blocks can no longer be reorged out.
+    async fn prune_finalized_blocks(&self) {
+        let checkpoints = match self.beacon_node.get_finality_checkpoints(StateId::Head).await {
+            Ok(checkpoints) => checkpoints,
+            Err(err) => {
+                warn!(%err, "could not fetch finality checkpoints; keeping delivered payloads");
+                return
+            }
+        };
+        let finalized_slot = checkpoints.finalized.epoch * self.context.slots_per_epoch;
+        let count = self.delivered_blocks.on_finalized(finalized_slot);
+        debug!(
+            finalized_slot,
+            count,
+            remaining = self.delivered_blocks.len(),
+            "dropped finalized payloads"
+        );
+    }
 
-        let retain_slot = epoch.checked_sub(HISTORY_LOOK_BEHIND_EPOCHS).unwrap_or_default() *
-            self.context.slots_per_epoch;
-        trace!(retain_slot, "pruning stale auctions");
+    // Drop any per-slot state for slots before `epoch`, in case it was missed by `on_slot`.
+    fn clean_up(&self, epoch: Epoch) {
+        let retain_slot =
+            (epoch * self.context.slots_per_epoch).saturating_sub(AUCTION_LIFETIME_SLOTS);
         let mut state = self.state.lock();
-        state.auctions.retain(|auction_request, _| auction_request.slot >= retain_slot);
-        state.other_submissions.retain(|auction_request, _| auction_request.slot >= retain_slot);
-        state.delivered_payloads.retain(|auction_request, _| auction_request.slot >= retain_slot);
+        state.open_auctions.retain(|auction_request, _| auction_request.slot >= retain_slot);
+        state.submission_outcomes.retain(|&slot, _| slot >= retain_slot);
+        state.constraints.retain(|&slot, _| slot >= retain_slot);
+        state.preconf_commitments.retain(|&slot, _| slot >= retain_slot);
+        state.simulated_blocks.retain(|&slot, _| slot >= retain_slot);
+        debug!(
+            epoch,
+            open_auctions = state.open_auctions.len(),
+            other_submissions = state.other_submissions.values().map(HashSet::len).sum::<usize>(),
+            delivered_payloads = state.delivered_payloads.len(),
+            submission_outcomes =
+                state.submission_outcomes.values().map(HashMap::len).sum::<usize>(),
+            constraints = state.constraints.len(),
+            preconf_commitments = state.preconf_commitments.values().map(Vec::len).sum::<usize>(),
+            "cleaned up relay state"
+        );
+    }
+
+    // Traces of all delivered payloads matching `filters`, in descending order of slot
+    fn delivered_payload_traces(&self, filters: &DeliveredPayloadFilter) -> Vec<PayloadTrace> {
+        let state = self.state.lock();
+        let mut traces = state
+            .delivered_payloads
+            .iter()
+            .map(|(auction_request, auction_context)| {
+                let get_payload_offset_ms = state.get_payload_offsets.get(auction_request).copied();
+                let trace = payload_trace_from_auction(auction_context, get_payload_offset_ms);
+                (auction_request, trace)
+            })
+            .filter(|(_, trace)| filters.matches(trace))
+            .collect::<Vec<_>>();
+        traces.sort_by(|a, b| a.0.cmp(b.0));
+        traces.into_iter().rev().map(|(_, trace)| trace).collect()
+    }
+
+    // Traces of all submissions matching `filters`, in descending order of slot and then receipt
+    fn block_submission_traces(&self, filters: &BlockSubmissionFilter) -> Vec<SubmissionTrace> {
+        let mut traces = self
+            .bids
+            .latest_bids()
+            .into_iter()
+            .filter_map(|(auction_request, bid)| match bid {
+                Bid::Full(auction_context) => {
+                    let trace = submission_trace_from_auction(&auction_context);
+                    Some((auction_request, trace))
+                }
+                Bid::Deferred(..) => None,
+            })
+            .collect::<Vec<_>>();
+        let state = self.state.lock();
+        let other_traces = state
+            .other_submissions
+            .iter()
+            .flat_map(|(auction_request, contexts)| {
+                contexts.iter().map(|auction_context| {
+                    let trace = submission_trace_from_auction(auction_context);
+                    (auction_request.clone(), trace)
+                })
+            })
+            .collect::<Vec<_>>();
+        traces.extend(other_traces);
+        traces.retain(|(_, trace)| filters.matches(trace));
+        // NOTE: sorted by slot and then receipt timestamp alone, in line with the cursor of the
+        // query, so that every entry of a slot is reached when paging through it
+        traces.sort_by_key(|(_, trace)| (trace.slot, trace.timestamp_ms));
+        traces.into_iter().rev().map(|(_, trace)| trace).collect()
+    }
+
+    /// Export the traces of delivered payloads and submissions for the slots in `epoch`
+    pub fn export(&self, epoch: Epoch, exporter: &Exporter) {
+        let slots_per_epoch = self.context.slots_per_epoch;
+        let min_slot = Some(epoch * slots_per_epoch);
+        let cursor = Some((epoch + 1) * slots_per_epoch - 1);
+        let delivered_payloads = self.delivered_payload_traces(&DeliveredPayloadFilter {
+            min_slot,
+            cursor,
+            ..Default::default()
+        });
+        let submissions = self.block_submission_traces(&BlockSubmissionFilter {
+            min_slot,
+            cursor,
+            ..Default::default()
+        });
+        exporter.export(epoch, &delivered_payloads, &submissions);
+    }
+
+    // Drop any data older than the windows given in `retention`, relative to `epoch`.
+    pub fn prune(&self, epoch: Epoch, retention: &RetentionConfig) {
+        let slots_per_epoch = self.context.slots_per_epoch;
+        let retain_slot = |epochs: Epoch| epoch.saturating_sub(epochs) * slots_per_epoch;
+
+        {
+            let bids_slot = retain_slot(retention.bids);
+            let submissions_slot = retain_slot(retention.submissions);
+            let delivered_payloads_slot = retain_slot(retention.delivered_payloads);
+            trace!(bids_slot, submissions_slot, delivered_payloads_slot, "pruning stale auctions");
+            self.bids.retain(bids_slot);
+            let mut state = self.state.lock();
+            state
+                .other_submissions
+                .retain(|auction_request, _| auction_request.slot >= submissions_slot);
+            state
+                .delivered_payloads
+                .retain(|auction_request, _| auction_request.slot >= delivered_payloads_slot);
+            state
+                .signed_bid_traces
+                .retain(|auction_request, _| auction_request.slot >= delivered_payloads_slot);
+            state
+                .get_payload_offsets
+                .retain(|auction_request, _| auction_request.slot >= delivered_payloads_slot);
+            state
+                .served_auctions
+                .retain(|auction_request| auction_request.slot >= delivered_payloads_slot);
+        }
+
+        if let Some(epochs) = retention.registrations {
+            // NOTE: registrations are refreshed with timestamps in nanoseconds
+            let window =
+                Duration::from_secs(epochs * slots_per_epoch * self.context.seconds_per_slot);
+            let retain_timestamp =
+                get_current_unix_time_in_nanos().saturating_sub(window.as_nanos());
+            let retain_timestamp = retain_timestamp.try_into().expect("fits in type");
+            let count = self.validator_registry.prune_registrations(retain_timestamp);
+            if count > 0 {
+                info!(count, epochs, "pruned stale validator registrations");
+            }
+        }
+    }
+
+    // Check the block of the payload delivered for `slot`, if any, made it on chain.
+    async fn verify_delivery(&self, slot: Slot) {
+        let auction_context = {
+            let state = self.state.lock();
+            state.delivered_payloads.iter().find_map(|(auction_request, auction_context)| {
+                (auction_request.slot == slot).then(|| auction_context.clone())
+            })
+        };
+        let Some(auction_context) = auction_context else { return };
+        let block_hash = match self.beacon_node.get_beacon_block(BlockId::Slot(slot)).await {
+            Ok(SignedBeaconBlock::Bellatrix(block)) => {
+                Some(block.message.body.execution_payload.block_hash)
+            }
+            Ok(SignedBeaconBlock::Capella(block)) => {
+                Some(block.message.body.execution_payload.block_hash)
+            }
+            Ok(SignedBeaconBlock::Deneb(block)) => {
+                Some(block.message.body.execution_payload.block_hash)
+            }
+            Ok(_) => return,
+            // NOTE: the beacon node has no block for an empty slot
+            Err(BeaconApiError::Api(ApiError::ErrorMessage { code, .. }))
+                if code == StatusCode::NOT_FOUND =>
+            {
+                None
+            }
+            Err(err) => {
+                warn!(%err, slot, "could not check delivered payload is on chain");
+                return
+            }
+        };
+        let bid_trace = auction_context.bid_trace();
+        if block_hash.as_ref() != Some(&bid_trace.block_hash) {
+            warn!(slot, block_hash = %bid_trace.block_hash, builder_public_key = %bid_trace.builder_public_key, "block of delivered payload is not on chain");
+            self.on_delivery_failure(bid_trace, DeliveryFailure::BlockMissing);
+        }
+    }
+
+    fn on_delivery_failure(&self, bid_trace: &BidTrace, failure: DeliveryFailure) {
+        metrics::on_delivery_failure(failure.as_str());
+        if let Some(alerter) = self.alerter.as_ref() {
+            alerter.alert(DeliveryAlert { failure, bid_trace: bid_trace.clone() });
+        }
     }
 
     async fn refresh_proposer_schedule(&self, epoch: Epoch) {
@@ -307,6 +844,24 @@ impl Relay {
     pub async fn on_slot(&self, slot: Slot) {
         info!(slot, "processing");
 
+        self.header_rate_limiter.on_slot();
+        self.api_keys.on_slot();
+
+        if let Some(degradation) = self.health.on_slot(slot) {
+            match degradation {
+                Some(degradation) => {
+                    warn!(%degradation, "relay is degraded; rejecting submissions")
+                }
+                None => info!("relay has recovered from degradation"),
+            }
+            metrics::on_degraded(degradation.is_some());
+        }
+
+        if let Some(delivery_slot) = slot.checked_sub(DELIVERY_CHECK_DELAY_SLOTS) {
+            let relay = self.clone();
+            tokio::spawn(async move { relay.verify_delivery(delivery_slot).await });
+        }
+
         // TODO: no reason to wait for slot boundary,
         // but likely want some more sophisticated channel machinery to dispatch updates
         let keys_to_refresh = {
@@ -324,33 +879,387 @@ impl Relay {
         let mut state = self.state.lock();
         state
             .open_auctions
-            .retain(|auction_request| auction_request.slot + AUCTION_LIFETIME_SLOTS >= slot);
+            .retain(|auction_request, _| auction_request.slot + AUCTION_LIFETIME_SLOTS >= slot);
+        state
+            .submission_outcomes
+            .retain(|&outcome_slot, _| outcome_slot + AUCTION_LIFETIME_SLOTS >= slot);
+        drop(state);
+
+        if let Some(simulator) = self.simulator.as_ref() {
+            simulator.on_slot(slot);
+        }
+        if let Some(fallback_builder) = self.fallback_builder.as_ref() {
+            fallback_builder.retain(slot);
+        }
     }
 
     // TODO: build tip context and support reorgs...
-    pub fn on_payload_attributes(&self, event: PayloadAttributesEvent) -> Result<(), Error> {
+    async fn fetch_gas_limit(&self, block_root: Root) -> Option<u64> {
+        let block = self
+            .beacon_node
+            .get_beacon_block(BlockId::Root(block_root))
+            .await
+            .inspect_err(|err| warn!(%err, %block_root, "could not fetch block"))
+            .ok()?;
+        match block {
+            SignedBeaconBlock::Bellatrix(block) => {
+                Some(block.message.body.execution_payload.gas_limit)
+            }
+            SignedBeaconBlock::Capella(block) => {
+                Some(block.message.body.execution_payload.gas_limit)
+            }
+            SignedBeaconBlock::Deneb(block) => Some(block.message.body.execution_payload.gas_limit),
+            _ => None,
+        }
+    }
+
+    pub async fn on_payload_attributes(&self, event: PayloadAttributesEvent) -> Result<(), Error> {
         trace!(?event, "processing payload attributes");
-        let proposer_public_key =
-            self.validator_registry.get_public_key(event.proposer_index).ok_or_else::<Error, _>(
-                || RelayError::UnknownValidatorIndex(event.proposer_index).into(),
-            )?;
+        self.health.on_payload_attributes(event.proposal_slot, event.parent_block_number);
+        let withdrawals_root = serde_json::to_value(&event.payload_attributes)
+            .ok()
+            .and_then(|payload_attributes| compute_withdrawals_root(&payload_attributes));
+        let auction_request = self
+            .open_auction(
+                event.proposer_index,
+                event.proposal_slot,
+                event.parent_block_hash.clone(),
+                event.parent_block_root,
+                withdrawals_root,
+            )
+            .await?;
+        if let (Some(auction_request), Some(fallback_builder)) =
+            (auction_request, self.fallback_builder.as_ref())
+        {
+            if let Err(err) =
+                self.start_fallback_build(fallback_builder, &auction_request, &event).await
+            {
+                warn!(%err, %auction_request, "could not start fallback build");
+            }
+        }
+        Ok(())
+    }
+
+    async fn start_fallback_build(
+        &self,
+        fallback_builder: &FallbackBuilder,
+        auction_request: &AuctionRequest,
+        event: &PayloadAttributesEvent,
+    ) -> Result<(), Error> {
+        let fee_recipient = self
+            .validator_registry
+            .get_signed_registration(&auction_request.public_key)
+            .map(|registration| registration.message.fee_recipient.clone())
+            .ok_or_else(|| {
+                RelayError::ValidatorNotRegistered(auction_request.public_key.clone())
+            })?;
+        let fork = self.context.fork_for(auction_request.slot);
+        let payload_attributes = serde_json::to_value(&event.payload_attributes)
+            .map_err(|err| RelayError::FallbackBuildFailed(err.to_string()))?;
+        fallback_builder
+            .start(
+                auction_request.clone(),
+                fork,
+                &fee_recipient,
+                &event.parent_block_root,
+                payload_attributes,
+            )
+            .await
+            .map_err(|err| RelayError::FallbackBuildFailed(err.to_string()))?;
+        Ok(())
+    }
+
+    // Open the auction for `proposal_slot` from the current head if the payload attributes for it
+    // have not been seen yet, in case the beacon node's event stream has dropped or lagged.
+    pub async fn derive_payload_attributes(&self, proposal_slot: Slot) -> Result<(), Error> {
+        {
+            let state = self.state.lock();
+            if state
+                .open_auctions
+                .keys()
+                .any(|auction_request| auction_request.slot == proposal_slot)
+            {
+                return Ok(())
+            }
+        }
+        let schedule = self.proposer_scheduler.get_proposal_schedule()?;
+        let Some(proposer_index) = schedule.iter().find_map(|schedule| {
+            (schedule.slot == proposal_slot).then_some(schedule.validator_index)
+        }) else {
+            // NOTE: no registered proposer for the slot, so there is no auction to run
+            return Ok(())
+        };
+
+        let parent_block_root = self.beacon_node.get_beacon_block_root(BlockId::Head).await?;
+        let block = self.beacon_node.get_beacon_block(BlockId::Root(parent_block_root)).await?;
+        let parent_block_hash = match block {
+            SignedBeaconBlock::Bellatrix(block) => block.message.body.execution_payload.block_hash,
+            SignedBeaconBlock::Capella(block) => block.message.body.execution_payload.block_hash,
+            SignedBeaconBlock::Deneb(block) => block.message.body.execution_payload.block_hash,
+            _ => return Ok(()),
+        };
+        warn!(proposal_slot, %parent_block_root, %parent_block_hash, "missing payload attributes; deriving from head");
+        // NOTE: the expected withdrawals are not known without the payload attributes, so
+        // submissions to a derived auction are only checked against them in simulation
+        self.open_auction(
+            proposer_index,
+            proposal_slot,
+            parent_block_hash,
+            parent_block_root,
+            None,
+        )
+        .await?;
+        Ok(())
+    }
+
+    // Returns the request for the auction if it was newly opened.
+    pub(crate) async fn open_auction(
+        &self,
+        proposer_index: ValidatorIndex,
+        proposal_slot: Slot,
+        parent_block_hash: Hash32,
+        parent_beacon_block_root: Root,
+        withdrawals_root: Option<Root>,
+    ) -> Result<Option<AuctionRequest>, Error> {
+        let proposer_public_key = self
+            .validator_registry
+            .get_public_key(proposer_index)
+            .ok_or_else::<Error, _>(|| RelayError::UnknownValidatorIndex(proposer_index).into())?;
+        if self.validate_allowed_proposer(&proposer_public_key).is_err() {
+            trace!(%proposer_public_key, "ignoring payload attributes for proposer not allowed");
+            return Ok(None)
+        }
         let auction_request = AuctionRequest {
-            slot: event.proposal_slot,
-            parent_hash: event.parent_block_hash,
+            slot: proposal_slot,
+            parent_hash: parent_block_hash,
             public_key: proposer_public_key,
         };
+        {
+            let state = self.state.lock();
+            let is_known = state.open_auctions.get(&auction_request).is_some_and(|auction| {
+                auction.parent_beacon_block_root == parent_beacon_block_root &&
+                    (withdrawals_root.is_none() || auction.withdrawals_root == withdrawals_root)
+            });
+            if is_known {
+                return Ok(None)
+            }
+        }
+        let parent_gas_limit = self.fetch_gas_limit(parent_beacon_block_root).await;
+        let mut state = self.state.lock();
+        if state.open_auctions.len() >= MAX_OPEN_AUCTIONS &&
+            !state.open_auctions.contains_key(&auction_request)
+        {
+            warn!(%auction_request, "too many open auctions; ignoring payload attributes");
+            return Ok(None)
+        }
+        state.open_auctions.insert(
+            auction_request.clone(),
+            OpenAuction { parent_beacon_block_root, withdrawals_root, parent_gas_limit },
+        );
+        Ok(Some(auction_request))
+    }
+
+    fn get_top_bid(&self, auction_request: &AuctionRequest) -> Option<Bid> {
+        self.bids.top_bid(auction_request)
+    }
+
+    // Wait for the builder of a deferred bid to upload the full payload, asking the builder for it
+    // in case it has not been uploaded yet.
+    async fn resolve_bid(
+        &self,
+        auction_request: &AuctionRequest,
+        bid: Bid,
+    ) -> Result<Arc<AuctionContext>, RelayError> {
+        let bid = match bid {
+            Bid::Full(auction_context) => return Ok(auction_context),
+            Bid::Deferred(bid) => bid,
+        };
+        let bid_trace = &bid.bid_trace;
+        let builder_public_key = &bid_trace.builder_public_key;
+        self.payload_requester.request(bid_trace);
+        if let Some(auction_context) = bid.payload(DEFERRED_PAYLOAD_TIMEOUT).await {
+            return Ok(auction_context)
+        }
+        warn!(%auction_request, %builder_public_key, block_hash = %bid_trace.block_hash, "builder did not upload payload for deferred bid");
+        self.builder_reputations.on_delivery_fault(builder_public_key);
+        self.collateral.debit(builder_public_key, bid_trace.value);
+        self.on_delivery_failure(bid_trace, DeliveryFailure::PayloadNotUploaded);
+        Err(RelayError::PayloadNotUploaded(bid_trace.block_hash.clone()))
+    }
+
+    // Build a bid from the fallback builder, if configured, for an auction no builder has bid in.
+    async fn build_fallback_bid(&self, auction_request: &AuctionRequest) -> Option<Bid> {
+        let fallback_builder = self.fallback_builder.as_ref()?;
+        let receive_duration = duration_since_unix_epoch();
+        let payload = match fallback_builder.build(auction_request).await {
+            Ok(payload) => payload?,
+            Err(err) => {
+                warn!(%err, %auction_request, "could not build fallback payload");
+                return None
+            }
+        };
+        // NOTE: a bid must carry a payload of the fork of its slot and the execution node is only
+        // driven to build Deneb payloads, so any other fork is refused rather than mislabelled
+        let fork = self.context.fork_for(auction_request.slot);
+        if fork != Fork::Deneb || !matches!(payload.execution_payload, ExecutionPayload::Deneb(_)) {
+            warn!(%fork, %auction_request, "fallback builder does not support fork");
+            return None
+        }
+        let execution_payload = &payload.execution_payload;
+        // NOTE: the execution node builds from its own mempool and gas limit, so its block is held
+        // to the same limits and constraints as a builder's
+        if let Err(err) = self.validate_gas_limit(auction_request, execution_payload.gas_limit()) {
+            warn!(%err, %auction_request, "fallback payload has invalid gas limit");
+            return None
+        }
+        if let Some(signed_constraints) = self.constraints_for(auction_request.slot) {
+            if let Err(err) = verify_constraints(&signed_constraints.message, execution_payload) {
+                warn!(%err, %auction_request, "fallback payload does not satisfy constraints");
+                return None
+            }
+        }
+        let message = BidTrace {
+            slot: auction_request.slot,
+            parent_hash: auction_request.parent_hash.clone(),
+            block_hash: execution_payload.block_hash().clone(),
+            builder_public_key: self.signer.public_key().clone(),
+            proposer_public_key: auction_request.public_key.clone(),
+            proposer_fee_recipient: execution_payload.fee_recipient().clone(),
+            gas_limit: execution_payload.gas_limit(),
+            gas_used: execution_payload.gas_used(),
+            value: payload.value,
+        };
+        let signature = self
+            .signer
+            .sign_builder_message(&message, &self.context)
+            .await
+            .inspect_err(|err| warn!(%err, "could not sign fallback submission"))
+            .ok()?;
+        let signed_submission =
+            SignedBidSubmission::Deneb(block_submission::deneb::SignedBidSubmission {
+                message,
+                execution_payload: payload.execution_payload,
+                blobs_bundle: payload.blobs_bundle,
+                signature,
+            });
+        info!(%auction_request, value = %payload.value, "serving bid from fallback builder");
+        self.insert_bid(auction_request.clone(), &signed_submission, receive_duration)
+            .await
+            .inspect_err(|err| warn!(%err, "could not insert fallback bid"))
+            .ok()?;
+        self.get_top_bid(auction_request)
+    }
+
+    fn validate_allowed_proposer(
+        &self,
+        proposer_public_key: &BlsPublicKey,
+    ) -> Result<(), RelayError> {
+        match self.allowed_proposers.as_ref() {
+            Some(allowed_proposers) if !allowed_proposers.contains(proposer_public_key) => {
+                Err(RelayError::ProposerNotAllowed(proposer_public_key.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn constraints_for(&self, slot: Slot) -> Option<SignedConstraints> {
+        let state = self.state.lock();
+        state.constraints.get(&slot).cloned()
+    }
+
+    fn process_constraints(&self, signed_constraints: &SignedConstraints) -> Result<(), Error> {
+        let constraints = &signed_constraints.message;
+        let slot = constraints.slot;
+        let proposer_public_key = &constraints.proposer_public_key;
+        self.validate_allowed_proposer(proposer_public_key)?;
+
+        let schedule = self.proposer_scheduler.get_proposal_schedule()?;
+        let is_proposer = schedule.iter().any(|schedule| {
+            schedule.slot == slot && &schedule.entry.message.public_key == proposer_public_key
+        });
+        if !is_proposer {
+            return Err(RelayError::UnexpectedProposer(proposer_public_key.clone(), slot).into())
+        }
+        verify_signed_builder_data(
+            constraints,
+            proposer_public_key,
+            &signed_constraints.signature,
+            &self.context,
+        )?;
+
         let mut state = self.state.lock();
-        state.open_auctions.insert(auction_request);
+        // NOTE: builders may already be building against the first set of constraints for a slot,
+        // so they can not be replaced
+        if state.constraints.contains_key(&slot) {
+            return Err(RelayError::DuplicateConstraints(slot).into())
+        }
+        info!(slot, %proposer_public_key, count = constraints.transactions.len(), "accepted constraints");
+        state.constraints.insert(slot, signed_constraints.clone());
         Ok(())
     }
 
-    fn get_auction_context(&self, auction_request: &AuctionRequest) -> Option<Arc<AuctionContext>> {
+    // The commitments the builder with `builder_public_key` made for `slot` before a bid received
+    // at `receive_duration`
+    fn preconf_commitments_for(
+        &self,
+        slot: Slot,
+        builder_public_key: &BlsPublicKey,
+        receive_duration: Duration,
+    ) -> Vec<PreconfCommitment> {
         let state = self.state.lock();
-        state.auctions.get(auction_request).cloned()
+        state
+            .preconf_commitments
+            .get(&slot)
+            .map(|commitments| {
+                commitments_before(commitments, builder_public_key, receive_duration)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn process_preconf_commitment(
+        &self,
+        signed_commitment: &SignedPreconfCommitment,
+    ) -> Result<(), Error> {
+        let commitment = &signed_commitment.message;
+        let slot = commitment.slot;
+        let builder_public_key = &commitment.builder_public_key;
+        self.validate_allowed_builder(builder_public_key)?;
+
+        let schedule = self.proposer_scheduler.get_proposal_schedule()?;
+        if !schedule.iter().any(|schedule| schedule.slot == slot) {
+            return Err(RelayError::NoProposerScheduled(slot).into())
+        }
+        verify_signed_builder_data(
+            commitment,
+            builder_public_key,
+            &signed_commitment.signature,
+            &self.context,
+        )?;
+
+        let receive_duration = duration_since_unix_epoch();
+        let mut state = self.state.lock();
+        let commitments = state.preconf_commitments.entry(slot).or_default();
+        let count = commitments
+            .iter()
+            .filter(|commitment| {
+                &commitment.signed_commitment.message.builder_public_key == builder_public_key
+            })
+            .count();
+        if count >= MAX_PRECONF_COMMITMENTS_PER_BUILDER {
+            return Err(RelayError::TooManyPreconfCommitments(count, slot).into())
+        }
+        info!(slot, %builder_public_key, count = commitment.transactions.len(), "accepted preconfirmation commitment");
+        commitments.push(ReceivedCommitment {
+            signed_commitment: signed_commitment.clone(),
+            receive_duration,
+        });
+        Ok(())
     }
 
     fn validate_allowed_builder(&self, builder_public_key: &BlsPublicKey) -> Result<(), Error> {
-        if self.builder_registry.contains(builder_public_key) {
+        if self.builder_registry.read().contains(builder_public_key) {
             Ok(())
         } else {
             Err(RelayError::BuilderNotRegistered(builder_public_key.clone()).into())
@@ -359,7 +1268,7 @@ impl Relay {
 
     fn validate_auction_request(&self, auction_request: &AuctionRequest) -> Result<(), RelayError> {
         let state = self.state.lock();
-        if state.open_auctions.contains(auction_request) {
+        if state.open_auctions.contains_key(auction_request) {
             Ok(())
         } else {
             let err = RelayError::InvalidAuctionRequest(auction_request.clone());
@@ -367,26 +1276,73 @@ impl Relay {
         }
     }
 
-    // NOTE: best route is likely through `execution-apis`
-    // fn compute_adjusted_gas_limit(&self, preferred_gas_limit: u64) -> u64 {
-    //     let parent_gas_limit = unimplemented!("need efficient way to get parent's gas limit");
-    //     compute_preferred_gas_limit(preferred_gas_limit, parent_gas_limit)
-    // }
-
-    // Assume:
-    // - `execution_payload` is valid
-    // - pays the proposer the amount claimed in the `bid_trace`
-    // - respects the proposer's preferred gas limit, within protocol tolerance
-    fn validate_builder_submission_trusted(
+    // Ensure `gas_limit` is within any configured bounds and is a valid adjustment from the
+    // parent's gas limit, if known.
+    fn validate_gas_limit(
         &self,
-        bid_trace: &BidTrace,
-        execution_payload: &ExecutionPayload,
+        auction_request: &AuctionRequest,
+        gas_limit: u64,
     ) -> Result<(), RelayError> {
-        let proposer_public_key = &bid_trace.proposer_public_key;
-        let signed_registration = self
-            .validator_registry
-            .get_signed_registration(proposer_public_key)
-            .ok_or_else(|| RelayError::ValidatorNotRegistered(proposer_public_key.clone()))?;
+        let min = self.min_gas_limit.unwrap_or_default();
+        let max = self.max_gas_limit.unwrap_or(u64::MAX);
+        if gas_limit < min || gas_limit > max {
+            return Err(RelayError::GasLimitOutOfBounds { gas_limit, min, max })
+        }
+
+        let parent_gas_limit = {
+            let state = self.state.lock();
+            state.open_auctions.get(auction_request).and_then(|auction| auction.parent_gas_limit)
+        };
+        if let Some(parent_gas_limit) = parent_gas_limit {
+            if !verify_gas_limit_adjustment(gas_limit, parent_gas_limit) {
+                return Err(RelayError::InvalidGasLimitAdjustment { gas_limit, parent_gas_limit })
+            }
+        }
+        Ok(())
+    }
+
+    // Check the payload was built against the payload attributes for the auction, so that blocks
+    // built on the wrong attributes are rejected before simulation.
+    fn validate_payload_attributes(
+        &self,
+        auction_request: &AuctionRequest,
+        execution_payload: &impl ExecutionBlockFields,
+    ) -> Result<(), RelayError> {
+        let withdrawals_root = {
+            let state = self.state.lock();
+            state.open_auctions.get(auction_request).and_then(|auction| auction.withdrawals_root)
+        };
+        if let Some(expected) = withdrawals_root {
+            let provided = execution_payload.withdrawals_root();
+            if provided != Some(expected) {
+                return Err(RelayError::InvalidWithdrawalsRoot { expected, provided })
+            }
+        }
+        // NOTE: the parent beacon block root is not part of the payload, only committed to by its
+        // block hash; simulation checks the block hash with the auction's parent beacon block root
+        Ok(())
+    }
+
+    // NOTE: best route is likely through `execution-apis`
+    // fn compute_adjusted_gas_limit(&self, preferred_gas_limit: u64) -> u64 {
+    //     let parent_gas_limit = unimplemented!("need efficient way to get parent's gas limit");
+    //     compute_preferred_gas_limit(preferred_gas_limit, parent_gas_limit)
+    // }
+
+    // Assume:
+    // - `execution_payload` is valid
+    // - pays the proposer the amount claimed in the `bid_trace`
+    // - respects the proposer's preferred gas limit, within protocol tolerance
+    fn validate_builder_submission_trusted(
+        &self,
+        bid_trace: &BidTrace,
+        execution_payload: &impl ExecutionBlockFields,
+    ) -> Result<(), RelayError> {
+        let proposer_public_key = &bid_trace.proposer_public_key;
+        let signed_registration = self
+            .validator_registry
+            .get_signed_registration(proposer_public_key)
+            .ok_or_else(|| RelayError::ValidatorNotRegistered(proposer_public_key.clone()))?;
 
         if bid_trace.proposer_fee_recipient != signed_registration.message.fee_recipient {
             let fee_recipient = &signed_registration.message.fee_recipient;
@@ -434,50 +1390,243 @@ impl Relay {
         Ok(())
     }
 
-    fn insert_bid_if_greater(
+    async fn insert_bid(
         &self,
         auction_request: AuctionRequest,
         signed_submission: &SignedBidSubmission,
-        value: U256,
         receive_duration: Duration,
     ) -> Result<(), Error> {
-        if let Some(bid) = self.get_auction_context(&auction_request) {
-            if bid.value() > value {
-                info!(%auction_request, builder_public_key = %bid.builder_public_key(), "block submission was not greater in value; ignoring");
-                return Ok(())
-            }
-        }
         let auction_context = AuctionContext::new(
             signed_submission.clone(),
             receive_duration,
-            self.public_key.clone(),
-            &self.secret_key,
+            &self.bid_adjustment,
+            &self.signer,
             &self.context,
-        )?;
+        )
+        .await?;
         let auction_context = Arc::new(auction_context);
         let block_hash = auction_context.execution_payload().block_hash();
         let txn_count = auction_context.execution_payload().transactions().len();
         let blob_count =
             auction_context.blobs_bundle().map(|bundle| bundle.blobs.len()).unwrap_or_default();
         info!(%auction_request, builder_public_key = %auction_context.builder_public_key(), %block_hash, txn_count, blob_count, "inserting new bid");
-        let mut state = self.state.lock();
-        let old_context = state.auctions.insert(auction_request.clone(), auction_context);
+        if let Some(deferred_bid) = self.bids.deferred_bid(&auction_request, block_hash) {
+            if deferred_bid.is_resolved_by(&auction_context) {
+                deferred_bid.resolve(auction_context.clone());
+            } else {
+                warn!(%auction_request, %block_hash, "payload does not match the header-only bid for its block");
+            }
+        }
+        self.insert_into_index(auction_request, Bid::Full(auction_context));
+        Ok(())
+    }
+
+    // Send an accepted submission to the other nodes of the relay, if any, for them to insert
+    fn share_submission(
+        &self,
+        auction_request: AuctionRequest,
+        signed_submission: &SignedBidSubmission,
+        receive_duration: Duration,
+    ) {
+        if let Some(cluster) = self.cluster.as_ref() {
+            cluster.publish(ClusterEvent::Submission {
+                auction_request,
+                signed_submission: signed_submission.clone(),
+                receive_duration,
+            });
+        }
+    }
 
-        // NOTE: save other submissions for data APIs
-        if let Some(context) = old_context {
+    /// Apply an update from another node of the relay, which has already validated it
+    pub async fn on_cluster_event(&self, event: ClusterEvent) {
+        match event {
+            ClusterEvent::Submission { auction_request, signed_submission, receive_duration } => {
+                if let Err(err) =
+                    self.insert_bid(auction_request, &signed_submission, receive_duration).await
+                {
+                    warn!(%err, "could not insert bid from other relay node");
+                }
+            }
+            ClusterEvent::Registrations { registrations } => {
+                let current_time =
+                    get_current_unix_time_in_nanos().try_into().expect("fits in type");
+                let (updated_keys, errs) = self.validator_registry.process_registrations(
+                    &registrations,
+                    current_time,
+                    &self.context,
+                );
+                self.state.lock().outstanding_validator_updates.extend(updated_keys);
+                if !errs.is_empty() {
+                    warn!(?errs, "could not process registrations from other relay node");
+                }
+            }
+        }
+    }
+
+    fn insert_into_index(&self, auction_request: AuctionRequest, bid: Bid) {
+        let old_bid = self.bids.insert(auction_request.clone(), bid.clone());
+        if let Some(top_bid) = self.bids.top_bid(&auction_request) {
+            metrics::on_top_bid(auction_request.slot, top_bid.value());
+            if top_bid.ptr_eq(&bid) {
+                // NOTE: sending only fails if there are no subscribers
+                let _ = self.top_bid_events.send(top_bid.bid_trace().clone());
+            }
+        }
+
+        // NOTE: save other submissions for data APIs; deferred bids are dropped as they have no
+        // payload to report
+        if let Some(Bid::Full(context)) = old_bid {
             // TODO: better way to remove from `Arc`?
             if let Some(context) = Arc::into_inner(context) {
+                let mut state = self.state.lock();
                 let entry = state.other_submissions.entry(auction_request).or_default();
-                entry.insert(context);
+                if entry.len() < MAX_OTHER_SUBMISSIONS_PER_AUCTION {
+                    entry.insert(context);
+                }
             }
         }
+    }
+
+    async fn simulate_submission(
+        &self,
+        simulator: &Simulator,
+        auction_request: &AuctionRequest,
+        signed_submission: &SignedBidSubmission,
+    ) -> Result<(), Error> {
+        let parent_beacon_block_root = {
+            let state = self.state.lock();
+            state
+                .open_auctions
+                .get(auction_request)
+                .map(|auction| auction.parent_beacon_block_root)
+                .ok_or_else(|| RelayError::InvalidAuctionRequest(auction_request.clone()))?
+        };
+        let registered_gas_limit = self
+            .validator_registry
+            .get_signed_registration(&auction_request.public_key)
+            .map(|registration| registration.message.gas_limit)
+            .ok_or_else(|| {
+                RelayError::ValidatorNotRegistered(auction_request.public_key.clone())
+            })?;
+        let request = SimulationRequest {
+            signed_submission: signed_submission.clone(),
+            registered_gas_limit,
+            parent_beacon_block_root,
+        };
+        let start = Instant::now();
+        let result = simulator.simulate(request).await;
+        metrics::on_simulation(&signed_submission.message().builder_public_key, start.elapsed());
+        if result.is_ok() {
+            let bid_trace = signed_submission.message();
+            let mut state = self.state.lock();
+            state
+                .simulated_blocks
+                .entry(bid_trace.slot)
+                .or_default()
+                .insert(bid_trace.block_hash.clone());
+        }
+        if let Err(err) = result {
+            warn!(%err, %auction_request, "could not simulate bid submission");
+            let err = if matches!(err, SimulationError::Invalid(..)) {
+                let builder_public_key = &signed_submission.message().builder_public_key;
+                self.builder_reputations.on_simulation_failure(builder_public_key);
+                RelayError::SimulationFailed(err.to_string())
+            } else {
+                RelayError::SimulationUnavailable(err.to_string())
+            };
+            return Err(err.into())
+        }
         Ok(())
     }
 
+    // Simulate a submission that was already accepted on the strength of the builder's collateral.
+    // If it is invalid, the bid is removed so the top bid falls back to the next best bid, further
+    // optimistic submissions from the builder are suspended, and the builder's collateral is
+    // debited if the payload was already delivered.
+    async fn simulate_optimistic_submission(
+        &self,
+        auction_request: AuctionRequest,
+        signed_submission: SignedBidSubmission,
+        receive_duration: Duration,
+    ) {
+        let Some(simulator) = self.simulator.as_ref() else { return };
+        let result =
+            self.simulate_submission(simulator, &auction_request, &signed_submission).await;
+        let bid_trace = signed_submission.message();
+        let builder_public_key = &bid_trace.builder_public_key;
+        match &result {
+            Ok(()) => {
+                self.builder_reputations.on_valid_submission(builder_public_key);
+                // NOTE: only shared once simulated, so other nodes never serve an invalid bid
+                self.share_submission(
+                    auction_request.clone(),
+                    &signed_submission,
+                    receive_duration,
+                );
+            }
+            Err(Error::Relay(RelayError::SimulationFailed(..))) => {
+                error!(%auction_request, %builder_public_key, block_hash = %bid_trace.block_hash, "optimistic submission failed simulation");
+                self.collateral.suspend(builder_public_key);
+                if self
+                    .bids
+                    .remove(&auction_request, builder_public_key, &bid_trace.block_hash)
+                    .is_some()
+                {
+                    if let Some(top_bid) = self.bids.top_bid(&auction_request) {
+                        metrics::on_top_bid(auction_request.slot, top_bid.value());
+                        // NOTE: sending only fails if there are no subscribers
+                        let _ = self.top_bid_events.send(top_bid.bid_trace().clone());
+                    }
+                }
+                let delivered = {
+                    let state = self.state.lock();
+                    state.delivered_payloads.get(&auction_request).is_some_and(|auction_context| {
+                        auction_context.execution_payload().block_hash() == &bid_trace.block_hash
+                    })
+                };
+                if delivered {
+                    self.collateral.debit(builder_public_key, bid_trace.value);
+                }
+            }
+            Err(err) => {
+                // NOTE: the simulation could not be run, e.g. as the beacon node or the simulator
+                // is unavailable, which says nothing about the builder, so the bid stands
+                warn!(%err, %auction_request, %builder_public_key, block_hash = %bid_trace.block_hash, "could not simulate optimistic submission");
+                return
+            }
+        }
+        let submission_root = match signed_submission.hash_tree_root() {
+            Ok(root) => root,
+            Err(err) => {
+                warn!(%err, "could not compute root of optimistic submission");
+                return
+            }
+        };
+        if let Some(outcome) = SubmissionOutcome::from_result(&result) {
+            self.record_submission_outcome(bid_trace.slot, submission_root, outcome);
+        }
+    }
+
+    // Sign the bid trace of a delivered payload, so third parties can attribute the payload to
+    // this relay. The trace carries the value of the bid served to the proposer.
+    async fn sign_bid_trace(&self, auction_context: &AuctionContext) -> Option<SignedBidTrace> {
+        let mut message = auction_context.bid_trace().clone();
+        message.value = auction_context.adjusted_value();
+        match self.signer.sign_builder_message(&message, &self.context).await {
+            Ok(signature) => Some(SignedBidTrace { message, signature }),
+            Err(err) => {
+                warn!(%err, slot = message.slot, block_hash = %message.block_hash, "could not sign bid trace of delivered payload");
+                None
+            }
+        }
+    }
+
     fn store_delivered_payload(
         &self,
         auction_request: AuctionRequest,
         auction_context: Arc<AuctionContext>,
+        signed_bid_trace: Option<SignedBidTrace>,
+        get_payload_offset_ms: i64,
     ) {
         let mut state = self.state.lock();
         if let Some(existing) = state.delivered_payloads.get(&auction_request) {
@@ -491,58 +1640,105 @@ impl Relay {
                 return
             }
         }
-        state.delivered_payloads.insert(auction_request, auction_context);
-    }
-}
+        if let Some(signed_bid_trace) = signed_bid_trace {
+            state.signed_bid_traces.insert(auction_request.clone(), signed_bid_trace);
+        }
+        state.get_payload_offsets.insert(auction_request.clone(), get_payload_offset_ms);
+        state.delivered_payloads.insert(auction_request.clone(), auction_context.clone());
+        drop(state);
 
-#[async_trait]
-impl BlindedBlockProvider for Relay {
-    async fn register_validators(
-        &self,
-        registrations: &[SignedValidatorRegistration],
-    ) -> Result<(), Error> {
-        let current_time = get_current_unix_time_in_nanos().try_into().expect("fits in type");
-        let (updated_keys, errs) = self.validator_registry.process_registrations(
-            registrations,
-            current_time,
-            &self.context,
-        );
+        let block_hash = auction_context.execution_payload().block_hash().clone();
+        self.delivered_blocks.insert(auction_request.slot, block_hash, auction_context.clone());
 
-        let updated_key_count = updated_keys.len();
-        info!(
-            updates = updated_key_count,
-            registrations = registrations.len(),
-            "processed validator registrations"
-        );
-        let mut state = self.state.lock();
-        state.outstanding_validator_updates.extend(updated_keys);
+        // NOTE: sending only fails if there are no subscribers
+        let trace = payload_trace_from_auction(&auction_context, Some(get_payload_offset_ms));
+        let _ = self.delivered_payload_events.send(trace);
 
-        if errs.is_empty() {
-            Ok(())
-        } else {
-            warn!(?errs, "error processing some registrations");
-            Err(Error::RegistrationErrors(errs))
+        if let Some(archiver) = self.archiver.as_ref() {
+            archiver.archive(auction_context);
         }
     }
 
-    async fn fetch_best_bid(
+    // Publish the unblinded `signed_block` to any additional beacon nodes so the proposal
+    // has a better chance of propagating, even if the proposer's own publication fails.
+    fn broadcast_signed_block(
+        &self,
+        signed_block: SignedBeaconBlock,
+        auction_context: Arc<AuctionContext>,
+    ) {
+        if self.broadcast_beacon_nodes.is_empty() {
+            return
+        }
+
+        let beacon_nodes = self.broadcast_beacon_nodes.clone();
+        tokio::spawn(async move {
+            let version = signed_block.version();
+            let blobs_bundle = auction_context.blobs_bundle();
+            let publications = beacon_nodes.iter().map(|beacon_node| async {
+                let request = SubmitSignedBeaconBlock {
+                    signed_block: &signed_block,
+                    kzg_proofs: blobs_bundle.map(|bundle| bundle.proofs.as_ref()),
+                    blobs: blobs_bundle.map(|bundle| bundle.blobs.as_ref()),
+                };
+                let result = beacon_node
+                    .post_signed_beacon_block_v2(
+                        request,
+                        version,
+                        Some(BroadcastValidation::Gossip),
+                    )
+                    .await;
+                (beacon_node, result)
+            });
+            for (beacon_node, result) in join_all(publications).await {
+                let endpoint = &beacon_node.endpoint;
+                if let Err(err) = result {
+                    warn!(%err, %endpoint, "could not broadcast block to beacon node");
+                } else {
+                    debug!(%endpoint, "broadcast block to beacon node");
+                }
+            }
+        });
+    }
+
+    async fn get_best_bid(
         &self,
         auction_request: &AuctionRequest,
     ) -> Result<SignedBuilderBid, Error> {
+        self.validate_allowed_proposer(&auction_request.public_key)?;
         if let Err(err) = self.validate_auction_request(auction_request) {
             warn!(%err, "could not fetch best bid");
             return Err(err.into())
         }
+        if let Err(err) = self
+            .auction_timing
+            .validate_header_request(auction_request.slot, duration_since_unix_epoch())
+        {
+            warn!(%err, %auction_request, "not serving bid");
+            return Err(err.into())
+        }
 
-        let auction_context = self
-            .get_auction_context(auction_request)
-            .ok_or_else(|| Error::NoBidPrepared(auction_request.clone()))?;
-        let signed_builder_bid = auction_context.signed_builder_bid();
+        let bid = match self.get_top_bid(auction_request) {
+            Some(bid) => bid,
+            None => self
+                .build_fallback_bid(auction_request)
+                .await
+                .ok_or_else(|| Error::NoBidPrepared(auction_request.clone()))?,
+        };
+        // NOTE: not worth asking the proposer to trust the relay and builder for a negligible
+        // payment
+        let value = bid.value();
+        let min_bid_value = *self.min_bid_value.read();
+        if value < min_bid_value {
+            info!(%auction_request, %value, %min_bid_value, "best bid is below minimum value; not serving");
+            return Err(Error::NoBidPrepared(auction_request.clone()))
+        }
+        let signed_builder_bid = bid.signed_builder_bid();
         info!(%auction_request, %signed_builder_bid, "serving bid");
+        self.state.lock().served_auctions.insert(auction_request.clone());
         Ok(signed_builder_bid.clone())
     }
 
-    async fn open_bid(
+    async fn deliver_payload(
         &self,
         signed_block: &SignedBlindedBeaconBlock,
     ) -> Result<AuctionContents, Error> {
@@ -559,21 +1755,37 @@ impl BlindedBlockProvider for Relay {
                 .ok_or(RelayError::UnknownValidatorIndex(proposer_index))?;
             AuctionRequest { slot, parent_hash, public_key }
         };
+        self.validate_allowed_proposer(&auction_request.public_key)?;
+
+        let block_hash =
+            signed_block.message().body().execution_payload_header().block_hash().clone();
+        if let Some(auction_context) = self.delivered_blocks.get(&block_hash) {
+            return self.redeliver_payload(&auction_request, signed_block, &auction_context)
+        }
 
         if let Err(err) = self.validate_auction_request(&auction_request) {
             warn!(%err, "could not open bid");
             return Err(err.into())
         }
+        let request_duration = duration_since_unix_epoch();
+        if let Err(err) =
+            self.auction_timing.validate_payload_request(auction_request.slot, request_duration)
+        {
+            warn!(%err, %auction_request, "not opening bid");
+            return Err(err.into())
+        }
+        let get_payload_offset_ms =
+            self.auction_timing.slot_offset_ms(auction_request.slot, request_duration);
 
-        let auction_context = self
-            .get_auction_context(&auction_request)
+        let bid = self
+            .get_top_bid(&auction_request)
             .ok_or_else(|| RelayError::MissingAuction(auction_request.clone()))?;
 
         {
             let block = signed_block.message();
             let body = block.body();
             let execution_payload_header = body.execution_payload_header();
-            let local_header = auction_context.signed_builder_bid().message.header();
+            let local_header = bid.signed_builder_bid().message.header();
             if let Err(err) = validate_header_equality(local_header, execution_payload_header) {
                 warn!(%err, %auction_request, "invalid incoming signed blinded beacon block");
                 return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
@@ -590,32 +1802,89 @@ impl BlindedBlockProvider for Relay {
             return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
         }
 
+        if let Some(cluster) = self.cluster.as_ref() {
+            // NOTE: refuse to deliver if the claim can not be made, rather than risk releasing a
+            // second payload for the slot from another node
+            match cluster.claim_delivery(&auction_request, &block_hash.to_string()).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    warn!(%auction_request, %block_hash, "another relay node delivered a different payload for this slot");
+                    return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+                }
+                Err(err) => {
+                    error!(%err, %auction_request, %block_hash, "could not claim payload delivery with other relay nodes");
+                    return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+                }
+            }
+        }
+
+        let auction_context = self.resolve_bid(&auction_request, bid).await?;
+        {
+            let builder_public_key = auction_context.builder_public_key();
+            // NOTE: the builder is only held to the commitments it made before its bid
+            let commitments = self.preconf_commitments_for(
+                auction_request.slot,
+                builder_public_key,
+                auction_context.receive_duration(),
+            );
+            if let Err(err) = verify_preconf_commitments(
+                auction_request.slot,
+                &commitments,
+                auction_context.execution_payload(),
+            ) {
+                // NOTE: the proposer has already committed to this block, so it is still delivered
+                error!(%err, %auction_request, %builder_public_key, "delivered payload does not honor preconfirmation commitments");
+                self.builder_reputations.on_delivery_fault(builder_public_key);
+            }
+        }
         match unblind_block(signed_block, auction_context.execution_payload()) {
             Ok(signed_block) => {
-                let version = signed_block.version();
                 let block_root =
                     signed_block.message().hash_tree_root().map_err(ConsensusError::from)?;
-                let request = SubmitSignedBeaconBlock {
-                    signed_block: &signed_block,
-                    kzg_proofs: auction_context.blobs_bundle().map(|bundle| bundle.proofs.as_ref()),
-                    blobs: auction_context.blobs_bundle().map(|bundle| bundle.blobs.as_ref()),
-                };
-                if let Err(err) = self
-                    .beacon_node
-                    .post_signed_beacon_block_v2(
-                        request,
-                        version,
-                        Some(BroadcastValidation::ConsensusAndEquivocation),
-                    )
-                    .await
-                {
-                    warn!(%err, %auction_request, %block_root, "block failed beacon node validation");
+                if let Err(err) = self.publish_block(&signed_block, &auction_context).await {
+                    let payload_simulated = {
+                        let bid_trace = auction_context.bid_trace();
+                        let state = self.state.lock();
+                        state
+                            .simulated_blocks
+                            .get(&bid_trace.slot)
+                            .is_some_and(|blocks| blocks.contains(&bid_trace.block_hash))
+                    };
+                    // NOTE: the builder is only at fault if the beacon node refused the block,
+                    // rather than failing to handle the request, and the payload was never shown
+                    // to be valid; otherwise, the part of the block the proposer signed is
+                    if is_rejection(&err) && payload_simulated {
+                        warn!(%err, %auction_request, %block_root, proposer = %auction_request.public_key, "block with a valid payload failed beacon node validation");
+                        self.on_delivery_failure(
+                            auction_context.bid_trace(),
+                            DeliveryFailure::ProposerBlockRejected,
+                        );
+                    } else if is_rejection(&err) {
+                        warn!(%err, %auction_request, %block_root, "block failed beacon node validation");
+                        self.builder_reputations
+                            .on_delivery_fault(auction_context.builder_public_key());
+                        self.collateral
+                            .debit(auction_context.builder_public_key(), auction_context.value());
+                        self.on_delivery_failure(
+                            auction_context.bid_trace(),
+                            DeliveryFailure::BlockRejected,
+                        );
+                    } else {
+                        error!(%err, %auction_request, %block_root, "could not publish block");
+                    }
                     Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
                 } else {
                     let block_hash = auction_context.execution_payload().block_hash();
-                    info!(%auction_request, %block_root, %block_hash, "returning local payload");
+                    info!(%auction_request, %block_root, %block_hash, get_payload_offset_ms, "returning local payload");
                     let auction_contents = auction_context.to_auction_contents();
-                    self.store_delivered_payload(auction_request, auction_context);
+                    self.broadcast_signed_block(signed_block, auction_context.clone());
+                    let signed_bid_trace = self.sign_bid_trace(&auction_context).await;
+                    self.store_delivered_payload(
+                        auction_request,
+                        auction_context,
+                        signed_bid_trace,
+                        get_payload_offset_ms,
+                    );
                     Ok(auction_contents)
                 }
             }
@@ -625,23 +1894,96 @@ impl BlindedBlockProvider for Relay {
             }
         }
     }
-}
 
-#[async_trait]
-impl BlindedBlockRelayer for Relay {
-    async fn get_proposal_schedule(&self) -> Result<Vec<ProposerSchedule>, Error> {
-        let schedule = self.proposer_scheduler.get_proposal_schedule()?;
-        let slots = schedule.iter().map(|schedule| schedule.slot).collect::<Vec<_>>();
-        debug!(?slots, "sending schedule");
-        Ok(schedule)
+    // Serve the payload for a block delivered before, e.g. if the proposer did not receive the
+    // first response in time or is proposing the block again after a reorg; the auction for the
+    // slot may have closed since, so the stored payload and blobs are used as they are.
+    fn redeliver_payload(
+        &self,
+        auction_request: &AuctionRequest,
+        signed_block: &SignedBlindedBeaconBlock,
+        auction_context: &AuctionContext,
+    ) -> Result<AuctionContents, Error> {
+        let bid_trace = auction_context.bid_trace();
+        let is_same_auction = bid_trace.slot == auction_request.slot &&
+            bid_trace.parent_hash == auction_request.parent_hash &&
+            bid_trace.proposer_public_key == auction_request.public_key;
+        let header_matches = validate_header_equality(
+            auction_context.signed_builder_bid().message.header(),
+            signed_block.message().body().execution_payload_header(),
+        )
+        .is_ok();
+        if !is_same_auction || !header_matches {
+            warn!(%auction_request, "signed blinded beacon block does not match delivered payload");
+            return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+        }
+        if let Err(err) = verify_blinded_block_signature(
+            auction_request,
+            signed_block,
+            &self.genesis_validators_root,
+            &self.context,
+        ) {
+            warn!(%err, %auction_request, "invalid incoming signed blinded beacon block signature");
+            return Err(RelayError::InvalidSignedBlindedBeaconBlock.into())
+        }
+        let block_hash = &bid_trace.block_hash;
+        info!(%auction_request, %block_hash, "returning payload delivered before");
+        Ok(auction_context.to_auction_contents())
     }
 
-    async fn submit_bid(&self, signed_submission: &SignedBidSubmission) -> Result<(), Error> {
+    // Publish `signed_block` to the beacon node, retrying with a short backoff while the beacon
+    // node can not be reached; blocks the beacon node rejects are not retried.
+    async fn publish_block(
+        &self,
+        signed_block: &SignedBeaconBlock,
+        auction_context: &AuctionContext,
+    ) -> Result<(), BeaconApiError> {
+        let version = signed_block.version();
+        let mut retry_delays = PUBLISH_RETRY_DELAYS.iter();
+        loop {
+            let request = SubmitSignedBeaconBlock {
+                signed_block,
+                kzg_proofs: auction_context.blobs_bundle().map(|bundle| bundle.proofs.as_ref()),
+                blobs: auction_context.blobs_bundle().map(|bundle| bundle.blobs.as_ref()),
+            };
+            let result = self
+                .beacon_node
+                .post_signed_beacon_block_v2(
+                    request,
+                    version,
+                    Some(BroadcastValidation::ConsensusAndEquivocation),
+                )
+                .await;
+            match result {
+                Err(err) if !is_rejection(&err) => match retry_delays.next() {
+                    Some(delay) => {
+                        warn!(%err, ?delay, "could not publish block; retrying");
+                        tokio::time::sleep(*delay).await;
+                    }
+                    None => return Err(err),
+                },
+                result => return result,
+            }
+        }
+    }
+
+    async fn process_bid_submission(
+        &self,
+        signed_submission: &SignedBidSubmission,
+    ) -> Result<(), Error> {
         let receive_duration = duration_since_unix_epoch();
-        let (auction_request, value) = {
+        let start = Instant::now();
+        self.health.check()?;
+        let auction_request = {
             let bid_trace = signed_submission.message();
             let builder_public_key = &bid_trace.builder_public_key;
             self.validate_allowed_builder(builder_public_key)?;
+            record_submission_timing(
+                builder_public_key,
+                signed_submission.payload(),
+                receive_duration,
+            );
+            self.auction_timing.validate_submission(bid_trace.slot, receive_duration)?;
 
             let auction_request = AuctionRequest {
                 slot: bid_trace.slot,
@@ -653,26 +1995,428 @@ impl BlindedBlockRelayer for Relay {
                 return Err(err.into())
             }
 
-            self.validate_builder_submission_trusted(bid_trace, signed_submission.payload())?;
+            // NOTE: faults are only counted against a builder once the submission is known to come
+            // from them, so that no one can get a builder demoted by submitting in their name
+            let signature = signed_submission.signature();
+            verify_signed_builder_data(bid_trace, builder_public_key, signature, &self.context)?;
+            if let Err(err) = self
+                .validate_builder_submission_trusted(bid_trace, signed_submission.payload())
+                .and_then(|_| {
+                    self.validate_payload_attributes(&auction_request, signed_submission.payload())
+                })
+            {
+                self.builder_reputations.on_simulation_failure(builder_public_key);
+                return Err(err.into())
+            }
             debug!(%auction_request, "validated builder submission");
-            (auction_request, bid_trace.value)
+            auction_request
         };
 
         let message = signed_submission.message();
         let public_key = &signed_submission.message().builder_public_key;
+        // NOTE: only count submissions against the rate limit once they are known to come from
+        // the builder, and count duplicates as well so they can not be used to flood the relay
+        self.builder_reputations.on_submission(public_key, message.slot)?;
+
+        // NOTE: builders often re-send the same block, so reuse the outcome of the first submission
+        // rather than validating, signing or inserting it again. Outcomes are keyed by the whole
+        // signed submission, as a submission of the same block with any other value or blobs must
+        // be validated again.
+        let block_hash = &message.block_hash;
+        let submission_root = signed_submission.hash_tree_root().map_err(ConsensusError::from)?;
+        let outcome = {
+            let state = self.state.lock();
+            state
+                .submission_outcomes
+                .get(&message.slot)
+                .and_then(|outcomes| outcomes.get(&submission_root))
+                .cloned()
+        };
+        if let Some(outcome) = outcome {
+            // NOTE: an optimistic submission is only recorded once its simulation completes, so a
+            // duplicate of one still being simulated is validated again
+            debug!(%auction_request, %block_hash, ?outcome, "received duplicate submission");
+            return match outcome {
+                SubmissionOutcome::Accepted => Ok(()),
+                SubmissionOutcome::Rejected(reason) => {
+                    Err(RelayError::DuplicateSubmissionRejected(block_hash.clone(), reason).into())
+                }
+            }
+        }
+
+        let result = self
+            .process_verified_submission(
+                auction_request,
+                signed_submission,
+                receive_duration,
+                start,
+            )
+            .await;
+        // NOTE: the outcome of an optimistic submission is recorded once it has been simulated
+        if !matches!(result, Ok(Simulation::Optimistic)) {
+            if let Some(outcome) = SubmissionOutcome::from_result(&result) {
+                self.record_submission_outcome(message.slot, submission_root, outcome);
+            }
+        }
+        result.map(|_| ())
+    }
+
+    fn record_submission_outcome(
+        &self,
+        slot: Slot,
+        submission_root: Root,
+        outcome: SubmissionOutcome,
+    ) {
+        let mut state = self.state.lock();
+        let outcomes = state.submission_outcomes.entry(slot).or_default();
+        if outcomes.len() < MAX_SUBMISSION_OUTCOMES_PER_SLOT {
+            outcomes.insert(submission_root, outcome);
+        }
+    }
+
+    async fn process_verified_submission(
+        &self,
+        auction_request: AuctionRequest,
+        signed_submission: &SignedBidSubmission,
+        receive_duration: Duration,
+        start: Instant,
+    ) -> Result<Simulation, Error> {
+        let public_key = &signed_submission.message().builder_public_key;
+        if let Some(blobs_bundle) = signed_submission.blobs_bundle() {
+            if let Err(err) = verify_blobs_bundle(signed_submission.payload(), blobs_bundle) {
+                warn!(%err, "could not verify blobs bundle in bid submission");
+                self.builder_reputations.on_invalid_submission(public_key);
+                return Err(err.into())
+            }
+        }
+        let gas_limit = signed_submission.message().gas_limit;
+        if let Err(err) = self.validate_gas_limit(&auction_request, gas_limit) {
+            self.builder_reputations.on_invalid_submission(public_key);
+            return Err(err.into())
+        }
+        if let Some(signed_constraints) = self.constraints_for(auction_request.slot) {
+            if let Err(err) =
+                verify_constraints(&signed_constraints.message, signed_submission.payload())
+            {
+                warn!(%err, "bid submission does not satisfy constraints");
+                self.builder_reputations.on_invalid_submission(public_key);
+                return Err(err.into())
+            }
+        }
+        let commitments =
+            self.preconf_commitments_for(auction_request.slot, public_key, receive_duration);
+        if let Err(err) = verify_preconf_commitments(
+            auction_request.slot,
+            &commitments,
+            signed_submission.payload(),
+        ) {
+            warn!(%err, "bid submission does not honor preconfirmation commitments");
+            self.builder_reputations.on_invalid_submission(public_key);
+            return Err(err.into())
+        }
+        // NOTE: the payload of a header-only bid is simulated before it is accepted, as the bid
+        // was already served on the strength of the builder's collateral
+        let block_hash = &signed_submission.message().block_hash;
+        let resolves_deferred_bid = self.bids.deferred_bid(&auction_request, block_hash).is_some();
+        let simulation = match self.simulator.as_ref() {
+            Some(_)
+                if !resolves_deferred_bid &&
+                    self.builder_reputations.allows_optimistic(public_key) &&
+                    self.collateral.covers(public_key, signed_submission.message().value) =>
+            {
+                Simulation::Optimistic
+            }
+            Some(simulator) => {
+                self.simulate_submission(simulator, &auction_request, signed_submission).await?;
+                Simulation::Complete
+            }
+            None => Simulation::Complete,
+        };
+        metrics::on_submission_validated(start.elapsed());
+
+        // NOTE: the latest submission from a builder always replaces their prior one,
+        // so builders can cancel a bid by submitting a lower value
+        self.insert_bid(auction_request.clone(), signed_submission, receive_duration).await?;
+        if simulation == Simulation::Optimistic {
+            // NOTE: only simulate once the bid is inserted, so that a failed simulation always
+            // finds the bid to remove
+            let relay = self.clone();
+            let signed_submission = signed_submission.clone();
+            tokio::spawn(async move {
+                relay
+                    .simulate_optimistic_submission(
+                        auction_request,
+                        signed_submission,
+                        receive_duration,
+                    )
+                    .await
+            });
+        } else {
+            self.builder_reputations.on_valid_submission(public_key);
+            self.share_submission(auction_request, signed_submission, receive_duration);
+        }
+
+        Ok(simulation)
+    }
+
+    async fn process_header_submission(
+        &self,
+        signed_submission: &SignedHeaderSubmission,
+    ) -> Result<(), Error> {
+        let receive_duration = duration_since_unix_epoch();
+        self.health.check()?;
+        let bid_trace = signed_submission.message();
+        let builder_public_key = &bid_trace.builder_public_key;
+        self.validate_allowed_builder(builder_public_key)?;
+        record_submission_timing(builder_public_key, signed_submission.header(), receive_duration);
+        self.auction_timing.validate_submission(bid_trace.slot, receive_duration)?;
+
+        let auction_request = AuctionRequest {
+            slot: bid_trace.slot,
+            parent_hash: bid_trace.parent_hash.clone(),
+            public_key: bid_trace.proposer_public_key.clone(),
+        };
+        if let Err(err) = self.validate_auction_request(&auction_request) {
+            warn!(%err, "could not validate header submission");
+            return Err(err.into())
+        }
+
+        // NOTE: only count faults against the builder once the header is known to come from them
         let signature = signed_submission.signature();
-        verify_signed_builder_data(message, public_key, signature, &self.context)?;
+        verify_signed_builder_data(bid_trace, builder_public_key, signature, &self.context)?;
+        if let Err(err) = self
+            .validate_builder_submission_trusted(bid_trace, signed_submission.header())
+            .and_then(|_| {
+                self.validate_payload_attributes(&auction_request, signed_submission.header())
+            })
+        {
+            self.builder_reputations.on_simulation_failure(builder_public_key);
+            return Err(err.into())
+        }
 
-        // NOTE: this does _not_ respect cancellations
-        // TODO: move to regime where we track best bid by builder
-        // and also move logic to cursor best bid for auction off this API
-        self.insert_bid_if_greater(auction_request, signed_submission, value, receive_duration)?;
+        self.builder_reputations.on_submission(builder_public_key, bid_trace.slot)?;
 
+        if let Err(err) = self.validate_gas_limit(&auction_request, bid_trace.gas_limit) {
+            self.builder_reputations.on_invalid_submission(builder_public_key);
+            return Err(err.into())
+        }
+        // NOTE: constraints can only be checked against the full payload
+        if let Some(signed_constraints) = self.constraints_for(auction_request.slot) {
+            let missing = signed_constraints.message.transactions.len();
+            if missing > 0 {
+                let slot = auction_request.slot;
+                return Err(RelayError::ConstraintsNotSatisfied { slot, missing }.into())
+            }
+        }
+        let commitments = self.preconf_commitments_for(
+            auction_request.slot,
+            builder_public_key,
+            receive_duration,
+        );
+        let missing = commitments.iter().map(|commitment| commitment.transactions.len()).sum();
+        if missing > 0 {
+            let slot = auction_request.slot;
+            return Err(RelayError::PreconfCommitmentsNotHonored { slot, missing }.into())
+        }
+        // NOTE: the block can not be validated until the builder uploads the payload, so the bid
+        // must be covered by the collateral of a builder in good standing
+        if !self.builder_reputations.allows_optimistic(builder_public_key) ||
+            !self.collateral.covers(builder_public_key, bid_trace.value)
+        {
+            return Err(RelayError::InsufficientCollateral(builder_public_key.clone()).into())
+        }
+
+        let bid = DeferredBid::new(
+            signed_submission,
+            receive_duration,
+            &self.bid_adjustment,
+            &self.signer,
+            &self.context,
+        )
+        .await?;
+        info!(%auction_request, %builder_public_key, block_hash = %bid_trace.block_hash, "inserting new deferred bid");
+        self.insert_into_index(auction_request, Bid::Deferred(Arc::new(bid)));
+        self.builder_reputations.on_valid_submission(builder_public_key);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlindedBlockProvider for Relay {
+    async fn register_validators(
+        &self,
+        registrations: &[SignedValidatorRegistration],
+    ) -> Result<(), Error> {
+        let (registrations, disallowed) = match self.allowed_proposers.as_ref() {
+            Some(allowed_proposers) => {
+                let (allowed, disallowed): (Vec<_>, Vec<_>) =
+                    registrations.iter().cloned().partition(|registration| {
+                        allowed_proposers.contains(&registration.message.public_key)
+                    });
+                (Cow::Owned(allowed), disallowed)
+            }
+            None => (Cow::Borrowed(registrations), vec![]),
+        };
+        let current_time = get_current_unix_time_in_nanos().try_into().expect("fits in type");
+        let (updated_keys, errs) = self.validator_registry.process_registrations(
+            &registrations,
+            current_time,
+            &self.context,
+        );
+
+        let updated_key_count = updated_keys.len();
+        info!(
+            updates = updated_key_count,
+            registrations = registrations.len(),
+            "processed validator registrations"
+        );
+        if let Some(cluster) = self.cluster.as_ref().filter(|_| updated_key_count > 0) {
+            let registrations = registrations
+                .iter()
+                .filter(|registration| updated_keys.contains(&registration.message.public_key))
+                .cloned()
+                .collect();
+            cluster.publish(ClusterEvent::Registrations { registrations });
+        }
+        let mut state = self.state.lock();
+        state.outstanding_validator_updates.extend(updated_keys);
+
+        drop(state);
+
+        if !errs.is_empty() {
+            warn!(?errs, "error processing some registrations");
+            return Err(Error::RegistrationErrors(errs))
+        }
+        if let Some(registration) = disallowed.first() {
+            warn!(count = disallowed.len(), "rejected registrations from proposers not allowed");
+            return Err(
+                RelayError::ProposerNotAllowed(registration.message.public_key.clone()).into()
+            )
+        }
         Ok(())
     }
+
+    async fn fetch_best_bid(
+        &self,
+        auction_request: &AuctionRequest,
+    ) -> Result<SignedBuilderBid, Error> {
+        let start = Instant::now();
+        let result = self.get_best_bid(auction_request).await;
+        metrics::on_get_header(start.elapsed());
+        result
+    }
+
+    async fn open_bid(
+        &self,
+        signed_block: &SignedBlindedBeaconBlock,
+    ) -> Result<AuctionContents, Error> {
+        let start = Instant::now();
+        let result = self.deliver_payload(signed_block).await;
+        metrics::on_get_payload(start.elapsed());
+        if result.is_ok() {
+            metrics::on_payload_delivered();
+        }
+        result
+    }
 }
 
-fn payload_trace_from_auction(auction_context: &AuctionContext) -> PayloadTrace {
+impl HeaderRequestLimiter for Relay {
+    fn admit_header_request(
+        &self,
+        auction_request: &AuctionRequest,
+        peer: IpAddr,
+    ) -> Result<(), Error> {
+        if let Err(err) = self.header_rate_limiter.admit(&auction_request.public_key, peer) {
+            debug!(%auction_request, %peer, %err, "rejecting request for header");
+            return Err(err.into())
+        }
+        Ok(())
+    }
+}
+
+impl SubmissionAuthenticator for Relay {
+    fn authenticate_submission(
+        &self,
+        builder_public_key: &BlsPublicKey,
+        api_key: Option<&str>,
+    ) -> Result<(), Error> {
+        if let Err(err) = self.api_keys.authenticate(builder_public_key, api_key) {
+            debug!(?builder_public_key, %err, "rejecting submission");
+            return Err(err.into())
+        }
+        Ok(())
+    }
+}
+
+impl HealthCheck for Relay {
+    fn check_health(&self) -> Result<(), Error> {
+        Ok(self.health.check()?)
+    }
+}
+
+impl RequestRejectionObserver for Relay {
+    fn on_request_rejected(&self, rejected: RejectedRequest) {
+        debug!(?rejected, "rejected request to builder endpoint");
+        metrics::on_request_rejected(rejected.as_str());
+    }
+}
+
+#[async_trait]
+impl BlindedBlockRelayer for Relay {
+    async fn get_proposal_schedule(&self) -> Result<Vec<ProposerSchedule>, Error> {
+        let schedule = self.proposer_scheduler.get_proposal_schedule()?;
+        let slots = schedule.iter().map(|schedule| schedule.slot).collect::<Vec<_>>();
+        debug!(?slots, "sending schedule");
+        Ok(schedule)
+    }
+
+    async fn submit_bid(&self, signed_submission: &SignedBidSubmission) -> Result<(), Error> {
+        let builder_public_key = &signed_submission.message().builder_public_key;
+        metrics::on_submission_received(builder_public_key);
+        let result = self.process_bid_submission(signed_submission).await;
+        if result.is_ok() {
+            metrics::on_submission_accepted(builder_public_key);
+        } else {
+            metrics::on_submission_rejected(builder_public_key);
+        }
+        result
+    }
+
+    async fn submit_header(&self, signed_submission: &SignedHeaderSubmission) -> Result<(), Error> {
+        let builder_public_key = &signed_submission.message().builder_public_key;
+        metrics::on_submission_received(builder_public_key);
+        let result = self.process_header_submission(signed_submission).await;
+        if result.is_ok() {
+            metrics::on_submission_accepted(builder_public_key);
+        } else {
+            metrics::on_submission_rejected(builder_public_key);
+        }
+        result
+    }
+
+    async fn submit_constraints(
+        &self,
+        signed_constraints: &SignedConstraints,
+    ) -> Result<(), Error> {
+        self.process_constraints(signed_constraints)
+    }
+
+    async fn get_constraints(&self, slot: Slot) -> Result<Vec<SignedConstraints>, Error> {
+        Ok(self.constraints_for(slot).into_iter().collect())
+    }
+
+    async fn submit_preconf_commitment(
+        &self,
+        signed_commitment: &SignedPreconfCommitment,
+    ) -> Result<(), Error> {
+        self.process_preconf_commitment(signed_commitment)
+    }
+}
+
+fn payload_trace_from_auction(
+    auction_context: &AuctionContext,
+    get_payload_offset_ms: Option<i64>,
+) -> PayloadTrace {
     let bid_trace = auction_context.bid_trace();
     let builder_bid = &auction_context.signed_builder_bid().message;
     let header = builder_bid.header();
@@ -686,12 +2430,14 @@ fn payload_trace_from_auction(auction_context: &AuctionContext) -> PayloadTrace
         gas_limit: bid_trace.gas_limit,
         gas_used: bid_trace.gas_used,
         value: bid_trace.value,
+        adjusted_value: auction_context.adjusted_value(),
         block_number: header.block_number(),
         transaction_count: auction_context.execution_payload().transactions().len(),
         blob_count: auction_context
             .blobs_bundle()
             .map(|bundle| bundle.blobs.len())
             .unwrap_or_default(),
+        get_payload_offset_ms,
     }
 }
 
@@ -710,6 +2456,7 @@ fn submission_trace_from_auction(auction_context: &AuctionContext) -> Submission
         gas_limit: bid_trace.gas_limit,
         gas_used: bid_trace.gas_used,
         value: bid_trace.value,
+        adjusted_value: auction_context.adjusted_value(),
         block_number: header.block_number(),
         transaction_count: auction_context.execution_payload().transactions().len(),
         blob_count: auction_context
@@ -724,7 +2471,7 @@ fn submission_trace_from_auction(auction_context: &AuctionContext) -> Submission
 #[async_trait]
 impl BlindedBlockDataProvider for Relay {
     fn public_key(&self) -> &BlsPublicKey {
-        &self.public_key
+        self.signer.public_key()
     }
 
     fn registered_validators_count(&self) -> usize {
@@ -733,55 +2480,49 @@ impl BlindedBlockDataProvider for Relay {
 
     async fn get_delivered_payloads(
         &self,
-        _filters: &DeliveredPayloadFilter,
+        filters: &DeliveredPayloadFilter,
     ) -> Result<Vec<PayloadTrace>, Error> {
+        let mut traces = self.delivered_payload_traces(filters);
+        traces.truncate(filters.limit());
+        Ok(traces)
+    }
+
+    fn subscribe_delivered_payloads(&self) -> broadcast::Receiver<PayloadTrace> {
+        self.delivered_payload_events.subscribe()
+    }
+
+    fn subscribe_top_bids(&self) -> broadcast::Receiver<BidTrace> {
+        self.top_bid_events.subscribe()
+    }
+
+    async fn get_signed_delivered_payloads(
+        &self,
+        filters: &DeliveredPayloadFilter,
+    ) -> Result<Vec<SignedBidTrace>, Error> {
         let state = self.state.lock();
+        // NOTE: filter on the full trace of each delivered payload, as some fields filtered on are
+        // not part of the signed trace
         let mut traces = state
             .delivered_payloads
             .iter()
-            .map(|(auction_request, auction_context)| {
-                let trace = payload_trace_from_auction(auction_context);
-                (auction_request, trace)
+            .filter(|(_, auction_context)| {
+                filters.matches(&payload_trace_from_auction(auction_context, None))
+            })
+            .filter_map(|(auction_request, _)| {
+                state.signed_bid_traces.get(auction_request).map(|trace| (auction_request, trace))
             })
             .collect::<Vec<_>>();
         traces.sort_by(|a, b| a.0.cmp(b.0));
-        Ok(traces.into_iter().rev().map(|(_, trace)| trace).collect())
+        Ok(traces.into_iter().rev().take(filters.limit()).map(|(_, trace)| trace.clone()).collect())
     }
 
     async fn get_block_submissions(
         &self,
-        _filters: &BlockSubmissionFilter,
+        filters: &BlockSubmissionFilter,
     ) -> Result<Vec<SubmissionTrace>, Error> {
-        let state = self.state.lock();
-        let mut traces = state
-            .auctions
-            .iter()
-            .map(|(auction_request, auction_context)| {
-                let trace = submission_trace_from_auction(auction_context);
-                (auction_request.clone(), trace)
-            })
-            .collect::<Vec<_>>();
-        let other_traces = state
-            .other_submissions
-            .iter()
-            .flat_map(|(auction_request, contexts)| {
-                contexts.iter().map(|auction_context| {
-                    let trace = submission_trace_from_auction(auction_context);
-                    (auction_request.clone(), trace)
-                })
-            })
-            .collect::<Vec<_>>();
-        traces.extend(other_traces);
-        // sort by primarily slot, and then receipt timestamp
-        traces.sort_by(|a, b| {
-            let auction_request = a.0.cmp(&b.0);
-            if let Ordering::Equal = auction_request {
-                a.1.timestamp_ms.cmp(&b.1.timestamp_ms)
-            } else {
-                auction_request
-            }
-        });
-        Ok(traces.into_iter().rev().map(|(_, trace)| trace).collect())
+        let mut traces = self.block_submission_traces(filters);
+        traces.truncate(filters.limit());
+        Ok(traces)
     }
 
     async fn fetch_validator_registration(
@@ -793,4 +2534,156 @@ impl BlindedBlockDataProvider for Relay {
             .ok_or_else(|| RelayError::ValidatorNotRegistered(public_key.clone()))
             .map_err(Into::into)
     }
+
+    async fn fetch_registration_statuses(
+        &self,
+        public_keys: &[BlsPublicKey],
+    ) -> Result<Vec<RegistrationStatus>, Error> {
+        let statuses = public_keys
+            .iter()
+            .map(|public_key| {
+                let registration = self.validator_registry.get_signed_registration(public_key).map(
+                    |signed_registration| {
+                        let registration = signed_registration.message;
+                        // NOTE: registrations are refreshed with timestamps in nanoseconds
+                        let last_refreshed = self
+                            .validator_registry
+                            .get_last_refreshed(public_key)
+                            .unwrap_or_default() /
+                            1_000_000_000;
+                        ActiveRegistration {
+                            fee_recipient: registration.fee_recipient,
+                            gas_limit: registration.gas_limit,
+                            timestamp: registration.timestamp,
+                            last_refreshed,
+                        }
+                    },
+                );
+                RegistrationStatus { public_key: public_key.clone(), registration }
+            })
+            .collect();
+        Ok(statuses)
+    }
+
+    async fn get_auction_analytics(&self) -> Result<AuctionAnalytics, Error> {
+        let submissions = self.block_submission_traces(&Default::default());
+        let delivered_payloads = self.delivered_payload_traces(&Default::default());
+        let auctions_served = self.state.lock().served_auctions.len();
+        Ok(analytics::summarize(&submissions, &delivered_payloads, auctions_served))
+    }
+
+    async fn get_preconf_commitments(
+        &self,
+        slot: Slot,
+    ) -> Result<Vec<SignedPreconfCommitment>, Error> {
+        let state = self.state.lock();
+        let commitments =
+            state.preconf_commitments.get(&slot).map(Vec::as_slice).unwrap_or_default();
+        Ok(commitments.iter().map(|commitment| commitment.signed_commitment.clone()).collect())
+    }
+
+    async fn get_builder_collateral(&self) -> Result<Vec<BuilderCollateral>, Error> {
+        Ok(self.collateral.balances())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::crypto::SecretKey;
+    use mev_rs::signer::Config as SignerConfig;
+    use url::Url;
+
+    // NOTE: a slot after the Deneb fork on mainnet
+    const SLOT: Slot = 9_000_000;
+
+    // A relay whose beacon node can not be reached, signing with `secret_key`
+    fn relay(secret_key: SecretKey) -> Relay {
+        let beacon_node = ApiClient::new(Url::parse("http://127.0.0.1:0").unwrap());
+        let signer = Signer::new(SignerConfig::Local { secret_key }).unwrap();
+        let context = Context::for_mainnet();
+        Relay::new(beacon_node, signer, vec![], context, Root::default(), Default::default())
+    }
+
+    #[tokio::test]
+    async fn test_signed_bid_traces_of_delivered_payloads() {
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let public_key = secret_key.public_key();
+        let relay = relay(secret_key);
+        let bid_adjustment = BidAdjustment { subsidy_wei: U256::from(5), ..Default::default() };
+
+        let bid_trace = BidTrace { slot: SLOT, value: U256::from(100), ..Default::default() };
+        let signed_submission =
+            SignedBidSubmission::Deneb(block_submission::deneb::SignedBidSubmission {
+                message: bid_trace.clone(),
+                execution_payload: ExecutionPayload::Deneb(Default::default()),
+                blobs_bundle: Default::default(),
+                signature: Default::default(),
+            });
+        let auction_context = AuctionContext::new(
+            signed_submission,
+            Duration::ZERO,
+            &bid_adjustment,
+            &relay.signer,
+            &relay.context,
+        )
+        .await
+        .unwrap();
+
+        // NOTE: the trace carries the value served to the proposer, signed by the relay
+        let signed_bid_trace = relay.sign_bid_trace(&auction_context).await.unwrap();
+        let SignedBidTrace { message, signature } = &signed_bid_trace;
+        assert_eq!(message, &BidTrace { value: U256::from(105), ..bid_trace });
+        assert!(verify_signed_builder_data(message, &public_key, signature, &relay.context).is_ok());
+
+        let auction_request = AuctionRequest { slot: SLOT, ..Default::default() };
+        relay.store_delivered_payload(
+            auction_request,
+            Arc::new(auction_context),
+            Some(signed_bid_trace.clone()),
+            0,
+        );
+        let filters = DeliveredPayloadFilter::default();
+        let signed_bid_traces = relay.get_signed_delivered_payloads(&filters).await.unwrap();
+        assert_eq!(signed_bid_traces, vec![signed_bid_trace]);
+    }
+
+    #[test]
+    fn test_compute_withdrawals_root() {
+        let payload_attributes = serde_json::json!({
+            "timestamp": "1",
+            "prev_randao": Hash32::default(),
+            "suggested_fee_recipient": "0x0000000000000000000000000000000000000001",
+            "withdrawals": [{
+                "index": "1",
+                "validator_index": "2",
+                "address": "0x0000000000000000000000000000000000000003",
+                "amount": "4",
+            }],
+            "parent_beacon_block_root": Root::default(),
+        });
+        let withdrawal = capella::Withdrawal {
+            index: 1,
+            validator_index: 2,
+            address: serde_json::from_value(
+                payload_attributes["withdrawals"][0]["address"].clone(),
+            )
+            .unwrap(),
+            amount: 4,
+        };
+        let expected = withdrawals_root(vec![withdrawal.clone()]).unwrap();
+        assert_eq!(compute_withdrawals_root(&payload_attributes), Some(expected));
+
+        // NOTE: the root matches that of a payload including the same withdrawals
+        let mut payload = deneb::ExecutionPayload::default();
+        payload.withdrawals.push(withdrawal);
+        let payload = ExecutionPayload::Deneb(payload);
+        assert_eq!(ExecutionBlockFields::withdrawals_root(&payload), Some(expected));
+        assert_ne!(withdrawals_root(vec![]), Some(expected));
+
+        // NOTE: payload attributes from before the Capella fork carry no withdrawals
+        let mut payload_attributes = payload_attributes;
+        payload_attributes.as_object_mut().unwrap().remove("withdrawals");
+        assert_eq!(compute_withdrawals_root(&payload_attributes), None);
+    }
 }
@@ -0,0 +1,68 @@
+#[cfg(unix)]
+use crate::relay::Relay;
+use crate::{
+    rate_limit::Config as HeaderRateLimitConfig, service::Config,
+    timing::Config as AuctionTimingConfig,
+};
+use ethereum_consensus::primitives::{BlsPublicKey, U256};
+#[cfg(unix)]
+use serde::Deserialize;
+#[cfg(unix)]
+use std::path::PathBuf;
+#[cfg(unix)]
+use tokio::task::JoinHandle;
+#[cfg(unix)]
+use tracing::{error, info, warn};
+
+/// Operational settings of the relay that can be changed without restarting it
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub accepted_builders: Vec<BlsPublicKey>,
+    pub min_bid_value: U256,
+    pub header_rate_limits: HeaderRateLimitConfig,
+    pub auction_timing: AuctionTimingConfig,
+}
+
+impl From<&Config> for Settings {
+    fn from(config: &Config) -> Self {
+        Self {
+            accepted_builders: config.accepted_builders.clone(),
+            min_bid_value: config.min_bid_value_wei.unwrap_or_default(),
+            header_rate_limits: config.header_rate_limits.clone(),
+            auction_timing: config.auction_timing,
+        }
+    }
+}
+
+// NOTE: the configuration file holds the configuration of every `mev-rs` component, so only the
+// `relay` table is read
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct ConfigFile {
+    relay: Option<Config>,
+}
+
+/// Reload the [`Settings`] from the configuration file at `path` each time the process receives
+/// `SIGHUP`; any other changes to the file only take effect after a restart
+#[cfg(unix)]
+pub fn spawn(path: PathBuf, relay: Relay) -> JoinHandle<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    tokio::spawn(async move {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(err) => {
+                error!(%err, "could not listen for SIGHUP; configuration will not be reloaded");
+                return
+            }
+        };
+        while hangups.recv().await.is_some() {
+            info!(path = %path.display(), "reloading configuration");
+            match mev_rs::config::from_toml_file::<_, ConfigFile>(&path) {
+                Ok(ConfigFile { relay: Some(config) }) => relay.reload(Settings::from(&config)),
+                Ok(_) => warn!("missing relay config from file; keeping current settings"),
+                Err(err) => warn!(%err, "could not load configuration; keeping current settings"),
+            }
+        }
+    })
+}
@@ -0,0 +1,246 @@
+use ethereum_consensus::primitives::{BlsPublicKey, Slot};
+use mev_rs::RelayError;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+// Number of valid submissions a builder on probation must make to be promoted to full status.
+const PROMOTION_VALID_SUBMISSIONS: u64 = 100;
+// Number of invalid submissions (including simulation failures) tolerated before demotion.
+const DEMOTION_INVALID_SUBMISSIONS: u64 = 10;
+// Maximum number of submissions accepted per slot from a builder on probation.
+const PROBATION_SUBMISSIONS_PER_SLOT: usize = 50;
+// Maximum number of submissions accepted per slot from a demoted builder.
+const DEMOTED_SUBMISSIONS_PER_SLOT: usize = 5;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BuilderStatus {
+    #[default]
+    Probation,
+    Full,
+    Demoted,
+}
+
+impl BuilderStatus {
+    fn submissions_per_slot(&self) -> Option<usize> {
+        match self {
+            Self::Probation => Some(PROBATION_SUBMISSIONS_PER_SLOT),
+            Self::Full => None,
+            Self::Demoted => Some(DEMOTED_SUBMISSIONS_PER_SLOT),
+        }
+    }
+
+    // NOTE: demoted builders have their bids simulated before they are accepted, whatever their
+    // collateral
+    fn allows_optimistic(&self) -> bool {
+        !matches!(self, Self::Demoted)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BuilderStats {
+    pub valid_submissions: u64,
+    pub invalid_submissions: u64,
+    // NOTE: includes failures of the relay's own consistency checks against the bid trace, in
+    // addition to any failed simulations against an execution node
+    pub simulation_failures: u64,
+    pub delivery_faults: u64,
+}
+
+#[derive(Debug, Default)]
+struct BuilderReputation {
+    status: BuilderStatus,
+    stats: BuilderStats,
+    // count of invalid submissions since the last status change
+    recent_faults: u64,
+    // count of valid submissions since the last status change
+    recent_successes: u64,
+    current_slot: Slot,
+    submissions_in_slot: usize,
+}
+
+impl BuilderReputation {
+    fn on_submission(&mut self, slot: Slot) -> bool {
+        if slot != self.current_slot {
+            self.current_slot = slot;
+            self.submissions_in_slot = 0;
+        }
+        if let Some(limit) = self.status.submissions_per_slot() {
+            if self.submissions_in_slot >= limit {
+                return false
+            }
+        }
+        self.submissions_in_slot += 1;
+        true
+    }
+
+    fn transition(&mut self, status: BuilderStatus) -> Option<BuilderStatus> {
+        if self.status == status {
+            return None
+        }
+        self.status = status;
+        self.recent_faults = 0;
+        self.recent_successes = 0;
+        Some(status)
+    }
+
+    fn on_valid_submission(&mut self) -> Option<BuilderStatus> {
+        self.stats.valid_submissions += 1;
+        self.recent_successes += 1;
+        match self.status {
+            BuilderStatus::Probation if self.recent_successes >= PROMOTION_VALID_SUBMISSIONS => {
+                self.transition(BuilderStatus::Full)
+            }
+            // NOTE: demoted builders must earn their way back through probation
+            BuilderStatus::Demoted if self.recent_successes >= PROMOTION_VALID_SUBMISSIONS => {
+                self.transition(BuilderStatus::Probation)
+            }
+            _ => None,
+        }
+    }
+
+    fn on_fault(&mut self) -> Option<BuilderStatus> {
+        self.recent_faults += 1;
+        if self.recent_faults >= DEMOTION_INVALID_SUBMISSIONS {
+            self.transition(BuilderStatus::Demoted)
+        } else {
+            None
+        }
+    }
+
+    fn on_invalid_submission(&mut self) -> Option<BuilderStatus> {
+        self.stats.invalid_submissions += 1;
+        self.on_fault()
+    }
+
+    fn on_simulation_failure(&mut self) -> Option<BuilderStatus> {
+        self.stats.simulation_failures += 1;
+        self.on_fault()
+    }
+
+    fn on_delivery_fault(&mut self) -> Option<BuilderStatus> {
+        self.stats.delivery_faults += 1;
+        // failing to deliver a payload is the most severe fault, so demote immediately
+        self.transition(BuilderStatus::Demoted)
+    }
+}
+
+/// Tracks the outcome of each builder's submissions and moves builders between
+/// [`BuilderStatus`]es accordingly
+#[derive(Debug, Default)]
+pub struct BuilderReputations {
+    builders: Mutex<HashMap<BlsPublicKey, BuilderReputation>>,
+}
+
+impl BuilderReputations {
+    /// Enforce the submission rate limit for the builder's current status
+    pub fn on_submission(
+        &self,
+        builder_public_key: &BlsPublicKey,
+        slot: Slot,
+    ) -> Result<(), RelayError> {
+        let mut builders = self.builders.lock();
+        let builder = builders.entry(builder_public_key.clone()).or_default();
+        if builder.on_submission(slot) {
+            Ok(())
+        } else {
+            Err(RelayError::SubmissionRateLimited(builder_public_key.clone(), slot))
+        }
+    }
+
+    fn update(
+        &self,
+        builder_public_key: &BlsPublicKey,
+        f: impl FnOnce(&mut BuilderReputation) -> Option<BuilderStatus>,
+    ) {
+        let mut builders = self.builders.lock();
+        let builder = builders.entry(builder_public_key.clone()).or_default();
+        if let Some(status) = f(builder) {
+            let BuilderStats {
+                valid_submissions,
+                invalid_submissions,
+                simulation_failures,
+                delivery_faults,
+            } = builder.stats;
+            if status == BuilderStatus::Demoted {
+                warn!(%builder_public_key, valid_submissions, invalid_submissions, simulation_failures, delivery_faults, "builder demoted");
+            } else {
+                info!(%builder_public_key, ?status, valid_submissions, invalid_submissions, simulation_failures, delivery_faults, "builder status updated");
+            }
+        }
+    }
+
+    /// Whether bids from the builder may be accepted on the strength of their collateral, before
+    /// the block is validated
+    pub fn allows_optimistic(&self, builder_public_key: &BlsPublicKey) -> bool {
+        let builders = self.builders.lock();
+        let status = builders.get(builder_public_key).map(|builder| builder.status);
+        status.unwrap_or_default().allows_optimistic()
+    }
+
+    pub fn on_valid_submission(&self, builder_public_key: &BlsPublicKey) {
+        self.update(builder_public_key, BuilderReputation::on_valid_submission)
+    }
+
+    pub fn on_invalid_submission(&self, builder_public_key: &BlsPublicKey) {
+        self.update(builder_public_key, BuilderReputation::on_invalid_submission)
+    }
+
+    pub fn on_simulation_failure(&self, builder_public_key: &BlsPublicKey) {
+        self.update(builder_public_key, BuilderReputation::on_simulation_failure)
+    }
+
+    pub fn on_delivery_fault(&self, builder_public_key: &BlsPublicKey) {
+        self.update(builder_public_key, BuilderReputation::on_delivery_fault)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_lifecycle() {
+        let mut builder = BuilderReputation::default();
+        assert_eq!(builder.status, BuilderStatus::Probation);
+
+        for _ in 0..PROMOTION_VALID_SUBMISSIONS - 1 {
+            assert!(builder.on_valid_submission().is_none());
+        }
+        assert_eq!(builder.on_valid_submission(), Some(BuilderStatus::Full));
+
+        for _ in 0..DEMOTION_INVALID_SUBMISSIONS - 1 {
+            assert!(builder.on_simulation_failure().is_none());
+        }
+        assert_eq!(builder.on_invalid_submission(), Some(BuilderStatus::Demoted));
+        assert!(!builder.status.allows_optimistic());
+
+        for _ in 0..PROMOTION_VALID_SUBMISSIONS {
+            builder.on_valid_submission();
+        }
+        assert_eq!(builder.status, BuilderStatus::Probation);
+        assert!(builder.status.allows_optimistic());
+        assert_eq!(builder.on_delivery_fault(), Some(BuilderStatus::Demoted));
+
+        let stats = builder.stats;
+        assert_eq!(stats.valid_submissions, 2 * PROMOTION_VALID_SUBMISSIONS);
+        assert_eq!(stats.invalid_submissions, 1);
+        assert_eq!(stats.simulation_failures, DEMOTION_INVALID_SUBMISSIONS - 1);
+        assert_eq!(stats.delivery_faults, 1);
+    }
+
+    #[test]
+    fn test_submission_rate_limits() {
+        let mut builder = BuilderReputation::default();
+        for _ in 0..PROBATION_SUBMISSIONS_PER_SLOT {
+            assert!(builder.on_submission(1));
+        }
+        assert!(!builder.on_submission(1));
+        assert!(builder.on_submission(2));
+
+        builder.status = BuilderStatus::Full;
+        for _ in 0..2 * PROBATION_SUBMISSIONS_PER_SLOT {
+            assert!(builder.on_submission(2));
+        }
+    }
+}
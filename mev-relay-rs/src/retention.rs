@@ -0,0 +1,30 @@
+use ethereum_consensus::primitives::Epoch;
+use serde::Deserialize;
+
+const DEFAULT_RETENTION_EPOCHS: Epoch = 4;
+
+/// Retention windows for the relay's data, in epochs
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Number of epochs to keep the winning bid of each auction
+    pub bids: Epoch,
+    /// Number of epochs to keep all other bid submissions
+    pub submissions: Epoch,
+    /// Number of epochs to keep delivered payloads
+    pub delivered_payloads: Epoch,
+    /// Number of epochs to keep validator registrations that have not been refreshed;
+    /// registrations are kept indefinitely if not provided
+    pub registrations: Option<Epoch>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bids: DEFAULT_RETENTION_EPOCHS,
+            submissions: DEFAULT_RETENTION_EPOCHS,
+            delivered_payloads: DEFAULT_RETENTION_EPOCHS,
+            registrations: None,
+        }
+    }
+}
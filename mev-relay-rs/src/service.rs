@@ -1,13 +1,45 @@
-use crate::relay::Relay;
+use crate::{
+    alerts::{Alerter, Config as DeliveryAlertsConfig},
+    api_keys::Config as ApiKeysConfig,
+    archive::{Archiver, Config as ArchiveConfig},
+    bid_adjustment::Config as BidAdjustmentConfig,
+    cluster::{Cluster, Config as ClusterConfig},
+    export::{Config as ExportConfig, Exporter},
+    fallback::{Config as FallbackConfig, FallbackBuilder},
+    health::Config as HealthConfig,
+    metrics,
+    mock::Config as MockConfig,
+    payload_requests::PayloadRequester,
+    rate_limit::Config as HeaderRateLimitConfig,
+    relay::{Options as RelayOptions, Relay},
+    retention::Config as RetentionConfig,
+    simulator::{Config as SimulationConfig, Simulator},
+    timing::{AuctionTiming, Config as AuctionTimingConfig},
+};
 use backoff::ExponentialBackoff;
 use beacon_api_client::PayloadAttributesTopic;
 use ethereum_consensus::{
-    crypto::SecretKey, networks::Network, primitives::BlsPublicKey, state_transition::Context,
+    networks::Network,
+    primitives::{BlsPublicKey, U256},
+    state_transition::Context,
 };
 use futures::StreamExt;
-use mev_rs::{blinded_block_relayer::Server as BlindedBlockRelayerServer, get_genesis_time, Error};
+use mev_rs::{
+    blinded_block_relayer::Server as BlindedBlockRelayerServer,
+    get_genesis_time,
+    signer::{Config as SignerConfig, Signer},
+    Error, RelayError, SubmissionLimits,
+};
 use serde::Deserialize;
-use std::{future::Future, net::Ipv4Addr, pin::Pin, task::Poll};
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    net::Ipv4Addr,
+    path::PathBuf,
+    pin::Pin,
+    task::Poll,
+    time::Duration,
+};
 use tokio::task::{JoinError, JoinHandle};
 use tracing::{error, warn};
 use url::Url;
@@ -22,8 +54,73 @@ pub struct Config {
     pub host: Ipv4Addr,
     pub port: u16,
     pub beacon_node_url: String,
-    pub secret_key: SecretKey,
+    /// Either a `secret_key` for the relay or the `remote_signer` holding it
+    #[serde(flatten)]
+    pub signer: SignerConfig,
     pub accepted_builders: Vec<BlsPublicKey>,
+    /// Additional beacon nodes to publish unblinded blocks to, beyond `beacon_node_url`
+    #[serde(default)]
+    pub broadcast_beacon_node_urls: Vec<String>,
+    /// Port to serve Prometheus metrics on, if provided
+    pub metrics_port: Option<u16>,
+    /// Port to serve block submission and top bids over gRPC on, if provided; requires the
+    /// `grpc` feature
+    pub grpc_port: Option<u16>,
+    /// Optional object storage to archive delivered payloads to
+    pub archive: Option<ArchiveConfig>,
+    /// Optional directory to export each epoch's bid traces to as CSV files
+    pub export: Option<ExportConfig>,
+    /// Optional webhook to alert when a payload fails to be delivered
+    pub delivery_alerts: Option<DeliveryAlertsConfig>,
+    /// How long to keep data around before pruning
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    /// Simulate submissions against an execution node before accepting them; if not provided,
+    /// submissions are trusted to be valid
+    pub simulation: Option<SimulationConfig>,
+    /// Minimum value in wei a bid must have to be served to proposers; bids below this value are
+    /// still accepted and made available via the data APIs
+    pub min_bid_value_wei: Option<U256>,
+    /// Bounds on the gas limit of submitted blocks, if provided
+    pub min_gas_limit: Option<u64>,
+    pub max_gas_limit: Option<u64>,
+    /// If provided, only these proposers are served by the relay
+    pub allowed_proposers: Option<Vec<BlsPublicKey>>,
+    /// Collateral in wei credited to each builder; bids covered by a builder's collateral are
+    /// accepted before they are simulated
+    #[serde(default)]
+    pub builder_collateral: HashMap<BlsPublicKey, U256>,
+    /// URL for each builder to `POST` the bid trace of its header-only bids to once a proposer
+    /// signs their header, so the builder uploads the payload right away if it has not yet
+    #[serde(default)]
+    pub payload_request_urls: HashMap<BlsPublicKey, String>,
+    /// Build blocks from a connected execution node when no builder has submitted a bid, if
+    /// provided
+    pub fallback_builder: Option<FallbackConfig>,
+    /// Adjustment applied to the value of every bid served to proposers
+    #[serde(default)]
+    pub bid_adjustment: BidAdjustmentConfig,
+    /// Limits on the `getHeader` requests served in each slot, per proposer and IP address
+    #[serde(default)]
+    pub header_rate_limits: HeaderRateLimitConfig,
+    /// API keys issued to builders, to accompany their submissions
+    #[serde(default)]
+    pub builder_api_keys: ApiKeysConfig,
+    /// Limits on the size and rate of requests to the builder-facing endpoints
+    #[serde(default)]
+    pub submission_limits: SubmissionLimits,
+    /// Windows in each slot during which submissions and requests from proposers are served
+    #[serde(default)]
+    pub auction_timing: AuctionTimingConfig,
+    /// How far the beacon node can fall behind before the relay stops accepting submissions
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// Behavior of the relay when run in mock mode
+    #[serde(default)]
+    pub mock: MockConfig,
+    /// Shared Redis instance to coordinate with the other nodes of the relay, if run across
+    /// several processes
+    pub cluster: Option<ClusterConfig>,
 }
 
 impl Default for Config {
@@ -32,56 +129,272 @@ impl Default for Config {
             host: Ipv4Addr::LOCALHOST,
             port: 28545,
             beacon_node_url: "http://127.0.0.1:5052".into(),
-            secret_key: Default::default(),
+            signer: Default::default(),
             accepted_builders: Default::default(),
+            broadcast_beacon_node_urls: Default::default(),
+            metrics_port: None,
+            grpc_port: None,
+            archive: None,
+            export: None,
+            delivery_alerts: None,
+            retention: Default::default(),
+            simulation: None,
+            min_bid_value_wei: None,
+            min_gas_limit: None,
+            max_gas_limit: None,
+            allowed_proposers: None,
+            builder_collateral: Default::default(),
+            payload_request_urls: Default::default(),
+            fallback_builder: None,
+            bid_adjustment: Default::default(),
+            header_rate_limits: Default::default(),
+            builder_api_keys: Default::default(),
+            submission_limits: Default::default(),
+            auction_timing: Default::default(),
+            health: Default::default(),
+            mock: Default::default(),
+            cluster: None,
         }
     }
 }
 
+fn invalid_config(component: &'static str, err: impl std::fmt::Display) -> RelayError {
+    error!(%err, "could not configure {component}");
+    RelayError::InvalidConfig(component, err.to_string())
+}
+
 pub struct Service {
     host: Ipv4Addr,
     port: u16,
     beacon_node: Client,
     network: Network,
-    secret_key: SecretKey,
+    signer: SignerConfig,
     accepted_builders: Vec<BlsPublicKey>,
+    broadcast_beacon_nodes: Vec<Client>,
+    metrics_port: Option<u16>,
+    grpc_port: Option<u16>,
+    archive: Option<ArchiveConfig>,
+    export: Option<ExportConfig>,
+    delivery_alerts: Option<DeliveryAlertsConfig>,
+    retention: RetentionConfig,
+    simulation: Option<SimulationConfig>,
+    min_bid_value: U256,
+    min_gas_limit: Option<u64>,
+    max_gas_limit: Option<u64>,
+    allowed_proposers: Option<Vec<BlsPublicKey>>,
+    builder_collateral: HashMap<BlsPublicKey, U256>,
+    payload_request_urls: HashMap<BlsPublicKey, String>,
+    fallback_builder: Option<FallbackConfig>,
+    bid_adjustment: BidAdjustmentConfig,
+    header_rate_limits: HeaderRateLimitConfig,
+    builder_api_keys: ApiKeysConfig,
+    submission_limits: SubmissionLimits,
+    auction_timing: AuctionTimingConfig,
+    health: HealthConfig,
+    cluster: Option<ClusterConfig>,
+    config_file: Option<PathBuf>,
 }
 
 impl Service {
     pub fn from(network: Network, config: Config) -> Self {
         let endpoint: Url = config.beacon_node_url.parse().unwrap();
         let beacon_node = Client::new(endpoint);
+        let broadcast_beacon_nodes = config
+            .broadcast_beacon_node_urls
+            .iter()
+            .filter_map(|url| match url.parse::<Url>() {
+                Ok(endpoint) => Some(Client::new(endpoint)),
+                Err(err) => {
+                    warn!(%err, %url, "could not parse beacon node URL for block broadcast");
+                    None
+                }
+            })
+            .collect();
         Self {
             host: config.host,
             port: config.port,
             beacon_node,
             network,
-            secret_key: config.secret_key,
+            signer: config.signer,
             accepted_builders: config.accepted_builders,
+            broadcast_beacon_nodes,
+            metrics_port: config.metrics_port,
+            grpc_port: config.grpc_port,
+            archive: config.archive,
+            export: config.export,
+            delivery_alerts: config.delivery_alerts,
+            retention: config.retention,
+            simulation: config.simulation,
+            min_bid_value: config.min_bid_value_wei.unwrap_or_default(),
+            min_gas_limit: config.min_gas_limit,
+            max_gas_limit: config.max_gas_limit,
+            allowed_proposers: config.allowed_proposers,
+            builder_collateral: config.builder_collateral,
+            payload_request_urls: config.payload_request_urls,
+            fallback_builder: config.fallback_builder,
+            bid_adjustment: config.bid_adjustment,
+            header_rate_limits: config.header_rate_limits,
+            builder_api_keys: config.builder_api_keys,
+            submission_limits: config.submission_limits,
+            auction_timing: config.auction_timing,
+            health: config.health,
+            cluster: config.cluster,
+            config_file: None,
         }
     }
 
+    /// Reload the relay's operational settings from the configuration file at `path` on `SIGHUP`
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
     /// Configures the [`Relay`] and the [`BlindedBlockProviderServer`] and spawns both to
     /// individual tasks
     pub async fn spawn(self) -> Result<ServiceHandle, Error> {
-        let Self { host, port, beacon_node, network, secret_key, accepted_builders } = self;
+        let Self {
+            host,
+            port,
+            beacon_node,
+            network,
+            signer,
+            accepted_builders,
+            broadcast_beacon_nodes,
+            metrics_port,
+            grpc_port,
+            archive,
+            export,
+            delivery_alerts,
+            retention,
+            simulation,
+            min_bid_value,
+            min_gas_limit,
+            max_gas_limit,
+            allowed_proposers,
+            builder_collateral,
+            payload_request_urls,
+            fallback_builder,
+            bid_adjustment,
+            header_rate_limits,
+            builder_api_keys,
+            submission_limits,
+            auction_timing,
+            health,
+            cluster,
+            config_file,
+        } = self;
+
+        if let Some(port) = metrics_port {
+            metrics::spawn(host, port);
+        }
 
         let context = Context::try_from(network)?;
-        let genesis_time = get_genesis_time(&context, None, Some(&beacon_node)).await;
+        let genesis_time = get_genesis_time(&context, &[], Some(&beacon_node)).await;
         let clock = context.clock_at(genesis_time);
+        // NOTE: payload attributes are expected once the block for the current slot is imported,
+        // so only derive them locally if they have not arrived by this point into the slot
+        let fallback_delay = Duration::from_secs(context.seconds_per_slot * 2 / 3);
         let genesis_validators_root =
             beacon_node.get_genesis_details().await?.genesis_validators_root;
 
+        // NOTE: a relay missing any component it is configured with, e.g. simulation after a typo
+        // in a URL, must not serve at all rather than serve without it
+        let archiver = archive
+            .map(|config| Archiver::new(config, context.slots_per_epoch))
+            .transpose()
+            .map_err(|err| invalid_config("payload archive", err))?;
+
+        let exporter = export.map(Exporter::new);
+
+        let alerter = delivery_alerts
+            .map(Alerter::new)
+            .transpose()
+            .map_err(|err| invalid_config("delivery alerts", err))?;
+
+        let payload_requester = PayloadRequester::new(payload_request_urls)
+            .map_err(|err| invalid_config("payload requests to builders", err))?;
+
+        let simulator = simulation
+            .map(Simulator::new)
+            .transpose()
+            .map_err(|err| invalid_config("submission simulation", err))?;
+
+        let fallback_builder = fallback_builder
+            .map(FallbackBuilder::new)
+            .transpose()
+            .map_err(|err| invalid_config("fallback builder", err))?;
+
+        // NOTE: a node that can not reach the other nodes must not serve on its own
+        let cluster = match cluster {
+            Some(config) => Some(Cluster::connect(config).await.map_err(|err| {
+                error!(%err, "could not connect to other relay nodes");
+                RelayError::Degraded(err.to_string())
+            })?),
+            None => None,
+        };
+
+        let signer = Signer::new(signer).map_err(|err| {
+            error!(%err, "could not configure relay signer");
+            RelayError::SigningFailed(err.to_string())
+        })?;
+
+        let options = RelayOptions {
+            broadcast_beacon_nodes,
+            archiver,
+            alerter,
+            payload_requester,
+            simulator,
+            min_bid_value,
+            min_gas_limit,
+            max_gas_limit,
+            allowed_proposers: allowed_proposers.map(HashSet::from_iter),
+            builder_collateral,
+            fallback_builder,
+            bid_adjustment,
+            header_rate_limits,
+            builder_api_keys,
+            auction_timing: AuctionTiming::new(
+                auction_timing,
+                genesis_time,
+                context.seconds_per_slot,
+            ),
+            health,
+            cluster: cluster.clone(),
+        };
         let relay = Relay::new(
             beacon_node.clone(),
-            secret_key,
+            signer,
             accepted_builders,
             context,
             genesis_validators_root,
+            options,
         );
 
         let relay_for_api = relay.clone();
-        let server = BlindedBlockRelayerServer::new(host, port, relay_for_api).spawn();
+        let server = BlindedBlockRelayerServer::new(host, port, relay_for_api)
+            .with_submission_limits(submission_limits)
+            .spawn();
+
+        if let Some(port) = grpc_port {
+            #[cfg(feature = "grpc")]
+            crate::grpc::spawn(host, port, relay.clone());
+            #[cfg(not(feature = "grpc"))]
+            warn!(
+                port,
+                "not serving gRPC endpoint as the relay was built without the `grpc` feature"
+            );
+        }
+
+        if let Some(cluster) = cluster {
+            crate::cluster::spawn(cluster, relay.clone());
+        }
+
+        if let Some(path) = config_file {
+            #[cfg(unix)]
+            crate::reload::spawn(path, relay.clone());
+            #[cfg(not(unix))]
+            warn!(path = %path.display(), "not reloading configuration as `SIGHUP` is only supported on unix");
+        }
 
         let relay_clone = relay.clone();
         let consensus = tokio::spawn(async move {
@@ -104,7 +417,8 @@ impl Service {
                         while let Some(event) = stream.next().await {
                             match event {
                                 Ok(event) => {
-                                    if let Err(err) = relay.on_payload_attributes(event.data) {
+                                    if let Err(err) = relay.on_payload_attributes(event.data).await
+                                    {
                                         warn!(%err, "could not process payload attributes");
                                         continue
                                     }
@@ -141,8 +455,26 @@ impl Service {
                 if epoch > current_epoch {
                     current_epoch = epoch;
                     relay.on_epoch(epoch).await;
+                    let relay = relay.clone();
+                    let retention = retention.clone();
+                    let exporter = exporter.clone();
+                    tokio::task::spawn_blocking(move || {
+                        // NOTE: export the epoch that just ended before any of it is pruned
+                        if let Some(exporter) = exporter.as_ref() {
+                            relay.export(epoch - 1, exporter);
+                        }
+                        relay.prune(epoch, &retention)
+                    });
                 }
                 relay.on_slot(slot).await;
+
+                let relay = relay.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(fallback_delay).await;
+                    if let Err(err) = relay.derive_payload_attributes(slot + 1).await {
+                        warn!(%err, slot = slot + 1, "could not derive payload attributes");
+                    }
+                });
             }
         });
 
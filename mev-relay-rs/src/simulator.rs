@@ -0,0 +1,439 @@
+use ethereum_consensus::primitives::{BlsPublicKey, Root, Slot, U256};
+use futures::future::join_all;
+use mev_rs::types::SignedBidSubmission;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+    sync::{
+        atomic::{self, AtomicBool, AtomicU64, AtomicUsize},
+        Arc,
+    },
+    time::Duration,
+};
+use thiserror::Error;
+use tokio::sync::{oneshot, Notify};
+use tracing::{debug, info, warn};
+use url::Url;
+
+const VALIDATION_METHOD: &str = "flashbots_validateBuilderSubmissionV3";
+const SYNCING_METHOD: &str = "eth_syncing";
+const DEFAULT_MAX_CONCURRENT_SIMULATIONS_PER_ENDPOINT: usize = 4;
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(6);
+// Errors reserved by the JSON-RPC specification, e.g. "method not found" or "internal error",
+// which are raised by the node rather than by the validation of the submission
+const RESERVED_ERROR_CODES: std::ops::RangeInclusive<i64> = -32768..=-32600;
+// Messages of errors raised when the node is not in a state to validate the submission, e.g. as it
+// has not yet imported the parent block
+const NODE_FAULTS: &[&str] =
+    &["unknown ancestor", "unknown parent", "parent not found", "missing trie node", "syncing"];
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("simulation superseded by a later submission from the same builder")]
+    Superseded,
+    #[error("simulation cancelled as slot {0} has passed")]
+    Expired(Slot),
+    #[error("simulation failed: {0}")]
+    Invalid(String),
+    #[error("simulation node could not validate the submission: {0}")]
+    Node(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("no healthy simulation endpoints available")]
+    NoHealthyEndpoint,
+    #[error("simulator shut down before completing simulation")]
+    Shutdown,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Config {
+    /// URLs of execution nodes exposing the `flashbots_validateBuilderSubmissionV3` method;
+    /// simulations are balanced across all healthy nodes
+    pub endpoints: Vec<String>,
+    /// Maximum number of simulations in flight at any time, across all endpoints
+    pub max_concurrent_simulations: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct ValidationRequest<'a> {
+    #[serde(flatten)]
+    signed_submission: &'a SignedBidSubmission,
+    #[serde(with = "ethereum_consensus::serde::as_str")]
+    registered_gas_limit: u64,
+    parent_beacon_block_root: &'a Root,
+}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'static str,
+    params: P,
+}
+
+impl<P> JsonRpcRequest<P> {
+    fn new(method: &'static str, params: P) -> Self {
+        Self { jsonrpc: "2.0", id: 1, method, params }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcError {
+    #[serde(default)]
+    code: i64,
+    message: String,
+}
+
+impl From<JsonRpcError> for Error {
+    // NOTE: only errors from validating the submission itself are held against the builder
+    fn from(err: JsonRpcError) -> Self {
+        let message = err.message.to_lowercase();
+        if RESERVED_ERROR_CODES.contains(&err.code) ||
+            NODE_FAULTS.iter().any(|fault| message.contains(fault))
+        {
+            Self::Node(err.message)
+        } else {
+            Self::Invalid(err.message)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse {
+    result: Option<serde_json::Value>,
+    error: Option<JsonRpcError>,
+}
+
+struct Endpoint {
+    url: Url,
+    healthy: AtomicBool,
+    in_flight: AtomicUsize,
+}
+
+impl Endpoint {
+    fn new(url: Url) -> Self {
+        Self { url, healthy: AtomicBool::new(true), in_flight: AtomicUsize::new(0) }
+    }
+
+    fn is_healthy(&self) -> bool {
+        self.healthy.load(atomic::Ordering::Relaxed)
+    }
+
+    fn set_healthy(&self, healthy: bool) {
+        let was_healthy = self.healthy.swap(healthy, atomic::Ordering::Relaxed);
+        if was_healthy && !healthy {
+            warn!(endpoint = %self.url, "simulation endpoint is unhealthy");
+        } else if !was_healthy && healthy {
+            info!(endpoint = %self.url, "simulation endpoint has recovered");
+        }
+    }
+}
+
+pub struct SimulationRequest {
+    pub signed_submission: SignedBidSubmission,
+    pub registered_gas_limit: u64,
+    pub parent_beacon_block_root: Root,
+}
+
+struct Job {
+    value: U256,
+    slot: Slot,
+    builder_public_key: BlsPublicKey,
+    sequence: u64,
+    request: SimulationRequest,
+    result: oneshot::Sender<Result<(), Error>>,
+}
+
+// Jobs are ordered by claimed value so the most valuable submissions are simulated first,
+// falling back to the order of arrival.
+impl Ord for Job {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value).then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Job {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Job {
+    fn eq(&self, other: &Self) -> bool {
+        self.sequence == other.sequence
+    }
+}
+
+impl Eq for Job {}
+
+#[derive(Default)]
+struct Queue {
+    jobs: BinaryHeap<Job>,
+    // sequence number of the latest job from each builder, for each slot
+    latest_jobs: HashMap<(Slot, BlsPublicKey), u64>,
+    next_sequence: u64,
+}
+
+impl Queue {
+    fn push(&mut self, request: SimulationRequest, result: oneshot::Sender<Result<(), Error>>) {
+        let bid_trace = request.signed_submission.message();
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        let job = Job {
+            value: bid_trace.value,
+            slot: bid_trace.slot,
+            builder_public_key: bid_trace.builder_public_key.clone(),
+            sequence,
+            request,
+            result,
+        };
+        self.latest_jobs.insert((job.slot, job.builder_public_key.clone()), sequence);
+        self.jobs.push(job);
+    }
+
+    // Pop the most valuable job that is still relevant, cancelling any stale jobs found along
+    // the way.
+    fn pop(&mut self, current_slot: Slot) -> Option<Job> {
+        while let Some(job) = self.jobs.pop() {
+            if job.slot < current_slot {
+                let _ = job.result.send(Err(Error::Expired(job.slot)));
+                continue
+            }
+            let key = (job.slot, job.builder_public_key.clone());
+            let latest = self.latest_jobs.get(&key).copied().unwrap_or_default();
+            if job.sequence < latest {
+                let _ = job.result.send(Err(Error::Superseded));
+                continue
+            }
+            return Some(job)
+        }
+        None
+    }
+
+    fn prune(&mut self, current_slot: Slot) {
+        self.latest_jobs.retain(|(slot, _), _| *slot >= current_slot);
+    }
+}
+
+struct Inner {
+    client: reqwest::Client,
+    endpoints: Vec<Endpoint>,
+    queue: Mutex<Queue>,
+    notify: Notify,
+    current_slot: AtomicU64,
+}
+
+/// Simulates builder submissions against a set of execution nodes, prioritizing the most valuable
+/// submissions and dropping any made irrelevant before they are simulated
+#[derive(Clone)]
+pub struct Simulator(Arc<Inner>);
+
+impl Simulator {
+    pub fn new(config: Config) -> Result<Self, url::ParseError> {
+        let endpoints = config
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.parse().map(Endpoint::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let default_workers = DEFAULT_MAX_CONCURRENT_SIMULATIONS_PER_ENDPOINT * endpoints.len();
+        let inner = Inner {
+            client: reqwest::Client::new(),
+            endpoints,
+            queue: Default::default(),
+            notify: Notify::new(),
+            current_slot: AtomicU64::new(0),
+        };
+        let simulator = Self(Arc::new(inner));
+        let workers = config.max_concurrent_simulations.unwrap_or(default_workers).max(1);
+        for _ in 0..workers {
+            let simulator = simulator.clone();
+            tokio::spawn(async move { simulator.run().await });
+        }
+        let health_checker = simulator.clone();
+        tokio::spawn(async move { health_checker.check_health().await });
+        Ok(simulator)
+    }
+
+    pub fn on_slot(&self, slot: Slot) {
+        self.0.current_slot.store(slot, atomic::Ordering::Relaxed);
+        self.0.queue.lock().prune(slot);
+        // wake workers so they can clear any expired jobs
+        self.0.notify.notify_waiters();
+    }
+
+    pub async fn simulate(&self, request: SimulationRequest) -> Result<(), Error> {
+        let (tx, rx) = oneshot::channel();
+        self.0.queue.lock().push(request, tx);
+        self.0.notify.notify_one();
+        rx.await.map_err(|_| Error::Shutdown)?
+    }
+
+    async fn run(&self) {
+        loop {
+            let current_slot = self.0.current_slot.load(atomic::Ordering::Relaxed);
+            let job = self.0.queue.lock().pop(current_slot);
+            let Some(job) = job else {
+                self.0.notify.notified().await;
+                continue
+            };
+            let result = self.validate(&job.request).await;
+            if let Err(err) = &result {
+                debug!(%err, slot = job.slot, builder_public_key = %job.builder_public_key, "simulation failed");
+            }
+            let _ = job.result.send(result);
+        }
+    }
+
+    // Periodically confirm each endpoint is reachable and synced, so that simulations are only
+    // sent to nodes that can answer them.
+    async fn check_health(&self) {
+        let mut interval = tokio::time::interval(HEALTH_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.check_endpoints().await;
+        }
+    }
+
+    async fn check_endpoints(&self) {
+        let checks = self.0.endpoints.iter().map(|endpoint| async move {
+            let request = JsonRpcRequest::new(SYNCING_METHOD, [(); 0]);
+            let healthy = match self.call(endpoint, &request).await {
+                // `eth_syncing` returns `false` once the node is synced
+                Ok(result) => matches!(result, Some(serde_json::Value::Bool(false))),
+                Err(err) => {
+                    debug!(%err, endpoint = %endpoint.url, "could not check sync state of simulation endpoint");
+                    false
+                }
+            };
+            endpoint.set_healthy(healthy);
+        });
+        join_all(checks).await;
+    }
+
+    async fn call<P: Serialize>(
+        &self,
+        endpoint: &Endpoint,
+        request: &JsonRpcRequest<P>,
+    ) -> Result<Option<serde_json::Value>, Error> {
+        let response = self.0.client.post(endpoint.url.clone()).json(request).send().await?;
+        let response: JsonRpcResponse = response.error_for_status()?.json().await?;
+        if let Some(err) = response.error {
+            return Err(err.into())
+        }
+        Ok(response.result)
+    }
+
+    // Order the healthy endpoints by their current load, least loaded first.
+    // NOTE: if no endpoint is healthy, every endpoint is tried rather than failing every
+    // simulation, in case the health checks are wrong about them
+    fn select_endpoints(&self) -> Vec<&Endpoint> {
+        let mut endpoints =
+            self.0.endpoints.iter().filter(|endpoint| endpoint.is_healthy()).collect::<Vec<_>>();
+        if endpoints.is_empty() {
+            endpoints = self.0.endpoints.iter().collect();
+        }
+        endpoints.sort_by_key(|endpoint| endpoint.in_flight.load(atomic::Ordering::Relaxed));
+        endpoints
+    }
+
+    async fn validate(&self, request: &SimulationRequest) -> Result<(), Error> {
+        let request = JsonRpcRequest::new(
+            VALIDATION_METHOD,
+            [ValidationRequest {
+                signed_submission: &request.signed_submission,
+                registered_gas_limit: request.registered_gas_limit,
+                parent_beacon_block_root: &request.parent_beacon_block_root,
+            }],
+        );
+        let mut result = Err(Error::NoHealthyEndpoint);
+        for endpoint in self.select_endpoints() {
+            endpoint.in_flight.fetch_add(1, atomic::Ordering::Relaxed);
+            result = self.call(endpoint, &request).await.map(|_| ());
+            endpoint.in_flight.fetch_sub(1, atomic::Ordering::Relaxed);
+            match &result {
+                Err(Error::Http(err)) => {
+                    // try the next endpoint, leaving the node for the health check to restore
+                    warn!(%err, endpoint = %endpoint.url, "could not reach simulation endpoint");
+                    endpoint.set_healthy(false);
+                }
+                Err(Error::Node(err)) => {
+                    // NOTE: another node may be able to validate the submission, e.g. if this one
+                    // is behind the chain
+                    warn!(%err, endpoint = %endpoint.url, "simulation endpoint could not validate submission");
+                }
+                Err(Error::Invalid(err)) => {
+                    warn!(%err, "submission failed simulation");
+                    break
+                }
+                _ => break,
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use std::net::TcpListener;
+
+    // Serve an endpoint answering `eth_syncing` with `syncing`, or an error if missing
+    fn spawn_endpoint(syncing: Option<serde_json::Value>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handler = move |Json(_): Json<serde_json::Value>| {
+            let response = match syncing.clone() {
+                Some(result) => serde_json::json!({ "id": 1, "result": result }),
+                None => serde_json::json!({
+                    "id": 1,
+                    "error": { "code": -32601, "message": "method not found" },
+                }),
+            };
+            async move { Json(response) }
+        };
+        let router = Router::new().route("/", post(handler));
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_health_checks() {
+        let unreachable = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let endpoints = vec![
+            spawn_endpoint(Some(serde_json::Value::Bool(false))),
+            spawn_endpoint(None),
+            spawn_endpoint(Some(serde_json::json!({ "currentBlock": "0x1" }))),
+            format!("http://{unreachable}"),
+        ];
+        let simulator =
+            Simulator::new(Config { endpoints, max_concurrent_simulations: None }).unwrap();
+        simulator.check_endpoints().await;
+        let healthy =
+            simulator.0.endpoints.iter().map(|endpoint| endpoint.is_healthy()).collect::<Vec<_>>();
+        assert_eq!(healthy, vec![true, false, false, false]);
+        assert_eq!(simulator.select_endpoints().len(), 1);
+
+        // NOTE: every endpoint is tried once none are considered healthy
+        for endpoint in &simulator.0.endpoints {
+            endpoint.set_healthy(false);
+        }
+        assert_eq!(simulator.select_endpoints().len(), 4);
+    }
+
+    #[test]
+    fn test_rpc_errors() {
+        let error =
+            |code, message: &str| Error::from(JsonRpcError { code, message: message.into() });
+        assert!(matches!(error(-32000, "invalid state root"), Error::Invalid(..)));
+        assert!(matches!(error(-32000, "incorrect gas limit set"), Error::Invalid(..)));
+        // NOTE: faults of the node are not held against the builder
+        assert!(matches!(error(-32601, "the method does not exist"), Error::Node(..)));
+        assert!(matches!(error(-32603, "internal error"), Error::Node(..)));
+        assert!(matches!(error(-32000, "Unknown parent block"), Error::Node(..)));
+        assert!(matches!(error(-32000, "node is syncing"), Error::Node(..)));
+    }
+}
@@ -0,0 +1,121 @@
+use ethereum_consensus::primitives::Slot;
+use mev_rs::RelayError;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Timing of each auction, as offsets in milliseconds from the start of the auction's slot.
+/// Negative offsets are before the start of the slot, and bounds that are not provided are not
+/// enforced.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct Config {
+    /// Earliest offset at which submissions for the slot are accepted
+    pub bid_submission_open_ms: Option<i64>,
+    /// Latest offset at which submissions for the slot are accepted
+    pub bid_submission_close_ms: Option<i64>,
+    /// Earliest offset at which `getHeader` requests for the slot are served
+    pub get_header_open_ms: Option<i64>,
+    /// Latest offset at which `getHeader` requests for the slot are served
+    pub get_header_close_ms: Option<i64>,
+    /// Latest offset at which `getPayload` requests for the slot are served
+    pub get_payload_close_ms: Option<i64>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bid_submission_open_ms: None,
+            bid_submission_close_ms: Some(4_000),
+            get_header_open_ms: None,
+            get_header_close_ms: Some(3_000),
+            get_payload_close_ms: Some(4_000),
+        }
+    }
+}
+
+fn in_window(offset_ms: i64, open_ms: Option<i64>, close_ms: Option<i64>) -> bool {
+    open_ms.map_or(true, |open_ms| offset_ms >= open_ms) &&
+        close_ms.map_or(true, |close_ms| offset_ms <= close_ms)
+}
+
+/// Enforces the [`Config`] against the start of each slot on the relay's network
+#[derive(Debug, Default)]
+pub struct AuctionTiming {
+    config: RwLock<Config>,
+    genesis_time: u64,
+    seconds_per_slot: u64,
+}
+
+impl AuctionTiming {
+    pub fn new(config: Config, genesis_time: u64, seconds_per_slot: u64) -> Self {
+        Self { config: RwLock::new(config), genesis_time, seconds_per_slot }
+    }
+
+    /// Replace the windows enforced from now on
+    pub fn reconfigure(&self, config: Config) {
+        *self.config.write() = config;
+    }
+
+    /// Milliseconds from the start of `slot` to `now`, given as the duration since the UNIX epoch
+    pub fn slot_offset_ms(&self, slot: Slot, now: Duration) -> i64 {
+        let slot_start_ms = (self.genesis_time + slot * self.seconds_per_slot) * 1000;
+        now.as_millis() as i64 - slot_start_ms as i64
+    }
+
+    pub fn validate_submission(&self, slot: Slot, now: Duration) -> Result<(), RelayError> {
+        let offset_ms = self.slot_offset_ms(slot, now);
+        let Config { bid_submission_open_ms, bid_submission_close_ms, .. } = *self.config.read();
+        if in_window(offset_ms, bid_submission_open_ms, bid_submission_close_ms) {
+            Ok(())
+        } else {
+            Err(RelayError::SubmissionOutsideWindow { slot, offset_ms })
+        }
+    }
+
+    pub fn validate_header_request(&self, slot: Slot, now: Duration) -> Result<(), RelayError> {
+        let offset_ms = self.slot_offset_ms(slot, now);
+        let Config { get_header_open_ms, get_header_close_ms, .. } = *self.config.read();
+        if in_window(offset_ms, get_header_open_ms, get_header_close_ms) {
+            Ok(())
+        } else {
+            Err(RelayError::HeaderRequestOutsideWindow { slot, offset_ms })
+        }
+    }
+
+    pub fn validate_payload_request(&self, slot: Slot, now: Duration) -> Result<(), RelayError> {
+        let offset_ms = self.slot_offset_ms(slot, now);
+        if in_window(offset_ms, None, self.config.read().get_payload_close_ms) {
+            Ok(())
+        } else {
+            Err(RelayError::PayloadRequestTooLate { slot, offset_ms })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auction_timing() {
+        let config = Config { bid_submission_open_ms: Some(-12_000), ..Default::default() };
+        let timing = AuctionTiming::new(config, 100, 12);
+        // slot 2 starts 124 seconds after the UNIX epoch
+        let at = |ms: u64| Duration::from_millis(124_000 + ms);
+
+        assert!(timing.validate_submission(2, Duration::from_millis(111_999)).is_err());
+        assert!(timing.validate_submission(2, Duration::from_millis(112_000)).is_ok());
+        assert!(timing.validate_submission(2, at(4_000)).is_ok());
+        assert!(timing.validate_submission(2, at(4_001)).is_err());
+
+        assert!(timing.validate_header_request(2, Duration::from_millis(0)).is_ok());
+        assert!(timing.validate_header_request(2, at(3_001)).is_err());
+
+        assert!(timing.validate_payload_request(2, at(4_000)).is_ok());
+        assert!(timing.validate_payload_request(2, at(4_001)).is_err());
+
+        timing.reconfigure(Config { get_payload_close_ms: Some(5_000), ..Default::default() });
+        assert!(timing.validate_payload_request(2, at(4_001)).is_ok());
+    }
+}
@@ -1,9 +1,13 @@
 use crate::{
     blinded_block_relayer::BlindedBlockRelayer,
-    types::{ProposerSchedule, SignedBidSubmission},
+    types::{
+        ProposerSchedule, SignedBidSubmission, SignedConstraints, SignedHeaderSubmission,
+        SignedPreconfCommitment,
+    },
     Error,
 };
 use beacon_api_client::api_error_or_ok;
+use ethereum_consensus::primitives::Slot;
 
 #[cfg(not(feature = "minimal-preset"))]
 use beacon_api_client::mainnet::Client as BeaconApiClient;
@@ -32,4 +36,32 @@ impl BlindedBlockRelayer for Client {
         let response = self.api.http_post("/relay/v1/builder/blocks", signed_submission).await?;
         api_error_or_ok(response).await.map_err(From::from)
     }
+
+    async fn submit_header(&self, signed_submission: &SignedHeaderSubmission) -> Result<(), Error> {
+        let response = self.api.http_post("/relay/v2/builder/headers", signed_submission).await?;
+        api_error_or_ok(response).await.map_err(From::from)
+    }
+
+    async fn submit_constraints(
+        &self,
+        signed_constraints: &SignedConstraints,
+    ) -> Result<(), Error> {
+        let response =
+            self.api.http_post("/constraints/v1/builder/constraints", signed_constraints).await?;
+        api_error_or_ok(response).await.map_err(From::from)
+    }
+
+    async fn get_constraints(&self, slot: Slot) -> Result<Vec<SignedConstraints>, Error> {
+        let target = format!("/relay/v1/builder/constraints?slot={slot}");
+        self.api.get(&target).await.map_err(From::from)
+    }
+
+    async fn submit_preconf_commitment(
+        &self,
+        signed_commitment: &SignedPreconfCommitment,
+    ) -> Result<(), Error> {
+        let response =
+            self.api.http_post("/relay/v1/builder/preconf_commitments", signed_commitment).await?;
+        api_error_or_ok(response).await.map_err(From::from)
+    }
 }
@@ -0,0 +1,389 @@
+use crate::{
+    blinded_block_relayer::{RejectedRequest, RequestRejectionObserver, SubmissionLimits},
+    error::{Error, RelayError},
+};
+use axum::{
+    extract::{connect_info::Connected, ConnectInfo, State},
+    http::{header::CONTENT_LENGTH, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hyper::server::{
+    accept::Accept,
+    conn::{AddrIncoming, AddrStream},
+};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, HashSet},
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    time::{sleep, Instant, Sleep},
+};
+
+pub(crate) type Observer = Arc<dyn RequestRejectionObserver + Send + Sync>;
+
+/// Enforces the [`SubmissionLimits`] on requests to the builder-facing endpoints
+#[derive(Clone)]
+pub(crate) struct SubmissionLimiter {
+    limits: SubmissionLimits,
+    observer: Observer,
+    // NOTE: connections are identified by the address of their peer, which is unique to each
+    // open TCP connection
+    // submissions in flight for each open connection that has made a submission
+    connections: Arc<Mutex<HashMap<SocketAddr, usize>>>,
+    // open connections serving a stream, which are exempt from the idle timeout
+    streams: Arc<Mutex<HashSet<SocketAddr>>>,
+}
+
+impl SubmissionLimiter {
+    pub(crate) fn new(limits: SubmissionLimits, observer: Observer) -> Self {
+        Self { limits, observer, connections: Default::default(), streams: Default::default() }
+    }
+
+    fn enter(&self, peer: SocketAddr) -> Result<InFlight, (RejectedRequest, RelayError)> {
+        let mut connections = self.connections.lock();
+        if !connections.contains_key(&peer) && connections.len() >= self.limits.max_connections {
+            return Err((RejectedRequest::TooManyConnections, RelayError::TooManyConnections))
+        }
+        let count = connections.entry(peer).or_default();
+        if *count >= self.limits.max_concurrent_submissions_per_connection {
+            return Err((
+                RejectedRequest::TooManyConcurrentSubmissions,
+                RelayError::TooManyConcurrentSubmissions,
+            ))
+        }
+        *count += 1;
+        Ok(InFlight { peer, connections: self.connections.clone() })
+    }
+
+    /// Count a submission made over a stream against the limits of its connection, as only the
+    /// request opening the stream passes through [`limit_submissions`]
+    pub(crate) fn enter_stream_submission(&self, peer: SocketAddr) -> Result<InFlight, RelayError> {
+        self.enter(peer).map_err(|(rejected, err)| {
+            self.observer.on_request_rejected(rejected);
+            err
+        })
+    }
+
+    fn on_stream_opened(&self, peer: SocketAddr) {
+        self.streams.lock().insert(peer);
+    }
+
+    fn is_streaming(&self, peer: &SocketAddr) -> bool {
+        self.streams.lock().contains(peer)
+    }
+
+    // Stop counting the connection to `peer` once it is closed
+    fn on_connection_closed(&self, peer: &SocketAddr) {
+        self.connections.lock().remove(peer);
+        self.streams.lock().remove(peer);
+    }
+
+    fn reject(&self, rejected: RejectedRequest, err: RelayError) -> Response {
+        self.observer.on_request_rejected(rejected);
+        Error::from(err).into_response()
+    }
+}
+
+/// Marks a submission in flight for a connection until dropped
+pub(crate) struct InFlight {
+    peer: SocketAddr,
+    connections: Arc<Mutex<HashMap<SocketAddr, usize>>>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        // NOTE: the connection still counts against `max_connections` until it is closed
+        if let Some(count) = self.connections.lock().get_mut(&self.peer) {
+            *count = count.saturating_sub(1);
+        }
+    }
+}
+
+// NOTE: bodies without a `Content-Length` are still bounded by the `DefaultBodyLimit` applied to
+// the same routes, but are not observed when rejected.
+pub(crate) async fn limit_submissions<B>(
+    State(limiter): State<SubmissionLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let max_body_size = limiter.limits.max_body_size;
+    let content_length = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if content_length.is_some_and(|content_length| content_length > max_body_size) {
+        return limiter
+            .reject(RejectedRequest::Oversized, RelayError::RequestTooLarge(max_body_size))
+    }
+    let _in_flight = match limiter.enter(peer) {
+        Ok(in_flight) => in_flight,
+        Err((rejected, err)) => return limiter.reject(rejected, err),
+    };
+    next.run(request).await
+}
+
+// NOTE: a stream holds its connection open until the connection is closed, so the connection stays
+// exempt from the idle timeout for the rest of its life
+pub(crate) async fn exempt_streams<B>(
+    State(limiter): State<SubmissionLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    limiter.on_stream_opened(peer);
+    next.run(request).await
+}
+
+/// Accepts connections to the relay, closing any that stay idle for longer than `idle_timeout_ms`
+/// and releasing their place in the [`SubmissionLimits`] once closed
+pub struct LimitedIncoming {
+    inner: AddrIncoming,
+    limiter: SubmissionLimiter,
+    idle_timeout: Duration,
+}
+
+impl LimitedIncoming {
+    pub(crate) fn new(inner: AddrIncoming, limiter: SubmissionLimiter) -> Self {
+        let idle_timeout = Duration::from_millis(limiter.limits.idle_timeout_ms);
+        Self { inner, limiter, idle_timeout }
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.inner.local_addr()
+    }
+}
+
+impl Accept for LimitedIncoming {
+    type Conn = LimitedStream;
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.get_mut();
+        let stream = ready!(Pin::new(&mut this.inner).poll_accept(cx));
+        let stream = stream.map(|stream| {
+            stream.map(|inner| LimitedStream {
+                inner,
+                limiter: this.limiter.clone(),
+                idle_timeout: this.idle_timeout,
+                idle: Box::pin(sleep(this.idle_timeout)),
+            })
+        });
+        Poll::Ready(stream)
+    }
+}
+
+/// A connection to the relay, which fails once idle for longer than its idle timeout unless it
+/// serves a stream
+pub struct LimitedStream {
+    inner: AddrStream,
+    limiter: SubmissionLimiter,
+    idle_timeout: Duration,
+    idle: Pin<Box<Sleep>>,
+}
+
+impl LimitedStream {
+    fn on_activity(&mut self) {
+        self.idle.as_mut().reset(Instant::now() + self.idle_timeout);
+    }
+
+    // Check if the connection went idle while waiting on `poll`, resetting the idle timeout on
+    // any progress
+    fn poll_idle<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        poll: Poll<io::Result<T>>,
+    ) -> Poll<io::Result<T>> {
+        if poll.is_ready() {
+            self.on_activity();
+            return poll
+        }
+        match self.idle.as_mut().poll(cx) {
+            Poll::Ready(()) if self.limiter.is_streaming(&self.inner.remote_addr()) => {
+                // NOTE: rearm the timer so this task is woken again when it next elapses
+                self.on_activity();
+                let _ = self.idle.as_mut().poll(cx);
+                Poll::Pending
+            }
+            Poll::Ready(()) => {
+                Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "connection is idle")))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for LimitedStream {
+    fn drop(&mut self) {
+        self.limiter.on_connection_closed(&self.inner.remote_addr());
+    }
+}
+
+impl Connected<&LimitedStream> for SocketAddr {
+    fn connect_info(target: &LimitedStream) -> Self {
+        target.inner.remote_addr()
+    }
+}
+
+impl AsyncRead for LimitedStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_read(cx, buf);
+        this.poll_idle(cx, poll)
+    }
+}
+
+impl AsyncWrite for LimitedStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write(cx, buf);
+        this.poll_idle(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.inner).poll_write_vectored(cx, bufs);
+        this.poll_idle(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+    use tokio::{io::AsyncReadExt, net::TcpStream};
+
+    struct Observed(Mutex<Vec<RejectedRequest>>);
+
+    impl RequestRejectionObserver for Observed {
+        fn on_request_rejected(&self, rejected: RejectedRequest) {
+            self.0.lock().push(rejected);
+        }
+    }
+
+    fn limiter(limits: SubmissionLimits) -> SubmissionLimiter {
+        SubmissionLimiter::new(limits, Arc::new(Observed(Default::default())))
+    }
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_concurrent_submissions_per_connection_are_limited() {
+        let limiter = limiter(SubmissionLimits {
+            max_concurrent_submissions_per_connection: 2,
+            ..Default::default()
+        });
+        let first = limiter.enter(peer(1)).unwrap();
+        let _second = limiter.enter(peer(1)).unwrap();
+        let Err((rejected, _)) = limiter.enter(peer(1)) else { panic!("submission is limited") };
+        assert_eq!(rejected, RejectedRequest::TooManyConcurrentSubmissions);
+        // NOTE: other connections are unaffected
+        assert!(limiter.enter(peer(2)).is_ok());
+
+        drop(first);
+        assert!(limiter.enter(peer(1)).is_ok());
+    }
+
+    #[test]
+    fn test_submitting_connections_are_limited_until_closed() {
+        let limiter = limiter(SubmissionLimits { max_connections: 1, ..Default::default() });
+        drop(limiter.enter(peer(1)).unwrap());
+        // NOTE: the first connection is still open, even without submissions in flight
+        let Err((rejected, _)) = limiter.enter(peer(2)) else { panic!("connection is limited") };
+        assert_eq!(rejected, RejectedRequest::TooManyConnections);
+        assert!(limiter.enter(peer(1)).is_ok());
+
+        limiter.on_connection_closed(&peer(1));
+        assert!(limiter.enter(peer(2)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_idle_connections_are_closed() {
+        let limiter = limiter(SubmissionLimits { idle_timeout_ms: 50, ..Default::default() });
+        let incoming = AddrIncoming::bind(&peer(0)).unwrap();
+        let mut incoming = LimitedIncoming::new(incoming, limiter.clone());
+        let _client = TcpStream::connect(incoming.local_addr()).await.unwrap();
+        let mut stream = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx))
+            .await
+            .expect("accepts connection")
+            .unwrap();
+        let remote_addr = stream.inner.remote_addr();
+        drop(limiter.enter(remote_addr).unwrap());
+
+        let err = stream.read(&mut [0u8; 8]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        drop(stream);
+        assert!(limiter.connections.lock().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_streaming_connections_are_not_idle() {
+        let limiter = limiter(SubmissionLimits { idle_timeout_ms: 50, ..Default::default() });
+        let incoming = AddrIncoming::bind(&peer(0)).unwrap();
+        let mut incoming = LimitedIncoming::new(incoming, limiter.clone());
+        let _client = TcpStream::connect(incoming.local_addr()).await.unwrap();
+        let mut stream = poll_fn(|cx| Pin::new(&mut incoming).poll_accept(cx))
+            .await
+            .expect("accepts connection")
+            .unwrap();
+        let remote_addr = stream.inner.remote_addr();
+        limiter.on_stream_opened(remote_addr);
+
+        let read = tokio::time::timeout(Duration::from_millis(200), stream.read(&mut [0u8; 8]));
+        assert!(read.await.is_err(), "connection is still open");
+        drop(stream);
+        assert!(limiter.streams.lock().is_empty());
+    }
+
+    #[test]
+    fn test_stream_submissions_are_limited() {
+        let limiter = limiter(SubmissionLimits {
+            max_concurrent_submissions_per_connection: 1,
+            ..Default::default()
+        });
+        let _in_flight = limiter.enter_stream_submission(peer(1)).unwrap();
+        assert!(matches!(
+            limiter.enter_stream_submission(peer(1)),
+            Err(RelayError::TooManyConcurrentSubmissions)
+        ));
+    }
+}
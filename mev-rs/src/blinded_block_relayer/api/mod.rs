@@ -1,2 +1,3 @@
 pub mod client;
+mod limits;
 pub mod server;
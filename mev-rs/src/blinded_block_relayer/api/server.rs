@@ -1,33 +1,53 @@
+use super::limits::{exempt_streams, limit_submissions, LimitedIncoming, SubmissionLimiter};
 use crate::{
     blinded_block_provider::{
-        api::server::{
-            handle_fetch_bid, handle_open_bid, handle_status_check, handle_validator_registration,
-        },
+        api::server::{handle_fetch_bid, handle_open_bid, handle_validator_registration},
         BlindedBlockProvider,
     },
     blinded_block_relayer::{
-        BlindedBlockDataProvider, BlindedBlockRelayer, BlockSubmissionFilter,
-        DeliveredPayloadFilter, ValidatorRegistrationQuery,
+        BlindedBlockDataProvider, BlindedBlockRelayer, BlockSubmissionFilter, ConstraintsQuery,
+        DeliveredPayloadFilter, HeaderRequestLimiter, HealthCheck, RequestRejectionObserver,
+        SubmissionAuthenticator, SubmissionLimits, ValidatorRegistrationQuery, API_KEY_HEADER,
     },
     error::Error,
     types::{
-        block_submission::data_api::{PayloadTrace, SubmissionTrace},
-        ProposerSchedule, SignedBidSubmission, SignedValidatorRegistration,
+        block_submission::data_api::{
+            AuctionAnalytics, BuilderCollateral, PayloadTrace, SubmissionTrace,
+        },
+        AuctionRequest, ProposerSchedule, RegistrationStatus, SignedBidSubmission, SignedBidTrace,
+        SignedBuilderBid, SignedConstraints, SignedHeaderSubmission, SignedPreconfCommitment,
+        SignedValidatorRegistration, SubmissionAck,
     },
 };
 use axum::{
-    extract::{Json, Query, State},
-    response::Html,
-    routing::{get, post, IntoMakeService},
+    extract::{
+        connect_info::IntoMakeServiceWithConnectInfo,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, DefaultBodyLimit, Extension, Json, Path, Query, State,
+    },
+    http::HeaderMap,
+    middleware,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html, Response,
+    },
+    routing::{get, post},
     Router,
 };
+use beacon_api_client::VersionedValue;
+use ethereum_consensus::primitives::BlsPublicKey;
 use hyper::server::conn::AddrIncoming;
-use std::net::{Ipv4Addr, SocketAddr};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::Arc,
+};
 use tokio::task::JoinHandle;
-use tracing::{error, info, trace};
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use tracing::{error, info, trace, warn};
 
 /// Type alias for the configured axum server
-pub type BlockRelayServer = axum::Server<AddrIncoming, IntoMakeService<Router>>;
+pub type BlockRelayServer =
+    axum::Server<LimitedIncoming, IntoMakeServiceWithConnectInfo<Router, SocketAddr>>;
 
 // cribbed from: https://github.com/tbjgolden/simple-json-viewer
 const ROOT_HTML_TRAILER: &str = r#"
@@ -102,6 +122,21 @@ async fn handle_get_root<R: BlindedBlockDataProvider>(
     Ok(Html(response))
 }
 
+// NOTE: unlike a builder, the relay reports an error if it is degraded so that proposers stop
+// relying on it
+async fn handle_status_check<R: HealthCheck>(State(relay): State<R>) -> Result<(), Error> {
+    relay.check_health()
+}
+
+async fn handle_fetch_bid_from_peer<R: BlindedBlockProvider + HeaderRequestLimiter>(
+    State(relay): State<R>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Path(auction_request): Path<AuctionRequest>,
+) -> Result<Json<VersionedValue<SignedBuilderBid>>, Error> {
+    relay.admit_header_request(&auction_request, peer.ip())?;
+    handle_fetch_bid(State(relay), Path(auction_request)).await
+}
+
 async fn handle_get_proposal_schedule<R: BlindedBlockRelayer>(
     State(relay): State<R>,
 ) -> Result<Json<Vec<ProposerSchedule>>, Error> {
@@ -109,14 +144,129 @@ async fn handle_get_proposal_schedule<R: BlindedBlockRelayer>(
     Ok(Json(relay.get_proposal_schedule().await?))
 }
 
-async fn handle_submit_bid<R: BlindedBlockRelayer>(
+fn api_key(headers: &HeaderMap) -> Option<&str> {
+    headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok())
+}
+
+async fn handle_submit_bid<R: BlindedBlockRelayer + SubmissionAuthenticator>(
     State(relay): State<R>,
+    headers: HeaderMap,
     Json(signed_bid_submission): Json<SignedBidSubmission>,
 ) -> Result<(), Error> {
     trace!("handling bid submission");
+    let builder_public_key = &signed_bid_submission.message().builder_public_key;
+    relay.authenticate_submission(builder_public_key, api_key(&headers))?;
     relay.submit_bid(&signed_bid_submission).await
 }
 
+async fn handle_submit_bid_stream<
+    R: BlindedBlockRelayer + SubmissionAuthenticator + Send + 'static,
+>(
+    State(relay): State<R>,
+    Extension(limits): Extension<SubmissionLimits>,
+    Extension(limiter): Extension<SubmissionLimiter>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    trace!("opening stream of bid submissions");
+    let api_key = api_key(&headers).map(String::from);
+    upgrade
+        .max_message_size(limits.max_body_size)
+        .on_upgrade(move |socket| stream_submissions(relay, api_key, limiter, peer, socket))
+}
+
+// Accept SSZ-encoded submissions in binary messages and JSON-encoded submissions in text messages,
+// responding to each with a `SubmissionAck`. The API key given when opening the stream applies to
+// every submission made over it, and each submission counts against the limits of the connection
+// to `peer` while it is handled.
+async fn stream_submissions<R: BlindedBlockRelayer + SubmissionAuthenticator>(
+    relay: R,
+    api_key: Option<String>,
+    limiter: SubmissionLimiter,
+    peer: SocketAddr,
+    mut socket: WebSocket,
+) {
+    while let Some(message) = socket.recv().await {
+        let signed_bid_submission = match message {
+            Ok(Message::Binary(bytes)) => {
+                SignedBidSubmission::from_ssz_bytes(&bytes).map_err(|err| err.to_string())
+            }
+            Ok(Message::Text(text)) => {
+                serde_json::from_str::<SignedBidSubmission>(&text).map_err(|err| err.to_string())
+            }
+            Ok(Message::Close(..)) => break,
+            Ok(..) => continue,
+            Err(err) => {
+                trace!(%err, "closing stream of bid submissions");
+                break
+            }
+        };
+        let ack = match signed_bid_submission {
+            Ok(signed_bid_submission) => {
+                let message = signed_bid_submission.message();
+                let block_hash = message.block_hash.clone();
+                let result = match limiter.enter_stream_submission(peer) {
+                    Ok(_in_flight) => match relay
+                        .authenticate_submission(&message.builder_public_key, api_key.as_deref())
+                    {
+                        Ok(()) => relay.submit_bid(&signed_bid_submission).await,
+                        Err(err) => Err(err),
+                    },
+                    Err(err) => Err(err.into()),
+                };
+                SubmissionAck {
+                    block_hash: Some(block_hash),
+                    error: result.err().map(|err| err.to_string()),
+                }
+            }
+            Err(err) => SubmissionAck { block_hash: None, error: Some(err) },
+        };
+        let ack = serde_json::to_string(&ack).expect("can serialize");
+        if socket.send(Message::Text(ack)).await.is_err() {
+            break
+        }
+    }
+}
+
+async fn handle_submit_header<R: BlindedBlockRelayer + SubmissionAuthenticator>(
+    State(relay): State<R>,
+    headers: HeaderMap,
+    Json(signed_header_submission): Json<SignedHeaderSubmission>,
+) -> Result<(), Error> {
+    trace!("handling header submission");
+    let builder_public_key = &signed_header_submission.message().builder_public_key;
+    relay.authenticate_submission(builder_public_key, api_key(&headers))?;
+    relay.submit_header(&signed_header_submission).await
+}
+
+async fn handle_submit_constraints<R: BlindedBlockRelayer>(
+    State(relay): State<R>,
+    Json(signed_constraints): Json<SignedConstraints>,
+) -> Result<(), Error> {
+    trace!("handling constraints submission");
+    relay.submit_constraints(&signed_constraints).await
+}
+
+async fn handle_get_constraints<R: BlindedBlockRelayer>(
+    State(relay): State<R>,
+    Query(params): Query<ConstraintsQuery>,
+) -> Result<Json<Vec<SignedConstraints>>, Error> {
+    trace!(slot = params.slot, "serving constraints");
+    Ok(Json(relay.get_constraints(params.slot).await?))
+}
+
+async fn handle_submit_preconf_commitment<R: BlindedBlockRelayer + SubmissionAuthenticator>(
+    State(relay): State<R>,
+    headers: HeaderMap,
+    Json(signed_commitment): Json<SignedPreconfCommitment>,
+) -> Result<(), Error> {
+    trace!("handling preconfirmation commitment");
+    let builder_public_key = &signed_commitment.message.builder_public_key;
+    relay.authenticate_submission(builder_public_key, api_key(&headers))?;
+    relay.submit_preconf_commitment(&signed_commitment).await
+}
+
 async fn handle_get_proposer_payloads_delivered<R: BlindedBlockDataProvider>(
     State(relay): State<R>,
     Query(filters): Query<DeliveredPayloadFilter>,
@@ -125,6 +275,48 @@ async fn handle_get_proposer_payloads_delivered<R: BlindedBlockDataProvider>(
     Ok(Json(relay.get_delivered_payloads(&filters).await?))
 }
 
+async fn handle_stream_proposer_payloads_delivered<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    trace!("streaming proposer payloads delivered");
+    let events = BroadcastStream::new(relay.subscribe_delivered_payloads()).filter_map(|trace| {
+        match trace {
+            Ok(trace) => Some(Event::default().event("payload_delivered").json_data(trace)),
+            Err(err) => {
+                // NOTE: a slow subscriber misses events, rather than holding up the relay
+                warn!(%err, "subscriber lagged behind stream of delivered payloads");
+                None
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn handle_stream_top_bids<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    trace!("streaming top bids");
+    let events = BroadcastStream::new(relay.subscribe_top_bids()).filter_map(|bid_trace| {
+        match bid_trace {
+            Ok(bid_trace) => Some(Event::default().event("top_bid").json_data(bid_trace)),
+            Err(err) => {
+                // NOTE: a slow subscriber misses events, rather than holding up the relay
+                warn!(%err, "subscriber lagged behind stream of top bids");
+                None
+            }
+        }
+    });
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn handle_get_signed_proposer_payloads_delivered<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+    Query(filters): Query<DeliveredPayloadFilter>,
+) -> Result<Json<Vec<SignedBidTrace>>, Error> {
+    trace!("handling signed proposer payloads delivered");
+    Ok(Json(relay.get_signed_delivered_payloads(&filters).await?))
+}
+
 async fn handle_get_builder_blocks_received<R: BlindedBlockDataProvider>(
     State(relay): State<R>,
     Query(filters): Query<BlockSubmissionFilter>,
@@ -141,16 +333,51 @@ async fn handle_get_validator_registration<R: BlindedBlockDataProvider>(
     Ok(Json(relay.fetch_validator_registration(&params.public_key).await?))
 }
 
+async fn handle_get_registration_statuses<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+    Json(public_keys): Json<Vec<BlsPublicKey>>,
+) -> Result<Json<Vec<RegistrationStatus>>, Error> {
+    trace!(count = public_keys.len(), "handling fetch registration statuses");
+    Ok(Json(relay.fetch_registration_statuses(&public_keys).await?))
+}
+
+async fn handle_get_builder_collateral<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+) -> Result<Json<Vec<BuilderCollateral>>, Error> {
+    trace!("handling fetch builder collateral");
+    Ok(Json(relay.get_builder_collateral().await?))
+}
+
+async fn handle_get_auction_analytics<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+) -> Result<Json<AuctionAnalytics>, Error> {
+    trace!("handling fetch auction analytics");
+    Ok(Json(relay.get_auction_analytics().await?))
+}
+
+async fn handle_get_preconf_commitments<R: BlindedBlockDataProvider>(
+    State(relay): State<R>,
+    Query(params): Query<ConstraintsQuery>,
+) -> Result<Json<Vec<SignedPreconfCommitment>>, Error> {
+    trace!(slot = params.slot, "handling fetch preconfirmation commitments");
+    Ok(Json(relay.get_preconf_commitments(params.slot).await?))
+}
+
 pub struct Server<R> {
     host: Ipv4Addr,
     port: u16,
     relay: R,
+    submission_limits: SubmissionLimits,
 }
 
 impl<
         R: BlindedBlockRelayer
             + BlindedBlockProvider
             + BlindedBlockDataProvider
+            + HeaderRequestLimiter
+            + SubmissionAuthenticator
+            + RequestRejectionObserver
+            + HealthCheck
             + Clone
             + Send
             + Sync
@@ -158,26 +385,68 @@ impl<
     > Server<R>
 {
     pub fn new(host: Ipv4Addr, port: u16, relay: R) -> Self {
-        Self { host, port, relay }
+        Self { host, port, relay, submission_limits: Default::default() }
+    }
+
+    pub fn with_submission_limits(mut self, submission_limits: SubmissionLimits) -> Self {
+        self.submission_limits = submission_limits;
+        self
     }
 
     /// Configures and returns the axum server
     pub fn serve(&self) -> BlockRelayServer {
+        self.bind().0
+    }
+
+    // Configure the axum server, returning it along with the address it is bound to
+    fn bind(&self) -> (BlockRelayServer, SocketAddr) {
+        let observer = Arc::new(self.relay.clone());
+        let limiter = SubmissionLimiter::new(self.submission_limits.clone(), observer);
+        let connection_limiter = limiter.clone();
+        let streaming = middleware::from_fn_with_state(limiter.clone(), exempt_streams);
+        let submissions = Router::new()
+            .route("/relay/v1/builder/blocks", post(handle_submit_bid::<R>))
+            .route(
+                "/relay/v1/builder/blocks/stream",
+                get(handle_submit_bid_stream::<R>).layer(streaming.clone()),
+            )
+            .route("/relay/v2/builder/headers", post(handle_submit_header::<R>))
+            .route(
+                "/relay/v1/builder/preconf_commitments",
+                post(handle_submit_preconf_commitment::<R>),
+            )
+            .layer(middleware::from_fn_with_state(limiter.clone(), limit_submissions))
+            .layer(DefaultBodyLimit::max(self.submission_limits.max_body_size))
+            .layer(Extension(self.submission_limits.clone()))
+            .layer(Extension(limiter));
         let router = Router::new()
             .route("/", get(handle_get_root::<R>))
-            .route("/eth/v1/builder/status", get(handle_status_check))
+            .route("/eth/v1/builder/status", get(handle_status_check::<R>))
             .route("/eth/v1/builder/validators", post(handle_validator_registration::<R>))
             .route(
                 "/eth/v1/builder/header/:slot/:parent_hash/:public_key",
-                get(handle_fetch_bid::<R>),
+                get(handle_fetch_bid_from_peer::<R>),
             )
             .route("/eth/v1/builder/blinded_blocks", post(handle_open_bid::<R>))
             .route("/relay/v1/builder/validators", get(handle_get_proposal_schedule::<R>))
-            .route("/relay/v1/builder/blocks", post(handle_submit_bid::<R>))
+            .route("/relay/v1/builder/constraints", get(handle_get_constraints::<R>))
+            .route("/constraints/v1/builder/constraints", post(handle_submit_constraints::<R>))
             .route(
                 "/relay/v1/data/bidtraces/proposer_payload_delivered",
                 get(handle_get_proposer_payloads_delivered::<R>),
             )
+            .route(
+                "/relay/v1/data/events/proposer_payload_delivered",
+                get(handle_stream_proposer_payloads_delivered::<R>).layer(streaming.clone()),
+            )
+            .route(
+                "/relay/v1/data/events/top_bid",
+                get(handle_stream_top_bids::<R>).layer(streaming),
+            )
+            .route(
+                "/relay/v1/data/bidtraces/signed_proposer_payload_delivered",
+                get(handle_get_signed_proposer_payloads_delivered::<R>),
+            )
             .route(
                 "/relay/v1/data/bidtraces/builder_blocks_received",
                 get(handle_get_builder_blocks_received::<R>),
@@ -186,15 +455,28 @@ impl<
                 "/relay/v1/data/validator_registration",
                 get(handle_get_validator_registration::<R>),
             )
+            .route(
+                "/relay/v1/data/validator_registration_status",
+                post(handle_get_registration_statuses::<R>),
+            )
+            .route("/relay/v1/data/builder_collateral", get(handle_get_builder_collateral::<R>))
+            .route("/relay/v1/data/auction_analytics", get(handle_get_auction_analytics::<R>))
+            .route("/relay/v1/data/preconf_commitments", get(handle_get_preconf_commitments::<R>))
+            .merge(submissions)
             .with_state(self.relay.clone());
         let addr = SocketAddr::from((self.host, self.port));
-        axum::Server::bind(&addr).serve(router.into_make_service())
+        let incoming = AddrIncoming::bind(&addr)
+            .unwrap_or_else(|err| panic!("error binding to {addr}: {err}"));
+        let incoming = LimitedIncoming::new(incoming, connection_limiter);
+        let local_addr = incoming.local_addr();
+        let server = axum::Server::builder(incoming)
+            .serve(router.into_make_service_with_connect_info::<SocketAddr>());
+        (server, local_addr)
     }
 
     /// Spawns the server on a new task returning the handle for it
     pub fn spawn(&self) -> JoinHandle<()> {
-        let server = self.serve();
-        let addr = server.local_addr();
+        let (server, addr) = self.bind();
         tokio::spawn(async move {
             info!("listening at {addr}...");
             if let Err(err) = server.await {
@@ -7,40 +7,229 @@ pub use {api::client::Client, api::server::Server};
 use crate::{
     error::Error,
     types::{
-        block_submission::data_api::{PayloadTrace, SubmissionTrace},
-        ProposerSchedule, SignedBidSubmission, SignedValidatorRegistration,
+        block_submission::data_api::{
+            AuctionAnalytics, BuilderCollateral, PayloadTrace, SubmissionTrace,
+        },
+        AuctionRequest, ProposerSchedule, RegistrationStatus, SignedBidSubmission, SignedBidTrace,
+        SignedConstraints, SignedHeaderSubmission, SignedPreconfCommitment,
+        SignedValidatorRegistration,
     },
 };
 use async_trait::async_trait;
 use ethereum_consensus::primitives::{BlsPublicKey, Bytes32, Slot};
+use std::net::IpAddr;
 
 #[async_trait]
 pub trait BlindedBlockRelayer {
     async fn get_proposal_schedule(&self) -> Result<Vec<ProposerSchedule>, Error>;
 
     async fn submit_bid(&self, signed_submission: &SignedBidSubmission) -> Result<(), Error>;
+
+    /// Submit only the header of a block; the full payload must follow via `submit_bid` before
+    /// the relay can deliver it to the proposer
+    async fn submit_header(&self, signed_submission: &SignedHeaderSubmission) -> Result<(), Error>;
+
+    /// Submit inclusion constraints, signed by the proposer of the constrained slot
+    async fn submit_constraints(&self, signed_constraints: &SignedConstraints)
+        -> Result<(), Error>;
+
+    /// Fetch the constraints any block submitted for `slot` must satisfy
+    async fn get_constraints(&self, slot: Slot) -> Result<Vec<SignedConstraints>, Error>;
+
+    /// Commit to including a set of transactions in any block later submitted for the slot
+    async fn submit_preconf_commitment(
+        &self,
+        signed_commitment: &SignedPreconfCommitment,
+    ) -> Result<(), Error>;
+}
+
+/// Admission control for the `getHeader` requests a relay serves to proposers
+pub trait HeaderRequestLimiter {
+    /// Returns an error if the request for a header for `auction_request` made by `peer` should
+    /// not be served
+    fn admit_header_request(
+        &self,
+        auction_request: &AuctionRequest,
+        peer: IpAddr,
+    ) -> Result<(), Error>;
 }
 
+/// Reports whether a relay is currently able to run auctions
+pub trait HealthCheck {
+    fn check_health(&self) -> Result<(), Error>;
+}
+
+/// Header carrying the API key a relay issued to the builder making a submission
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Authentication of builders by API key, in addition to the signatures on their submissions
+pub trait SubmissionAuthenticator {
+    /// Returns an error if a submission from `builder_public_key` that carried `api_key` should
+    /// not be accepted
+    fn authenticate_submission(
+        &self,
+        builder_public_key: &BlsPublicKey,
+        api_key: Option<&str>,
+    ) -> Result<(), Error>;
+}
+
+/// Limits protecting the builder-facing endpoints of a relay from oversized or flooding requests
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[serde(default)]
+pub struct SubmissionLimits {
+    /// Maximum size in bytes of the body of a submission, or of a message on a submission stream
+    pub max_body_size: usize,
+    /// Maximum number of submissions handled at once for a single connection
+    pub max_concurrent_submissions_per_connection: usize,
+    /// Maximum number of connections making submissions to the relay at once; connections only
+    /// serving other endpoints are not counted
+    pub max_connections: usize,
+    /// Milliseconds a connection to the relay can go without reading or writing before it is
+    /// closed; connections serving a stream are exempt
+    pub idle_timeout_ms: u64,
+}
+
+impl Default for SubmissionLimits {
+    fn default() -> Self {
+        Self {
+            max_body_size: 16 * 1024 * 1024,
+            max_concurrent_submissions_per_connection: 8,
+            max_connections: 1024,
+            idle_timeout_ms: 60_000,
+        }
+    }
+}
+
+/// Why a request was rejected before being handled by the relay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectedRequest {
+    /// The body of the request exceeded `max_body_size`
+    Oversized,
+    /// The connection already had `max_concurrent_submissions_per_connection` submissions in
+    /// flight
+    TooManyConcurrentSubmissions,
+    /// The relay already had `max_connections` connections making submissions
+    TooManyConnections,
+}
+
+impl RejectedRequest {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Oversized => "oversized",
+            Self::TooManyConcurrentSubmissions => "too_many_concurrent_submissions",
+            Self::TooManyConnections => "too_many_connections",
+        }
+    }
+}
+
+/// Observes the requests rejected by the [`SubmissionLimits`] of a relay
+pub trait RequestRejectionObserver {
+    fn on_request_rejected(&self, rejected: RejectedRequest);
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+pub struct ConstraintsQuery {
+    #[serde(with = "crate::serde::as_str")]
+    pub slot: Slot,
+}
+
+/// Maximum number of entries returned for any query of the data APIs
+pub const MAX_DATA_API_LIMIT: usize = 200;
+
+fn matches<T: PartialEq>(filter: Option<&T>, value: &T) -> bool {
+    filter.map_or(true, |filter| filter == value)
+}
+
+// Entries are returned in descending order of slot, so the `cursor` is the highest slot to return
+// and `min_slot` the lowest.
+fn in_slot_range(cursor: Option<Slot>, min_slot: Option<Slot>, slot: Slot) -> bool {
+    cursor.map_or(true, |cursor| slot <= cursor) && min_slot.map_or(true, |min| slot >= min)
+}
+
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct DeliveredPayloadFilter {
     pub slot: Option<Slot>,
+    pub cursor: Option<Slot>,
+    // NOTE: non-standard field
+    pub min_slot: Option<Slot>,
+    pub limit: Option<usize>,
     pub block_hash: Option<Bytes32>,
-    pub block_number: Option<usize>,
+    pub block_number: Option<u64>,
     #[serde(rename = "proposer_pubkey")]
     pub proposer_public_key: Option<BlsPublicKey>,
     #[serde(rename = "builder_pubkey")]
     pub builder_public_key: Option<BlsPublicKey>,
 }
 
-#[derive(Debug, Clone)]
+impl DeliveredPayloadFilter {
+    /// Returns `true` if `trace` satisfies every filter provided
+    pub fn matches(&self, trace: &PayloadTrace) -> bool {
+        matches(self.slot.as_ref(), &trace.slot) &&
+            in_slot_range(self.cursor, self.min_slot, trace.slot) &&
+            matches(self.block_hash.as_ref(), &trace.block_hash) &&
+            matches(self.block_number.as_ref(), &trace.block_number) &&
+            matches(self.proposer_public_key.as_ref(), &trace.proposer_public_key) &&
+            matches(self.builder_public_key.as_ref(), &trace.builder_public_key)
+    }
+
+    /// Maximum number of entries to return for this query
+    pub fn limit(&self) -> usize {
+        self.limit.map_or(MAX_DATA_API_LIMIT, |limit| limit.min(MAX_DATA_API_LIMIT))
+    }
+}
+
+#[derive(Debug, Default, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize))]
 pub struct BlockSubmissionFilter {
     pub slot: Option<Slot>,
+    // NOTE: non-standard field
+    pub cursor: Option<Slot>,
+    // NOTE: non-standard field; receipt time in milliseconds of the last entry seen in the
+    // `cursor` slot, so that a slot with more entries than fit in one response can be paged
+    // through
+    pub cursor_timestamp_ms: Option<u64>,
+    // NOTE: non-standard field
+    pub min_slot: Option<Slot>,
+    pub limit: Option<usize>,
     pub block_hash: Option<Bytes32>,
-    pub block_number: Option<usize>,
+    pub block_number: Option<u64>,
     #[serde(rename = "builder_pubkey")]
     pub builder_public_key: Option<BlsPublicKey>,
+    // NOTE: non-standard field
+    #[serde(rename = "proposer_pubkey")]
+    pub proposer_public_key: Option<BlsPublicKey>,
+}
+
+impl BlockSubmissionFilter {
+    /// Returns `true` if `trace` satisfies every filter provided
+    pub fn matches(&self, trace: &SubmissionTrace) -> bool {
+        matches(self.slot.as_ref(), &trace.slot) &&
+            in_slot_range(self.cursor, self.min_slot, trace.slot) &&
+            self.before_cursor_timestamp(trace) &&
+            matches(self.block_hash.as_ref(), &trace.block_hash) &&
+            matches(self.block_number.as_ref(), &trace.block_number) &&
+            matches(self.builder_public_key.as_ref(), &trace.builder_public_key) &&
+            matches(self.proposer_public_key.as_ref(), &trace.proposer_public_key)
+    }
+
+    // Entries in the `cursor` slot are returned in descending order of receipt, so only those
+    // received before `cursor_timestamp_ms` are left to return
+    fn before_cursor_timestamp(&self, trace: &SubmissionTrace) -> bool {
+        match (self.cursor, self.cursor_timestamp_ms) {
+            (Some(cursor), Some(timestamp_ms)) if trace.slot == cursor => {
+                trace.timestamp_ms < timestamp_ms as u128
+            }
+            _ => true,
+        }
+    }
+
+    /// Maximum number of entries to return for this query
+    pub fn limit(&self) -> usize {
+        self.limit.map_or(MAX_DATA_API_LIMIT, |limit| limit.min(MAX_DATA_API_LIMIT))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -61,6 +250,20 @@ pub trait BlindedBlockDataProvider {
         filters: &DeliveredPayloadFilter,
     ) -> Result<Vec<PayloadTrace>, Error>;
 
+    /// Subscribe to the traces of payloads as the relay delivers them
+    #[cfg(feature = "relay-api")]
+    fn subscribe_delivered_payloads(&self) -> tokio::sync::broadcast::Receiver<PayloadTrace>;
+
+    /// Subscribe to the bid trace of each new top bid, as the relay receives it
+    #[cfg(feature = "relay-api")]
+    fn subscribe_top_bids(&self) -> tokio::sync::broadcast::Receiver<crate::types::BidTrace>;
+
+    /// Fetch the bid traces of delivered payloads, signed by the relay
+    async fn get_signed_delivered_payloads(
+        &self,
+        filters: &DeliveredPayloadFilter,
+    ) -> Result<Vec<SignedBidTrace>, Error>;
+
     async fn get_block_submissions(
         &self,
         filters: &BlockSubmissionFilter,
@@ -70,4 +273,60 @@ pub trait BlindedBlockDataProvider {
         &self,
         public_key: &BlsPublicKey,
     ) -> Result<SignedValidatorRegistration, Error>;
+
+    async fn fetch_registration_statuses(
+        &self,
+        public_keys: &[BlsPublicKey],
+    ) -> Result<Vec<RegistrationStatus>, Error>;
+
+    async fn get_builder_collateral(&self) -> Result<Vec<BuilderCollateral>, Error>;
+
+    /// Summarize the auctions the relay has retained data for
+    async fn get_auction_analytics(&self) -> Result<AuctionAnalytics, Error>;
+
+    async fn get_preconf_commitments(
+        &self,
+        slot: Slot,
+    ) -> Result<Vec<SignedPreconfCommitment>, Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delivered_payload_filter() {
+        let trace = PayloadTrace { slot: 10, block_number: 5, ..Default::default() };
+
+        let filters = DeliveredPayloadFilter::default();
+        assert!(filters.matches(&trace));
+        assert_eq!(filters.limit(), MAX_DATA_API_LIMIT);
+
+        let filters = DeliveredPayloadFilter { cursor: Some(9), ..Default::default() };
+        assert!(!filters.matches(&trace));
+        let filters =
+            DeliveredPayloadFilter { cursor: Some(10), min_slot: Some(10), ..Default::default() };
+        assert!(filters.matches(&trace));
+        let filters = DeliveredPayloadFilter { block_number: Some(6), ..Default::default() };
+        assert!(!filters.matches(&trace));
+
+        let filters = DeliveredPayloadFilter { limit: Some(1_000), ..Default::default() };
+        assert_eq!(filters.limit(), MAX_DATA_API_LIMIT);
+    }
+
+    #[test]
+    fn test_block_submission_cursor() {
+        let trace =
+            |slot, timestamp_ms| SubmissionTrace { slot, timestamp_ms, ..Default::default() };
+        let filters = BlockSubmissionFilter {
+            cursor: Some(10),
+            cursor_timestamp_ms: Some(2_000),
+            ..Default::default()
+        };
+        assert!(filters.matches(&trace(10, 1_999)));
+        assert!(!filters.matches(&trace(10, 2_000)));
+        // NOTE: the timestamp only bounds the entries of the `cursor` slot
+        assert!(filters.matches(&trace(9, 3_000)));
+        assert!(!filters.matches(&trace(11, 1_000)));
+    }
 }
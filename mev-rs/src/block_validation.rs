@@ -1,6 +1,7 @@
 use std::cmp::Ordering;
 
 pub const GAS_BOUND_DIVISOR: u64 = 1024;
+pub const MIN_GAS_LIMIT: u64 = 5000;
 
 pub fn compute_preferred_gas_limit(preferred_gas_limit: u64, parent_gas_limit: u64) -> u64 {
     match preferred_gas_limit.cmp(&parent_gas_limit) {
@@ -16,24 +17,28 @@ pub fn compute_preferred_gas_limit(preferred_gas_limit: u64, parent_gas_limit: u
     }
 }
 
+// Check `gas_limit` is a valid adjustment from `parent_gas_limit` under EIP-1559.
+pub fn verify_gas_limit_adjustment(gas_limit: u64, parent_gas_limit: u64) -> bool {
+    if gas_limit < MIN_GAS_LIMIT {
+        return false
+    }
+    match gas_limit.cmp(&parent_gas_limit) {
+        Ordering::Equal => true,
+        Ordering::Greater => {
+            let bound = parent_gas_limit + parent_gas_limit / GAS_BOUND_DIVISOR;
+            gas_limit < bound
+        }
+        Ordering::Less => {
+            let bound = parent_gas_limit - parent_gas_limit / GAS_BOUND_DIVISOR;
+            gas_limit > bound
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn verify_limits(gas_limit: u64, parent_gas_limit: u64) -> bool {
-        match gas_limit.cmp(&parent_gas_limit) {
-            Ordering::Equal => true,
-            Ordering::Greater => {
-                let bound = parent_gas_limit + parent_gas_limit / GAS_BOUND_DIVISOR;
-                gas_limit < bound
-            }
-            Ordering::Less => {
-                let bound = parent_gas_limit - parent_gas_limit / GAS_BOUND_DIVISOR;
-                gas_limit > bound
-            }
-        }
-    }
-
     #[test]
     fn test_compute_preferred_gas_limit() {
         for t in &[
@@ -45,7 +50,14 @@ mod tests {
             (29_970_700, 30_000_000, 29_970_705),
         ] {
             assert_eq!(compute_preferred_gas_limit(t.0, t.1), t.2);
-            assert!(verify_limits(t.2, t.1))
+            assert!(verify_gas_limit_adjustment(t.2, t.1))
         }
     }
+
+    #[test]
+    fn test_verify_gas_limit_adjustment() {
+        assert!(!verify_gas_limit_adjustment(30_029_296, 30_000_000));
+        assert!(!verify_gas_limit_adjustment(29_970_704, 30_000_000));
+        assert!(!verify_gas_limit_adjustment(MIN_GAS_LIMIT - 1, MIN_GAS_LIMIT));
+    }
 }
@@ -2,7 +2,7 @@ use crate::types::AuctionRequest;
 use beacon_api_client::Error as ApiError;
 use ethereum_consensus::{
     crypto::KzgCommitment,
-    primitives::{BlsPublicKey, ExecutionAddress, Hash32, ValidatorIndex},
+    primitives::{BlsPublicKey, ExecutionAddress, Hash32, Root, Slot, ValidatorIndex, U256},
     Error as ConsensusError, Fork,
 };
 use thiserror::Error;
@@ -39,12 +39,22 @@ pub enum RelayError {
     // InvalidGasLimitForProposer(BlsPublicKey, u64),
     #[error("bid trace declares gas limit of {0:?} but execution payload has {1:?}")]
     InvalidGasLimit(u64, u64),
+    #[error("gas limit {gas_limit} is outside of the accepted range [{min}, {max}]")]
+    GasLimitOutOfBounds { gas_limit: u64, min: u64, max: u64 },
+    #[error(
+        "gas limit {gas_limit} is not a valid adjustment from parent gas limit {parent_gas_limit}"
+    )]
+    InvalidGasLimitAdjustment { gas_limit: u64, parent_gas_limit: u64 },
     #[error("bid trace declares gas usage of {0} but execution payload uses {1}")]
     InvalidGasUsed(u64, u64),
     #[error("bid trace declares parent hash of {0:?} but execution payload has {1:?}")]
     InvalidParentHash(Hash32, Hash32),
     #[error("bid trace declares block hash of {0:?} but execution payload has {1:?}")]
     InvalidBlockHash(Hash32, Hash32),
+    #[error(
+        "execution payload has withdrawals root {provided:?} but payload attributes expect {expected:?}"
+    )]
+    InvalidWithdrawalsRoot { expected: Root, provided: Option<Root> },
     #[error("missing auction for {0}")]
     MissingAuction(AuctionRequest),
     #[error("signed blinded beacon block is invalid or equivocated")]
@@ -53,8 +63,76 @@ pub enum RelayError {
     ValidatorNotRegistered(BlsPublicKey),
     #[error("validator with index {0} was not found in consensus")]
     UnknownValidatorIndex(ValidatorIndex),
+    #[error("proposer with public key {0:?} is not allowed by this relay")]
+    ProposerNotAllowed(BlsPublicKey),
     #[error("builder with public key {0:?} is not currently registered")]
     BuilderNotRegistered(BlsPublicKey),
+    #[error("blobs bundle has {commitments} commitments, {proofs} proofs and {blobs} blobs")]
+    InvalidBlobsBundleLength { commitments: usize, proofs: usize, blobs: usize },
+    #[error("blobs bundle commitments do not match blob transactions in execution payload")]
+    InvalidBlobCommitments,
+    #[error("blobs bundle contains invalid KZG proofs")]
+    InvalidBlobProofs,
+    #[error("builder with public key {0:?} exceeded its submission limit for slot {1}")]
+    SubmissionRateLimited(BlsPublicKey, Slot),
+    #[error("could not simulate submission: {0}")]
+    SimulationFailed(String),
+    #[error("simulation is currently unavailable: {0}")]
+    SimulationUnavailable(String),
+    #[error("builder with public key {0:?} does not have enough collateral to cover the bid")]
+    InsufficientCollateral(BlsPublicKey),
+    #[error("payload for block {0:?} was not uploaded by the builder in time")]
+    PayloadNotUploaded(Hash32),
+    #[error("block {0:?} was already submitted and rejected: {1}")]
+    DuplicateSubmissionRejected(Hash32, String),
+    #[error("validator with public key {0:?} is not the proposer for slot {1}")]
+    UnexpectedProposer(BlsPublicKey, Slot),
+    #[error("constraints for slot {0} were already submitted")]
+    DuplicateConstraints(Slot),
+    #[error(
+        "block for slot {slot} is missing {missing} transaction(s) required by its constraints"
+    )]
+    ConstraintsNotSatisfied { slot: Slot, missing: usize },
+    #[error("no proposer is scheduled for slot {0}")]
+    NoProposerScheduled(Slot),
+    #[error("builder has already made {0} preconfirmation commitments for slot {1}")]
+    TooManyPreconfCommitments(usize, Slot),
+    #[error(
+        "block for slot {slot} is missing {missing} transaction(s) its builder committed to include"
+    )]
+    PreconfCommitmentsNotHonored { slot: Slot, missing: usize },
+    #[error("could not build fallback block: {0}")]
+    FallbackBuildFailed(String),
+    #[error("too many requests for headers from {0} in this slot")]
+    TooManyHeaderRequests(String),
+    #[error("missing or invalid API key for builder with public key {0:?}")]
+    InvalidApiKey(BlsPublicKey),
+    #[error("API key exceeded its limit of {0} submissions in this slot")]
+    ApiKeyRateLimited(usize),
+    #[error("request body exceeds the maximum size of {0} bytes")]
+    RequestTooLarge(usize),
+    #[error("connection has too many submissions in flight")]
+    TooManyConcurrentSubmissions,
+    #[error("too many connections are making submissions to the relay")]
+    TooManyConnections,
+    #[error("submission for slot {slot} is outside of its window at {offset_ms}ms into the slot")]
+    SubmissionOutsideWindow { slot: Slot, offset_ms: i64 },
+    #[error(
+        "request for header for slot {slot} is outside of its window at {offset_ms}ms into the slot"
+    )]
+    HeaderRequestOutsideWindow { slot: Slot, offset_ms: i64 },
+    #[error(
+        "payload for slot {slot} was requested past its deadline, {offset_ms}ms into the slot"
+    )]
+    PayloadRequestTooLate { slot: Slot, offset_ms: i64 },
+    #[error("relay is degraded: {0}")]
+    Degraded(String),
+    #[error("could not sign with relay key: {0}")]
+    SigningFailed(String),
+    #[error("could not configure {0}: {1}")]
+    InvalidConfig(&'static str, String),
+    #[error("adjusting bid value {0} overflows")]
+    BidAdjustmentOverflow(U256),
 }
 
 #[derive(Debug, Error)]
@@ -79,6 +157,13 @@ pub enum Error {
     Api(#[from] ApiError),
 }
 
+#[cfg(all(feature = "api", feature = "serde"))]
+impl From<crate::signer::Error> for Error {
+    fn from(err: crate::signer::Error) -> Self {
+        Self::Relay(RelayError::SigningFailed(err.to_string()))
+    }
+}
+
 #[cfg(feature = "api")]
 use axum::extract::Json;
 #[cfg(feature = "api")]
@@ -92,6 +177,13 @@ impl IntoResponse for Error {
         let message = self.to_string();
         let code = match self {
             Self::NoBidPrepared(..) => StatusCode::NO_CONTENT,
+            Self::Relay(RelayError::TooManyHeaderRequests(..)) |
+            Self::Relay(RelayError::ApiKeyRateLimited(..)) |
+            Self::Relay(RelayError::TooManyConcurrentSubmissions) => StatusCode::TOO_MANY_REQUESTS,
+            Self::Relay(RelayError::TooManyConnections) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Relay(RelayError::RequestTooLarge(..)) => StatusCode::PAYLOAD_TOO_LARGE,
+            Self::Relay(RelayError::Degraded(..)) => StatusCode::SERVICE_UNAVAILABLE,
+            Self::Relay(RelayError::InvalidApiKey(..)) => StatusCode::UNAUTHORIZED,
             _ => StatusCode::BAD_REQUEST,
         };
         (code, Json(beacon_api_client::ApiError::ErrorMessage { code, message })).into_response()
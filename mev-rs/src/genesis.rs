@@ -1,4 +1,6 @@
-use ethereum_consensus::{networks::typical_genesis_time, state_transition::Context};
+use ethereum_consensus::{
+    networks::typical_genesis_time, primitives::Root, state_transition::Context,
+};
 use tracing::warn;
 use url::Url;
 
@@ -9,7 +11,7 @@ use beacon_api_client::minimal::Client;
 
 pub async fn get_genesis_time(
     context: &Context,
-    beacon_node_url: Option<&String>,
+    beacon_node_urls: &[String],
     beacon_node: Option<&Client>,
 ) -> u64 {
     match context.genesis_time() {
@@ -22,13 +24,16 @@ pub async fn get_genesis_time(
                 }
             }
 
-            // use provided url for beacon node
-            if let Some(url) = beacon_node_url {
-                if let Ok(url) = Url::parse(url) {
-                    let client = Client::new(url);
-                    if let Ok(genesis_details) = client.get_genesis_details().await {
-                        return genesis_details.genesis_time
-                    }
+            // use provided urls for beacon nodes, in order, until one responds
+            for url in beacon_node_urls {
+                let Ok(endpoint) = Url::parse(url) else {
+                    warn!(%url, "could not parse beacon node url");
+                    continue
+                };
+                let client = Client::new(endpoint);
+                match client.get_genesis_details().await {
+                    Ok(genesis_details) => return genesis_details.genesis_time,
+                    Err(err) => warn!(%err, %url, "could not get genesis details from beacon node"),
                 }
             }
 
@@ -39,3 +44,20 @@ pub async fn get_genesis_time(
         }
     }
 }
+
+/// Fetch the genesis validators root from the provided urls for beacon nodes, in order, until one
+/// responds
+pub async fn get_genesis_validators_root(beacon_node_urls: &[String]) -> Option<Root> {
+    for url in beacon_node_urls {
+        let Ok(endpoint) = Url::parse(url) else {
+            warn!(%url, "could not parse beacon node url");
+            continue
+        };
+        let client = Client::new(endpoint);
+        match client.get_genesis_details().await {
+            Ok(genesis_details) => return Some(genesis_details.genesis_validators_root),
+            Err(err) => warn!(%err, %url, "could not get genesis details from beacon node"),
+        }
+    }
+    None
+}
@@ -8,16 +8,22 @@ mod proposer_scheduler;
 pub mod relay;
 #[cfg(feature = "serde")]
 pub mod serde;
+#[cfg(all(feature = "api", feature = "serde"))]
+pub mod signer;
 pub mod signing;
 pub mod types;
 mod validator_registry;
 
 pub use blinded_block_provider::BlindedBlockProvider;
-pub use blinded_block_relayer::{BlindedBlockDataProvider, BlindedBlockRelayer};
+pub use blinded_block_relayer::{
+    BlindedBlockDataProvider, BlindedBlockRelayer, HeaderRequestLimiter, HealthCheck,
+    RejectedRequest, RequestRejectionObserver, SubmissionAuthenticator, SubmissionLimits,
+    API_KEY_HEADER,
+};
 
 pub use block_validation::*;
 pub use error::*;
-pub use genesis::get_genesis_time;
+pub use genesis::{get_genesis_time, get_genesis_validators_root};
 pub use proposer_scheduler::ProposerScheduler;
 pub use relay::{Relay, RelayEndpoint};
 pub use validator_registry::ValidatorRegistry;
@@ -1,13 +1,18 @@
 use crate::{
     blinded_block_provider::Client as BlockProvider,
-    blinded_block_relayer::{BlindedBlockRelayer, Client as Relayer},
+    blinded_block_relayer::{BlindedBlockRelayer, Client as Relayer, API_KEY_HEADER},
     error::Error,
-    types::{ProposerSchedule, SignedBidSubmission},
+    types::{
+        ProposerSchedule, SignedBidSubmission, SignedConstraints, SignedHeaderSubmission,
+        SignedPreconfCommitment,
+    },
 };
 use async_trait::async_trait;
 use beacon_api_client::Client as BeaconClient;
 use ethereum_consensus::{
-    crypto::BlsError, primitives::BlsPublicKey, serde::try_bytes_from_hex_str,
+    crypto::BlsError,
+    primitives::{BlsPublicKey, Slot},
+    serde::try_bytes_from_hex_str,
 };
 use std::{cmp, fmt, hash, ops::Deref};
 use tracing::{error, warn};
@@ -60,6 +65,7 @@ pub fn parse_relay_endpoints(relay_urls: &[String]) -> Vec<RelayEndpoint> {
     relays
 }
 
+#[derive(Clone)]
 pub struct Relay {
     provider: BlockProvider,
     relayer: Relayer,
@@ -101,11 +107,30 @@ impl Deref for Relay {
     }
 }
 
+// Build an HTTP client sending the `api_key` a relay issued to this builder with every request.
+fn http_client_with_api_key(api_key: &str) -> Result<reqwest::Client, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    let api_key = reqwest::header::HeaderValue::from_str(api_key).map_err(|err| err.to_string())?;
+    headers.insert(API_KEY_HEADER, api_key);
+    reqwest::Client::builder().default_headers(headers).build().map_err(|err| err.to_string())
+}
+
 impl From<RelayEndpoint> for Relay {
     fn from(value: RelayEndpoint) -> Self {
-        let RelayEndpoint { url, public_key } = value;
+        let RelayEndpoint { mut url, public_key } = value;
+        // NOTE: the password of a relay URL, if any, is the API key the relay issued to this
+        // builder; remove it from the URL so it is not logged
+        let api_key = url.password().map(String::from);
+        let _ = url.set_password(None);
         let endpoint = url.clone();
-        let api_client = BeaconClient::new(url);
+        let api_client = match api_key.as_deref().map(http_client_with_api_key) {
+            Some(Ok(http_client)) => BeaconClient::new_with_client(http_client, url),
+            Some(Err(err)) => {
+                warn!(%err, %endpoint, "could not use API key for relay");
+                BeaconClient::new(url)
+            }
+            None => BeaconClient::new(url),
+        };
         let provider = BlockProvider::new(api_client.clone());
         let relayer = Relayer::new(api_client.clone());
         Self { provider, relayer, public_key, endpoint }
@@ -122,6 +147,28 @@ impl BlindedBlockRelayer for Relay {
         // TODO: retry on error
         self.relayer.submit_bid(signed_submission).await
     }
+
+    async fn submit_header(&self, signed_submission: &SignedHeaderSubmission) -> Result<(), Error> {
+        self.relayer.submit_header(signed_submission).await
+    }
+
+    async fn submit_constraints(
+        &self,
+        signed_constraints: &SignedConstraints,
+    ) -> Result<(), Error> {
+        self.relayer.submit_constraints(signed_constraints).await
+    }
+
+    async fn get_constraints(&self, slot: Slot) -> Result<Vec<SignedConstraints>, Error> {
+        self.relayer.get_constraints(slot).await
+    }
+
+    async fn submit_preconf_commitment(
+        &self,
+        signed_commitment: &SignedPreconfCommitment,
+    ) -> Result<(), Error> {
+        self.relayer.submit_preconf_commitment(signed_commitment).await
+    }
 }
 
 #[cfg(test)]
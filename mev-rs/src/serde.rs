@@ -2,7 +2,12 @@ pub(crate) use ethereum_consensus::serde::as_str;
 
 #[cfg(test)]
 mod tests {
-    use ethereum_consensus::{primitives::U256, types::mainnet::ExecutionPayloadHeader};
+    use ethereum_consensus::primitives::U256;
+
+    #[cfg(not(feature = "minimal-preset"))]
+    use ethereum_consensus::types::mainnet::ExecutionPayloadHeader;
+    #[cfg(feature = "minimal-preset")]
+    use ethereum_consensus::types::minimal::ExecutionPayloadHeader;
 
     use crate::types::{builder_bid::deneb, AuctionRequest, BuilderBid, SignedBuilderBid};
 
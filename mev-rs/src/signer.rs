@@ -0,0 +1,233 @@
+//! Signatures over builder messages, from either a local key or a key held by a remote signer,
+//! shared by the builder and the relay
+
+use crate::{
+    signing::sign_builder_message,
+    types::{BidTrace, BuilderBid},
+};
+use ethereum_consensus::{
+    builder::compute_builder_domain,
+    crypto::SecretKey,
+    primitives::{BlsPublicKey, BlsSignature, Root},
+    signing::compute_signing_root,
+    ssz::prelude::HashTreeRoot,
+    state_transition::Context,
+    Error as ConsensusError,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Consensus(#[from] ConsensusError),
+    #[error("could not encode signing request: {0}")]
+    Encoding(#[from] serde_json::Error),
+    #[error("remote signer failed: {0}")]
+    Remote(#[from] reqwest::Error),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum Config {
+    /// Sign with a key held by the process
+    Local { secret_key: SecretKey },
+    /// Sign with a key held by a remote signer
+    Remote { remote_signer: RemoteConfig },
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::Local { secret_key: Default::default() }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct RemoteConfig {
+    /// URL of a signer exposing the Web3Signer `/api/v1/eth2/sign/{identifier}` endpoint,
+    /// e.g. Web3Signer itself or Dirk behind a compatible proxy; any path in the URL is kept
+    pub url: String,
+    /// Public key of the key held by the remote signer
+    pub public_key: BlsPublicKey,
+}
+
+/// A builder message signed by a remote signer.
+///
+/// Like the messages of the Web3Signer API, a request names the `type` of the message and carries
+/// the message under the field named after it, e.g. `bid_trace`, so the signer can check the
+/// signing root against the message rather than sign any root it is sent.
+pub trait RemoteMessage: HashTreeRoot + Serialize {
+    const TYPE: &'static str;
+    const FIELD: &'static str;
+}
+
+impl RemoteMessage for BuilderBid {
+    const TYPE: &'static str = "BUILDER_BID";
+    const FIELD: &'static str = "builder_bid";
+}
+
+impl RemoteMessage for BidTrace {
+    const TYPE: &'static str = "BID_TRACE";
+    const FIELD: &'static str = "bid_trace";
+}
+
+fn sign_request<T: RemoteMessage>(
+    message: &T,
+    signing_root: &Root,
+) -> Result<serde_json::Value, serde_json::Error> {
+    let mut request = serde_json::Map::new();
+    request.insert("type".to_string(), T::TYPE.into());
+    request.insert("signingRoot".to_string(), serde_json::to_value(signing_root)?);
+    request.insert(T::FIELD.to_string(), serde_json::to_value(message)?);
+    Ok(request.into())
+}
+
+// The sign endpoint for `public_key` under `url`
+fn sign_endpoint(url: &str, public_key: &BlsPublicKey) -> Result<Url, url::ParseError> {
+    let mut url: Url = url.parse()?;
+    // NOTE: joining a path to a URL replaces its last segment unless the URL ends with a slash,
+    // which would drop the path of a signer served under one, e.g. behind a proxy
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    url.join(&format!("api/v1/eth2/sign/{public_key}"))
+}
+
+#[derive(Deserialize)]
+struct SignResponse {
+    signature: BlsSignature,
+}
+
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    endpoint: Url,
+    public_key: BlsPublicKey,
+}
+
+impl RemoteSigner {
+    async fn sign(&self, request: &serde_json::Value) -> Result<BlsSignature, reqwest::Error> {
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header("accept", "application/json")
+            .json(request)
+            .send()
+            .await?;
+        let response: SignResponse = response.error_for_status()?.json().await?;
+        Ok(response.signature)
+    }
+}
+
+/// Provides signatures over builder messages, from either a local or remote key
+pub enum Signer {
+    Local { secret_key: SecretKey, public_key: BlsPublicKey },
+    Remote(RemoteSigner),
+}
+
+impl Signer {
+    pub fn new(config: Config) -> Result<Self, url::ParseError> {
+        let signer = match config {
+            Config::Local { secret_key } => {
+                let public_key = secret_key.public_key();
+                Self::Local { secret_key, public_key }
+            }
+            Config::Remote { remote_signer } => {
+                let RemoteConfig { url, public_key } = remote_signer;
+                let endpoint = sign_endpoint(&url, &public_key)?;
+                Self::Remote(RemoteSigner { client: reqwest::Client::new(), endpoint, public_key })
+            }
+        };
+        Ok(signer)
+    }
+
+    pub fn public_key(&self) -> &BlsPublicKey {
+        match self {
+            Self::Local { public_key, .. } => public_key,
+            Self::Remote(signer) => &signer.public_key,
+        }
+    }
+
+    pub async fn sign_builder_message<T: RemoteMessage>(
+        &self,
+        message: &T,
+        context: &Context,
+    ) -> Result<BlsSignature, Error> {
+        match self {
+            Self::Local { secret_key, .. } => {
+                Ok(sign_builder_message(message, secret_key, context)?)
+            }
+            Self::Remote(signer) => {
+                let domain = compute_builder_domain(context)?;
+                let signing_root = compute_signing_root(message, domain)?;
+                let request = sign_request(message, &signing_root)?;
+                Ok(signer.sign(&request).await?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::post, Json, Router};
+    use std::net::TcpListener;
+
+    // Serve the Web3Signer sign endpoint for `secret_key` under `base_path`, signing only requests
+    // for bid traces
+    fn spawn_remote_signer(secret_key: SecretKey, base_path: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let public_key = secret_key.public_key().to_string();
+        let handler = move |Path(identifier): Path<String>,
+                            Json(request): Json<serde_json::Value>| {
+            let (secret_key, public_key) = (secret_key.clone(), public_key.clone());
+            async move {
+                assert_eq!(identifier, public_key);
+                assert_eq!(request["type"], "BID_TRACE");
+                assert!(request["bid_trace"]["slot"].is_string());
+                let signing_root: Root =
+                    serde_json::from_value(request["signingRoot"].clone()).unwrap();
+                let signature = secret_key.sign(signing_root.as_ref());
+                Json(serde_json::json!({ "signature": signature }))
+            }
+        };
+        let route = format!("{base_path}/api/v1/eth2/sign/:identifier");
+        let router = Router::new().route(&route, post(handler));
+        let server = axum::Server::from_tcp(listener).unwrap().serve(router.into_make_service());
+        tokio::spawn(server);
+        format!("http://{addr}{base_path}")
+    }
+
+    #[test]
+    fn test_sign_endpoint_keeps_base_path() {
+        let public_key = SecretKey::key_gen(&[1u8; 32]).unwrap().public_key();
+        let expected = format!("/api/v1/eth2/sign/{public_key}");
+        let endpoint = sign_endpoint("http://127.0.0.1:9000", &public_key).unwrap();
+        assert_eq!(endpoint.path(), expected);
+        for url in ["http://127.0.0.1:9000/signer", "http://127.0.0.1:9000/signer/"] {
+            let endpoint = sign_endpoint(url, &public_key).unwrap();
+            assert_eq!(endpoint.path(), format!("/signer{expected}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer() {
+        let context = Context::for_mainnet();
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let public_key = secret_key.public_key();
+        let url = spawn_remote_signer(secret_key.clone(), "/signer");
+        let signer =
+            Signer::new(Config::Remote { remote_signer: RemoteConfig { url, public_key } })
+                .unwrap();
+
+        let message = BidTrace { slot: 7, ..Default::default() };
+        let signature = signer.sign_builder_message(&message, &context).await.unwrap();
+        assert_eq!(signature, sign_builder_message(&message, &secret_key, &context).unwrap());
+
+        let local = Signer::new(Config::Local { secret_key }).unwrap();
+        assert_eq!(local.public_key(), signer.public_key());
+        assert_eq!(local.sign_builder_message(&message, &context).await.unwrap(), signature);
+    }
+}
@@ -1,3 +1,4 @@
+use blst::{blst_scalar, min_pk, BLST_ERROR};
 use ethereum_consensus::{
     builder::compute_builder_domain,
     crypto,
@@ -10,6 +11,15 @@ use ethereum_consensus::{
     Error,
 };
 pub use ethereum_consensus::{crypto::SecretKey, signing::verify_signed_data};
+use rand::RngCore;
+use rayon::prelude::*;
+
+// Number of signatures aggregated and verified together by `batch_verify_signatures`
+const SIGNATURE_BATCH_SIZE: usize = 64;
+// Domain separation tag of the proof-of-possession ciphersuite used by the consensus layer
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+// Bits of randomness in the scalar each signature of a batch is weighted by
+const BATCH_RANDOM_BITS: usize = 64;
 
 pub fn compute_consensus_domain(
     slot: Slot,
@@ -45,3 +55,123 @@ pub fn verify_signed_builder_data<T: HashTreeRoot>(
     let signing_root = compute_signing_root(data, domain)?;
     crypto::verify_signature(public_key, signing_root.as_ref(), signature).map_err(Into::into)
 }
+
+// Random non-zero scalar to weigh a signature of a batch by
+fn random_scalar(rng: &mut impl RngCore) -> blst_scalar {
+    let mut scalar = blst_scalar::default();
+    let mut value = 0u64;
+    while value == 0 {
+        value = rng.next_u64();
+    }
+    scalar.b[..8].copy_from_slice(&value.to_le_bytes());
+    scalar
+}
+
+// Indicates if every signature of `batch` is valid, by checking the sum of the signatures, each
+// weighted by a random scalar, against the public keys weighted the same way.
+// NOTE: as the scalars are unknown to whoever made the signatures, invalid signatures can not be
+// chosen to cancel each other out in the sum
+fn verify_signature_batch(batch: &[(&BlsPublicKey, Root, &BlsSignature)]) -> bool {
+    let public_keys = batch
+        .iter()
+        .map(|(public_key, ..)| min_pk::PublicKey::key_validate(public_key.as_ref()))
+        .collect::<Result<Vec<_>, _>>();
+    let signatures = batch
+        .iter()
+        .map(|(.., signature)| min_pk::Signature::sig_validate(signature.as_ref(), true))
+        .collect::<Result<Vec<_>, _>>();
+    let (Ok(public_keys), Ok(signatures)) = (public_keys, signatures) else { return false };
+    let public_keys = public_keys.iter().collect::<Vec<_>>();
+    let signatures = signatures.iter().collect::<Vec<_>>();
+    let messages =
+        batch.iter().map(|(_, signing_root, _)| signing_root.as_ref()).collect::<Vec<_>>();
+    let mut rng = rand::thread_rng();
+    let scalars = batch.iter().map(|_| random_scalar(&mut rng)).collect::<Vec<_>>();
+    let result = min_pk::Signature::verify_multiple_aggregate_signatures(
+        &messages,
+        BLS_DST,
+        &public_keys,
+        false,
+        &signatures,
+        false,
+        &scalars,
+        BATCH_RANDOM_BITS,
+    );
+    result == BLST_ERROR::BLST_SUCCESS
+}
+
+/// Verify each signature over its signing root, returning the result for each in order.
+///
+/// Signatures are verified in parallel batches, each verified at once with every signature
+/// weighted by a random scalar; the signatures of a batch that fails are then verified
+/// individually to find the invalid ones.
+pub fn batch_verify_signatures(
+    signatures: &[(&BlsPublicKey, Root, &BlsSignature)],
+) -> Vec<Result<(), Error>> {
+    signatures
+        .par_chunks(SIGNATURE_BATCH_SIZE)
+        .flat_map_iter(|batch| {
+            if verify_signature_batch(batch) {
+                return batch.iter().map(|_| Ok(())).collect::<Vec<_>>()
+            }
+            batch
+                .iter()
+                .map(|(public_key, signing_root, signature)| {
+                    crypto::verify_signature(public_key, signing_root.as_ref(), signature)
+                        .map_err(Into::into)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_consensus::builder::ValidatorRegistration;
+
+    fn signed_roots(count: u8) -> Vec<(BlsPublicKey, Root, BlsSignature)> {
+        (1..=count)
+            .map(|i| {
+                let secret_key = SecretKey::key_gen(&[i; 32]).unwrap();
+                let signing_root = Root::try_from([i; 32].as_ref()).unwrap();
+                let signature = secret_key.sign(signing_root.as_ref());
+                (secret_key.public_key(), signing_root, signature)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_builder_messages_follow_context() {
+        let (mainnet, sepolia) = (Context::for_mainnet(), Context::for_sepolia());
+        // NOTE: a message signed for one network is not valid on another
+        let secret_key = SecretKey::key_gen(&[1u8; 32]).unwrap();
+        let public_key = secret_key.public_key();
+        let message =
+            ValidatorRegistration { public_key: public_key.clone(), ..Default::default() };
+        let signature = sign_builder_message(&message, &secret_key, &mainnet).unwrap();
+        assert!(verify_signed_builder_data(&message, &public_key, &signature, &mainnet).is_ok());
+        assert!(verify_signed_builder_data(&message, &public_key, &signature, &sepolia).is_err());
+    }
+
+    #[test]
+    fn test_batch_verify_signatures() {
+        let signed_roots = signed_roots(4);
+        let signatures = signed_roots
+            .iter()
+            .map(|(public_key, signing_root, signature)| (public_key, *signing_root, signature))
+            .collect::<Vec<_>>();
+        assert!(batch_verify_signatures(&signatures).iter().all(Result::is_ok));
+
+        // NOTE: swapping two signatures leaves each invalid while their sum is unchanged, so the
+        // batch only fails if the signatures are weighted before they are summed
+        let mut signatures = signatures;
+        let (first, second) = (signatures[0].2, signatures[1].2);
+        signatures[0].2 = second;
+        signatures[1].2 = first;
+        let verifications = batch_verify_signatures(&signatures);
+        assert!(verifications[0].is_err());
+        assert!(verifications[1].is_err());
+        assert!(verifications[2..].iter().all(Result::is_ok));
+    }
+}
@@ -1,10 +1,16 @@
-use crate::types::{auction_contents::BlobsBundle, ExecutionPayload};
+use crate::types::{auction_contents::BlobsBundle, ExecutionPayload, ExecutionPayloadHeader};
 use ethereum_consensus::{
+    crypto::KzgCommitment,
     primitives::{BlsPublicKey, BlsSignature, ExecutionAddress, Hash32, Slot},
     ssz::prelude::*,
     Fork,
 };
 
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::deneb::mainnet::MAX_BLOB_COMMITMENTS_PER_BLOCK;
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::deneb::minimal::MAX_BLOB_COMMITMENTS_PER_BLOCK;
+
 #[derive(Debug, Default, Clone, PartialEq, Eq, Hash, SimpleSerialize)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BidTrace {
@@ -25,9 +31,68 @@ pub struct BidTrace {
     pub value: U256,
 }
 
+/// The relay's response to each submission streamed over a WebSocket connection
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubmissionAck {
+    // `None` if the submission could not be decoded
+    pub block_hash: Option<Hash32>,
+    // `None` if the submission was accepted, otherwise the reason it was rejected
+    pub error: Option<String>,
+}
+
+/// A `BidTrace` of a delivered payload, signed by the relay that delivered it
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedBidTrace {
+    pub message: BidTrace,
+    pub signature: BlsSignature,
+}
+
 pub mod data_api {
     use super::*;
 
+    #[derive(Debug, Default, Clone)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BuilderCollateral {
+        #[serde(rename = "builder_pubkey")]
+        pub builder_public_key: BlsPublicKey,
+        #[serde(with = "crate::serde::as_str")]
+        pub collateral: U256,
+        // `false` if the builder's bids are currently always simulated before being accepted
+        pub is_optimistic: bool,
+    }
+
+    /// Participation and outcomes of a builder across recent auctions
+    #[derive(Debug, Default, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct BuilderAnalytics {
+        #[serde(rename = "builder_pubkey")]
+        pub builder_public_key: BlsPublicKey,
+        // number of auctions the builder submitted to
+        pub auctions: usize,
+        // number of auctions where the builder's payload was delivered
+        pub wins: usize,
+        pub win_rate: f64,
+    }
+
+    /// Summary of the auctions the relay has retained data for
+    #[derive(Debug, Default, Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct AuctionAnalytics {
+        // number of auctions with at least one submission
+        pub auctions: usize,
+        // number of auctions where a bid was served to the proposer
+        pub auctions_served: usize,
+        pub payloads_delivered: usize,
+        // fraction of served auctions where the payload was delivered
+        pub delivery_success_rate: f64,
+        // mean increase in value from the first submission of an auction to the delivered payload
+        #[serde(with = "crate::serde::as_str")]
+        pub average_value_uplift: U256,
+        pub builders: Vec<BuilderAnalytics>,
+    }
+
     #[derive(Debug, Default, Clone)]
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct PayloadTrace {
@@ -46,6 +111,10 @@ pub mod data_api {
         pub gas_used: u64,
         #[serde(with = "crate::serde::as_str")]
         pub value: U256,
+        // NOTE: non-standard field; value of the bid served to the proposer after any adjustment
+        // made by the relay
+        #[serde(default, with = "crate::serde::as_str")]
+        pub adjusted_value: U256,
         #[serde(with = "crate::serde::as_str")]
         pub block_number: u64,
         #[serde(rename = "num_tx")]
@@ -55,6 +124,10 @@ pub mod data_api {
         #[serde(rename = "num_blob")]
         #[serde(with = "crate::serde::as_str")]
         pub blob_count: usize,
+        // NOTE: non-standard field; milliseconds from the start of the slot to when the proposer
+        // requested the payload
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub get_payload_offset_ms: Option<i64>,
     }
 
     #[derive(Debug, Default, Clone)]
@@ -75,6 +148,10 @@ pub mod data_api {
         pub gas_used: u64,
         #[serde(with = "crate::serde::as_str")]
         pub value: U256,
+        // NOTE: non-standard field; value of the bid served to the proposer after any adjustment
+        // made by the relay
+        #[serde(default, with = "crate::serde::as_str")]
+        pub adjusted_value: U256,
         #[serde(with = "crate::serde::as_str")]
         pub block_number: u64,
         #[serde(rename = "num_tx")]
@@ -92,7 +169,7 @@ pub mod data_api {
 }
 
 pub mod bellatrix {
-    use super::{BidTrace, BlsSignature, ExecutionPayload};
+    use super::{BidTrace, BlsSignature, ExecutionPayload, ExecutionPayloadHeader};
     use ethereum_consensus::ssz::prelude::*;
 
     #[derive(Debug, Clone, Serializable, HashTreeRoot)]
@@ -102,6 +179,14 @@ pub mod bellatrix {
         pub execution_payload: ExecutionPayload,
         pub signature: BlsSignature,
     }
+
+    #[derive(Debug, Clone, Serializable, HashTreeRoot)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SignedHeaderSubmission {
+        pub message: BidTrace,
+        pub execution_payload_header: ExecutionPayloadHeader,
+        pub signature: BlsSignature,
+    }
 }
 
 pub mod capella {
@@ -109,7 +194,10 @@ pub mod capella {
 }
 
 pub mod deneb {
-    use super::{BidTrace, BlsSignature, ExecutionPayload};
+    use super::{
+        BidTrace, BlsSignature, ExecutionPayload, ExecutionPayloadHeader, KzgCommitment,
+        MAX_BLOB_COMMITMENTS_PER_BLOCK,
+    };
     use crate::types::auction_contents::deneb::BlobsBundle;
     use ethereum_consensus::ssz::prelude::*;
 
@@ -121,6 +209,15 @@ pub mod deneb {
         pub blobs_bundle: BlobsBundle,
         pub signature: BlsSignature,
     }
+
+    #[derive(Debug, Clone, Serializable, HashTreeRoot)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SignedHeaderSubmission {
+        pub message: BidTrace,
+        pub execution_payload_header: ExecutionPayloadHeader,
+        pub blob_kzg_commitments: List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>,
+        pub signature: BlsSignature,
+    }
 }
 
 #[derive(Debug, Clone, Serializable, HashTreeRoot)]
@@ -153,6 +250,19 @@ impl<'de> serde::Deserialize<'de> for SignedBidSubmission {
 }
 
 impl SignedBidSubmission {
+    /// Decode a submission from its SSZ encoding
+    pub fn from_ssz_bytes(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        if let Ok(inner) = deneb::SignedBidSubmission::deserialize(bytes) {
+            return Ok(Self::Deneb(inner))
+        }
+        // NOTE: submissions before Deneb share a type, so the fork is given by the payload
+        let inner = bellatrix::SignedBidSubmission::deserialize(bytes)?;
+        match inner.execution_payload.version() {
+            Fork::Bellatrix => Ok(Self::Bellatrix(inner)),
+            _ => Ok(Self::Capella(inner)),
+        }
+    }
+
     pub fn version(&self) -> Fork {
         match self {
             Self::Bellatrix(..) => Fork::Bellatrix,
@@ -193,3 +303,121 @@ impl SignedBidSubmission {
         }
     }
 }
+
+/// A submission of only the header of a block, ahead of the full payload which is submitted
+/// separately as a [`SignedBidSubmission`]
+#[derive(Debug, Clone, Serializable, HashTreeRoot)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[serde(untagged)]
+#[ssz(transparent)]
+pub enum SignedHeaderSubmission {
+    Bellatrix(bellatrix::SignedHeaderSubmission),
+    Capella(capella::SignedHeaderSubmission),
+    Deneb(deneb::SignedHeaderSubmission),
+}
+
+impl<'de> serde::Deserialize<'de> for SignedHeaderSubmission {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        if let Ok(inner) = <_ as serde::Deserialize>::deserialize(&value) {
+            return Ok(Self::Deneb(inner))
+        }
+        if let Ok(inner) = <_ as serde::Deserialize>::deserialize(&value) {
+            return Ok(Self::Capella(inner))
+        }
+        if let Ok(inner) = <_ as serde::Deserialize>::deserialize(&value) {
+            return Ok(Self::Bellatrix(inner))
+        }
+        Err(serde::de::Error::custom("no variant could be deserialized from input"))
+    }
+}
+
+impl SignedHeaderSubmission {
+    pub fn version(&self) -> Fork {
+        match self {
+            Self::Bellatrix(..) => Fork::Bellatrix,
+            Self::Capella(..) => Fork::Capella,
+            Self::Deneb(..) => Fork::Deneb,
+        }
+    }
+
+    pub fn message(&self) -> &BidTrace {
+        match self {
+            Self::Bellatrix(inner) => &inner.message,
+            Self::Capella(inner) => &inner.message,
+            Self::Deneb(inner) => &inner.message,
+        }
+    }
+
+    pub fn header(&self) -> &ExecutionPayloadHeader {
+        match self {
+            Self::Bellatrix(inner) => &inner.execution_payload_header,
+            Self::Capella(inner) => &inner.execution_payload_header,
+            Self::Deneb(inner) => &inner.execution_payload_header,
+        }
+    }
+
+    pub fn signature(&self) -> &BlsSignature {
+        match self {
+            Self::Bellatrix(inner) => &inner.signature,
+            Self::Capella(inner) => &inner.signature,
+            Self::Deneb(inner) => &inner.signature,
+        }
+    }
+
+    pub fn blob_kzg_commitments(
+        &self,
+    ) -> Option<&List<KzgCommitment, MAX_BLOB_COMMITMENTS_PER_BLOCK>> {
+        match self {
+            Self::Bellatrix(..) => None,
+            Self::Capella(..) => None,
+            Self::Deneb(inner) => Some(&inner.blob_kzg_commitments),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bid_trace(slot: Slot) -> BidTrace {
+        BidTrace { slot, gas_limit: 30_000_000, value: U256::from(100), ..Default::default() }
+    }
+
+    #[test]
+    fn test_submission_ssz_round_trip() {
+        let submissions = [
+            SignedBidSubmission::Capella(capella::SignedBidSubmission {
+                message: bid_trace(7),
+                execution_payload: ExecutionPayload::Capella(Default::default()),
+                signature: Default::default(),
+            }),
+            SignedBidSubmission::Deneb(deneb::SignedBidSubmission {
+                message: bid_trace(8),
+                execution_payload: ExecutionPayload::Deneb(Default::default()),
+                blobs_bundle: Default::default(),
+                signature: Default::default(),
+            }),
+        ];
+        for submission in submissions {
+            let encoding = serialize(&submission).unwrap();
+            let decoded = SignedBidSubmission::from_ssz_bytes(&encoding).unwrap();
+            assert_eq!(decoded.version(), submission.version());
+            assert_eq!(decoded.message(), submission.message());
+            assert_eq!(serialize(&decoded).unwrap(), encoding);
+        }
+        assert!(SignedBidSubmission::from_ssz_bytes(&[0u8; 8]).is_err());
+    }
+
+    #[test]
+    fn test_submission_ack_encoding() {
+        let ack = SubmissionAck { block_hash: Some(Hash32::default()), error: None };
+        let encoding = serde_json::to_string(&ack).unwrap();
+        let decoded = serde_json::from_str::<SubmissionAck>(&encoding).unwrap();
+        assert_eq!(decoded.block_hash, ack.block_hash);
+        assert_eq!(decoded.error, None);
+    }
+}
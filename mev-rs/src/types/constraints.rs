@@ -0,0 +1,47 @@
+use ethereum_consensus::{
+    primitives::{BlsPublicKey, BlsSignature, Slot},
+    ssz::prelude::*,
+};
+
+#[cfg(not(feature = "minimal-preset"))]
+use ethereum_consensus::deneb::mainnet::Transaction;
+#[cfg(feature = "minimal-preset")]
+use ethereum_consensus::deneb::minimal::Transaction;
+
+pub const MAX_CONSTRAINTS_PER_SLOT: usize = 256;
+
+/// Transactions a proposer requires to be included in the block for their slot
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Constraints {
+    #[serde(with = "crate::serde::as_str")]
+    pub slot: Slot,
+    #[serde(rename = "proposer_pubkey")]
+    pub proposer_public_key: BlsPublicKey,
+    pub transactions: List<Transaction, MAX_CONSTRAINTS_PER_SLOT>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedConstraints {
+    pub message: Constraints,
+    pub signature: BlsSignature,
+}
+
+/// Transactions a builder has committed to include in any block it submits for a slot
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PreconfCommitment {
+    #[serde(with = "crate::serde::as_str")]
+    pub slot: Slot,
+    #[serde(rename = "builder_pubkey")]
+    pub builder_public_key: BlsPublicKey,
+    pub transactions: List<Transaction, MAX_CONSTRAINTS_PER_SLOT>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignedPreconfCommitment {
+    pub message: PreconfCommitment,
+    pub signature: BlsSignature,
+}
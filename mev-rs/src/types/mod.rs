@@ -2,17 +2,23 @@ pub mod auction_contents;
 mod auction_request;
 pub mod block_submission;
 pub mod builder_bid;
+mod constraints;
 mod proposer_schedule;
+mod registration_status;
 
 pub use auction_contents::{deneb::BlobsBundle, AuctionContents};
 pub use auction_request::*;
-pub use block_submission::{BidTrace, SignedBidSubmission};
+pub use block_submission::{
+    BidTrace, SignedBidSubmission, SignedBidTrace, SignedHeaderSubmission, SubmissionAck,
+};
 pub use builder_bid::{BuilderBid, SignedBuilderBid};
+pub use constraints::*;
 pub use ethereum_consensus::builder::SignedValidatorRegistration;
 pub use ethereum_consensus_types::{
     BlindedBeaconBlockBody, ExecutionPayload, ExecutionPayloadHeader, SignedBlindedBeaconBlock,
 };
 pub use proposer_schedule::*;
+pub use registration_status::*;
 
 #[cfg(not(feature = "minimal-preset"))]
 use ethereum_consensus::types::mainnet as ethereum_consensus_types;
@@ -0,0 +1,24 @@
+use ethereum_consensus::primitives::{BlsPublicKey, ExecutionAddress};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegistrationStatus {
+    #[serde(rename = "pubkey")]
+    pub public_key: BlsPublicKey,
+    // `None` if the validator is not currently registered
+    pub registration: Option<ActiveRegistration>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ActiveRegistration {
+    pub fee_recipient: ExecutionAddress,
+    #[serde(with = "crate::serde::as_str")]
+    pub gas_limit: u64,
+    // timestamp of the active registration message
+    #[serde(with = "crate::serde::as_str")]
+    pub timestamp: u64,
+    // local time the relay last accepted a registration from the validator, in seconds
+    #[serde(with = "crate::serde::as_str")]
+    pub last_refreshed: u64,
+}
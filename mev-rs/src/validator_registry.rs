@@ -1,8 +1,9 @@
-use crate::{signing::verify_signed_builder_data, types::SignedValidatorRegistration};
+use crate::{signing::batch_verify_signatures, types::SignedValidatorRegistration};
 use beacon_api_client::{Error as ApiError, StateId, ValidatorStatus, ValidatorSummary};
 use ethereum_consensus::{
-    builder::ValidatorRegistration,
-    primitives::{BlsPublicKey, Epoch, Slot, ValidatorIndex},
+    builder::{compute_builder_domain, ValidatorRegistration},
+    primitives::{BlsPublicKey, Domain, Epoch, Root, Slot, ValidatorIndex},
+    signing::compute_signing_root,
     state_transition::Context,
     Error as ConsensusError,
 };
@@ -85,6 +86,8 @@ pub struct State {
     // data from consensus
     validators: HashMap<BlsPublicKey, ValidatorSummary>,
     pubkeys_by_index: HashMap<ValidatorIndex, BlsPublicKey>,
+    // local timestamp of the last time each validator submitted a valid registration
+    last_refreshed: HashMap<BlsPublicKey, u64>,
 }
 
 // Maintains validators we are aware of
@@ -104,13 +107,19 @@ impl ValidatorRegistry {
     pub async fn on_epoch(&self, epoch: Epoch) -> Result<(), Error> {
         let slot = epoch * self.slots_per_epoch;
         let summaries = self.client.get_validators(StateId::Slot(slot), &[], &[]).await?;
+        self.insert_validators(summaries);
+        Ok(())
+    }
+
+    // Add `summaries` to the known validator set, replacing any existing summaries for the same
+    // validators.
+    pub fn insert_validators(&self, summaries: impl IntoIterator<Item = ValidatorSummary>) {
         let mut state = self.state.write();
-        for summary in summaries.into_iter() {
+        for summary in summaries {
             let public_key = summary.validator.public_key.clone();
             state.pubkeys_by_index.insert(summary.index, public_key.clone());
             state.validators.insert(public_key, summary);
         }
-        Ok(())
     }
 
     // Return the BLS public key for the validator's `index`, reflecting the index
@@ -140,6 +149,12 @@ impl ValidatorRegistry {
         state.validator_preferences.get(public_key).cloned()
     }
 
+    // Return the local timestamp of the last valid registration processed for `public_key`, if any.
+    pub fn get_last_refreshed(&self, public_key: &BlsPublicKey) -> Option<u64> {
+        let state = self.state.read();
+        state.last_refreshed.get(public_key).copied()
+    }
+
     // pub fn find_public_key_by_fee_recipient(
     //     &self,
     //     fee_recipient: &ExecutionAddress,
@@ -152,17 +167,29 @@ impl ValidatorRegistry {
     //         .map(|(key, _)| key.clone())
     // }
 
+    // Remove any registrations that have not been refreshed since `retain_timestamp`, returning
+    // the number of registrations removed.
+    pub fn prune_registrations(&self, retain_timestamp: u64) -> usize {
+        let mut state = self.state.write();
+        let State { validator_preferences, last_refreshed, .. } = &mut *state;
+        last_refreshed.retain(|_, timestamp| *timestamp >= retain_timestamp);
+        let count = validator_preferences.len();
+        validator_preferences.retain(|public_key, _| last_refreshed.contains_key(public_key));
+        count - validator_preferences.len()
+    }
+
+    // Validate `registration` against the known validator set, returning the signing root of the
+    // registration if its signature still needs to be verified.
     fn process_registration<'a>(
         &'a self,
         registration: &'a SignedValidatorRegistration,
         current_timestamp: u64,
-        context: &Context,
-    ) -> Result<Option<&'a SignedValidatorRegistration>, Error> {
+        domain: Domain,
+    ) -> Result<(&'a SignedValidatorRegistration, ValidatorRegistrationStatus, Option<Root>), Error>
+    {
         let state = self.state.read();
-        let latest_timestamp = state
-            .validator_preferences
-            .get(&registration.message.public_key)
-            .map(|r| r.message.timestamp);
+        let latest_registration = state.validator_preferences.get(&registration.message.public_key);
+        let latest_timestamp = latest_registration.map(|r| r.message.timestamp);
         let message = &registration.message;
 
         validate_registration_is_not_from_future(message, current_timestamp)?;
@@ -186,15 +213,18 @@ impl ValidatorRegistry {
             .ok_or(Error::UnknownPubkey)?;
         validate_validator_status(message, validator_status)?;
 
-        verify_signed_builder_data(message, &message.public_key, &registration.signature, context)?;
-
-        let update = if matches!(registration_status, ValidatorRegistrationStatus::New) {
-            trace!(%public_key, "processed new registration");
-            Some(registration)
-        } else {
+        // NOTE: validators re-send the same registration often, so skip verifying any registration
+        // identical to the one already accepted
+        let signing_root = if latest_registration == Some(registration) {
             None
+        } else {
+            Some(compute_signing_root(message, domain)?)
         };
-        Ok(update)
+
+        if matches!(registration_status, ValidatorRegistrationStatus::New) {
+            trace!(%public_key, "processed new registration");
+        }
+        Ok((registration, registration_status, signing_root))
     }
 
     // Returns set of public keys for updated (including new) registrations successfully processed
@@ -205,20 +235,49 @@ impl ValidatorRegistry {
         current_timestamp: u64,
         context: &Context,
     ) -> (HashSet<BlsPublicKey>, Vec<Error>) {
+        let domain = match compute_builder_domain(context) {
+            Ok(domain) => domain,
+            Err(err) => return (Default::default(), vec![err.into()]),
+        };
         let (updates, errs): (Vec<_>, Vec<_>) = registrations
             .par_iter()
-            .map(|registration| self.process_registration(registration, current_timestamp, context))
+            .map(|registration| self.process_registration(registration, current_timestamp, domain))
             .partition(|result| result.is_ok());
+        let updates = updates
+            .into_iter()
+            .map(|update| update.expect("validated successfully"))
+            .collect::<Vec<_>>();
+        let mut errs =
+            errs.into_iter().map(|err| err.expect_err("validation failed")).collect::<Vec<_>>();
+
+        let signatures = updates
+            .iter()
+            .filter_map(|(signed_registration, _, signing_root)| {
+                let public_key = &signed_registration.message.public_key;
+                signing_root
+                    .map(|signing_root| (public_key, signing_root, &signed_registration.signature))
+            })
+            .collect::<Vec<_>>();
+        let mut verifications = batch_verify_signatures(&signatures).into_iter();
+
         let mut state = self.state.write();
         let mut updated_keys = HashSet::new();
-        for update in updates {
-            if let Some(signed_registration) = update.expect("validated successfully") {
-                let public_key = signed_registration.message.public_key.clone();
+        for (signed_registration, status, signing_root) in updates {
+            let message = &signed_registration.message;
+            let public_key = message.public_key.clone();
+            if signing_root.is_some() {
+                if let Err(err) = verifications.next().expect("verified each signature") {
+                    errs.push(err.into());
+                    continue
+                }
+            }
+            state.last_refreshed.insert(public_key.clone(), current_timestamp);
+            if matches!(status, ValidatorRegistrationStatus::New) {
                 updated_keys.insert(public_key.clone());
                 state.validator_preferences.insert(public_key, signed_registration.clone());
             }
         }
 
-        (updated_keys, errs.into_iter().map(|err| err.expect_err("validation failed")).collect())
+        (updated_keys, errs)
     }
 }